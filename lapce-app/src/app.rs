@@ -12,7 +12,7 @@
 use clap::Parser;
 use crossbeam_channel::Sender;
 use floem::{
-    cosmic_text::{Style as FontStyle, Weight},
+    cosmic_text::{Style as FontStyle, TextLayout, Weight},
     event::{Event, EventListener},
     ext_event::create_signal_from_channel,
     menu::{Menu, MenuItem},
@@ -64,6 +64,7 @@
     },
     db::LapceDb,
     debug::RunDebugMode,
+    disassembly::disassembly_view,
     editor::{
         diff::{diff_show_more_section_view, DiffEditorData},
         location::{EditorLocation, EditorPosition},
@@ -72,6 +73,7 @@
     },
     editor_tab::{EditorTabChild, EditorTabData},
     focus_text::focus_text,
+    git_log::git_log_view,
     id::{DiffEditorId, EditorId, EditorTabId, SplitId},
     keymap::keymap_view,
     keypress::keymap::KeyMap,
@@ -87,6 +89,7 @@
     panel::{position::PanelContainerPosition, view::panel_container_view},
     plugin::PluginData,
     settings::settings_view,
+    signature::{active_signature_index, signature_documentation, signature_label},
     status::status,
     text_input::text_input,
     title::title,
@@ -1112,6 +1115,13 @@ fn editor_tab_content(
             EditorTabChild::Keymap(_) => {
                 container_box(move || Box::new(keymap_view(common)))
             }
+            EditorTabChild::Disassembly(_) => {
+                container_box(move || Box::new(disassembly_view(common)))
+            }
+            EditorTabChild::GitLog(_) => {
+                let main_split = main_split.clone();
+                container_box(move || Box::new(git_log_view(main_split)))
+            }
         };
         child.style(|s| s.size_pct(100.0, 100.0))
     };
@@ -2103,6 +2113,9 @@ fn palette_item(
         | PaletteItemContent::Language { .. }
         | PaletteItemContent::ColorTheme { .. }
         | PaletteItemContent::SCMReference { .. }
+        | PaletteItemContent::ColorPresentation { .. }
+        | PaletteItemContent::AttachProcess { .. }
+        | PaletteItemContent::RunAndDebugCompound { .. }
         | PaletteItemContent::IconTheme { .. } => {
             let text = item.filter_text;
             let indices = item.indices;
@@ -2519,32 +2532,67 @@ fn hover(window_tab_data: Rc<WindowTabData>) -> impl View {
     let config = window_tab_data.common.config;
     let id = AtomicU64::new(0);
     let layout_rect = window_tab_data.common.hover.layout_rect;
+    let blame = window_tab_data.common.hover.blame;
+    let hover_active = window_tab_data.common.hover.active;
+    let main_split = window_tab_data.main_split.clone();
 
     scroll(|| {
-        list(
-            move || hover_data.content.get(),
-            move |_| id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
-            move |content| match content {
-                MarkdownContent::Text(text_layout) => container_box(|| {
-                    Box::new(
-                        rich_text(move || text_layout.clone())
-                            .style(|s| s.max_width_px(600.0)),
-                    )
-                })
-                .style(|s| s.max_width_pct(100.0)),
-                MarkdownContent::Image { .. } => container_box(|| Box::new(empty())),
-                MarkdownContent::Separator => container_box(|| {
-                    Box::new(empty().style(move |s| {
-                        s.width_pct(100.0)
-                            .margin_vert_px(5.0)
-                            .height_px(1.0)
-                            .background(
-                                *config.get().get_color(LapceColor::LAPCE_BORDER),
+        stack(|| {
+            (
+                list(
+                    move || hover_data.content.get(),
+                    move |_| id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+                    move |content| match content {
+                        MarkdownContent::Text(text_layout) => container_box(|| {
+                            Box::new(
+                                rich_text(move || text_layout.clone())
+                                    .style(|s| s.max_width_px(600.0)),
                             )
-                    }))
-                }),
-            },
-        )
+                        })
+                        .style(|s| s.max_width_pct(100.0)),
+                        MarkdownContent::Image { .. } => {
+                            container_box(|| Box::new(empty()))
+                        }
+                        MarkdownContent::Separator => container_box(|| {
+                            Box::new(empty().style(move |s| {
+                                s.width_pct(100.0)
+                                    .margin_vert_px(5.0)
+                                    .height_px(1.0)
+                                    .background(
+                                        *config
+                                            .get()
+                                            .get_color(LapceColor::LAPCE_BORDER),
+                                    )
+                            }))
+                        }),
+                    },
+                )
+                .style(|s| s.flex_col()),
+                // The blame hover opens the diff of the commit
+                label(|| "Show Commit Diff".to_string())
+                    .on_click(move |_| {
+                        if let Some((path, hunk)) = blame.get_untracked() {
+                            hover_active.set(false);
+                            main_split.open_commit_file_changes(
+                                path,
+                                None,
+                                hunk.commit_id,
+                            );
+                        }
+                        true
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        let committed = blame
+                            .with(|b| b.as_ref().map(|(_, h)| h.is_committed()))
+                            .unwrap_or(false);
+                        s.margin_top_px(5.0)
+                            .cursor(CursorStyle::Pointer)
+                            .color(*config.get_color(LapceColor::EDITOR_LINK))
+                            .apply_if(!committed, |s| s.hide())
+                    }),
+            )
+        })
         .style(|s| s.flex_col().padding_horiz_px(10.0).padding_vert_px(5.0))
     })
     .on_resize(move |rect| {
@@ -2573,6 +2621,144 @@ fn hover(window_tab_data: Rc<WindowTabData>) -> impl View {
     })
 }
 
+fn signature(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let signature_data = window_tab_data.common.signature.clone();
+    let config = window_tab_data.common.config;
+    let id = AtomicU64::new(0);
+    let layout_rect = signature_data.layout_rect;
+    let signature_help = signature_data.signature_help;
+    let language = signature_data.language;
+
+    let picker_button = move |text: &'static str, forward: bool| {
+        let signature_data = signature_data.clone();
+        label(move || text.to_string())
+            .on_click(move |_| {
+                signature_data.cycle_signature(forward);
+                true
+            })
+            .style(move |s| {
+                s.padding_horiz_px(4.0)
+                    .cursor(CursorStyle::Pointer)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            })
+    };
+
+    stack(|| {
+        (
+            stack(|| {
+                (
+                    stack(|| {
+                        (
+                            picker_button("\u{25b2}", false),
+                            label(move || {
+                                signature_help.with(|help| {
+                                    help.as_ref()
+                                        .map(|help| {
+                                            format!(
+                                                "{}/{}",
+                                                active_signature_index(help) + 1,
+                                                help.signatures.len()
+                                            )
+                                        })
+                                        .unwrap_or_default()
+                                })
+                            }),
+                            picker_button("\u{25bc}", true),
+                        )
+                    })
+                    .style(move |s| {
+                        s.margin_right_px(6.0).apply_if(
+                            signature_help.with(|help| {
+                                help.as_ref()
+                                    .map(|help| help.signatures.len() < 2)
+                                    .unwrap_or(true)
+                            }),
+                            |s| s.hide(),
+                        )
+                    }),
+                    rich_text(move || {
+                        let config = config.get();
+                        signature_help
+                            .with(|help| {
+                                help.as_ref().and_then(|help| {
+                                    signature_label(help, language.get(), &config)
+                                })
+                            })
+                            .unwrap_or_else(TextLayout::new)
+                    })
+                    .style(|s| s.max_width_px(600.0)),
+                )
+            })
+            .style(|s| s.items_center()),
+            scroll(|| {
+                list(
+                    move || {
+                        let config = config.get();
+                        signature_help.with(|help| {
+                            help.as_ref()
+                                .map(|help| signature_documentation(help, &config))
+                                .unwrap_or_default()
+                        })
+                    },
+                    move |_| id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+                    move |content| match content {
+                        MarkdownContent::Text(text_layout) => container_box(|| {
+                            Box::new(
+                                rich_text(move || text_layout.clone())
+                                    .style(|s| s.max_width_px(600.0)),
+                            )
+                        })
+                        .style(|s| s.max_width_pct(100.0)),
+                        MarkdownContent::Image { .. } => {
+                            container_box(|| Box::new(empty()))
+                        }
+                        MarkdownContent::Separator => container_box(|| {
+                            Box::new(empty().style(move |s| {
+                                s.width_pct(100.0)
+                                    .margin_vert_px(5.0)
+                                    .height_px(1.0)
+                                    .background(
+                                        *config
+                                            .get()
+                                            .get_color(LapceColor::LAPCE_BORDER),
+                                    )
+                            }))
+                        }),
+                    },
+                )
+                .style(|s| s.flex_col().margin_top_px(5.0))
+            })
+            .style(|s| s.max_height_px(200.0)),
+        )
+    })
+    .on_resize(move |rect| {
+        layout_rect.set(rect);
+    })
+    .on_event(EventListener::PointerMove, |_| true)
+    .style(move |s| {
+        let active = window_tab_data.common.signature.active.get();
+        if !active || !config.get().editor.show_signature {
+            s.hide()
+        } else {
+            let config = config.get();
+            if let Some(origin) = window_tab_data.signature_origin() {
+                s.absolute()
+                    .flex_col()
+                    .margin_left_px(origin.x as f32)
+                    .margin_top_px(origin.y as f32)
+                    .padding_horiz_px(10.0)
+                    .padding_vert_px(5.0)
+                    .border(1.0)
+                    .border_radius(6.0)
+                    .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+                    .background(*config.get_color(LapceColor::PANEL_BACKGROUND))
+            } else {
+                s.hide()
+            }
+        }
+    })
+}
+
 fn completion(window_tab_data: Rc<WindowTabData>) -> impl View {
     let completion_data = window_tab_data.common.completion;
     let config = window_tab_data.common.config;
@@ -2842,6 +3028,7 @@ fn window_tab(window_tab_data: Rc<WindowTabData>) -> impl View {
             .style(|s| s.size_pct(100.0, 100.0).flex_col()),
             completion(window_tab_data.clone()),
             hover(window_tab_data.clone()),
+            signature(window_tab_data.clone()),
             code_action(window_tab_data.clone()),
             rename(window_tab_data.clone()),
             palette(window_tab_data.clone()),
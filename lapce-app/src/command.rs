@@ -9,18 +9,20 @@
     dap_types::RunDebugConfig, plugin::PluginId, proxy::ProxyStatus,
     terminal::TermId,
 };
-use lsp_types::{CodeActionOrCommand, Position, WorkspaceEdit};
+use lsp_types::{
+    CodeActionOrCommand, Position, Range, ShowMessageParams, WorkspaceEdit,
+};
 use serde_json::Value;
 use strum::{EnumMessage, IntoEnumIterator};
 use strum_macros::{Display, EnumIter, EnumMessage, EnumString, IntoStaticStr};
 
 use crate::{
     alert::AlertButton,
-    debug::RunDebugMode,
+    debug::{BreakpointField, RunDebugMode},
     doc::Document,
     editor::location::EditorLocation,
     editor_tab::EditorTabChild,
-    id::EditorTabId,
+    id::{EditorId, EditorTabId},
     main_split::{SplitDirection, SplitMoveDirection},
     workspace::LapceWorkspace,
 };
@@ -325,6 +327,42 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "palette.run_and_debug_stop")]
     RunAndDebugStop,
 
+    #[strum(message = "Debug: Step Over")]
+    #[strum(serialize = "debug.step_over")]
+    DebugStepOver,
+
+    #[strum(message = "Debug: Step Into")]
+    #[strum(serialize = "debug.step_into")]
+    DebugStepInto,
+
+    #[strum(message = "Debug: Step Out")]
+    #[strum(serialize = "debug.step_out")]
+    DebugStepOut,
+
+    #[strum(message = "Debug: Step Back")]
+    #[strum(serialize = "debug.step_back")]
+    DebugStepBack,
+
+    #[strum(message = "Debug: Step Instruction")]
+    #[strum(serialize = "debug.step_instruction")]
+    DebugStepInstruction,
+
+    #[strum(message = "Debug: Open Disassembly")]
+    #[strum(serialize = "debug.open_disassembly")]
+    DebugOpenDisassembly,
+
+    #[strum(message = "Debug: Toggle Breakpoint")]
+    #[strum(serialize = "debug.toggle_breakpoint")]
+    DebugToggleBreakpoint,
+
+    #[strum(message = "Debug: Run to Cursor")]
+    #[strum(serialize = "debug.run_to_cursor")]
+    DebugRunToCursor,
+
+    #[strum(message = "Debug: Jump to Cursor")]
+    #[strum(serialize = "debug.jump_to_cursor")]
+    DebugJumpToCursor,
+
     #[strum(serialize = "source_control.checkout_reference")]
     CheckoutReference,
 
@@ -431,6 +469,18 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "source_control_discard_workspace_changes")]
     SourceControlDiscardWorkspaceChanges,
 
+    #[strum(message = "Source Control: Toggle Blame")]
+    #[strum(serialize = "source_control_toggle_blame")]
+    SourceControlToggleBlame,
+
+    #[strum(message = "Source Control: Show Log")]
+    #[strum(serialize = "source_control_show_log")]
+    SourceControlShowLog,
+
+    #[strum(message = "Source Control: Show File History")]
+    #[strum(serialize = "source_control_show_file_history")]
+    SourceControlShowFileHistory,
+
     #[strum(serialize = "export_current_theme_settings")]
     #[strum(message = "Export current settings to a theme file")]
     ExportCurrentThemeSettings,
@@ -523,6 +573,16 @@ pub enum InternalCommand {
     PaletteReferences {
         references: Vec<EditorLocation>,
     },
+    PaletteColorPicker {
+        path: PathBuf,
+        range: Range,
+        color: lsp_types::Color,
+    },
+    PeekLocation {
+        editor_id: EditorId,
+        offset: usize,
+        location: EditorLocation,
+    },
     SaveJumpLocation {
         path: PathBuf,
         offset: usize,
@@ -628,12 +688,25 @@ pub enum InternalCommand {
         buttons: Vec<AlertButton>,
     },
     HideAlert,
+    ShowMessage {
+        title: String,
+        message: ShowMessageParams,
+    },
     SaveScratchDoc {
         doc: Rc<Document>,
     },
     UpdateProxyStatus {
         status: ProxyStatus,
     },
+    ToggleBreakpoint {
+        path: PathBuf,
+        line: usize,
+    },
+    EditBreakpoint {
+        path: PathBuf,
+        line: usize,
+        field: BreakpointField,
+    },
 }
 
 #[derive(Clone)]
@@ -4,11 +4,14 @@
     peniko::kurbo::Rect,
     reactive::{ReadSignal, RwSignal, Scope},
 };
-use lapce_core::{buffer::rope_text::RopeText, movement::Movement};
+use lapce_core::{
+    buffer::{rope_text::RopeText, Buffer},
+    movement::Movement,
+};
 use lapce_rpc::{plugin::PluginId, proxy::ProxyRpcHandler};
 use lsp_types::{
-    CompletionItem, CompletionResponse, CompletionTextEdit, InsertTextFormat,
-    Position,
+    CompletionItem, CompletionResponse, CompletionTextEdit, CompletionTriggerKind,
+    InsertTextFormat, Position,
 };
 use nucleo::Utf32Str;
 
@@ -31,6 +34,9 @@ pub struct ScoredCompletionItem {
     pub score: u32,
     pub label_score: u32,
     pub indices: Vec<usize>,
+    /// The text between the start of the item's edit and the start of the input, such as
+    /// a `.` or `@` that the server replaces, which is matched against the filter text too
+    pub filter_prefix: String,
 }
 
 #[derive(Clone)]
@@ -52,6 +58,9 @@ pub struct CompletionData {
     pub input: String,
     /// `(Input, CompletionItems)`
     pub input_items: im::HashMap<String, im::Vector<ScoredCompletionItem>>,
+    /// The inputs whose items were marked as incomplete by the server, so they have to be
+    /// requested again as the user keeps typing
+    pub incomplete_inputs: im::HashSet<String>,
     /// The filtered items that are being displayed to the user
     pub filtered_items: im::Vector<ScoredCompletionItem>,
     /// The size of the completion element.  
@@ -78,6 +87,7 @@ pub fn new(cx: Scope, config: ReadSignal<Arc<LapceConfig>>) -> Self {
             active,
             input: "".to_string(),
             input_items: im::HashMap::new(),
+            incomplete_inputs: im::HashSet::new(),
             filtered_items: im::Vector::new(),
             layout_rect: Rect::ZERO,
             matcher: cx
@@ -94,6 +104,7 @@ pub fn receive(
         input: &str,
         resp: &CompletionResponse,
         plugin_id: PluginId,
+        buffer: Option<&Buffer>,
     ) {
         // If we've been canceled or the request id is old, ignore the response.
         if self.status == CompletionStatus::Inactive || self.request_id != request_id
@@ -103,9 +114,17 @@ pub fn receive(
 
         let items = match resp {
             CompletionResponse::Array(items) => items,
-            // TODO: Possibly handle the 'is_incomplete' field on List.
             CompletionResponse::List(list) => &list.items,
         };
+        let is_incomplete = match resp {
+            CompletionResponse::Array(_) => false,
+            CompletionResponse::List(list) => list.is_incomplete,
+        };
+        if is_incomplete {
+            self.incomplete_inputs.insert(input.to_string());
+        } else {
+            self.incomplete_inputs.remove(input);
+        }
         let items: im::Vector<ScoredCompletionItem> = items
             .iter()
             .map(|i| ScoredCompletionItem {
@@ -114,10 +133,14 @@ pub fn receive(
                 score: 0,
                 label_score: 0,
                 indices: Vec::new(),
+                filter_prefix: buffer
+                    .map(|buffer| filter_prefix(i, buffer, self.offset))
+                    .unwrap_or_default(),
             })
             .collect();
         self.input_items.insert(input.to_string(), items);
         self.filter_items();
+        self.select_preselected();
     }
 
     /// Request for completion items wit the current request id.
@@ -128,10 +151,17 @@ pub fn request(
         path: PathBuf,
         input: String,
         position: Position,
+        trigger_kind: CompletionTriggerKind,
     ) {
         self.latest_editor_id = Some(editor_id);
         self.input_items.insert(input.clone(), im::Vector::new());
-        proxy_rpc.completion(self.request_id, path, input, position);
+        self.incomplete_inputs.remove(&input);
+        proxy_rpc.completion(self.request_id, path, input, position, trigger_kind);
+    }
+
+    /// Whether the items received for the input were marked as incomplete.
+    pub fn is_incomplete(&self, input: &str) -> bool {
+        self.incomplete_inputs.contains(input)
     }
 
     /// Close the completion, clearing all the data.
@@ -145,6 +175,7 @@ pub fn cancel(&mut self) {
         self.active.set(0);
         self.input.clear();
         self.input_items.clear();
+        self.incomplete_inputs.clear();
         self.filtered_items.clear();
     }
 
@@ -157,8 +188,21 @@ pub fn update_input(&mut self, input: String) {
         // try keeping that item active. Possibly give this a setting.
         // ex: `p` has `print!` and `println!` has options. If you select the second, then type
         // `r` then it should stay on `println!` even as the overall filtering of the list changes.
-        self.active.set(0);
         self.filter_items();
+        self.select_preselected();
+    }
+
+    /// Make the item that the server asked to preselect active, if it is one of the best
+    /// matches of the input. Otherwise the first item is made active.
+    fn select_preselected(&self) {
+        let best_score = self.filtered_items.front().map(|i| i.score);
+        let index = self
+            .filtered_items
+            .iter()
+            .take_while(|i| Some(i.score) == best_score)
+            .position(|i| i.item.preselect == Some(true))
+            .unwrap_or(0);
+        self.active.set(index);
     }
 
     fn all_items(&self) -> im::Vector<ScoredCompletionItem> {
@@ -174,7 +218,9 @@ fn all_items(&self) -> im::Vector<ScoredCompletionItem> {
     pub fn filter_items(&mut self) {
         self.input_id += 1;
         if self.input.is_empty() {
-            self.filtered_items = self.all_items();
+            let mut items = self.all_items();
+            items.sort_by(|a, b| sort_text(&a.item).cmp(sort_text(&b.item)));
+            self.filtered_items = items;
             return;
         }
 
@@ -189,51 +235,69 @@ pub fn filter_items(&mut self) {
                 self.all_items()
                     .iter()
                     .filter_map(|i| {
+                        let prefixed_pattern;
+                        let pattern = if i.filter_prefix.is_empty() {
+                            &pattern
+                        } else {
+                            prefixed_pattern = nucleo::pattern::Pattern::parse(
+                                &format!("{}{}", i.filter_prefix, self.input),
+                                nucleo::pattern::CaseMatching::Ignore,
+                            );
+                            &prefixed_pattern
+                        };
                         let filter_text =
                             i.item.filter_text.as_ref().unwrap_or(&i.item.label);
+                        // The matched indices are in chars
                         let shift = i
                             .item
                             .label
-                            .match_indices(filter_text)
+                            .match_indices(filter_text.as_str())
                             .next()
-                            .map(|(shift, _)| shift)
-                            .unwrap_or(0);
+                            .map(|(shift, _)| i.item.label[..shift].chars().count());
                         let mut indices = Vec::new();
                         let mut filter_text_buf = Vec::new();
                         let filter_text =
                             Utf32Str::new(filter_text, &mut filter_text_buf);
-                        if let Some(score) =
-                            pattern.indices(filter_text, matcher, &mut indices)
-                        {
-                            if shift > 0 {
+                        let score =
+                            pattern.indices(filter_text, matcher, &mut indices)?;
+
+                        let mut item = i.clone();
+                        item.score = score;
+                        item.label_score = score;
+
+                        let mut label_buf = Vec::new();
+                        let label_text =
+                            Utf32Str::new(&i.item.label, &mut label_buf);
+                        match shift {
+                            Some(shift) => {
                                 for idx in indices.iter_mut() {
                                     *idx += shift as u32;
                                 }
                             }
-                            let mut item = i.clone();
-                            item.score = score;
-                            item.label_score = score;
-                            item.indices =
-                                indices.into_iter().map(|i| i as usize).collect();
-
-                            let mut label_buf = Vec::new();
-                            let label_text =
-                                Utf32Str::new(&i.item.label, &mut label_buf);
-                            if let Some(score) = pattern.score(label_text, matcher) {
-                                item.label_score = score;
+                            // The filter text isn't part of the label, so the label
+                            // is matched separately for the highlighted chars
+                            None => {
+                                indices.clear();
+                                pattern.indices(label_text, matcher, &mut indices);
                             }
-                            Some(item)
-                        } else {
-                            None
                         }
+                        item.indices =
+                            indices.into_iter().map(|i| i as usize).collect();
+
+                        if let Some(score) = pattern.score(label_text, matcher) {
+                            item.label_score = score;
+                        }
+                        Some(item)
                     })
                     .collect()
             })
             .unwrap();
-        // Sort all the items by their score, then their label score, then their length.
+        // Sort all the items by their score, then the order given by the server, then their
+        // label score, then their length.
         items.sort_by(|a, b| {
             b.score
                 .cmp(&a.score)
+                .then_with(|| sort_text(&a.item).cmp(sort_text(&b.item)))
                 .then_with(|| b.label_score.cmp(&a.label_score))
                 .then_with(|| a.item.label.len().cmp(&b.item.label.len()))
         });
@@ -338,6 +402,29 @@ pub fn update_document_completion(
     }
 }
 
+/// The text from the start of the item's edit up to the `offset` the input starts at.
+fn filter_prefix(item: &CompletionItem, buffer: &Buffer, offset: usize) -> String {
+    let start = match item.text_edit.as_ref() {
+        Some(CompletionTextEdit::Edit(edit)) => &edit.range.start,
+        Some(CompletionTextEdit::InsertAndReplace(edit)) => &edit.insert.start,
+        None => return String::new(),
+    };
+    let start = buffer.offset_of_position(start);
+    if start >= offset {
+        return String::new();
+    }
+    let prefix = buffer.slice_to_cow(start..offset);
+    if prefix.contains(['\n', '\r']) {
+        return String::new();
+    }
+    prefix.to_string()
+}
+
+/// The text that the server wants the item to be sorted by, which defaults to the label.
+fn sort_text(item: &CompletionItem) -> &str {
+    item.sort_text.as_deref().unwrap_or(&item.label)
+}
+
 /// Clear the current completion lens. Only `update`s if there is a completion lens.
 pub fn clear_completion_lens(doc: Rc<Document>) {
     let has_completion = doc.completion_lens.with_untracked(|lens| lens.is_some());
@@ -365,15 +452,18 @@ fn completion_lens_text(
             .insert_text_format
             .unwrap_or(InsertTextFormat::PLAIN_TEXT);
 
-        // We don't display insert and replace
-        let CompletionTextEdit::Edit(edit) = edit else {
-            return None;
+        // The insert and replace ranges of an edit start at the same position
+        let (edit_start, new_text) = match edit {
+            CompletionTextEdit::Edit(edit) => (&edit.range.start, &edit.new_text),
+            CompletionTextEdit::InsertAndReplace(edit) => {
+                (&edit.insert.start, &edit.new_text)
+            }
         };
         // The completion offset can be different from the current cursor offset.
         let completion_offset = completion.offset;
 
         let start_offset = rope_text.prev_code_boundary(cursor_offset);
-        let edit_start = rope_text.offset_of_position(&edit.range.start);
+        let edit_start = rope_text.offset_of_position(edit_start);
 
         // If the start of the edit isn't where the cursor currently is,
         // and it is not at the start of the completion, then we ignore it.
@@ -387,11 +477,11 @@ fn completion_lens_text(
             InsertTextFormat::PLAIN_TEXT => {
                 // This is not entirely correct because it assumes that the position is
                 // `{start,end}_offset` when it may not necessarily be.
-                Cow::Borrowed(&edit.new_text)
+                Cow::Borrowed(new_text)
             }
             InsertTextFormat::SNIPPET => {
                 // Parse the snippet. Bail if it's invalid.
-                let snippet = Snippet::from_str(&edit.new_text).ok()?;
+                let snippet = Snippet::from_str(new_text).ok()?;
 
                 let text = snippet.text();
 
@@ -422,3 +512,64 @@ fn completion_lens_text(
         Some(Some(item.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::buffer::Buffer;
+    use lsp_types::{
+        CompletionItem, CompletionTextEdit, InsertReplaceEdit, Position, Range,
+        TextEdit,
+    };
+
+    use super::filter_prefix;
+
+    fn range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    fn item(text_edit: Option<CompletionTextEdit>) -> CompletionItem {
+        CompletionItem {
+            label: "foo".to_string(),
+            text_edit,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filter_prefix_before_the_input() {
+        let buffer = Buffer::new("let x = a.fo");
+        // The input is `fo`, and the server replaces the `.` too
+        let edit = CompletionTextEdit::Edit(TextEdit {
+            range: range(0, 9, 12),
+            new_text: "?.foo".to_string(),
+        });
+        assert_eq!(filter_prefix(&item(Some(edit)), &buffer, 10), ".");
+
+        let edit = CompletionTextEdit::InsertAndReplace(InsertReplaceEdit {
+            new_text: "@foo".to_string(),
+            insert: range(0, 9, 12),
+            replace: range(0, 9, 12),
+        });
+        assert_eq!(filter_prefix(&item(Some(edit)), &buffer, 10), ".");
+    }
+
+    #[test]
+    fn no_filter_prefix() {
+        let buffer = Buffer::new("a\nfo");
+        assert_eq!(filter_prefix(&item(None), &buffer, 2), "");
+
+        // The edit starts at the input
+        let edit = CompletionTextEdit::Edit(TextEdit {
+            range: range(1, 0, 2),
+            new_text: "foo".to_string(),
+        });
+        assert_eq!(filter_prefix(&item(Some(edit)), &buffer, 2), "");
+
+        // The edit starts on a line before
+        let edit = CompletionTextEdit::Edit(TextEdit {
+            range: Range::new(Position::new(0, 0), Position::new(1, 2)),
+            new_text: "foo".to_string(),
+        });
+        assert_eq!(filter_prefix(&item(Some(edit)), &buffer, 2), "");
+    }
+}
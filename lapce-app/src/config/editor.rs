@@ -49,6 +49,10 @@ pub struct EditorConfig {
         desc = "If the editor should show the documentation of the current completion item"
     )]
     pub completion_show_documentation: bool,
+    #[field_names(
+        desc = "If accepting a completion item should replace the rest of the word after the cursor, when the language server provides both an insert and a replace range"
+    )]
+    pub completion_replace: bool,
     #[field_names(
         desc = "If the editor should show the signature of the function as the parameters are being typed"
     )]
@@ -73,6 +77,10 @@ pub struct EditorConfig {
         desc = "Whether it should format the document on save (if there is an available formatter)"
     )]
     pub format_on_save: bool,
+    #[field_names(
+        desc = "Whether it should format the pasted text (if there is an available range formatter)"
+    )]
+    pub format_on_paste: bool,
 
     #[field_names(desc = "If matching brackets are highlighted")]
     pub highlight_matching_brackets: bool,
@@ -117,6 +125,18 @@ pub struct EditorConfig {
         desc = "Set the completion lens font size. If 0 it uses the inlay hint font size."
     )]
     pub completion_lens_font_size: usize,
+    #[field_names(
+        desc = "If the editor should request inline completions while typing and display them as phantom text"
+    )]
+    pub enable_inline_completion: bool,
+    #[field_names(
+        desc = "If the values of the variables should be displayed at the end of the lines that use them while debugging"
+    )]
+    pub enable_inline_values: bool,
+    #[field_names(
+        desc = "If the author and the summary of the last commit that changed the cursor line should be displayed at its end"
+    )]
+    pub enable_inline_blame: bool,
     #[field_names(
         desc = "Set the cursor blink interval (in milliseconds). Set to 0 to completely disable."
     )]
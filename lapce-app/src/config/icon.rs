@@ -15,10 +15,16 @@ impl LapceIcons {
     pub const DEBUG: &str = "debug";
     pub const DEBUG_ALT: &str = "debug_alt";
     pub const DEBUG_BREAKPOINT: &str = "debug_breakpoint";
+    pub const DEBUG_BREAKPOINT_CONDITIONAL: &str = "debug_breakpoint_conditional";
+    pub const DEBUG_BREAKPOINT_LOG: &str = "debug_breakpoint_log";
     pub const DEBUG_SMALL: &str = "debug_small";
     pub const DEBUG_RESTART: &str = "debug_restart";
     pub const DEBUG_CONTINUE: &str = "debug_continue";
     pub const DEBUG_PAUSE: &str = "debug_pause";
+    pub const DEBUG_STEP_OVER: &str = "debug_step_over";
+    pub const DEBUG_STEP_INTO: &str = "debug_step_into";
+    pub const DEBUG_STEP_OUT: &str = "debug_step_out";
+    pub const DEBUG_STEP_BACK: &str = "debug_step_back";
     pub const DEBUG_STOP: &str = "debug_stop";
     pub const DEBUG_CONSOLE: &str = "debug_console";
     pub const DEBUG_DISCONNECT: &str = "debug_disconnect";
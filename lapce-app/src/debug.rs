@@ -1,21 +1,58 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Display,
     path::{Path, PathBuf},
+    rc::Rc,
     time::Instant,
 };
 
-use floem::reactive::{RwSignal, Scope};
+use base64::{engine::general_purpose, Engine as _};
+use floem::{
+    ext_event::create_ext_action,
+    keyboard::ModifiersState,
+    reactive::{RwSignal, Scope},
+};
+use lapce_core::{
+    command::FocusCommand,
+    encoding::{offset_utf16_to_utf8_str, offset_utf8_to_utf16_str},
+    mode::Mode,
+    selection::Selection,
+};
 use lapce_rpc::{
     dap_types::{
-        DapId, RunDebugConfig, SourceBreakpoint, StackFrame, Stopped, ThreadId,
+        self, CompletionItem, DapId, DebuggerCapabilities, EvaluateResponse,
+        ExceptionBreakpointsFilter, FunctionBreakpoint, Output, ReadMemoryResponse,
+        RunDebugConfig, SourceBreakpoint, StackFrame, Stopped, ThreadId, Variable,
     },
+    proxy::{ProxyResponse, ProxyRpcHandler},
     terminal::TermId,
 };
+use lapce_xi_rope::Rope;
+use lsp_types::{MessageType, ShowMessageParams};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    command::{CommandExecuted, CommandKind, InternalCommand, LapceCommand},
+    config::icon::LapceIcons,
+    editor::EditorData,
+    id::{DapVariableId, DebugConsoleEntryId, EditorId},
+    keypress::{condition::Condition, KeyPressFocus},
+    window_tab::CommonData,
+};
 
 const DEFAULT_RUN_TOML: &str = include_str!("../../defaults/run.toml");
 
+/// The number of entries the debug console keeps, before the oldest ones are
+/// dropped
+const MAX_CONSOLE_ENTRIES: usize = 5000;
+
+/// The number of bytes read for the memory view
+const MEMORY_VIEW_BYTES: usize = 256;
+
+/// The number of bytes on each row of the hex layout
+const HEX_ROW_BYTES: usize = 16;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RunDebugMode {
     Run,
@@ -43,6 +80,16 @@ pub struct RunDebugProcess {
 #[derive(Deserialize, Serialize)]
 pub struct RunDebugConfigs {
     pub configs: Vec<RunDebugConfig>,
+    #[serde(default)]
+    pub compounds: Vec<RunDebugCompound>,
+}
+
+/// Configs that are run or debugged together, like a server and its client
+#[derive(Deserialize, Serialize)]
+pub struct RunDebugCompound {
+    pub name: String,
+    /// The names of the configs
+    pub configs: Vec<String>,
 }
 
 pub fn run_configs(workspace: Option<&Path>) -> Option<RunDebugConfigs> {
@@ -60,46 +107,1062 @@ pub fn run_configs(workspace: Option<&Path>) -> Option<RunDebugConfigs> {
     Some(configs)
 }
 
+/// Replace the variables in the config with their values: `${workspaceFolder}`
+/// (or `${workspace}`), `${file}` for the file of the active editor, and
+/// `${env:NAME}` for an environment variable.
+pub fn resolve_config_variables(
+    config: &mut RunDebugConfig,
+    workspace: Option<&Path>,
+    file: Option<&Path>,
+) {
+    let resolve = |text: &str| resolve_variables(text, workspace, file);
+    config.program = resolve(&config.program);
+    for arg in config.args.iter_mut() {
+        *arg = resolve(arg);
+    }
+    for text in [
+        config.cwd.as_mut(),
+        config.pre_launch.as_mut(),
+        config.host.as_mut(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        *text = resolve(text);
+    }
+    if let Some(env) = config.env.as_mut() {
+        for value in env.values_mut() {
+            *value = resolve(value);
+        }
+    }
+    for value in [config.launch.as_mut(), config.attach.as_mut()]
+        .into_iter()
+        .flatten()
+    {
+        resolve_value_variables(value, &resolve);
+    }
+}
+
+fn resolve_value_variables(value: &mut Value, resolve: &impl Fn(&str) -> String) {
+    match value {
+        Value::String(text) => *text = resolve(text),
+        Value::Array(values) => {
+            for value in values {
+                resolve_value_variables(value, resolve);
+            }
+        }
+        Value::Object(values) => {
+            for value in values.values_mut() {
+                resolve_value_variables(value, resolve);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The text with its variables replaced, where the unknown ones are left as
+/// they are.
+fn resolve_variables(
+    text: &str,
+    workspace: Option<&Path>,
+    file: Option<&Path>,
+) -> String {
+    let mut resolved = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let name = &rest[start + 2..end];
+        let value = match name {
+            "workspaceFolder" | "workspace" => {
+                workspace.map(|path| path.to_string_lossy().to_string())
+            }
+            "file" => file.map(|path| path.to_string_lossy().to_string()),
+            _ => name
+                .strip_prefix("env:")
+                .map(|name| std::env::var(name).unwrap_or_default()),
+        };
+        resolved.push_str(&rest[..start]);
+        resolved.push_str(value.as_deref().unwrap_or(&rest[start..=end]));
+        rest = &rest[end + 1..];
+    }
+    resolved.push_str(rest);
+    resolved
+}
+
 #[derive(Clone)]
 pub struct RunDebugData {
     pub active_term: RwSignal<Option<TermId>>,
+    /// The session selected in the session tree, which the debug controls of
+    /// its terminal act on instead of the terminal's own session
+    pub active_session: RwSignal<Option<DapId>>,
     pub daps: RwSignal<im::HashMap<DapId, DapData>>,
     pub breakpoints: RwSignal<BTreeMap<PathBuf, Vec<LapceBreakpoint>>>,
+    /// The expressions that are evaluated every time the debuggee stops
+    pub watches: RwSignal<im::Vector<WatchExpression>>,
+    pub watch_editor: EditorData,
+    /// The variable whose value is being changed in the variable editor
+    pub editing_variable: RwSignal<Option<DapVariable>>,
+    pub variable_editor: EditorData,
+    /// The breakpoint, by its file and line, whose field is being changed in
+    /// the breakpoint editor
+    pub editing_breakpoint: RwSignal<Option<(PathBuf, usize, BreakpointField)>>,
+    pub breakpoint_editor: EditorData,
+    /// The exception filters that were turned on or off, by their id. The
+    /// other filters use the default of the debug adapter.
+    pub exception_filters: RwSignal<HashMap<String, bool>>,
+    /// The names of the functions to break on
+    pub function_breakpoints: RwSignal<im::Vector<String>>,
+    pub function_breakpoint_editor: EditorData,
+    /// The input that receives the typed text, unless a variable is being
+    /// changed
+    pub active_input: RwSignal<DebugInput>,
+    /// The input of the debug console, where expressions are evaluated
+    pub console_editor: EditorData,
+    /// The expressions evaluated in the debug console, the newest last
+    pub console_history: RwSignal<im::Vector<String>>,
+    /// The position in the history while going through it
+    pub console_history_index: RwSignal<Option<usize>>,
+    pub console_completions: RwSignal<im::Vector<CompletionItem>>,
+    pub console_completion_index: RwSignal<usize>,
+    /// Incremented for every completions request, so that the responses to the
+    /// older requests are ignored
+    console_completion_request: RwSignal<usize>,
+    /// The memory reference whose memory is shown, which is read again every
+    /// time the debuggee stops
+    pub memory_reference: RwSignal<Option<String>>,
+    pub memory_editor: EditorData,
+    /// The memory that was read last, or the error message if it failed
+    pub memory: RwSignal<Option<Result<MemoryDump, String>>>,
+    /// The debug console output of the pre-launch commands, by the session
+    /// they run for, until the session is started and takes it
+    pub pre_launch_output:
+        RwSignal<im::HashMap<DapId, im::Vector<DebugConsoleEntry>>>,
+    pub common: Rc<CommonData>,
+}
+
+impl KeyPressFocus for RunDebugData {
+    fn get_mode(&self) -> Mode {
+        Mode::Insert
+    }
+
+    fn check_condition(&self, condition: Condition) -> bool {
+        match condition {
+            Condition::PanelFocus | Condition::ListFocus | Condition::ModalFocus => {
+                true
+            }
+            Condition::CompletionFocus => self.is_console_completion_shown(),
+            _ => false,
+        }
+    }
+
+    fn run_command(
+        &self,
+        command: &LapceCommand,
+        count: Option<usize>,
+        mods: ModifiersState,
+    ) -> CommandExecuted {
+        match &command.kind {
+            CommandKind::Focus(FocusCommand::ListSelect) => {
+                self.confirm_input();
+                CommandExecuted::Yes
+            }
+            CommandKind::Focus(FocusCommand::ModalClose)
+                if self.editing_variable.with_untracked(|v| v.is_some()) =>
+            {
+                self.editing_variable.set(None);
+                CommandExecuted::Yes
+            }
+            CommandKind::Focus(FocusCommand::ModalClose)
+                if self.editing_breakpoint.with_untracked(|b| b.is_some()) =>
+            {
+                self.editing_breakpoint.set(None);
+                CommandExecuted::Yes
+            }
+            CommandKind::Focus(FocusCommand::ModalClose)
+                if self.is_console_completion_shown() =>
+            {
+                self.console_completions.set(im::Vector::new());
+                CommandExecuted::Yes
+            }
+            CommandKind::Focus(FocusCommand::ListNext)
+                if self.is_console_input() =>
+            {
+                self.console_next(false);
+                CommandExecuted::Yes
+            }
+            CommandKind::Focus(FocusCommand::ListPrevious)
+                if self.is_console_input() =>
+            {
+                self.console_next(true);
+                CommandExecuted::Yes
+            }
+            CommandKind::Edit(_)
+            | CommandKind::Move(_)
+            | CommandKind::MultiSelection(_) => {
+                let executed = self.input_editor().run_command(command, count, mods);
+                if self.is_console_completion_shown() {
+                    // The completions follow the edits, like deleting a
+                    // character, and are hidden when the cursor moves
+                    if let CommandKind::Edit(_) = &command.kind {
+                        self.request_console_completions();
+                    } else {
+                        self.console_completions.set(im::Vector::new());
+                    }
+                }
+                executed
+            }
+            _ => CommandExecuted::No,
+        }
+    }
+
+    fn receive_char(&self, c: &str) {
+        self.input_editor().receive_char(c);
+        if self.is_console_input() {
+            self.request_console_completions();
+        }
+    }
 }
 
 impl RunDebugData {
-    pub fn new(cx: Scope) -> Self {
+    pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
         let active_term = cx.create_rw_signal(None);
+        let active_session = cx.create_rw_signal(None);
         let daps = cx.create_rw_signal(im::HashMap::new());
-        let breakpoints = cx.create_rw_signal(BTreeMap::new());
+        let breakpoints = common.breakpoints;
+        let watches = cx.create_rw_signal(im::Vector::new());
+        let watch_editor =
+            EditorData::new_local(cx, EditorId::next(), common.clone());
+        let editing_variable = cx.create_rw_signal(None);
+        let variable_editor =
+            EditorData::new_local(cx, EditorId::next(), common.clone());
+        let editing_breakpoint = cx.create_rw_signal(None);
+        let breakpoint_editor =
+            EditorData::new_local(cx, EditorId::next(), common.clone());
+        let exception_filters = cx.create_rw_signal(HashMap::new());
+        let function_breakpoints = cx.create_rw_signal(im::Vector::new());
+        let function_breakpoint_editor =
+            EditorData::new_local(cx, EditorId::next(), common.clone());
+        let active_input = cx.create_rw_signal(DebugInput::Watch);
+        let console_editor =
+            EditorData::new_local(cx, EditorId::next(), common.clone());
+        let console_history = cx.create_rw_signal(im::Vector::new());
+        let console_history_index = cx.create_rw_signal(None);
+        let console_completions = cx.create_rw_signal(im::Vector::new());
+        let console_completion_index = cx.create_rw_signal(0);
+        let console_completion_request = cx.create_rw_signal(0);
+        let memory_reference = cx.create_rw_signal(None);
+        let memory_editor =
+            EditorData::new_local(cx, EditorId::next(), common.clone());
+        let memory = cx.create_rw_signal(None);
+        let pre_launch_output = cx.create_rw_signal(im::HashMap::new());
         Self {
             active_term,
+            active_session,
             daps,
             breakpoints,
+            watches,
+            watch_editor,
+            editing_variable,
+            variable_editor,
+            editing_breakpoint,
+            breakpoint_editor,
+            exception_filters,
+            function_breakpoints,
+            function_breakpoint_editor,
+            active_input,
+            console_editor,
+            console_history,
+            console_history_index,
+            console_completions,
+            console_completion_index,
+            console_completion_request,
+            memory_reference,
+            memory_editor,
+            memory,
+            pre_launch_output,
+            common,
         }
     }
 
+    /// Keep the output of the session in the debug console, if it isn't
+    /// started yet, which is the output of its pre-launch command.
+    pub fn output(&self, dap_id: &DapId, output: &Output) {
+        match self.daps.with_untracked(|daps| daps.get(dap_id).cloned()) {
+            Some(dap) => dap.console_output(output),
+            None => self.pre_launch_output.update(|pre_launch_output| {
+                push_console_output(
+                    pre_launch_output.entry(*dap_id).or_default(),
+                    output,
+                );
+            }),
+        }
+    }
+
+    /// Take the output of the pre-launch command of the session, when it's
+    /// started.
+    pub fn take_pre_launch_output(
+        &self,
+        dap_id: &DapId,
+    ) -> Option<im::Vector<DebugConsoleEntry>> {
+        let mut output = None;
+        self.pre_launch_output.update(|pre_launch_output| {
+            output = pre_launch_output.remove(dap_id);
+        });
+        output
+    }
+
     pub fn source_breakpoints(&self) -> HashMap<PathBuf, Vec<SourceBreakpoint>> {
         self.breakpoints
             .get_untracked()
             .iter()
             .map(|(path, breakpoints)| {
-                (
-                    path.to_path_buf(),
-                    breakpoints
-                        .iter()
-                        .map(|b| SourceBreakpoint {
-                            line: b.line + 1,
-                            column: None,
-                            condition: None,
-                            hit_condition: None,
-                            log_message: None,
-                        })
-                        .collect(),
-                )
+                (path.to_path_buf(), to_source_breakpoints(breakpoints))
             })
             .collect()
     }
+
+    /// Add a breakpoint on the line, starting at 0, or remove the one that's
+    /// there.
+    pub fn toggle_breakpoint(&self, path: PathBuf, line: usize) {
+        self.breakpoints.update(|breakpoints| {
+            let file_breakpoints = breakpoints.entry(path.clone()).or_default();
+            if let Some(i) = file_breakpoints.iter().position(|b| b.line == line) {
+                file_breakpoints.remove(i);
+                if file_breakpoints.is_empty() {
+                    breakpoints.remove(&path);
+                }
+            } else {
+                file_breakpoints.push(LapceBreakpoint {
+                    line,
+                    ..Default::default()
+                });
+                file_breakpoints.sort_by_key(|b| b.line);
+            }
+        });
+        self.send_breakpoints(path);
+    }
+
+    pub fn remove_breakpoint(&self, path: PathBuf, line: usize) {
+        self.breakpoints.update(|breakpoints| {
+            if let Some(file_breakpoints) = breakpoints.get_mut(&path) {
+                file_breakpoints.retain(|b| b.line != line);
+                if file_breakpoints.is_empty() {
+                    breakpoints.remove(&path);
+                }
+            }
+        });
+        self.send_breakpoints(path);
+    }
+
+    /// Start changing the field of the breakpoint on the line, which is added
+    /// if there's none yet.
+    pub fn start_editing_breakpoint(
+        &self,
+        path: PathBuf,
+        line: usize,
+        field: BreakpointField,
+    ) {
+        let value = self.breakpoints.with_untracked(|breakpoints| {
+            breakpoints
+                .get(&path)
+                .and_then(|b| b.iter().find(|b| b.line == line))
+                .and_then(|b| b.field(field).cloned())
+                .unwrap_or_default()
+        });
+        set_editor_text(
+            &self.breakpoint_editor,
+            &value,
+            Selection::region(0, value.len()),
+        );
+        self.editing_variable.set(None);
+        self.editing_breakpoint.set(Some((path, line, field)));
+    }
+
+    /// Set the field of the breakpoint on the line, or clear it if the value
+    /// is empty.
+    fn set_breakpoint_field(
+        &self,
+        path: PathBuf,
+        line: usize,
+        field: BreakpointField,
+        value: String,
+    ) {
+        let value = value.trim().to_string();
+        let value = if value.is_empty() { None } else { Some(value) };
+        self.breakpoints.update(|breakpoints| {
+            let file_breakpoints = breakpoints.entry(path.clone()).or_default();
+            match file_breakpoints.iter_mut().find(|b| b.line == line) {
+                Some(breakpoint) => *breakpoint.field_mut(field) = value,
+                None => {
+                    let mut breakpoint = LapceBreakpoint {
+                        line,
+                        ..Default::default()
+                    };
+                    *breakpoint.field_mut(field) = value;
+                    file_breakpoints.push(breakpoint);
+                    file_breakpoints.sort_by_key(|b| b.line);
+                }
+            }
+        });
+        self.send_breakpoints(path);
+    }
+
+    /// Send the breakpoints of the file to the running debug adapters
+    fn send_breakpoints(&self, path: PathBuf) {
+        let breakpoints = self.path_source_breakpoints(&path);
+        for dap_id in self.dap_ids() {
+            self.common.proxy.dap_set_breakpoints(
+                dap_id,
+                path.clone(),
+                breakpoints.clone(),
+            );
+        }
+    }
+
+    /// The breakpoints of a single file, as they're sent to the debug adapter.
+    pub fn path_source_breakpoints(&self, path: &Path) -> Vec<SourceBreakpoint> {
+        self.breakpoints.with_untracked(|breakpoints| {
+            breakpoints
+                .get(path)
+                .map(|breakpoints| to_source_breakpoints(breakpoints))
+                .unwrap_or_default()
+        })
+    }
+
+    /// The exception filters advertised by the running debug adapters
+    pub fn exception_breakpoint_filters(&self) -> Vec<ExceptionBreakpointsFilter> {
+        self.common.dap_capabilities.with(|capabilities| {
+            let mut filters: Vec<ExceptionBreakpointsFilter> = Vec::new();
+            for filter in capabilities
+                .values()
+                .flat_map(|c| c.exception_breakpoint_filters.iter().flatten())
+            {
+                if !filters.iter().any(|f| f.filter == filter.filter) {
+                    filters.push(filter.clone());
+                }
+            }
+            filters
+        })
+    }
+
+    pub fn is_exception_filter_enabled(
+        &self,
+        filter: &ExceptionBreakpointsFilter,
+    ) -> bool {
+        self.exception_filters
+            .with(|filters| filters.get(&filter.filter).copied())
+            .unwrap_or_else(|| filter.default.unwrap_or(false))
+    }
+
+    pub fn toggle_exception_filter(&self, filter: &ExceptionBreakpointsFilter) {
+        let enabled = !self.is_exception_filter_enabled(filter);
+        self.exception_filters.update(|filters| {
+            filters.insert(filter.filter.clone(), enabled);
+        });
+        let exception_filters = self.exception_filters.get_untracked();
+        for dap_id in self.dap_ids() {
+            self.common
+                .proxy
+                .dap_set_exception_breakpoints(dap_id, exception_filters.clone());
+        }
+    }
+
+    pub fn add_function_breakpoint(&self, name: String) {
+        let name = name.trim().to_string();
+        if name.is_empty()
+            || self
+                .function_breakpoints
+                .with_untracked(|breakpoints| breakpoints.contains(&name))
+        {
+            return;
+        }
+        self.function_breakpoints
+            .update(|breakpoints| breakpoints.push_back(name));
+        self.send_function_breakpoints();
+    }
+
+    pub fn remove_function_breakpoint(&self, name: &str) {
+        self.function_breakpoints.update(|breakpoints| {
+            breakpoints.retain(|b| b != name);
+        });
+        self.send_function_breakpoints();
+    }
+
+    /// The function breakpoints, as they're sent to the debug adapter.
+    pub fn source_function_breakpoints(&self) -> Vec<FunctionBreakpoint> {
+        self.function_breakpoints
+            .get_untracked()
+            .into_iter()
+            .map(|name| FunctionBreakpoint {
+                name,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn send_function_breakpoints(&self) {
+        let breakpoints = self.source_function_breakpoints();
+        for dap_id in self.dap_ids() {
+            self.common
+                .proxy
+                .dap_set_function_breakpoints(dap_id, breakpoints.clone());
+        }
+    }
+
+    fn dap_ids(&self) -> Vec<DapId> {
+        self.daps
+            .with_untracked(|daps| daps.keys().copied().collect::<Vec<_>>())
+    }
+
+    /// The debug session of the run and debug terminal that's shown, or the
+    /// child session selected in it
+    pub fn active_dap(&self) -> Option<DapData> {
+        let term_id = self.active_term.get_untracked()?;
+        let active_session = self.active_session.get_untracked();
+        self.daps.with_untracked(|daps| {
+            active_session
+                .and_then(|dap_id| daps.get(&dap_id))
+                .filter(|dap| dap.term_id == term_id)
+                .or_else(|| {
+                    daps.values()
+                        .find(|dap| dap.term_id == term_id && dap.parent.is_none())
+                })
+                .or_else(|| daps.values().find(|dap| dap.term_id == term_id))
+                .cloned()
+        })
+    }
+
+    /// The sessions that were started from the session, and the ones started
+    /// from those, in order with their depth in the session tree
+    pub fn child_sessions(&self, dap_id: DapId) -> Vec<(usize, DapData)> {
+        fn push_children(
+            daps: &im::HashMap<DapId, DapData>,
+            parent: DapId,
+            depth: usize,
+            children: &mut Vec<(usize, DapData)>,
+        ) {
+            let mut direct: Vec<&DapData> = daps
+                .values()
+                .filter(|dap| dap.parent == Some(parent))
+                .collect();
+            direct.sort_by_key(|dap| dap.dap_id.0);
+            for dap in direct {
+                children.push((depth, dap.clone()));
+                push_children(daps, dap.dap_id, depth + 1, children);
+            }
+        }
+
+        let mut children = Vec::new();
+        self.daps
+            .with(|daps| push_children(daps, dap_id, 1, &mut children));
+        children
+    }
+
+    /// The editor that receives the typed text, which is the variable or the
+    /// breakpoint editor while they're used, and the active input otherwise.
+    fn input_editor(&self) -> &EditorData {
+        if self.editing_variable.with_untracked(|v| v.is_some()) {
+            return &self.variable_editor;
+        }
+        if self.editing_breakpoint.with_untracked(|b| b.is_some()) {
+            return &self.breakpoint_editor;
+        }
+        match self.active_input.get_untracked() {
+            DebugInput::Watch => &self.watch_editor,
+            DebugInput::Console => &self.console_editor,
+            DebugInput::FunctionBreakpoint => &self.function_breakpoint_editor,
+            DebugInput::Memory => &self.memory_editor,
+        }
+    }
+
+    fn is_console_input(&self) -> bool {
+        self.editing_variable.with_untracked(|v| v.is_none())
+            && self.editing_breakpoint.with_untracked(|b| b.is_none())
+            && self.active_input.get_untracked() == DebugInput::Console
+    }
+
+    fn is_console_completion_shown(&self) -> bool {
+        self.is_console_input()
+            && self.console_completions.with_untracked(|c| !c.is_empty())
+    }
+
+    fn confirm_input(&self) {
+        if let Some(variable) = self.editing_variable.get_untracked() {
+            let value = take_editor_text(&self.variable_editor);
+            self.editing_variable.set(None);
+            self.set_variable(&variable, value);
+            return;
+        }
+        if let Some((path, line, field)) = self.editing_breakpoint.get_untracked() {
+            let value = take_editor_text(&self.breakpoint_editor);
+            self.editing_breakpoint.set(None);
+            self.set_breakpoint_field(path, line, field, value);
+            return;
+        }
+        match self.active_input.get_untracked() {
+            DebugInput::Watch => {
+                let expression = take_editor_text(&self.watch_editor);
+                self.add_watch(expression);
+            }
+            DebugInput::Console => {
+                if self.is_console_completion_shown() {
+                    let index = self.console_completion_index.get_untracked();
+                    self.apply_console_completion(index);
+                } else {
+                    self.submit_console();
+                }
+            }
+            DebugInput::FunctionBreakpoint => {
+                let name = take_editor_text(&self.function_breakpoint_editor);
+                self.add_function_breakpoint(name);
+            }
+            DebugInput::Memory => {
+                let (reference, _) = editor_text_and_offset(&self.memory_editor);
+                let reference = reference.trim().to_string();
+                if reference.is_empty() {
+                    self.memory_reference.set(None);
+                    self.memory.set(None);
+                } else {
+                    self.memory_reference.set(Some(reference));
+                    self.read_memory();
+                }
+            }
+        }
+    }
+
+    /// Read the memory at the memory reference of the memory view, from the
+    /// debug session that's paused.
+    pub fn read_memory(&self) {
+        let reference = match self.memory_reference.get_untracked() {
+            Some(reference) => reference,
+            None => return,
+        };
+        let dap_id = match self.common.paused_frame.get_untracked() {
+            Some((dap_id, _)) => dap_id,
+            None => return,
+        };
+        let supported = self.common.dap_capabilities.with_untracked(|caps| {
+            caps.get(&dap_id)
+                .and_then(|caps| caps.supports_read_memory_request)
+                .unwrap_or(false)
+        });
+        if !supported {
+            self.memory
+                .set(Some(Err("the debug adapter can't read memory".to_string())));
+            return;
+        }
+
+        let memory = self.memory;
+        let memory_reference = self.memory_reference;
+        let requested = reference.clone();
+        let send = create_ext_action(self.common.scope, move |result| {
+            if memory_reference.with_untracked(|r| r.as_ref() != Some(&requested)) {
+                return;
+            }
+            let dump = match result {
+                Ok(ProxyResponse::DapReadMemoryResponse { resp }) => {
+                    MemoryDump::from_response(resp)
+                }
+                Ok(_) => return,
+                Err(err) => Err(err.message),
+            };
+            memory.set(Some(dump));
+        });
+        self.common.proxy.dap_read_memory(
+            dap_id,
+            reference,
+            0,
+            MEMORY_VIEW_BYTES,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// Start changing the value of the variable, with its current value in
+    /// the variable editor.
+    pub fn start_editing_variable(&self, variable: DapVariable) {
+        let value = variable.value.clone().unwrap_or_default();
+        set_editor_text(
+            &self.variable_editor,
+            &value,
+            Selection::region(0, value.len()),
+        );
+        self.editing_breakpoint.set(None);
+        self.editing_variable.set(Some(variable));
+    }
+
+    fn set_variable(&self, variable: &DapVariable, value: String) {
+        let (dap_id, frame_id) = match self.common.paused_frame.get_untracked() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let debug = self.clone();
+        let internal_command = self.common.internal_command;
+        let send = create_ext_action(self.common.scope, move |result| {
+            match result {
+                Ok(ProxyResponse::DapSetVariableResponse { .. }) => {
+                    // The new value can change other variables and watches too
+                    let dap =
+                        debug.daps.with_untracked(|daps| daps.get(&dap_id).cloned());
+                    if let Some(dap) = dap {
+                        dap.select_frame(&debug.common.proxy, frame_id);
+                    }
+                    debug.evaluate_watches();
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    internal_command.send(InternalCommand::ShowMessage {
+                        title: "Set Value".to_string(),
+                        message: ShowMessageParams {
+                            typ: MessageType::ERROR,
+                            message: err.message,
+                        },
+                    });
+                }
+            }
+        });
+        self.common.proxy.dap_set_variable(
+            dap_id,
+            variable.parent_reference,
+            variable.name.clone(),
+            value,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    pub fn add_watch(&self, expression: String) {
+        let expression = expression.trim().to_string();
+        if expression.is_empty()
+            || self.watches.with_untracked(|watches| {
+                watches.iter().any(|w| w.expression == expression)
+            })
+        {
+            return;
+        }
+        let watch = WatchExpression {
+            expression,
+            value: self.common.scope.create_rw_signal(None),
+        };
+        if let Some((dap_id, frame_id)) = self.common.paused_frame.get_untracked() {
+            self.evaluate_watch(&watch, dap_id, frame_id);
+        }
+        self.watches.update(|watches| watches.push_back(watch));
+    }
+
+    pub fn remove_watch(&self, expression: &str) {
+        let mut removed = None;
+        self.watches.update(|watches| {
+            if let Some(index) =
+                watches.iter().position(|w| w.expression == expression)
+            {
+                removed = Some(watches.remove(index));
+            }
+        });
+        if let Some(watch) = removed {
+            watch.set_value(None);
+        }
+    }
+
+    /// Evaluate the watch expressions in the stack frame that's being
+    /// inspected, or clear their values if the debuggee isn't paused.
+    pub fn evaluate_watches(&self) {
+        let paused_frame = self.common.paused_frame.get_untracked();
+        for watch in self.watches.get_untracked() {
+            match paused_frame {
+                Some((dap_id, frame_id)) => {
+                    self.evaluate_watch(&watch, dap_id, frame_id)
+                }
+                None => watch.set_value(None),
+            }
+        }
+    }
+
+    fn evaluate_watch(
+        &self,
+        watch: &WatchExpression,
+        dap_id: DapId,
+        frame_id: usize,
+    ) {
+        let cx = self.common.scope;
+        let local_watch = watch.clone();
+        let expression = watch.expression.clone();
+        let send = create_ext_action(cx, move |result| {
+            let result = match result {
+                Ok(ProxyResponse::DapEvaluateResponse { resp }) => {
+                    Ok(DapVariable::evaluated(cx, expression, resp))
+                }
+                Ok(_) => return,
+                Err(err) => Err(err.message),
+            };
+            local_watch.set_value(Some(result));
+        });
+        self.common.proxy.dap_evaluate(
+            dap_id,
+            watch.expression.clone(),
+            Some(frame_id),
+            Some("watch".to_string()),
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// Evaluate the expression in the debug console, in the stack frame that's
+    /// being inspected if the debuggee is paused.
+    fn submit_console(&self) {
+        let expression = take_editor_text(&self.console_editor);
+        self.console_history_index.set(None);
+        self.console_completions.set(im::Vector::new());
+        let expression = expression.trim().to_string();
+        if expression.is_empty() {
+            return;
+        }
+        let dap = match self.active_dap() {
+            Some(dap) => dap,
+            None => return,
+        };
+        self.console_history.update(|history| {
+            if history.back() != Some(&expression) {
+                history.push_back(expression.clone());
+            }
+        });
+        dap.push_console(DebugConsoleKind::Input, expression.clone(), None);
+
+        let frame_id = if dap.stopped.get_untracked() {
+            dap.active_frame.get_untracked()
+        } else {
+            None
+        };
+        let cx = self.common.scope;
+        let debug = self.clone();
+        let dap_id = dap.dap_id;
+        let local_expression = expression.clone();
+        let send = create_ext_action(cx, move |result| match result {
+            Ok(ProxyResponse::DapEvaluateResponse { resp }) => {
+                let variable = DapVariable::evaluated(cx, local_expression, resp);
+                let text = variable.value.clone().unwrap_or_default();
+                let variable = if variable.has_children() {
+                    Some(variable)
+                } else {
+                    variable.dispose();
+                    None
+                };
+                dap.push_console(DebugConsoleKind::Result, text, variable);
+                // The expression can have changed the state of the debuggee
+                if let Some(frame_id) = frame_id {
+                    dap.select_frame(&debug.common.proxy, frame_id);
+                    debug.evaluate_watches();
+                }
+            }
+            Ok(_) => {}
+            Err(err) => {
+                dap.push_console(DebugConsoleKind::Error, err.message, None);
+            }
+        });
+        self.common.proxy.dap_evaluate(
+            dap_id,
+            expression,
+            frame_id,
+            Some("repl".to_string()),
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// Select the next or previous completion if they're shown, or go through
+    /// the history of the debug console otherwise.
+    fn console_next(&self, previous: bool) {
+        let len = self.console_completions.with_untracked(|c| c.len());
+        if len > 0 {
+            self.console_completion_index.update(|index| {
+                *index = if previous {
+                    (*index + len - 1) % len
+                } else {
+                    (*index + 1) % len
+                };
+            });
+            return;
+        }
+
+        let history = self.console_history.get_untracked();
+        if history.is_empty() {
+            return;
+        }
+        let index = match (self.console_history_index.get_untracked(), previous) {
+            (None, true) => Some(history.len() - 1),
+            (None, false) => return,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index + 1 < history.len() => Some(index + 1),
+            // Going past the newest entry gets back to an empty input
+            (Some(_), false) => None,
+        };
+        self.console_history_index.set(index);
+        let text = index
+            .and_then(|index| history.get(index).cloned())
+            .unwrap_or_default();
+        set_editor_text(&self.console_editor, &text, Selection::caret(text.len()));
+    }
+
+    fn request_console_completions(&self) {
+        let dap = match self.active_dap() {
+            Some(dap) => dap,
+            None => return,
+        };
+        let (text, offset) = editor_text_and_offset(&self.console_editor);
+        let before = &text[..offset];
+        if before.trim().is_empty() || before.ends_with(char::is_whitespace) {
+            self.console_completions.set(im::Vector::new());
+            return;
+        }
+
+        let frame_id = if dap.stopped.get_untracked() {
+            dap.active_frame.get_untracked()
+        } else {
+            None
+        };
+        let column = offset_utf8_to_utf16_str(&text, offset) + 1;
+        let request_id = self.console_completion_request.get_untracked() + 1;
+        self.console_completion_request.set(request_id);
+
+        let debug = self.clone();
+        let send = create_ext_action(self.common.scope, move |result| {
+            if debug.console_completion_request.get_untracked() != request_id {
+                return;
+            }
+            if let Ok(ProxyResponse::DapCompletionsResponse { targets }) = result {
+                debug.set_console_completions(targets);
+            }
+        });
+        self.common.proxy.dap_completions(
+            dap.dap_id,
+            frame_id,
+            text,
+            column,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// Show the completions that match the word before the cursor
+    fn set_console_completions(&self, targets: Vec<CompletionItem>) {
+        let (text, offset) = editor_text_and_offset(&self.console_editor);
+        let prefix = text[word_start(&text, offset)..offset].to_lowercase();
+        let mut completions = targets
+            .into_iter()
+            .filter(|item| item.label.to_lowercase().starts_with(&prefix))
+            .collect::<Vec<_>>();
+        completions.sort_by(|a, b| {
+            let a = a.sort_text.as_ref().unwrap_or(&a.label);
+            let b = b.sort_text.as_ref().unwrap_or(&b.label);
+            a.cmp(b)
+        });
+        self.console_completion_index.set(0);
+        self.console_completions.set(completions.into());
+    }
+
+    pub fn apply_console_completion(&self, index: usize) {
+        let item = match self
+            .console_completions
+            .with_untracked(|completions| completions.get(index).cloned())
+        {
+            Some(item) => item,
+            None => return,
+        };
+        self.console_completions.set(im::Vector::new());
+
+        let (text, offset) = editor_text_and_offset(&self.console_editor);
+        // The start and the length are in utf16, and the start is 1 based. If
+        // there's no start, the word before the cursor is replaced.
+        let (start, end) = match item.start {
+            Some(start) => {
+                let start = start.saturating_sub(1);
+                (
+                    offset_utf16_to_utf8_str(&text, start),
+                    offset_utf16_to_utf8_str(
+                        &text,
+                        start + item.length.unwrap_or(0),
+                    ),
+                )
+            }
+            None => (word_start(&text, offset), offset),
+        };
+        let end = end.max(start);
+        let insert = item.text.unwrap_or(item.label);
+        let new_text = format!("{}{}{}", &text[..start], insert, &text[end..]);
+        let selection = match item.selection_start {
+            Some(selection_start) => Selection::region(
+                start + offset_utf16_to_utf8_str(&insert, selection_start),
+                start
+                    + offset_utf16_to_utf8_str(
+                        &insert,
+                        selection_start + item.selection_length.unwrap_or(0),
+                    ),
+            ),
+            None => Selection::caret(start + insert.len()),
+        };
+        set_editor_text(&self.console_editor, &new_text, selection);
+    }
+}
+
+fn take_editor_text(editor: &EditorData) -> String {
+    let (text, _) = editor_text_and_offset(editor);
+    set_editor_text(editor, "", Selection::caret(0));
+    text
+}
+
+fn set_editor_text(editor: &EditorData, text: &str, selection: Selection) {
+    editor
+        .view
+        .doc
+        .get_untracked()
+        .reload(Rope::from(text), true);
+    editor.cursor.update(|cursor| cursor.set_insert(selection));
+}
+
+fn editor_text_and_offset(editor: &EditorData) -> (String, usize) {
+    let text = editor
+        .view
+        .doc
+        .get_untracked()
+        .buffer
+        .with_untracked(|buffer| buffer.to_string());
+    let offset = editor
+        .cursor
+        .with_untracked(|cursor| cursor.offset())
+        .min(text.len());
+    (text, offset)
+}
+
+/// The start of the word that ends at `offset`
+fn word_start(text: &str, offset: usize) -> usize {
+    text[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(offset)
+}
+
+fn to_source_breakpoints(breakpoints: &[LapceBreakpoint]) -> Vec<SourceBreakpoint> {
+    breakpoints
+        .iter()
+        .map(|b| SourceBreakpoint {
+            line: b.line + 1,
+            column: None,
+            condition: b.condition.clone(),
+            hit_condition: b.hit_condition.clone(),
+            log_message: b.log_message.clone(),
+        })
+        .collect()
 }
 
 #[derive(Clone, PartialEq)]
@@ -109,36 +1172,156 @@ pub struct StackTraceData {
     pub frames_shown: usize,
 }
 
-#[derive(Clone)]
+/// A breakpoint of the workspace. Only the line and what's set by the user are
+/// kept with the workspace, the rest comes from the debug adapter.
+#[derive(Clone, Default, Deserialize, Serialize)]
 pub struct LapceBreakpoint {
+    #[serde(skip)]
     pub id: Option<usize>,
+    #[serde(skip)]
     pub verified: bool,
+    #[serde(skip)]
     pub message: Option<String>,
     pub line: usize,
+    #[serde(skip)]
     pub offset: usize,
+    #[serde(skip)]
     pub dap_line: Option<usize>,
+    /// The expression that has to be true for the debuggee to stop
+    #[serde(default)]
+    pub condition: Option<String>,
+    /// The number of hits before the debuggee stops, as interpreted by the
+    /// debug adapter
+    #[serde(default)]
+    pub hit_condition: Option<String>,
+    /// Makes it a logpoint, which logs the message instead of stopping
+    #[serde(default)]
+    pub log_message: Option<String>,
+}
+
+impl LapceBreakpoint {
+    pub fn field(&self, field: BreakpointField) -> Option<&String> {
+        match field {
+            BreakpointField::Condition => self.condition.as_ref(),
+            BreakpointField::HitCondition => self.hit_condition.as_ref(),
+            BreakpointField::LogMessage => self.log_message.as_ref(),
+        }
+    }
+
+    fn field_mut(&mut self, field: BreakpointField) -> &mut Option<String> {
+        match field {
+            BreakpointField::Condition => &mut self.condition,
+            BreakpointField::HitCondition => &mut self.hit_condition,
+            BreakpointField::LogMessage => &mut self.log_message,
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        if self.log_message.is_some() {
+            LapceIcons::DEBUG_BREAKPOINT_LOG
+        } else if self.condition.is_some() || self.hit_condition.is_some() {
+            LapceIcons::DEBUG_BREAKPOINT_CONDITIONAL
+        } else {
+            LapceIcons::DEBUG_BREAKPOINT
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BreakpointField {
+    Condition,
+    HitCondition,
+    LogMessage,
+}
+
+impl BreakpointField {
+    pub fn title(&self) -> &'static str {
+        match self {
+            BreakpointField::Condition => "Condition",
+            BreakpointField::HitCondition => "Hit Count",
+            BreakpointField::LogMessage => "Log Message",
+        }
+    }
+
+    /// Whether the debug adapters support it. It's assumed to be supported
+    /// until a debug adapter that doesn't is started.
+    pub fn is_supported(
+        &self,
+        capabilities: &im::HashMap<DapId, DebuggerCapabilities>,
+    ) -> bool {
+        capabilities.values().all(|c| {
+            match self {
+                BreakpointField::Condition => c.supports_conditional_breakpoints,
+                BreakpointField::HitCondition => {
+                    c.supports_hit_conditional_breakpoints
+                }
+                BreakpointField::LogMessage => c.supports_log_points,
+            }
+            .unwrap_or(false)
+        })
+    }
 }
 
 #[derive(Clone)]
 pub struct DapData {
     pub term_id: TermId,
     pub dap_id: DapId,
+    /// The session that started this one, whose terminal it shares unless the
+    /// debug adapter asked for its own
+    pub parent: Option<DapId>,
+    pub name: String,
     pub stopped: RwSignal<bool>,
     pub thread_id: RwSignal<Option<ThreadId>>,
     pub stack_traces: RwSignal<BTreeMap<ThreadId, StackTraceData>>,
+    /// The file of the breakpoint that was added to run to the cursor, which is
+    /// removed again the next time the debuggee stops
+    pub temporary_breakpoint: RwSignal<Option<PathBuf>>,
+    /// The stack frame whose variables are shown
+    pub active_frame: RwSignal<Option<usize>>,
+    pub scopes: RwSignal<im::Vector<DapVariable>>,
+    /// The scope the signals of `scopes` and of their variables are created
+    /// in, which is disposed when they're replaced
+    scopes_cx: RwSignal<Option<Scope>>,
+    /// The expanded scopes and variables when the variables tree was cleared,
+    /// to expand them again the next time the debuggee stops
+    cleared_expanded: RwSignal<HashSet<Vec<String>>>,
+    /// The output of the debuggee and the evaluations of the debug console
+    pub console: RwSignal<im::Vector<DebugConsoleEntry>>,
+    cx: Scope,
 }
 
 impl DapData {
-    pub fn new(cx: Scope, dap_id: DapId, term_id: TermId) -> Self {
+    pub fn new(
+        cx: Scope,
+        dap_id: DapId,
+        term_id: TermId,
+        parent: Option<DapId>,
+        name: String,
+    ) -> Self {
         let stopped = cx.create_rw_signal(false);
         let thread_id = cx.create_rw_signal(None);
         let stack_traces = cx.create_rw_signal(BTreeMap::new());
+        let temporary_breakpoint = cx.create_rw_signal(None);
+        let active_frame = cx.create_rw_signal(None);
+        let scopes = cx.create_rw_signal(im::Vector::new());
+        let scopes_cx = cx.create_rw_signal(None);
+        let cleared_expanded = cx.create_rw_signal(HashSet::new());
+        let console = cx.create_rw_signal(im::Vector::new());
         Self {
             term_id,
             dap_id,
+            parent,
+            name,
             stopped,
             thread_id,
             stack_traces,
+            temporary_breakpoint,
+            active_frame,
+            scopes,
+            scopes_cx,
+            cleared_expanded,
+            console,
+            cx,
         }
     }
 
@@ -176,4 +1359,771 @@ pub fn stopped(
             }
         });
     }
+
+    /// The memory reference of the instruction the stack frame is at.
+    pub fn frame_instruction_pointer(&self, frame_id: usize) -> Option<String> {
+        self.stack_traces.with_untracked(|stack_traces| {
+            stack_traces.values().find_map(|stack_trace| {
+                stack_trace.frames.with_untracked(|frames| {
+                    frames.iter().find(|frame| frame.id == frame_id).and_then(
+                        |frame| frame.instruction_pointer_reference.clone(),
+                    )
+                })
+            })
+        })
+    }
+
+    /// The top stack frame of the thread that stopped
+    pub fn top_frame(&self) -> Option<usize> {
+        let thread_id = self.thread_id.get_untracked()?;
+        self.stack_traces.with_untracked(|stack_traces| {
+            stack_traces
+                .get(&thread_id)?
+                .frames
+                .with_untracked(|frames| frames.front().map(|frame| frame.id))
+        })
+    }
+
+    /// The file and the line of the stack frame, with the values of the
+    /// variables of its scopes that were fetched, the innermost scope first.
+    pub fn frame_values(
+        &self,
+        frame_id: usize,
+    ) -> Option<(PathBuf, usize, HashMap<String, String>)> {
+        let frame = self.stack_traces.with(|stack_traces| {
+            stack_traces.values().find_map(|stack_trace| {
+                stack_trace.frames.with(|frames| {
+                    frames.iter().find(|frame| frame.id == frame_id).cloned()
+                })
+            })
+        })?;
+        let path = frame.source.as_ref()?.path.clone()?;
+
+        let mut values = HashMap::new();
+        for scope in self.scopes.get() {
+            if let Some(variables) = scope.children.get() {
+                for variable in variables {
+                    if let Some(value) = variable.value {
+                        values.entry(variable.name).or_insert(value);
+                    }
+                }
+            }
+        }
+        Some((path, frame.line.saturating_sub(1), values))
+    }
+
+    /// Show the variables of the stack frame. The variables that were expanded
+    /// stay expanded if the frame still has them, so that they can be followed
+    /// while stepping.
+    pub fn select_frame(&self, proxy: &ProxyRpcHandler, frame_id: usize) {
+        let mut expanded = HashSet::new();
+        self.scopes
+            .with_untracked(|scopes| expanded_paths(scopes, &[], &mut expanded));
+        if self.scopes.with_untracked(|scopes| scopes.is_empty()) {
+            expanded = self.cleared_expanded.get_untracked();
+        }
+        let expanded = Rc::new(expanded);
+
+        self.active_frame.set(Some(frame_id));
+        let active_frame = self.active_frame;
+        let dap = self.clone();
+        let dap_id = self.dap_id;
+        let local_proxy = proxy.clone();
+        let send = create_ext_action(self.cx, move |result| {
+            if active_frame.get_untracked() != Some(frame_id) {
+                return;
+            }
+            if let Ok(ProxyResponse::DapScopesResponse { scopes: new_scopes }) =
+                result
+            {
+                let cx = dap.cx.create_child();
+                let new_scopes = new_scopes
+                    .into_iter()
+                    .map(|scope| DapVariable::scope(cx, scope))
+                    .collect::<im::Vector<_>>();
+                for (i, scope) in new_scopes.iter().enumerate() {
+                    // Without anything expanded before, the first scope is
+                    // expanded, which usually has the local variables
+                    if expanded.is_empty() && i == 0 && !scope.expensive {
+                        scope.expanded.set(true);
+                        scope.load_children(
+                            &local_proxy,
+                            dap_id,
+                            expanded.clone(),
+                            vec![scope.name.clone()],
+                        );
+                    } else {
+                        scope.restore_expanded(&local_proxy, dap_id, &expanded, &[]);
+                    }
+                }
+                dap.set_scopes(new_scopes, Some(cx));
+            }
+        });
+        proxy.dap_scopes(dap_id, frame_id, move |result| {
+            send(result);
+        });
+    }
+
+    /// Clear the variables tree, when the debuggee continues or the session
+    /// ends.
+    pub fn clear_scopes(&self) {
+        if self.scopes.with_untracked(|scopes| scopes.is_empty()) {
+            return;
+        }
+        let mut expanded = HashSet::new();
+        self.scopes
+            .with_untracked(|scopes| expanded_paths(scopes, &[], &mut expanded));
+        self.cleared_expanded.set(expanded);
+        self.set_scopes(im::Vector::new(), None);
+    }
+
+    fn set_scopes(&self, scopes: im::Vector<DapVariable>, cx: Option<Scope>) {
+        self.scopes.set(scopes);
+        // Disposed after the views of the old variables are gone
+        if let Some(old_cx) = self.scopes_cx.get_untracked() {
+            old_cx.dispose();
+        }
+        self.scopes_cx.set(cx);
+    }
+
+    /// Dispose the signals of the variables of the session, when it's gone.
+    pub fn dispose_variables(&self) {
+        self.clear_scopes();
+        self.console.with_untracked(|console| {
+            for entry in console.iter() {
+                if let Some(variable) = entry.variable.as_ref() {
+                    variable.dispose();
+                }
+            }
+        });
+    }
+
+    /// Add the output of an `output` event to the debug console
+    pub fn console_output(&self, output: &Output) {
+        self.console
+            .update(|console| push_console_output(console, output));
+    }
+
+    pub fn push_console(
+        &self,
+        kind: DebugConsoleKind,
+        text: String,
+        variable: Option<DapVariable>,
+    ) {
+        self.console.update(|console| {
+            console.push_back(DebugConsoleEntry {
+                id: DebugConsoleEntryId::next(),
+                kind,
+                text,
+                variable,
+            });
+            while console.len() > MAX_CONSOLE_ENTRIES {
+                if let Some(variable) =
+                    console.pop_front().and_then(|entry| entry.variable)
+                {
+                    variable.dispose();
+                }
+            }
+        });
+    }
+}
+
+/// A scope or a variable in the variables tree of the debug panel
+#[derive(Clone)]
+pub struct DapVariable {
+    pub id: DapVariableId,
+    pub name: String,
+    /// The value of the variable, which a scope doesn't have
+    pub value: Option<String>,
+    pub ty: Option<String>,
+    /// The `variablesReference` of the scope or variable that contains this
+    /// variable, which is needed to change its value. It's 0 for the scopes and
+    /// the evaluated expressions, whose value can't be changed.
+    pub parent_reference: usize,
+    /// Non zero if it has children, which are fetched when it's expanded
+    pub variables_reference: usize,
+    /// Whether it's expensive for the debug adapter to get the children
+    pub expensive: bool,
+    pub expanded: RwSignal<bool>,
+    pub children: RwSignal<Option<im::Vector<DapVariable>>>,
+    /// The scope of the signals of the variable and of its children
+    cx: Scope,
+}
+
+impl DapVariable {
+    fn scope(cx: Scope, scope: dap_types::Scope) -> Self {
+        Self {
+            id: DapVariableId::next(),
+            name: scope.name,
+            value: None,
+            ty: None,
+            parent_reference: 0,
+            variables_reference: scope.variables_reference,
+            expensive: scope.expensive,
+            expanded: cx.create_rw_signal(false),
+            children: cx.create_rw_signal(None),
+            cx,
+        }
+    }
+
+    fn variable(cx: Scope, parent_reference: usize, variable: Variable) -> Self {
+        Self {
+            id: DapVariableId::next(),
+            name: variable.name,
+            value: Some(variable.value),
+            ty: variable.ty,
+            parent_reference,
+            variables_reference: variable.variables_reference,
+            expensive: false,
+            expanded: cx.create_rw_signal(false),
+            children: cx.create_rw_signal(None),
+            cx,
+        }
+    }
+
+    /// The result of an evaluation, in its own child scope of `cx` which is
+    /// disposed with [`DapVariable::dispose`] when the result is replaced.
+    pub fn evaluated(cx: Scope, expression: String, resp: EvaluateResponse) -> Self {
+        let cx = cx.create_child();
+        Self {
+            id: DapVariableId::next(),
+            name: expression,
+            value: Some(resp.result),
+            ty: resp.ty,
+            parent_reference: 0,
+            variables_reference: resp.variables_reference,
+            expensive: false,
+            expanded: cx.create_rw_signal(false),
+            children: cx.create_rw_signal(None),
+            cx,
+        }
+    }
+
+    pub fn has_children(&self) -> bool {
+        self.variables_reference > 0
+    }
+
+    /// Whether the value can be edited, which also needs the debug adapter to
+    /// support `setVariable`.
+    pub fn can_set_value(
+        &self,
+        capabilities: Option<&DebuggerCapabilities>,
+    ) -> bool {
+        self.parent_reference > 0
+            && capabilities
+                .and_then(|c| c.supports_set_variable)
+                .unwrap_or(false)
+    }
+
+    /// Dispose the signals of an evaluated expression and of its children.
+    pub fn dispose(&self) {
+        self.cx.dispose();
+    }
+
+    pub fn toggle_expanded(&self, proxy: &ProxyRpcHandler, dap_id: DapId) {
+        let expanded = !self.expanded.get_untracked();
+        self.expanded.set(expanded);
+        if expanded && self.children.with_untracked(|c| c.is_none()) {
+            self.load_children(proxy, dap_id, Rc::new(HashSet::new()), Vec::new());
+        }
+    }
+
+    /// Fetch the children, and expand again the ones in `expanded`. `path` is
+    /// the names from the scope down to this variable.
+    fn load_children(
+        &self,
+        proxy: &ProxyRpcHandler,
+        dap_id: DapId,
+        expanded: Rc<HashSet<Vec<String>>>,
+        path: Vec<String>,
+    ) {
+        if !self.has_children() {
+            return;
+        }
+        let cx = self.cx;
+        let children = self.children;
+        let parent_reference = self.variables_reference;
+        let local_proxy = proxy.clone();
+        let send = create_ext_action(cx, move |result| {
+            if let Ok(ProxyResponse::DapVariablesResponse { variables }) = result {
+                let variables = variables
+                    .into_iter()
+                    .map(|v| DapVariable::variable(cx, parent_reference, v))
+                    .collect::<im::Vector<_>>();
+                for variable in variables.iter() {
+                    variable.restore_expanded(
+                        &local_proxy,
+                        dap_id,
+                        &expanded,
+                        &path,
+                    );
+                }
+                children.set(Some(variables));
+            }
+        });
+        proxy.dap_variables(dap_id, parent_reference, move |result| {
+            send(result);
+        });
+    }
+
+    fn restore_expanded(
+        &self,
+        proxy: &ProxyRpcHandler,
+        dap_id: DapId,
+        expanded: &Rc<HashSet<Vec<String>>>,
+        parent_path: &[String],
+    ) {
+        let mut path = parent_path.to_vec();
+        path.push(self.name.clone());
+        if expanded.contains(&path) {
+            self.expanded.set(true);
+            self.load_children(proxy, dap_id, expanded.clone(), path);
+        }
+    }
+}
+
+/// Collect the name paths of the expanded scopes and variables
+fn expanded_paths(
+    variables: &im::Vector<DapVariable>,
+    parent_path: &[String],
+    paths: &mut HashSet<Vec<String>>,
+) {
+    for variable in variables.iter() {
+        if variable.expanded.get_untracked() {
+            let mut path = parent_path.to_vec();
+            path.push(variable.name.clone());
+            variable.children.with_untracked(|children| {
+                if let Some(children) = children {
+                    expanded_paths(children, &path, paths);
+                }
+            });
+            paths.insert(path);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WatchExpression {
+    pub expression: String,
+    /// The result of the last evaluation, or the error message if it failed
+    pub value: RwSignal<Option<Result<DapVariable, String>>>,
+}
+
+impl WatchExpression {
+    /// Replace the result, disposing the signals of the previous one.
+    fn set_value(&self, value: Option<Result<DapVariable, String>>) {
+        let old = self.value.get_untracked();
+        self.value.set(value);
+        if let Some(Ok(old)) = old {
+            old.dispose();
+        }
+    }
+}
+
+/// The bytes read from the memory of the debuggee
+#[derive(Clone, PartialEq, Eq)]
+pub struct MemoryDump {
+    pub address: u64,
+    pub bytes: Vec<u8>,
+    /// The number of bytes after `bytes` that couldn't be read
+    pub unreadable_bytes: usize,
+}
+
+impl MemoryDump {
+    fn from_response(resp: ReadMemoryResponse) -> Result<Self, String> {
+        let address = parse_address(&resp.address)
+            .ok_or_else(|| format!("invalid address {}", resp.address))?;
+        let mut bytes = match resp.data {
+            Some(data) => general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| e.to_string())?,
+            None => Vec::new(),
+        };
+        // Only what was requested is shown, whatever the debug adapter sends
+        bytes.truncate(MEMORY_VIEW_BYTES);
+        let unreadable_bytes = resp
+            .unreadable_bytes
+            .unwrap_or(0)
+            .min(MEMORY_VIEW_BYTES - bytes.len());
+        Ok(Self {
+            address,
+            bytes,
+            unreadable_bytes,
+        })
+    }
+
+    /// The rows of the hex layout, with the address of the row, the bytes in
+    /// hex and the bytes as ASCII. Unreadable bytes are shown as `??`.
+    pub fn rows(&self) -> Vec<(String, String, String)> {
+        let bytes = self
+            .bytes
+            .iter()
+            .map(|b| Some(*b))
+            .chain(std::iter::repeat(None).take(self.unreadable_bytes))
+            .collect::<Vec<_>>();
+        bytes
+            .chunks(HEX_ROW_BYTES)
+            .enumerate()
+            .map(|(i, row)| {
+                // Wraps around at the top of the address space
+                let address = self.address.wrapping_add((i * HEX_ROW_BYTES) as u64);
+                let hex = row
+                    .iter()
+                    .map(|b| match b {
+                        Some(b) => format!("{b:02x}"),
+                        None => "??".to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                // The last row is padded so that its ASCII lines up
+                let hex = format!("{hex:<width$}", width = HEX_ROW_BYTES * 3 - 1);
+                let ascii = row
+                    .iter()
+                    .map(|b| match b {
+                        Some(b) if b.is_ascii_graphic() || *b == b' ' => *b as char,
+                        _ => '.',
+                    })
+                    .collect::<String>();
+                (format!("{address:016x}"), hex, ascii)
+            })
+            .collect()
+    }
+}
+
+/// Parse an address reported by the debug adapter, which is either in hex with
+/// a `0x` prefix, or decimal.
+pub fn parse_address(address: &str) -> Option<u64> {
+    let address = address.trim();
+    match address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => address.parse().ok(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugInput {
+    Watch,
+    Console,
+    FunctionBreakpoint,
+    /// The memory reference of the memory view
+    Memory,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugConsoleKind {
+    Stdout,
+    Stderr,
+    Console,
+    Important,
+    /// An expression evaluated in the debug console
+    Input,
+    Result,
+    Error,
+}
+
+/// Add the output event to the debug console, continuing the last entry if
+/// it's a line that isn't finished yet.
+fn push_console_output(
+    console: &mut im::Vector<DebugConsoleEntry>,
+    output: &Output,
+) {
+    let kind = match output.category.as_deref() {
+        Some("stdout") => DebugConsoleKind::Stdout,
+        Some("stderr") => DebugConsoleKind::Stderr,
+        Some("important") => DebugConsoleKind::Important,
+        _ => DebugConsoleKind::Console,
+    };
+    // A line of the output can come in several events
+    if let Some(last) = console.back_mut() {
+        if last.kind == kind && !last.text.ends_with('\n') {
+            last.text.push_str(&output.output);
+            return;
+        }
+    }
+    console.push_back(DebugConsoleEntry {
+        id: DebugConsoleEntryId::next(),
+        kind,
+        text: output.output.clone(),
+        variable: None,
+    });
+    while console.len() > MAX_CONSOLE_ENTRIES {
+        console.pop_front();
+    }
+}
+
+#[derive(Clone)]
+pub struct DebugConsoleEntry {
+    pub id: DebugConsoleEntryId,
+    pub kind: DebugConsoleKind,
+    pub text: String,
+    /// The result of an evaluation that has children, which can be expanded
+    pub variable: Option<DapVariable>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lapce_rpc::dap_types::{Output, ReadMemoryResponse, RunDebugConfig};
+    use serde_json::json;
+
+    use super::{
+        parse_address, push_console_output, resolve_config_variables,
+        resolve_variables, DebugConsoleKind, MemoryDump, HEX_ROW_BYTES,
+        MAX_CONSOLE_ENTRIES, MEMORY_VIEW_BYTES,
+    };
+
+    fn output(category: Option<&str>, text: &str) -> Output {
+        Output {
+            output: text.to_string(),
+            category: category.map(|c| c.to_string()),
+            group: None,
+            line: None,
+            column: None,
+            variables_reference: None,
+            source: None,
+            data: None,
+        }
+    }
+
+    #[test]
+    fn resolve_known_variables() {
+        let workspace = Some(Path::new("/work"));
+        let file = Some(Path::new("/work/src/main.rs"));
+
+        assert_eq!(
+            resolve_variables("${workspaceFolder}/target", workspace, file),
+            "/work/target"
+        );
+        assert_eq!(resolve_variables("${workspace}", workspace, file), "/work");
+        assert_eq!(
+            resolve_variables("run ${file} now", workspace, file),
+            "run /work/src/main.rs now"
+        );
+        assert_eq!(
+            resolve_variables("${workspace}:${file}", workspace, file),
+            "/work:/work/src/main.rs"
+        );
+        assert_eq!(resolve_variables("plain", workspace, file), "plain");
+    }
+
+    #[test]
+    fn resolve_env_variables() {
+        std::env::set_var("LAPCE_TEST_RESOLVE_VARIABLES", "value");
+
+        assert_eq!(
+            resolve_variables("a ${env:LAPCE_TEST_RESOLVE_VARIABLES} b", None, None),
+            "a value b"
+        );
+        // A variable that isn't set is empty
+        assert_eq!(
+            resolve_variables("[${env:LAPCE_TEST_UNSET_VARIABLE}]", None, None),
+            "[]"
+        );
+    }
+
+    #[test]
+    fn keep_unresolved_variables() {
+        // Unknown variables, and the ones without a value, are left as they are
+        assert_eq!(
+            resolve_variables("${unknown}/a", None, None),
+            "${unknown}/a"
+        );
+        assert_eq!(
+            resolve_variables("${workspace}", None, None),
+            "${workspace}"
+        );
+        assert_eq!(resolve_variables("${file}", None, None), "${file}");
+        // So is an unterminated variable
+        assert_eq!(
+            resolve_variables("${workspace}/${file", Some(Path::new("/w")), None),
+            "/w/${file"
+        );
+        assert_eq!(resolve_variables("${", None, None), "${");
+    }
+
+    #[test]
+    fn resolve_config() {
+        let mut config: RunDebugConfig = toml::from_str(
+            r#"
+                name = "test"
+                program = "${workspaceFolder}/target/debug/app"
+                args = ["${file}", "--flag"]
+                cwd = "${workspace}/sub"
+                pre_launch = "cargo build --manifest-path ${workspace}/Cargo.toml"
+                host = "${unknown}"
+                env = { FILE = "${file}", OTHER = "value" }
+            "#,
+        )
+        .unwrap();
+        config.launch = Some(json!({
+            "stopOnEntry": true,
+            "sourceMap": { "/src": "${workspace}/src" },
+            "paths": ["${file}", 1],
+        }));
+        config.attach = Some(json!("${workspace}"));
+
+        resolve_config_variables(
+            &mut config,
+            Some(Path::new("/work")),
+            Some(Path::new("/work/main.rs")),
+        );
+
+        assert_eq!(config.program, "/work/target/debug/app");
+        assert_eq!(config.args, vec!["/work/main.rs", "--flag"]);
+        assert_eq!(config.cwd.as_deref(), Some("/work/sub"));
+        assert_eq!(
+            config.pre_launch.as_deref(),
+            Some("cargo build --manifest-path /work/Cargo.toml")
+        );
+        assert_eq!(config.host.as_deref(), Some("${unknown}"));
+        let env = config.env.unwrap();
+        assert_eq!(env["FILE"], "/work/main.rs");
+        assert_eq!(env["OTHER"], "value");
+        assert_eq!(
+            config.launch,
+            Some(json!({
+                "stopOnEntry": true,
+                "sourceMap": { "/src": "/work/src" },
+                "paths": ["/work/main.rs", 1],
+            }))
+        );
+        assert_eq!(config.attach, Some(json!("/work")));
+    }
+
+    #[test]
+    fn parse_addresses() {
+        assert_eq!(parse_address("0x10"), Some(16));
+        assert_eq!(parse_address("0XfF"), Some(255));
+        assert_eq!(parse_address("  0x7ffd1234  "), Some(0x7ffd1234));
+        assert_eq!(parse_address("1234"), Some(1234));
+        assert_eq!(parse_address("0xffffffffffffffff"), Some(u64::MAX));
+
+        assert_eq!(parse_address(""), None);
+        assert_eq!(parse_address("0x"), None);
+        assert_eq!(parse_address("0xg1"), None);
+        assert_eq!(parse_address("-1"), None);
+        assert_eq!(parse_address("0x1ffffffffffffffff"), None);
+    }
+
+    #[test]
+    fn memory_rows() {
+        let dump = MemoryDump {
+            address: 0x1000,
+            bytes: b"Hello,\n world!\x00\xffab".to_vec(),
+            unreadable_bytes: 0,
+        };
+        let rows = dump.rows();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[0],
+            (
+                "0000000000001000".to_string(),
+                "48 65 6c 6c 6f 2c 0a 20 77 6f 72 6c 64 21 00 ff".to_string(),
+                "Hello,. world!..".to_string(),
+            )
+        );
+        // The last row is padded to the width of a full one
+        assert_eq!(rows[1].0, "0000000000001010");
+        assert_eq!(rows[1].1.trim_end(), "61 62");
+        assert_eq!(rows[1].1.len(), HEX_ROW_BYTES * 3 - 1);
+        assert_eq!(rows[1].2, "ab");
+    }
+
+    #[test]
+    fn memory_rows_with_unreadable_bytes() {
+        let dump = MemoryDump {
+            address: 0xfff0,
+            bytes: vec![0x41; HEX_ROW_BYTES - 1],
+            unreadable_bytes: 3,
+        };
+        let rows = dump.rows();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].1.ends_with("41 ??"));
+        assert_eq!(rows[0].2, format!("{}.", "A".repeat(HEX_ROW_BYTES - 1)));
+        assert_eq!(rows[1].0, format!("{:016x}", 0xfff0 + HEX_ROW_BYTES));
+        assert_eq!(rows[1].1.trim_end(), "?? ??");
+        assert_eq!(rows[1].2, "..");
+
+        let empty = MemoryDump {
+            address: 0,
+            bytes: Vec::new(),
+            unreadable_bytes: 0,
+        };
+        assert!(empty.rows().is_empty());
+    }
+
+    #[test]
+    fn memory_rows_at_the_top_of_the_address_space() {
+        let dump = MemoryDump {
+            address: u64::MAX - 7,
+            bytes: vec![0; HEX_ROW_BYTES * 2],
+            unreadable_bytes: 0,
+        };
+        let rows = dump.rows();
+        assert_eq!(rows[0].0, "fffffffffffffff8");
+        assert_eq!(rows[1].0, format!("{:016x}", HEX_ROW_BYTES - 8));
+    }
+
+    #[test]
+    fn memory_dump_is_capped() {
+        let dump = MemoryDump::from_response(ReadMemoryResponse {
+            address: "0x1000".to_string(),
+            unreadable_bytes: Some(usize::MAX),
+            // "AAAA" is 3 zero bytes
+            data: Some("AAAA".to_string()),
+        })
+        .unwrap();
+        assert_eq!(dump.address, 0x1000);
+        assert_eq!(dump.bytes, vec![0; 3]);
+        assert_eq!(dump.unreadable_bytes, MEMORY_VIEW_BYTES - 3);
+        assert_eq!(dump.rows().len(), MEMORY_VIEW_BYTES / HEX_ROW_BYTES);
+    }
+
+    #[test]
+    fn console_output_merges_partial_lines() {
+        let mut console = im::Vector::new();
+        push_console_output(&mut console, &output(Some("stdout"), "foo"));
+        push_console_output(&mut console, &output(Some("stdout"), "bar\n"));
+        push_console_output(&mut console, &output(Some("stdout"), "baz"));
+        // Another kind of output isn't part of the line
+        push_console_output(&mut console, &output(Some("stderr"), "error\n"));
+        push_console_output(&mut console, &output(None, "info"));
+        push_console_output(&mut console, &output(Some("console"), " more\n"));
+
+        let entries = console
+            .iter()
+            .map(|entry| (entry.kind, entry.text.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                (DebugConsoleKind::Stdout, "foobar\n"),
+                (DebugConsoleKind::Stdout, "baz"),
+                (DebugConsoleKind::Stderr, "error\n"),
+                (DebugConsoleKind::Console, "info more\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn console_output_is_capped() {
+        let mut console = im::Vector::new();
+        for i in 0..MAX_CONSOLE_ENTRIES + 10 {
+            push_console_output(
+                &mut console,
+                &output(Some("stdout"), &format!("{i}\n")),
+            );
+        }
+        assert_eq!(console.len(), MAX_CONSOLE_ENTRIES);
+        // The oldest entries are dropped
+        assert_eq!(console.front().unwrap().text, "10\n");
+        assert_eq!(
+            console.back().unwrap().text,
+            format!("{}\n", MAX_CONSOLE_ENTRIES + 9)
+        );
+    }
 }
@@ -0,0 +1,219 @@
+use std::{rc::Rc, sync::Arc};
+
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{create_effect, create_rw_signal, ReadSignal, RwSignal, Scope},
+    view::View,
+    views::{container, label, list, scroll, stack, Decorators},
+};
+use lapce_rpc::{dap_types::DisassembledInstruction, proxy::ProxyResponse};
+
+use crate::{
+    app::clickable_icon,
+    command::LapceWorkbenchCommand,
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    debug::parse_address,
+    window_tab::CommonData,
+};
+
+/// The number of instructions shown before the instruction pointer
+const INSTRUCTIONS_BEFORE: usize = 20;
+
+/// The number of instructions shown from the instruction pointer on
+const INSTRUCTIONS_AFTER: usize = 40;
+
+pub fn disassembly_view(common: Rc<CommonData>) -> impl View {
+    let config = common.config;
+    let paused_frame = common.paused_frame;
+    let instruction_pointer = common.instruction_pointer;
+    let dap_capabilities = common.dap_capabilities;
+    let workbench_command = common.workbench_command;
+    let proxy = common.proxy.clone();
+    let cx = Scope::current();
+
+    let instructions = create_rw_signal(Vec::<DisassembledInstruction>::new());
+    let message = create_rw_signal(None::<String>);
+
+    create_effect(move |_| {
+        let dap_id = paused_frame.get().map(|(dap_id, _)| dap_id);
+        let ip = instruction_pointer.get();
+        let (dap_id, ip) = match (dap_id, ip) {
+            (Some(dap_id), Some(ip)) => (dap_id, ip),
+            (Some(_), None) => {
+                instructions.set(Vec::new());
+                message.set(Some(
+                    "The stack frame has no instruction pointer".to_string(),
+                ));
+                return;
+            }
+            (None, _) => {
+                instructions.set(Vec::new());
+                message.set(Some("The debuggee isn't paused".to_string()));
+                return;
+            }
+        };
+        let supported = dap_capabilities.with_untracked(|caps| {
+            caps.get(&dap_id)
+                .and_then(|caps| caps.supports_disassemble_request)
+                .unwrap_or(false)
+        });
+        if !supported {
+            instructions.set(Vec::new());
+            message.set(Some("The debug adapter can't disassemble".to_string()));
+            return;
+        }
+
+        let requested = ip.clone();
+        let send = create_ext_action(cx, move |result| {
+            if instruction_pointer
+                .with_untracked(|ip| ip.as_ref() != Some(&requested))
+            {
+                return;
+            }
+            match result {
+                Ok(ProxyResponse::DapDisassembleResponse {
+                    instructions: new_instructions,
+                }) => {
+                    instructions.set(new_instructions);
+                    message.set(None);
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    instructions.set(Vec::new());
+                    message.set(Some(err.message));
+                }
+            }
+        });
+        proxy.dap_disassemble(
+            dap_id,
+            ip,
+            -(INSTRUCTIONS_BEFORE as i64),
+            INSTRUCTIONS_BEFORE + INSTRUCTIONS_AFTER,
+            move |result| {
+                send(result);
+            },
+        );
+    });
+
+    stack(move || {
+        (
+            stack(move || {
+                (
+                    label(move || {
+                        message.get().unwrap_or_else(|| {
+                            instruction_pointer.get().unwrap_or_default()
+                        })
+                    })
+                    .style(move |s| {
+                        s.flex_grow(1.0)
+                            .min_width_px(0.0)
+                            .text_ellipsis()
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                    }),
+                    clickable_icon(
+                        || LapceIcons::DEBUG_STEP_OVER,
+                        move || {
+                            workbench_command
+                                .send(LapceWorkbenchCommand::DebugStepInstruction);
+                        },
+                        || false,
+                        move || {
+                            // Stepping by instruction needs the granularity
+                            !paused_frame.with(|f| {
+                                f.map(|(dap_id, _)| {
+                                    dap_capabilities.with(|caps| {
+                                        caps.get(&dap_id)
+                                            .and_then(|caps| {
+                                                caps.supports_stepping_granularity
+                                            })
+                                            .unwrap_or(false)
+                                    })
+                                })
+                                .unwrap_or(false)
+                            })
+                        },
+                        config,
+                    ),
+                )
+            })
+            .style(move |s| {
+                s.width_pct(100.0)
+                    .items_center()
+                    .padding_horiz_px(10.0)
+                    .padding_vert_px(4.0)
+                    .border_bottom(1.0)
+                    .border_color(*config.get().get_color(LapceColor::LAPCE_BORDER))
+            }),
+            container(move || {
+                scroll(move || {
+                    list(
+                        move || instructions.get(),
+                        |instruction| instruction.address.clone(),
+                        move |instruction| {
+                            disassembled_instruction(
+                                instruction,
+                                instruction_pointer,
+                                config,
+                            )
+                        },
+                    )
+                    .style(|s| s.flex_col().min_width_pct(100.0))
+                })
+                .style(|s| s.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|s| s.width_pct(100.0).flex_grow(1.0).flex_basis_px(0.0)),
+        )
+    })
+    .style(|s| s.flex_col().size_pct(100.0, 100.0))
+}
+
+fn disassembled_instruction(
+    instruction: DisassembledInstruction,
+    instruction_pointer: RwSignal<Option<String>>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    // The addresses can be formatted differently, with leading zeros or not
+    let parsed = parse_address(&instruction.address);
+    let is_current = move || {
+        parsed.is_some()
+            && instruction_pointer.with(|ip| ip.as_deref().and_then(parse_address))
+                == parsed
+    };
+    let address = instruction.address;
+    let bytes = instruction.instruction_bytes.unwrap_or_default();
+    let text = instruction.instruction;
+    let symbol = instruction.symbol.unwrap_or_default();
+
+    stack(move || {
+        (
+            label(move || if is_current() { "→" } else { "" }.to_string())
+                .style(|s| s.width_px(20.0)),
+            label(move || address.clone()).style(move |s| {
+                s.margin_right_px(12.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+            label(move || bytes.clone()).style(move |s| {
+                s.min_width_px(200.0)
+                    .margin_right_px(12.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+            label(move || text.clone()).style(|s| s.min_width_px(300.0)),
+            label(move || symbol.clone()).style(move |s| {
+                s.margin_left_px(12.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+        )
+    })
+    .style(move |s| {
+        let config = config.get();
+        let current = is_current();
+        s.padding_horiz_px(10.0)
+            .line_height(1.6)
+            .min_width_pct(100.0)
+            .font_family(config.editor.font_family.clone())
+            .font_size(config.editor.font_size() as f32)
+            .apply_if(current, |s| {
+                s.background(*config.get_color(LapceColor::EDITOR_CURRENT_LINE))
+            })
+    })
+}
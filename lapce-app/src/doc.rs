@@ -1,6 +1,6 @@
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::PathBuf,
     rc::Rc,
     sync::{atomic, Arc},
@@ -34,6 +34,7 @@
     buffer::BufferId,
     plugin::PluginId,
     proxy::ProxyResponse,
+    source_control::BlameHunk,
     style::{LineStyle, LineStyles, Style},
 };
 use lapce_xi_rope::{
@@ -42,22 +43,44 @@
 };
 use lsp_types::{
     CodeActionResponse, Diagnostic, DiagnosticSeverity, InlayHint, InlayHintLabel,
+    Range,
 };
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
-use self::phantom_text::{PhantomText, PhantomTextKind, PhantomTextLine};
+use self::{
+    document_color::find_document_colors,
+    document_link::{find_document_links, DocumentLinkTarget},
+    phantom_text::{PhantomText, PhantomTextKind, PhantomTextLine},
+};
 use crate::{
     config::{color::LapceColor, LapceConfig},
     editor::view_data::{LineExtraStyle, TextLayoutCache, TextLayoutLine},
     find::{Find, FindProgress, FindResult},
     history::DocumentHistory,
+    source_control::{blame_annotation, line_blame},
     window_tab::CommonData,
     workspace::LapceWorkspace,
 };
 
+pub mod document_color;
+pub mod document_link;
 pub mod phantom_text;
 
+/// How many lines above the line the debuggee is paused at can have inline
+/// values, at most
+const INLINE_VALUES_MAX_LINES: usize = 100;
+/// The length that the inline value of a variable is cut to
+const INLINE_VALUE_MAX_LEN: usize = 50;
+/// How long after the last edit the blame is requested again, in milliseconds
+const BLAME_DELAY: u64 = 500;
+/// How long after the last edit the semantic styles of the visible lines are
+/// requested, in milliseconds
+const SEMANTIC_RANGE_DELAY: u64 = 100;
+/// How long after the last edit the links and the colors of the document are
+/// found again, in milliseconds
+const DOCUMENT_LINKS_DELAY: u64 = 300;
+
 pub struct SystemClipboard {
     ctx: ClipboardContext,
 }
@@ -179,13 +202,37 @@ pub struct Document {
     pub syntax: RwSignal<Syntax>,
     /// Semantic highlighting information (which is provided by the LSP)
     semantic_styles: RwSignal<Option<Spans<Style>>>,
+    /// Whether the semantic styles of the whole buffer have arrived, rather
+    /// than only those of the ranges requested while they're pending
+    semantic_full: RwSignal<bool>,
+    /// The revision and the lines of the last range whose semantic styles
+    /// were requested
+    semantic_range: RwSignal<Option<(u64, usize, usize)>>,
     /// Inlay hints for the document
     pub inlay_hints: RwSignal<Option<Spans<InlayHint>>>,
+    /// Links in the document, from the LSP and from the urls and file references in the text
+    pub document_links: RwSignal<Option<Spans<DocumentLinkTarget>>>,
+    /// Colors in the document, which get a swatch in front of them. They're
+    /// provided by the LSP, or found in the text when no LSP provides them
+    pub document_colors: RwSignal<Option<Spans<lsp_types::Color>>>,
+    /// Whether an LSP provided the colors in the document
+    document_colors_from_lsp: RwSignal<bool>,
     /// Current completion lens text, if any.  
     /// This will be displayed even on views that are not focused.
     pub completion_lens: RwSignal<Option<String>>,
     /// (line, col)
     pub completion_pos: RwSignal<(usize, usize)>,
+    /// The text of the inline completion at the cursor that is yet to be
+    /// typed, with the (line, col) it's displayed at
+    pub inline_completion: RwSignal<Option<(String, usize, usize)>>,
+    /// The values of the variables used on each line, by line, while the
+    /// debuggee is paused in this file
+    pub inline_values: RwSignal<im::HashMap<usize, String>>,
+    /// The git blame of the lines, by the commit that last changed them
+    pub blame: RwSignal<im::Vector<BlameHunk>>,
+    /// The line whose blame is shown at its end, which is the cursor line of
+    /// the active editor
+    pub blame_line: RwSignal<Option<usize>>,
     /// ime preedit information
     pub preedit: RwSignal<Option<Preedit>>,
     /// (Offset -> (Plugin the code actions are from, Code Actions))
@@ -221,10 +268,19 @@ pub fn new(
             syntax: cx.create_rw_signal(syntax),
             line_styles: Rc::new(RefCell::new(HashMap::new())),
             semantic_styles: cx.create_rw_signal(None),
+            semantic_full: cx.create_rw_signal(false),
+            semantic_range: cx.create_rw_signal(None),
             inlay_hints: cx.create_rw_signal(None),
+            document_links: cx.create_rw_signal(None),
+            document_colors: cx.create_rw_signal(None),
+            document_colors_from_lsp: cx.create_rw_signal(false),
             diagnostics,
             completion_lens: cx.create_rw_signal(None),
             completion_pos: cx.create_rw_signal((0, 0)),
+            inline_completion: cx.create_rw_signal(None),
+            inline_values: cx.create_rw_signal(im::HashMap::new()),
+            blame: cx.create_rw_signal(im::Vector::new()),
+            blame_line: cx.create_rw_signal(None),
             content: cx.create_rw_signal(DocContent::File {
                 path,
                 read_only: false,
@@ -261,13 +317,22 @@ pub fn new_content(
             line_styles: Rc::new(RefCell::new(HashMap::new())),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             semantic_styles: cx.create_rw_signal(None),
+            semantic_full: cx.create_rw_signal(false),
+            semantic_range: cx.create_rw_signal(None),
             inlay_hints: cx.create_rw_signal(None),
+            document_links: cx.create_rw_signal(None),
+            document_colors: cx.create_rw_signal(None),
+            document_colors_from_lsp: cx.create_rw_signal(false),
             diagnostics: DiagnosticData {
                 expanded: cx.create_rw_signal(true),
                 diagnostics: cx.create_rw_signal(im::Vector::new()),
             },
             completion_lens: cx.create_rw_signal(None),
             completion_pos: cx.create_rw_signal((0, 0)),
+            inline_completion: cx.create_rw_signal(None),
+            inline_values: cx.create_rw_signal(im::HashMap::new()),
+            blame: cx.create_rw_signal(im::Vector::new()),
+            blame_line: cx.create_rw_signal(None),
             loaded: cx.create_rw_signal(true),
             histories: cx.create_rw_signal(im::HashMap::new()),
             head_changes: cx.create_rw_signal(im::Vector::new()),
@@ -300,13 +365,22 @@ pub fn new_hisotry(
             line_styles: Rc::new(RefCell::new(HashMap::new())),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             semantic_styles: cx.create_rw_signal(None),
+            semantic_full: cx.create_rw_signal(false),
+            semantic_range: cx.create_rw_signal(None),
             inlay_hints: cx.create_rw_signal(None),
+            document_links: cx.create_rw_signal(None),
+            document_colors: cx.create_rw_signal(None),
+            document_colors_from_lsp: cx.create_rw_signal(false),
             diagnostics: DiagnosticData {
                 expanded: cx.create_rw_signal(true),
                 diagnostics: cx.create_rw_signal(im::Vector::new()),
             },
             completion_lens: cx.create_rw_signal(None),
             completion_pos: cx.create_rw_signal((0, 0)),
+            inline_completion: cx.create_rw_signal(None),
+            inline_values: cx.create_rw_signal(im::HashMap::new()),
+            blame: cx.create_rw_signal(im::Vector::new()),
+            blame_line: cx.create_rw_signal(None),
             loaded: cx.create_rw_signal(true),
             histories: cx.create_rw_signal(im::HashMap::new()),
             head_changes: cx.create_rw_signal(im::Vector::new()),
@@ -467,11 +541,15 @@ pub fn do_edit(
 
     pub fn apply_deltas(&self, deltas: &[(RopeDelta, InvalLines, SyntaxEdit)]) {
         let rev = self.rev() - deltas.len() as u64;
-        for (i, (delta, _, _)) in deltas.iter().enumerate() {
+        for (i, (delta, inval_lines, _)) in deltas.iter().enumerate() {
             self.update_styles(delta);
             self.update_inlay_hints(delta);
+            self.update_document_links(delta);
+            self.update_document_colors(delta);
             self.update_diagnostics(delta);
             self.update_completion_lens(delta);
+            self.clear_inline_completion();
+            self.update_inline_values(inval_lines);
             self.update_find_result(delta);
             if let DocContent::File { path, .. } = self.content.get_untracked() {
                 self.common
@@ -505,6 +583,9 @@ pub fn rev(&self) -> u64 {
         self.check_auto_save();
         self.get_semantic_styles();
         self.get_inlay_hints();
+        self.get_document_links();
+        self.get_document_colors();
+        self.get_blame();
         self.find_result.reset();
     }
 
@@ -562,6 +643,24 @@ fn update_inlay_hints(&self, delta: &RopeDelta) {
         });
     }
 
+    /// Update the document links so their positions are correct after an edit.
+    fn update_document_links(&self, delta: &RopeDelta) {
+        self.document_links.update(|links| {
+            if let Some(links) = links.as_mut() {
+                links.apply_shape(delta);
+            }
+        });
+    }
+
+    /// Update the document colors so their positions are correct after an edit.
+    fn update_document_colors(&self, delta: &RopeDelta) {
+        self.document_colors.update(|colors| {
+            if let Some(colors) = colors.as_mut() {
+                colors.apply_shape(delta);
+            }
+        });
+    }
+
     pub fn trigger_syntax_change(&self, edits: Option<SmallVec<[SyntaxEdit; 3]>>) {
         let (rev, text) =
             self.buffer.with_untracked(|b| (b.rev(), b.text().clone()));
@@ -661,6 +760,8 @@ fn get_semantic_styles(&self) {
             };
 
         let (rev, len) = self.buffer.with_untracked(|b| (b.rev(), b.len()));
+        // The styles of the visible lines are requested until these arrive
+        self.semantic_full.set(false);
 
         let syntactic_styles =
             self.syntax.with_untracked(|syntax| syntax.styles.clone());
@@ -669,6 +770,7 @@ fn get_semantic_styles(&self) {
         let send = create_ext_action(self.scope, move |styles| {
             if doc.buffer.with_untracked(|b| b.rev()) == rev {
                 doc.semantic_styles.set(Some(styles));
+                doc.semantic_full.set(true);
                 doc.clear_style_cache();
             }
         });
@@ -704,6 +806,141 @@ fn get_semantic_styles(&self) {
         });
     }
 
+    /// Request semantic styles for the lines from the LSP through the proxy,
+    /// which is quicker than the whole buffer. This is only done while the
+    /// semantic styles of the whole buffer are pending, and the styles are
+    /// merged into the existing ones.
+    pub fn get_semantic_range_styles(&self, start_line: usize, end_line: usize) {
+        if !self.loaded() || self.semantic_full.get_untracked() {
+            return;
+        }
+
+        let request = (self.rev(), start_line, end_line);
+        if self.semantic_range.get_untracked() == Some(request) {
+            return;
+        }
+        self.semantic_range.set(Some(request));
+        let doc = self.clone();
+        exec_after(Duration::from_millis(SEMANTIC_RANGE_DELAY), move |_| {
+            // Only the last of the lines scrolled through, or edited, are
+            // requested
+            let current = doc.semantic_range.try_get_untracked().flatten();
+            let current_rev = match doc
+                .buffer
+                .try_with_untracked(|b| b.as_ref().map(|b| b.rev()))
+            {
+                Some(rev) => rev,
+                None => return,
+            };
+            if current != Some(request)
+                || current_rev != request.0
+                || doc.semantic_full.get_untracked()
+            {
+                return;
+            }
+            doc.request_semantic_range_styles(start_line, end_line);
+        });
+    }
+
+    fn request_semantic_range_styles(&self, start_line: usize, end_line: usize) {
+        let path =
+            if let DocContent::File { path, .. } = self.content.get_untracked() {
+                path
+            } else {
+                return;
+            };
+
+        let (rev, len, start, end, range) = self.buffer.with_untracked(|b| {
+            let end_line = end_line.min(b.last_line());
+            let start_line = start_line.min(end_line);
+            let start = b.offset_of_line(start_line);
+            let end = b.line_end_offset(end_line, true);
+            let range = Range {
+                start: b.offset_to_position(start),
+                end: b.offset_to_position(end),
+            };
+            (b.rev(), b.len(), start, end, range)
+        });
+        if start >= end {
+            return;
+        }
+        let interval = Interval::new(start, end);
+
+        let syntactic_styles =
+            self.syntax.with_untracked(|syntax| syntax.styles.clone());
+
+        let doc = self.clone();
+        let send = create_ext_action(self.scope, move |styles: Spans<Style>| {
+            if doc.buffer.with_untracked(|b| b.rev()) != rev {
+                return;
+            }
+            if doc.semantic_full.get_untracked() {
+                return;
+            }
+            // merge into the styles from earlier ranges, or into the syntax
+            // styles, so the lines outside of the range keep their styles
+            let base = doc
+                .semantic_styles
+                .get_untracked()
+                .filter(|base| base.len() == len)
+                .or_else(|| {
+                    doc.syntax
+                        .with_untracked(|syntax| syntax.styles.clone())
+                        .filter(|base| base.len() == len)
+                });
+            let Some(mut base) = base else {
+                return;
+            };
+            base.edit(interval, styles);
+            doc.semantic_styles.set(Some(base));
+            doc.clear_style_cache();
+        });
+
+        self.common
+            .proxy
+            .get_semantic_tokens_range(path, range, move |result| {
+                if let Ok(ProxyResponse::GetSemanticTokensRange { styles }) = result
+                {
+                    rayon::spawn(move || {
+                        let mut styles_span = SpansBuilder::new(end - start);
+                        for style in styles.styles {
+                            if style.end <= start || style.start >= end {
+                                continue;
+                            }
+                            styles_span.add_span(
+                                Interval::new(
+                                    style.start.max(start) - start,
+                                    style.end.min(end) - start,
+                                ),
+                                style.style,
+                            );
+                        }
+
+                        let styles = styles_span.build();
+
+                        let styles = match syntactic_styles {
+                            Some(syntactic_styles)
+                                if syntactic_styles.len() == len =>
+                            {
+                                syntactic_styles.subseq(interval).merge(
+                                    &styles,
+                                    |a, b| {
+                                        if let Some(b) = b {
+                                            return b.clone();
+                                        }
+                                        a.clone()
+                                    },
+                                )
+                            }
+                            _ => styles,
+                        };
+
+                        send(styles);
+                    });
+                }
+            });
+    }
+
     /// Request inlay hints for the buffer from the LSP through the proxy.
     fn get_inlay_hints(&self) {
         if !self.loaded() {
@@ -750,6 +987,212 @@ fn get_inlay_hints(&self) {
         });
     }
 
+    /// Request the document links from the LSP through the proxy, and find the urls and
+    /// file references in the text, which are linked even if the LSP doesn't provide them.
+    /// It's done once the edits stop, off the UI thread.
+    fn get_document_links(&self) {
+        if !self.loaded() {
+            return;
+        }
+        let rev = self.rev();
+        let doc = self.clone();
+        exec_after(Duration::from_millis(DOCUMENT_LINKS_DELAY), move |_| {
+            if doc.buffer.try_with_untracked(|b| b.map(|b| b.rev())) == Some(rev) {
+                doc.request_document_links();
+            }
+        });
+    }
+
+    fn request_document_links(&self) {
+        let path =
+            if let DocContent::File { path, .. } = self.content.get_untracked() {
+                path
+            } else {
+                return;
+            };
+
+        let (buffer, rev, len) = self
+            .buffer
+            .with_untracked(|b| (b.clone(), b.rev(), b.len()));
+        let base = self
+            .common
+            .workspace
+            .path
+            .clone()
+            .or_else(|| path.parent().map(|p| p.to_path_buf()));
+        let check_exists = self.common.workspace.kind.is_local();
+
+        let doc = self.clone();
+        let send = create_ext_action(self.scope, move |links| {
+            if doc.buffer.with_untracked(|b| b.rev()) == rev {
+                doc.document_links.set(Some(links));
+                doc.clear_text_cache();
+            }
+        });
+
+        self.common.proxy.get_document_links(path, move |result| {
+            rayon::spawn(move || {
+                let mut links = Vec::new();
+                if let Ok(ProxyResponse::GetDocumentLinks { links: lsp_links }) =
+                    result
+                {
+                    for link in lsp_links {
+                        // Links without a target would need to be resolved first
+                        let target = match link
+                            .target
+                            .as_ref()
+                            .and_then(DocumentLinkTarget::from_url)
+                        {
+                            Some(target) => target,
+                            None => continue,
+                        };
+                        let start =
+                            buffer.offset_of_position(&link.range.start).min(len);
+                        let end =
+                            buffer.offset_of_position(&link.range.end).min(len);
+                        links.push((start, end, target));
+                    }
+                }
+
+                for (start, end, target) in
+                    find_document_links(buffer.text(), base.as_deref(), check_exists)
+                {
+                    if !links.iter().any(|(s, e, _)| start < *e && *s < end) {
+                        links.push((start, end, target));
+                    }
+                }
+                links.sort_by_key(|(start, _, _)| *start);
+
+                let mut links_span = SpansBuilder::new(len);
+                let mut last_end = 0;
+                for (start, end, target) in links {
+                    if start < last_end || start >= end {
+                        continue;
+                    }
+                    last_end = end;
+                    links_span.add_span(Interval::new(start, end), target);
+                }
+                send(links_span.build());
+            });
+        });
+    }
+
+    /// Request the colors in the document from the LSP through the proxy once
+    /// the edits stop. Until an LSP provides them, the hex and rgb colors in
+    /// the text are found instead.
+    fn get_document_colors(&self) {
+        if !self.loaded() {
+            return;
+        }
+        let rev = self.rev();
+        let doc = self.clone();
+        exec_after(Duration::from_millis(DOCUMENT_LINKS_DELAY), move |_| {
+            if doc.buffer.try_with_untracked(|b| b.map(|b| b.rev())) == Some(rev) {
+                doc.request_document_colors();
+            }
+        });
+    }
+
+    fn request_document_colors(&self) {
+        let path =
+            if let DocContent::File { path, .. } = self.content.get_untracked() {
+                path
+            } else {
+                return;
+            };
+
+        let (buffer, rev, len) = self
+            .buffer
+            .with_untracked(|b| (b.clone(), b.rev(), b.len()));
+
+        if !self.document_colors_from_lsp.get_untracked() {
+            let doc = self.clone();
+            let send = create_ext_action(self.scope, move |colors| {
+                if doc.rev() == rev && !doc.document_colors_from_lsp.get_untracked()
+                {
+                    doc.document_colors.set(Some(colors));
+                    doc.clear_text_cache();
+                }
+            });
+            let buffer = buffer.clone();
+            rayon::spawn(move || {
+                let text = buffer.text().slice_to_cow(0..len);
+                let mut colors_span = SpansBuilder::new(len);
+                for (start, end, color) in find_document_colors(&text) {
+                    colors_span.add_span(Interval::new(start, end), color);
+                }
+                send(colors_span.build());
+            });
+        }
+
+        let doc = self.clone();
+        let send = create_ext_action(self.scope, move |colors| {
+            doc.document_colors_from_lsp.set(true);
+            if doc.rev() == rev {
+                doc.document_colors.set(Some(colors));
+                doc.clear_text_cache();
+            }
+        });
+
+        self.common.proxy.get_document_colors(path, move |result| {
+            if let Ok(ProxyResponse::GetDocumentColors { mut colors }) = result {
+                colors
+                    .sort_by(|left, right| left.range.start.cmp(&right.range.start));
+
+                let mut colors_span = SpansBuilder::new(len);
+                let mut last_end = 0;
+                for info in colors {
+                    let start =
+                        buffer.offset_of_position(&info.range.start).min(len);
+                    let end = buffer.offset_of_position(&info.range.end).min(len);
+                    if start < last_end || start >= end {
+                        continue;
+                    }
+                    last_end = end;
+                    colors_span.add_span(Interval::new(start, end), info.color);
+                }
+                send(colors_span.build());
+            }
+        });
+    }
+
+    /// Get the target of the link at the offset, if there is one
+    pub fn document_link_at(&self, offset: usize) -> Option<DocumentLinkTarget> {
+        self.document_links.with_untracked(|links| {
+            links
+                .as_ref()?
+                .iter_chunks(offset..offset + 1)
+                .find(|(interval, _)| {
+                    interval.start <= offset && offset < interval.end
+                })
+                .map(|(_, target)| target.clone())
+        })
+    }
+
+    /// Get the range and the value of the color at the offset, if there is one
+    pub fn document_color_at(
+        &self,
+        offset: usize,
+    ) -> Option<(Interval, lsp_types::Color)> {
+        self.document_colors.with_untracked(|colors| {
+            colors
+                .as_ref()?
+                .iter_chunks(offset..offset + 1)
+                .find(|(interval, _)| {
+                    interval.start <= offset && offset < interval.end
+                })
+                .map(|(interval, color)| {
+                    let color = lsp_types::Color {
+                        red: color.red,
+                        green: color.green,
+                        blue: color.blue,
+                        alpha: color.alpha,
+                    };
+                    (interval, color)
+                })
+        })
+    }
+
     /// Get the phantom text for a given line
     pub fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
         let config = self.common.config.get_untracked();
@@ -798,6 +1241,37 @@ pub fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
         // overall.
         let mut text: SmallVec<[PhantomText; 6]> = hints.collect();
 
+        // Show a swatch in front of each color that the LSP found on this line
+        let document_colors = self.document_colors.get_untracked();
+        let swatches = document_colors
+            .as_ref()
+            .map(|colors| colors.iter_chunks(start_offset..end_offset))
+            .into_iter()
+            .flatten()
+            .filter(|(interval, _)| {
+                interval.start >= start_offset && interval.start < end_offset
+            })
+            .map(|(interval, color)| {
+                let (_, col) = self
+                    .buffer
+                    .with_untracked(|b| b.offset_to_line_col(interval.start));
+                PhantomText {
+                    kind: PhantomTextKind::Color,
+                    col,
+                    text: "\u{25a0} ".to_string(),
+                    fg: Some(floem::peniko::Color::rgba(
+                        color.red as f64,
+                        color.green as f64,
+                        color.blue as f64,
+                        color.alpha as f64,
+                    )),
+                    font_size: None,
+                    bg: None,
+                    under_line: None,
+                }
+            });
+        text.extend(swatches);
+
         // The max severity is used to determine the color given to the background of the line
         let mut max_severity = None;
         // If error lens is enabled, and the diagnostics field is filled, then get the diagnostics
@@ -861,6 +1335,44 @@ pub fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
             });
         let mut diag_text: SmallVec<[PhantomText; 6]> = diag_text.collect();
 
+        if let Some(values) = self
+            .inline_values
+            .with_untracked(|values| values.get(&line).cloned())
+        {
+            let col = self.buffer.with_untracked(|buffer| {
+                buffer.offset_of_line(line + 1) - buffer.offset_of_line(line)
+            });
+            text.push(PhantomText {
+                kind: PhantomTextKind::InlineValue,
+                col,
+                text: format!("    {values}"),
+                fg: Some(*config.get_color(LapceColor::INLAY_HINT_FOREGROUND)),
+                font_size: Some(config.editor.inlay_hint_font_size()),
+                bg: None,
+                under_line: None,
+            });
+        }
+
+        if config.editor.enable_inline_blame
+            && self.blame_line.get_untracked() == Some(line)
+            && text.iter().all(|t| t.kind != PhantomTextKind::InlineValue)
+        {
+            if let Some(hunk) = self.line_blame(line) {
+                let col = self.buffer.with_untracked(|buffer| {
+                    buffer.offset_of_line(line + 1) - buffer.offset_of_line(line)
+                });
+                text.push(PhantomText {
+                    kind: PhantomTextKind::Blame,
+                    col,
+                    text: format!("    {}", blame_annotation(&hunk)),
+                    fg: Some(*config.get_color(LapceColor::EDITOR_DIM)),
+                    font_size: Some(config.editor.inlay_hint_font_size()),
+                    bg: None,
+                    under_line: None,
+                });
+            }
+        }
+
         text.append(&mut diag_text);
 
         let (completion_line, completion_col) = self.completion_pos.get_untracked();
@@ -886,6 +1398,26 @@ pub fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
             text.push(completion_text);
         }
 
+        // Only the first line of the inline completion is phantom text, the
+        // rest is painted over the lines below by the editor view
+        if let Some((inline_completion, inline_line, inline_col)) =
+            self.inline_completion.get_untracked()
+        {
+            if line == inline_line {
+                text.push(PhantomText {
+                    kind: PhantomTextKind::InlineCompletion,
+                    col: inline_col,
+                    text: inline_completion.lines().next().unwrap_or("").to_string(),
+                    fg: Some(
+                        *config.get_color(LapceColor::COMPLETION_LENS_FOREGROUND),
+                    ),
+                    font_size: None,
+                    bg: None,
+                    under_line: None,
+                });
+            }
+        }
+
         if let Some(preedit) = self.preedit.get_untracked() {
             let (ime_line, col) = self
                 .buffer
@@ -994,6 +1526,135 @@ pub fn clear_completion_lens(&self) {
         self.completion_lens.set(None);
     }
 
+    pub fn set_inline_completion(&self, text: String, line: usize, col: usize) {
+        // TODO: more granular invalidation
+        self.clear_text_cache();
+        self.inline_completion.set(Some((text, line, col)));
+    }
+
+    pub fn clear_inline_completion(&self) {
+        if self.inline_completion.with_untracked(|c| c.is_none()) {
+            return;
+        }
+        // TODO: more granular invalidation
+        self.clear_text_cache();
+        self.inline_completion.set(None);
+    }
+
+    /// Show the values of the variables where they're used, on the lines up
+    /// to the one the debuggee is paused at, from the start of the function
+    /// it's in.
+    pub fn set_inline_values(&self, line: usize, values: &HashMap<String, String>) {
+        let inline_values = self.find_inline_values(line, values);
+        if self
+            .inline_values
+            .with_untracked(|current| current == &inline_values)
+        {
+            return;
+        }
+        // TODO: more granular invalidation
+        self.clear_text_cache();
+        self.inline_values.set(inline_values);
+    }
+
+    pub fn clear_inline_values(&self) {
+        if self
+            .inline_values
+            .with_untracked(|values| values.is_empty())
+        {
+            return;
+        }
+        // TODO: more granular invalidation
+        self.clear_text_cache();
+        self.inline_values.set(im::HashMap::new());
+    }
+
+    /// Move the inline values to the lines they're on after an edit, so they
+    /// stay until the debuggee stops again.
+    fn update_inline_values(&self, inval_lines: &InvalLines) {
+        if self
+            .inline_values
+            .with_untracked(|values| values.is_empty())
+        {
+            return;
+        }
+        self.inline_values
+            .update(|values| *values = shift_inline_values(values, inval_lines));
+    }
+
+    fn find_inline_values(
+        &self,
+        line: usize,
+        values: &HashMap<String, String>,
+    ) -> im::HashMap<usize, String> {
+        if values.is_empty() {
+            return im::HashMap::new();
+        }
+
+        let function_line = self
+            .sticky_headers(line)
+            .and_then(|lines| lines.last().copied());
+        self.buffer.with_untracked(|buffer| {
+            let start_line = line
+                .saturating_sub(INLINE_VALUES_MAX_LINES)
+                .max(function_line.unwrap_or(0));
+            let start = buffer.offset_of_line(start_line);
+            let end = buffer.offset_of_line(line + 1);
+
+            // The identifiers from the syntax tree skip the strings and the
+            // comments, and the words are used without one
+            let identifiers = self
+                .syntax
+                .with_untracked(|syntax| syntax.find_identifiers(start, end))
+                .unwrap_or_else(|| {
+                    let text = buffer.slice_to_cow(start..end);
+                    find_words(&text)
+                        .into_iter()
+                        .map(|(s, e)| (start + s, start + e))
+                        .collect()
+                });
+
+            let mut names: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+            for (s, e) in identifiers {
+                if s < start || e > end {
+                    continue;
+                }
+                let name = buffer.slice_to_cow(s..e).to_string();
+                if !values.contains_key(&name) {
+                    continue;
+                }
+                let names = names.entry(buffer.line_of_offset(s)).or_default();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+
+            names
+                .into_iter()
+                .map(|(line, names)| {
+                    let text = names
+                        .iter()
+                        .map(|name| {
+                            let value = &values[name];
+                            let value =
+                                if value.chars().count() > INLINE_VALUE_MAX_LEN {
+                                    let value: String = value
+                                        .chars()
+                                        .take(INLINE_VALUE_MAX_LEN)
+                                        .collect();
+                                    format!("{value}…")
+                                } else {
+                                    value.clone()
+                                };
+                            format!("{name} = {}", value.lines().join(" "))
+                        })
+                        .join(", ");
+                    (line, text)
+                })
+                .collect()
+        })
+    }
+
     fn update_find_result(&self, delta: &RopeDelta) {
         self.find_result.occurrences.update(|s| {
             *s = s.apply_delta(delta, true, InsertDrift::Default);
@@ -1134,6 +1795,132 @@ pub fn sticky_headers(&self, line: usize) -> Option<Vec<usize>> {
         lines
     }
 
+    /// Request the git blame of the lines, if it's shown inline or in the
+    /// gutter. Blaming is slow for large files, so it's only requested once
+    /// the edits stop for a moment.
+    pub fn get_blame(&self) {
+        if !self.loaded() {
+            return;
+        }
+        let path =
+            if let DocContent::File { path, .. } = self.content.get_untracked() {
+                path
+            } else {
+                return;
+            };
+        if !self
+            .common
+            .config
+            .get_untracked()
+            .editor
+            .enable_inline_blame
+            && !self.common.blame_gutter.get_untracked()
+        {
+            return;
+        }
+
+        let rev = self.rev();
+        let doc = self.clone();
+        exec_after(Duration::from_millis(BLAME_DELAY), move |_| {
+            let current_rev = match doc
+                .buffer
+                .try_with_untracked(|b| b.as_ref().map(|b| b.rev()))
+            {
+                Some(rev) => rev,
+                None => return,
+            };
+            if current_rev != rev {
+                return;
+            }
+
+            let blame = doc.blame;
+            let local_doc = doc.clone();
+            let send = create_ext_action(doc.scope, move |result| {
+                if local_doc.rev() != rev {
+                    return;
+                }
+                // A file that can't be blamed, like an untracked one, has no
+                // blame left from before
+                let hunks = match result {
+                    Ok(ProxyResponse::GitBlameResponse { hunks }) => hunks.into(),
+                    _ => im::Vector::new(),
+                };
+                if blame.with_untracked(|blame| blame.is_empty() && hunks.is_empty())
+                {
+                    return;
+                }
+                blame.set(hunks);
+                // TODO: more granular invalidation
+                local_doc.clear_text_cache();
+            });
+            doc.common.proxy.git_blame(path, move |result| {
+                send(result);
+            });
+        });
+    }
+
+    /// The blame of the commit that last changed the line.
+    pub fn line_blame(&self, line: usize) -> Option<BlameHunk> {
+        self.blame
+            .with_untracked(|blame| line_blame(blame, line).cloned())
+    }
+
+    /// Show the blame of the line at its end, instead of the line before.
+    pub fn set_blame_line(&self, line: Option<usize>) {
+        if self.blame_line.get_untracked() == line {
+            return;
+        }
+        self.blame_line.set(line);
+        if self
+            .common
+            .config
+            .get_untracked()
+            .editor
+            .enable_inline_blame
+        {
+            // TODO: more granular invalidation
+            self.clear_text_cache();
+        }
+    }
+
+    /// The lines of the buffer changed since `head`, grouped in hunks. The
+    /// hunks of deleted lines are empty, at the line they were deleted before.
+    pub fn diff_hunks(&self) -> Vec<std::ops::Range<usize>> {
+        let mut hunks = Vec::new();
+        let mut current: Option<std::ops::Range<usize>> = None;
+        let mut line = 0;
+        self.head_changes.with_untracked(|changes| {
+            for change in changes.iter() {
+                match change {
+                    DiffLines::Both(info) => {
+                        hunks.extend(current.take());
+                        line = info.right.end;
+                    }
+                    DiffLines::Left(_) => {
+                        current.get_or_insert(line..line);
+                    }
+                    DiffLines::Right(range) => {
+                        current.get_or_insert(range.start..range.start).end =
+                            range.end;
+                        line = range.end;
+                    }
+                }
+            }
+        });
+        hunks.extend(current);
+        hunks
+    }
+
+    /// The hunk of changes that the line is in, or is next to if the hunk is
+    /// of deleted lines.
+    pub fn diff_hunk_at_line(&self, line: usize) -> Option<std::ops::Range<usize>> {
+        self.diff_hunks().into_iter().find(|hunk| {
+            hunk.contains(&line)
+                || (hunk.is_empty()
+                    && (hunk.start == line || hunk.start == line + 1))
+        })
+    }
+
     /// Retrieve the `head` version of the buffer
     pub fn retrieve_head(&self) {
         if let DocContent::File { path, .. } = self.content.get_untracked() {
@@ -1403,6 +2190,39 @@ fn new_text_layout(&self, line: usize, _font_size: usize) -> TextLayoutLine {
             });
         }
 
+        // Underline the links on this line
+        self.document_links.with_untracked(|links| {
+            let links = match links.as_ref() {
+                Some(links) => links,
+                None => return,
+            };
+            let (line_start, line_end) = self.buffer.with_untracked(|buffer| {
+                (
+                    buffer.offset_of_line(line),
+                    buffer.line_end_offset(line, true),
+                )
+            });
+            let color = *config.get_color(LapceColor::EDITOR_LINK);
+            for (interval, _) in links.iter_chunks(line_start..line_end) {
+                let start = interval.start.max(line_start) - line_start;
+                let end = interval.end.min(line_end) - line_start;
+                if start >= end {
+                    continue;
+                }
+                let start = phantom_text.col_after(start, true);
+                let end = phantom_text.col_after(end, false);
+                let x0 = text_layout.hit_position(start).point.x;
+                let x1 = text_layout.hit_position(end).point.x;
+                extra_style.push(LineExtraStyle {
+                    x: x0,
+                    width: Some(x1 - x0),
+                    bg_color: None,
+                    under_line: Some(color),
+                    wave_line: None,
+                });
+            }
+        });
+
         self.diagnostics.diagnostics.with_untracked(|diags| {
             self.buffer.with_untracked(|buffer| {
                 for diag in diags {
@@ -1565,3 +2385,109 @@ pub fn save(&self, after_action: impl Fn() + 'static) {
         }
     }
 }
+
+/// The inline values on the lines they're on after the lines were changed.
+/// The values of the changed lines stay on the lines that are left of them.
+fn shift_inline_values(
+    values: &im::HashMap<usize, String>,
+    inval_lines: &InvalLines,
+) -> im::HashMap<usize, String> {
+    let start = inval_lines.start_line;
+    let end = start + inval_lines.inval_count;
+    values
+        .iter()
+        .filter_map(|(line, value)| {
+            let line = if *line < start {
+                *line
+            } else if *line >= end {
+                *line + inval_lines.new_count - inval_lines.inval_count
+            } else if *line - start < inval_lines.new_count {
+                *line
+            } else {
+                return None;
+            };
+            Some((line, value.clone()))
+        })
+        .collect()
+}
+
+/// The byte ranges of the words in the text that could be variable names
+fn find_words(text: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut word_start = None;
+    for (i, c) in text.char_indices() {
+        let is_word = c.is_alphanumeric() || c == '_';
+        match (word_start, is_word) {
+            (None, true) => word_start = Some(i),
+            (Some(start), false) => {
+                words.push((start, i));
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = word_start {
+        words.push((start, text.len()));
+    }
+    words
+        .into_iter()
+        .filter(|(start, _)| {
+            !text[*start..].starts_with(|c: char| c.is_ascii_digit())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::buffer::InvalLines;
+
+    use super::{find_words, shift_inline_values};
+
+    #[test]
+    fn find_words_of_text() {
+        let text = "let x_1 = self.y + 2 * z3;";
+        let words = find_words(text)
+            .into_iter()
+            .map(|(s, e)| &text[s..e])
+            .collect::<Vec<_>>();
+        // The number isn't a word
+        assert_eq!(words, vec!["let", "x_1", "self", "y", "z3"]);
+    }
+
+    #[test]
+    fn find_words_with_multibyte_chars() {
+        let text = "größe + ñ";
+        assert_eq!(find_words(text), vec![(0, 7), (10, 12)]);
+        assert_eq!(find_words(""), vec![]);
+    }
+
+    #[test]
+    fn shift_inline_values_through_edits() {
+        let values = [(1, "a = 1"), (3, "b = 2"), (5, "c = 3")]
+            .into_iter()
+            .map(|(line, value)| (line, value.to_string()))
+            .collect::<im::HashMap<_, _>>();
+        let shift = |start_line, inval_count, new_count| {
+            let mut values = shift_inline_values(
+                &values,
+                &InvalLines {
+                    start_line,
+                    inval_count,
+                    new_count,
+                },
+            )
+            .into_iter()
+            .map(|(line, value)| (line, value.chars().next().unwrap()))
+            .collect::<Vec<_>>();
+            values.sort();
+            values
+        };
+
+        // Typing on a line
+        assert_eq!(shift(3, 1, 1), vec![(1, 'a'), (3, 'b'), (5, 'c')]);
+        // Inserting two lines after the line 3
+        assert_eq!(shift(3, 1, 3), vec![(1, 'a'), (3, 'b'), (7, 'c')]);
+        // Deleting the lines 2 to 4
+        assert_eq!(shift(2, 3, 1), vec![(1, 'a'), (3, 'c')]);
+    }
+}
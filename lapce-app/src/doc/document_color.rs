@@ -0,0 +1,133 @@
+use lsp_types::Color;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Find the hex colors, like `#f80` or `#ff8800cc`, and the `rgb()` and
+/// `rgba()` colors in the text, which get a swatch when no language server
+/// provides the colors of the document.
+pub fn find_document_colors(text: &str) -> Vec<(usize, usize, Color)> {
+    static COLOR_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(concat!(
+            r"(?:^|[^\w&#])(#(?:[0-9a-fA-F]{8}|[0-9a-fA-F]{6}|[0-9a-fA-F]{3,4}))\b",
+            r"|\brgba?\(\s*(\d{1,3})\s*,\s*(\d{1,3})\s*,\s*(\d{1,3})",
+            r"\s*(?:,\s*(\d*\.?\d+)(%?)\s*)?\)",
+        ))
+        .unwrap()
+    });
+
+    COLOR_REGEX
+        .captures_iter(text)
+        .filter_map(|captures| {
+            if let Some(hex) = captures.get(1) {
+                let color = hex_color(&hex.as_str()[1..])?;
+                return Some((hex.start(), hex.end(), color));
+            }
+
+            let m = captures.get(0)?;
+            let channel = |i: usize| -> Option<f32> {
+                let value = captures[i].parse::<u32>().ok()?;
+                (value <= 255).then_some(value as f32 / 255.0)
+            };
+            let alpha = match captures.get(5) {
+                Some(alpha) => {
+                    let alpha = alpha.as_str().parse::<f32>().ok()?;
+                    let alpha = if captures.get(6).map(|m| m.as_str()) == Some("%") {
+                        alpha / 100.0
+                    } else {
+                        alpha
+                    };
+                    alpha.clamp(0.0, 1.0)
+                }
+                None => 1.0,
+            };
+            Some((
+                m.start(),
+                m.end(),
+                Color {
+                    red: channel(2)?,
+                    green: channel(3)?,
+                    blue: channel(4)?,
+                    alpha,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// The color of the hex digits of a `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`
+/// color.
+fn hex_color(hex: &str) -> Option<Color> {
+    let digits = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as f32))
+        .collect::<Option<Vec<_>>>()?;
+    let channels = match digits.len() {
+        3 | 4 => digits.iter().map(|d| d * 17.0 / 255.0).collect::<Vec<_>>(),
+        6 | 8 => digits
+            .chunks(2)
+            .map(|d| (d[0] * 16.0 + d[1]) / 255.0)
+            .collect::<Vec<_>>(),
+        _ => return None,
+    };
+    Some(Color {
+        red: channels[0],
+        green: channels[1],
+        blue: channels[2],
+        alpha: channels.get(3).copied().unwrap_or(1.0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Color;
+
+    use super::find_document_colors;
+
+    fn rgba(red: u8, green: u8, blue: u8, alpha: f32) -> Color {
+        Color {
+            red: red as f32 / 255.0,
+            green: green as f32 / 255.0,
+            blue: blue as f32 / 255.0,
+            alpha,
+        }
+    }
+
+    #[test]
+    fn hex_colors() {
+        let text = "color: #f80; background: #112233cc; border: #abcd #123456";
+        assert_eq!(
+            find_document_colors(text),
+            vec![
+                (7, 11, rgba(0xff, 0x88, 0x00, 1.0)),
+                (25, 34, rgba(0x11, 0x22, 0x33, 0xcc as f32 / 255.0)),
+                (44, 49, rgba(0xaa, 0xbb, 0xcc, 0xdd as f32 / 255.0)),
+                (50, 57, rgba(0x12, 0x34, 0x56, 1.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn not_hex_colors() {
+        // Too long, not hex digits, an html entity and part of a word
+        let text = "#1234567 #ggg &#123; a#fff #12";
+        assert!(find_document_colors(text).is_empty());
+    }
+
+    #[test]
+    fn rgb_colors() {
+        let text = "rgb(255, 0, 10) rgba(0,128,255,0.5) rgba(1, 2, 3, 50%)";
+        assert_eq!(
+            find_document_colors(text),
+            vec![
+                (0, 15, rgba(255, 0, 10, 1.0)),
+                (16, 35, rgba(0, 128, 255, 0.5)),
+                (36, 54, rgba(1, 2, 3, 0.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_rgb_colors() {
+        assert!(find_document_colors("rgb(256, 0, 0) rgb(1, 2)").is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+use std::path::{Path, PathBuf};
+
+use lapce_xi_rope::Rope;
+use lsp_types::{Position, Url};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::web_link::find_web_links;
+
+/// Where a link in the document leads to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DocumentLinkTarget {
+    /// A web address, which is opened in the browser
+    Uri(String),
+    /// A file, with the position to jump to if there is one
+    File {
+        path: PathBuf,
+        position: Option<Position>,
+    },
+}
+
+impl DocumentLinkTarget {
+    /// Convert the target of a link provided by a language server.
+    pub fn from_url(url: &Url) -> Option<DocumentLinkTarget> {
+        if url.scheme() != "file" {
+            return Some(DocumentLinkTarget::Uri(url.to_string()));
+        }
+
+        let mut path_url = url.clone();
+        path_url.set_fragment(None);
+        let path = path_url.to_file_path().ok()?;
+        // Servers commonly point at a line with a `#L10` or `#L10,5` fragment
+        let position = url.fragment().and_then(|fragment| {
+            let mut parts = fragment.strip_prefix('L')?.splitn(2, ',');
+            let line = parts.next()?.parse::<u32>().ok()?;
+            let col = parts
+                .next()
+                .and_then(|col| col.parse::<u32>().ok())
+                .unwrap_or(1);
+            Some(Position::new(line.saturating_sub(1), col.saturating_sub(1)))
+        });
+        Some(DocumentLinkTarget::File { path, position })
+    }
+}
+
+/// Find the web addresses and `path:line:col` references in the text, which
+/// are linked even when no language server provides links for the document.
+/// Relative paths are resolved against `base`. When `check_exists` is set,
+/// paths to files that do not exist are ignored.
+pub fn find_document_links(
+    text: &Rope,
+    base: Option<&Path>,
+    check_exists: bool,
+) -> Vec<(usize, usize, DocumentLinkTarget)> {
+    static PATH_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?:[A-Za-z]:)?[\w./\\-]*\w\.\w+:(\d+)(?::(\d+))?").unwrap()
+    });
+
+    let content = text.slice_to_cow(0..text.len());
+    let mut links = find_web_links(&content)
+        .map(|(start, end)| {
+            (
+                start,
+                end,
+                DocumentLinkTarget::Uri(content[start..end].to_string()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for captures in PATH_REGEX.captures_iter(&content) {
+        let m = captures.get(0).unwrap();
+        if links
+            .iter()
+            .any(|(start, end, _)| m.start() < *end && *start < m.end())
+        {
+            continue;
+        }
+
+        let line_start = captures.get(1).unwrap().start();
+        let path = Path::new(&content[m.start()..line_start - 1]);
+        let path = match base {
+            Some(base) if path.is_relative() => base.join(path),
+            _ => path.to_path_buf(),
+        };
+        if check_exists && !path.is_file() {
+            continue;
+        }
+
+        let line = captures[1].parse::<u32>().unwrap_or(1);
+        let col = captures
+            .get(2)
+            .and_then(|col| col.as_str().parse::<u32>().ok())
+            .unwrap_or(1);
+        links.push((
+            m.start(),
+            m.end(),
+            DocumentLinkTarget::File {
+                path,
+                position: Some(Position::new(
+                    line.saturating_sub(1),
+                    col.saturating_sub(1),
+                )),
+            },
+        ));
+    }
+
+    links.sort_by_key(|(start, _, _)| *start);
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use lapce_xi_rope::Rope;
+    use lsp_types::{Position, Url};
+
+    use super::{find_document_links, DocumentLinkTarget};
+
+    #[test]
+    fn web_links() {
+        let text = Rope::from("see https://lapce.dev/docs. (http://a.b/c)");
+        let links = find_document_links(&text, None, false);
+        assert_eq!(
+            links,
+            vec![
+                (
+                    4,
+                    26,
+                    DocumentLinkTarget::Uri("https://lapce.dev/docs".to_string())
+                ),
+                (29, 41, DocumentLinkTarget::Uri("http://a.b/c".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn path_links() {
+        let text = Rope::from("error at src/main.rs:10:5 and lib.rs:3");
+        let links = find_document_links(&text, Some(Path::new("/base")), false);
+        assert_eq!(
+            links,
+            vec![
+                (
+                    9,
+                    25,
+                    DocumentLinkTarget::File {
+                        path: PathBuf::from("/base/src/main.rs"),
+                        position: Some(Position::new(9, 4)),
+                    }
+                ),
+                (
+                    30,
+                    38,
+                    DocumentLinkTarget::File {
+                        path: PathBuf::from("/base/lib.rs"),
+                        position: Some(Position::new(2, 0)),
+                    }
+                ),
+            ]
+        );
+
+        let links = find_document_links(&text, Some(Path::new("/base")), true);
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn path_inside_web_link() {
+        let text = Rope::from("https://host/file.rs:10");
+        let links = find_document_links(&text, None, false);
+        assert_eq!(links.len(), 1);
+        assert!(matches!(links[0].2, DocumentLinkTarget::Uri(_)));
+    }
+
+    #[test]
+    fn link_target_from_url() {
+        let url = Url::parse("https://lapce.dev").unwrap();
+        assert_eq!(
+            DocumentLinkTarget::from_url(&url),
+            Some(DocumentLinkTarget::Uri("https://lapce.dev/".to_string()))
+        );
+
+        let url = Url::parse("file:///tmp/main.rs#L10,5").unwrap();
+        assert_eq!(
+            DocumentLinkTarget::from_url(&url),
+            Some(DocumentLinkTarget::File {
+                path: PathBuf::from("/tmp/main.rs"),
+                position: Some(Position::new(9, 4)),
+            })
+        );
+
+        let url = Url::parse("file:///tmp/main.rs").unwrap();
+        assert_eq!(
+            DocumentLinkTarget::from_url(&url),
+            Some(DocumentLinkTarget::File {
+                path: PathBuf::from("/tmp/main.rs"),
+                position: None,
+            })
+        );
+    }
+}
@@ -3,7 +3,7 @@
 use smallvec::SmallVec;
 
 /// `PhantomText` is for text that is not in the actual document, but should be rendered with it.  
-/// Ex: Inlay hints, IME text, error lens' diagnostics, etc
+/// Ex: Inlay hints, IME text, error lens' diagnostics, color swatches, etc
 pub struct PhantomText {
     /// The kind is currently used for sorting the phantom text on a line
     pub kind: PhantomTextKind,
@@ -23,8 +23,16 @@ pub enum PhantomTextKind {
     Ime,
     /// Completion lens
     Completion,
+    /// The inline completion suggested at the cursor
+    InlineCompletion,
     /// Inlay hints supplied by an LSP/PSP (like type annotations)
     InlayHint,
+    /// Swatches in front of the colors supplied by an LSP/PSP
+    Color,
+    /// The values of the variables on a line while debugging
+    InlineValue,
+    /// The git blame of the cursor line
+    Blame,
     /// Error lens
     Diagnostic,
 }
@@ -14,21 +14,29 @@
     reactive::{use_context, ReadSignal, RwSignal, Scope},
 };
 use lapce_core::{
-    buffer::{diff::DiffLines, rope_text::RopeText, InvalLines},
+    buffer::{diff::DiffLines, rope_text::RopeText, Buffer, InvalLines},
     command::{EditCommand, FocusCommand, MotionModeCommand, MultiSelectionCommand},
     cursor::{Cursor, CursorMode},
     editor::EditType,
     mode::{Mode, MotionMode},
     movement::Movement,
-    selection::{InsertDrift, Selection},
+    selection::{InsertDrift, SelRegion, Selection},
     syntax::edit::SyntaxEdit,
+    word::WordCursor,
+};
+use lapce_rpc::{
+    buffer::BufferId, dap_types::DapId,
+    inline_completion::InlineCompletionTriggerKind, plugin::PluginId,
+    proxy::ProxyResponse, RpcError,
 };
-use lapce_rpc::{buffer::BufferId, plugin::PluginId, proxy::ProxyResponse};
 use lapce_xi_rope::{Rope, RopeDelta, Transformer};
 use lsp_types::{
-    CompletionItem, CompletionTextEdit, GotoDefinitionResponse, HoverContents,
-    Location, MarkedString, MarkupKind, TextEdit,
+    CompletionItem, CompletionTextEdit, CompletionTriggerKind,
+    GotoDefinitionResponse, HoverContents, Location, MarkedString, MarkupKind,
+    Range, SignatureHelpContext, SignatureHelpTriggerKind, TextEdit,
 };
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -36,13 +44,16 @@
         CommandExecuted, CommandKind, InternalCommand, LapceCommand,
         LapceWorkbenchCommand,
     },
-    completion::{clear_completion_lens, CompletionStatus},
+    completion::{clear_completion_lens, CompletionStatus, ScoredCompletionItem},
     config::LapceConfig,
     db::LapceDb,
-    doc::{DocContent, Document},
+    doc::{document_link::DocumentLinkTarget, DocContent, Document},
     editor::location::{EditorLocation, EditorPosition},
     editor_tab::EditorTabChild,
     id::{DiffEditorId, EditorId, EditorTabId},
+    inline_completion::{
+        next_line, next_word, InlineCompletionStatus, INLINE_COMPLETION_DELAY,
+    },
     keypress::{condition::Condition, KeyPressFocus},
     main_split::{MainSplitData, SplitDirection, SplitMoveDirection},
     markdown::{
@@ -50,6 +61,7 @@
     },
     proxy::path_from_url,
     snippet::Snippet,
+    source_control::blame_hover_text,
     window_tab::{CommonData, Focus, WindowTabData},
 };
 
@@ -161,6 +173,53 @@ pub fn to_data(
 
 pub type SnippetIndex = Vec<(usize, (usize, usize))>;
 
+/// A read-only editor embedded below a line of its host editor, showing
+/// another location without leaving the current file.
+#[derive(Clone)]
+pub struct PeekData {
+    /// The offset in the host editor that the peek is shown below
+    pub offset: usize,
+    pub editor: Rc<EditorData>,
+}
+
+/// The word pattern used for linked editing when the language server
+/// doesn't provide one
+static DEFAULT_WORD_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(-?\d*\.\d\w*)|([^`~!@#$%^&*()\-=+\[{\]}\\|;:'",.<>/?\s]+)"#)
+        .unwrap()
+});
+
+/// The ranges that are edited together, such as the names of an opening
+/// and a closing tag.
+#[derive(Clone)]
+pub struct LinkedEditing {
+    /// The index of the range the primary cursor is in
+    pub index: usize,
+    pub ranges: Vec<(usize, usize)>,
+    /// Linked editing stops once the text of a range no longer matches this
+    pub word_pattern: Regex,
+}
+
+impl LinkedEditing {
+    fn is_valid(&self, buffer: &Buffer) -> bool {
+        self.ranges.iter().all(|(start, end)| {
+            let text = buffer.slice_to_cow(*start..*end);
+            text.is_empty()
+                || self
+                    .word_pattern
+                    .find(&text)
+                    .map_or(false, |m| m.start() == 0 && m.end() == text.len())
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum GotoKind {
+    TypeDefinition,
+    Implementation,
+    Declaration,
+}
+
 #[derive(Clone)]
 pub struct EditorData {
     pub scope: Scope,
@@ -175,12 +234,17 @@ pub struct EditorData {
     pub scroll_delta: RwSignal<Vec2>,
     pub scroll_to: RwSignal<Option<Vec2>>,
     pub snippet: RwSignal<Option<SnippetIndex>>,
+    pub linked_editing: RwSignal<Option<LinkedEditing>>,
+    /// The word that linked editing ranges were last requested for, so that
+    /// they're only requested again once the cursor enters another word
+    linked_editing_word: RwSignal<Option<(usize, usize)>>,
     pub last_movement: RwSignal<Movement>,
     pub inline_find: RwSignal<Option<InlineFindDirection>>,
     pub last_inline_find: RwSignal<Option<(InlineFindDirection, String)>>,
     pub find_focus: RwSignal<bool>,
     pub active: RwSignal<bool>,
     pub sticky_header_height: RwSignal<f64>,
+    pub peek: RwSignal<Option<PeekData>>,
     pub common: Rc<CommonData>,
 }
 
@@ -232,6 +296,8 @@ pub fn new(
             cursor,
             confirmed: cx.create_rw_signal(false),
             snippet: cx.create_rw_signal(None),
+            linked_editing: cx.create_rw_signal(None),
+            linked_editing_word: cx.create_rw_signal(None),
             window_origin: cx.create_rw_signal(Point::ZERO),
             viewport: cx.create_rw_signal(Rect::ZERO),
             scroll_delta: cx.create_rw_signal(Vec2::ZERO),
@@ -242,6 +308,7 @@ pub fn new(
             find_focus: cx.create_rw_signal(false),
             active: cx.create_rw_signal(false),
             sticky_header_height: cx.create_rw_signal(0.0),
+            peek: cx.create_rw_signal(None),
             common,
         }
     }
@@ -304,12 +371,15 @@ pub fn copy(
             window_origin: cx.create_rw_signal(Point::ZERO),
             confirmed: cx.create_rw_signal(true),
             snippet: cx.create_rw_signal(None),
+            linked_editing: cx.create_rw_signal(None),
+            linked_editing_word: cx.create_rw_signal(None),
             last_movement: cx.create_rw_signal(self.last_movement.get_untracked()),
             inline_find: cx.create_rw_signal(None),
             last_inline_find: cx.create_rw_signal(None),
             find_focus: cx.create_rw_signal(false),
             active: cx.create_rw_signal(false),
             sticky_header_height: cx.create_rw_signal(0.0),
+            peek: cx.create_rw_signal(None),
             common: self.common.clone(),
         }
     }
@@ -354,6 +424,31 @@ fn run_edit_command(&self, cmd: &EditCommand) -> CommandExecuted {
         self.cursor.set(cursor);
         self.common.register.set(register);
 
+        match cmd {
+            EditCommand::ClipboardPaste
+            | EditCommand::PasteBefore
+            | EditCommand::Paste
+                if !deltas.is_empty()
+                    && self
+                        .common
+                        .config
+                        .with_untracked(|config| config.editor.format_on_paste) =>
+            {
+                let (start, end) = deltas.iter().fold(
+                    (usize::MAX, 0),
+                    |(start, end), (delta, _, _)| {
+                        let (iv, new_len) = delta.summary();
+                        (start.min(iv.start), end.max(iv.start + new_len))
+                    },
+                );
+                self.format_range(start, end);
+            }
+            EditCommand::InsertNewLine if !deltas.is_empty() => {
+                self.on_type_formatting("\n");
+            }
+            _ => {}
+        }
+
         if show_completion(cmd, &doc_before_edit, &deltas) {
             self.update_completion(false);
         } else {
@@ -362,7 +457,13 @@ fn run_edit_command(&self, cmd: &EditCommand) -> CommandExecuted {
         self.apply_deltas(&deltas);
         if let EditCommand::NormalMode = cmd {
             self.snippet.set(None);
+            self.cancel_signature();
+            self.cancel_inline_completion();
+        } else if !deltas.is_empty() {
+            self.update_signature(None);
+            self.update_inline_completion(InlineCompletionTriggerKind::AUTOMATIC);
         }
+        self.update_linked_editing();
 
         CommandExecuted::Yes
     }
@@ -402,7 +503,9 @@ fn run_multi_selection_command(
         let mut cursor = self.cursor.get_untracked();
         movement::do_multi_selection(&self.view, &mut cursor, cmd);
         self.cursor.set(cursor);
-        // self.cancel_signature();
+        self.update_linked_editing();
+        self.cancel_signature();
+        self.cancel_inline_completion();
         self.cancel_completion();
         CommandExecuted::Yes
     }
@@ -463,6 +566,9 @@ fn run_move_command(
                 }
             })
         }
+        self.update_linked_editing();
+        self.update_signature(None);
+        self.cancel_inline_completion();
         self.cancel_completion();
         CommandExecuted::Yes
     }
@@ -701,7 +807,7 @@ pub fn run_focus_command(
                         if last_placeholder {
                             *snippet = None;
                         }
-                        // self.update_signature();
+                        self.update_signature(None);
                         self.cancel_completion();
                     }
                 });
@@ -733,7 +839,7 @@ pub fn run_focus_command(
                                     cursor.set_insert(selection);
                                 });
                             }
-                            // self.update_signature();
+                            self.update_signature(None);
                             self.cancel_completion();
                         }
                     }
@@ -742,6 +848,59 @@ pub fn run_focus_command(
             FocusCommand::GotoDefinition => {
                 self.go_to_definition();
             }
+            FocusCommand::GotoTypeDefinition => {
+                self.go_to_kind(GotoKind::TypeDefinition);
+            }
+            FocusCommand::GotoImplementation => {
+                self.go_to_kind(GotoKind::Implementation);
+            }
+            FocusCommand::GotoDeclaration => {
+                self.go_to_kind(GotoKind::Declaration);
+            }
+            FocusCommand::PeekDefinition => {
+                self.peek_definition();
+            }
+            FocusCommand::ModalClose => {
+                self.close_peek();
+            }
+            FocusCommand::InlineCompletionInvoke => {
+                self.update_inline_completion(InlineCompletionTriggerKind::INVOKED);
+            }
+            FocusCommand::InlineCompletionAccept
+            | FocusCommand::InlineCompletionAcceptWord
+            | FocusCommand::InlineCompletionAcceptLine => {
+                self.accept_inline_completion(cmd);
+            }
+            FocusCommand::InlineCompletionNext => {
+                self.common.inline_completion.update(|c| c.next());
+                self.show_inline_completion();
+            }
+            FocusCommand::InlineCompletionPrevious => {
+                self.common.inline_completion.update(|c| c.previous());
+                self.show_inline_completion();
+            }
+            FocusCommand::FormatSelection => {
+                let (start, end) = self
+                    .view
+                    .doc
+                    .get_untracked()
+                    .buffer
+                    .with_untracked(|buffer| {
+                        let selection =
+                            self.cursor.with_untracked(|c| c.edit_selection(buffer));
+                        if selection.is_caret() {
+                            // Without a selection, format the current line
+                            let line = buffer.line_of_offset(selection.min_offset());
+                            (
+                                buffer.offset_of_line(line),
+                                buffer.line_end_offset(line, true),
+                            )
+                        } else {
+                            (selection.min_offset(), selection.max_offset())
+                        }
+                    });
+                self.format_range(start, end);
+            }
             FocusCommand::ShowCodeActions => {
                 self.show_code_actions(false);
             }
@@ -785,6 +944,36 @@ pub fn run_focus_command(
                     self.common.find.replace_focus.set(true);
                 }
             }
+            FocusCommand::NextDiff => {
+                self.next_diff();
+            }
+            FocusCommand::PreviousDiff => {
+                self.previous_diff();
+            }
+            FocusCommand::StageHunk => {
+                if let Some(lines) = self.cursor_diff_hunk() {
+                    self.stage_lines(lines);
+                }
+            }
+            FocusCommand::UnstageHunk => {
+                if let Some(lines) = self.cursor_diff_hunk() {
+                    self.unstage_lines(lines);
+                }
+            }
+            FocusCommand::RevertHunk => {
+                if let Some(lines) = self.cursor_diff_hunk() {
+                    self.revert_lines(lines);
+                }
+            }
+            FocusCommand::StageSelectedLines => {
+                self.stage_lines(self.selected_lines());
+            }
+            FocusCommand::UnstageSelectedLines => {
+                self.unstage_lines(self.selected_lines());
+            }
+            FocusCommand::RevertSelectedLines => {
+                self.revert_lines(self.selected_lines());
+            }
             _ => {}
         }
 
@@ -909,23 +1098,9 @@ enum DefinitionOrReferece {
                     definition, ..
                 }) = result
                 {
-                    if let Some(location) = match definition {
-                        GotoDefinitionResponse::Scalar(location) => Some(location),
-                        GotoDefinitionResponse::Array(locations) => {
-                            if !locations.is_empty() {
-                                Some(locations[0].clone())
-                            } else {
-                                None
-                            }
-                        }
-                        GotoDefinitionResponse::Link(location_links) => {
-                            let location_link = location_links[0].clone();
-                            Some(Location {
-                                uri: location_link.target_uri,
-                                range: location_link.target_selection_range,
-                            })
-                        }
-                    } {
+                    if let Some(location) =
+                        goto_response_locations(definition).into_iter().next()
+                    {
                         if location.range.start == start_position {
                             proxy.get_references(
                                 path.clone(),
@@ -983,6 +1158,182 @@ enum DefinitionOrReferece {
         );
     }
 
+    /// Go to the type definition, implementation or declaration of the symbol
+    /// under the cursor. Multiple results are shown in the palette.
+    fn go_to_kind(&self, kind: GotoKind) {
+        let doc = self.view.doc.get_untracked();
+        let path = match if doc.loaded() {
+            doc.content.with_untracked(|c| c.path().cloned())
+        } else {
+            None
+        } {
+            Some(path) => path,
+            None => return,
+        };
+
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let position = doc
+            .buffer
+            .with_untracked(|buffer| buffer.offset_to_position(offset));
+
+        let internal_command = self.common.internal_command;
+        let cursor = self.cursor.read_only();
+        let send = create_ext_action(self.scope, move |locations: Vec<Location>| {
+            let current_offset = cursor.with_untracked(|c| c.offset());
+            if current_offset != offset {
+                return;
+            }
+
+            let mut locations: Vec<EditorLocation> = locations
+                .into_iter()
+                .map(|l| EditorLocation {
+                    path: path_from_url(&l.uri),
+                    position: Some(EditorPosition::Position(l.range.start)),
+                    scroll_offset: None,
+                    ignore_unconfirmed: false,
+                    same_editor_tab: false,
+                })
+                .collect();
+            if locations.len() == 1 {
+                internal_command.send(InternalCommand::JumpToLocation {
+                    location: locations.remove(0),
+                });
+            } else {
+                internal_command.send(InternalCommand::PaletteReferences {
+                    references: locations,
+                });
+            }
+        });
+        let f = move |result: Result<ProxyResponse, RpcError>| {
+            let response = match result {
+                Ok(ProxyResponse::GetTypeDefinition { definition, .. }) => {
+                    definition
+                }
+                Ok(ProxyResponse::GetImplementationResponse {
+                    implementation,
+                    ..
+                }) => implementation,
+                Ok(ProxyResponse::GetDeclarationResponse {
+                    declaration, ..
+                }) => declaration,
+                _ => return,
+            };
+            let locations = goto_response_locations(response);
+            if !locations.is_empty() {
+                send(locations);
+            }
+        };
+
+        let proxy = &self.common.proxy;
+        match kind {
+            GotoKind::TypeDefinition => {
+                proxy.get_type_definition(offset, path, position, f)
+            }
+            GotoKind::Implementation => {
+                proxy.get_implementation(offset, path, position, f)
+            }
+            GotoKind::Declaration => {
+                proxy.get_declaration(offset, path, position, f)
+            }
+        }
+    }
+
+    fn peek_definition(&self) {
+        let doc = self.view.doc.get_untracked();
+        let path = match if doc.loaded() {
+            doc.content.with_untracked(|c| c.path().cloned())
+        } else {
+            None
+        } {
+            Some(path) => path,
+            None => return,
+        };
+
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let position = doc
+            .buffer
+            .with_untracked(|buffer| buffer.offset_to_position(offset));
+
+        let editor_id = self.editor_id;
+        let internal_command = self.common.internal_command;
+        let cursor = self.cursor.read_only();
+        let send = create_ext_action(self.scope, move |location: Location| {
+            let current_offset = cursor.with_untracked(|c| c.offset());
+            if current_offset != offset {
+                return;
+            }
+
+            internal_command.send(InternalCommand::PeekLocation {
+                editor_id,
+                offset,
+                location: EditorLocation {
+                    path: path_from_url(&location.uri),
+                    position: Some(EditorPosition::Position(location.range.start)),
+                    scroll_offset: None,
+                    ignore_unconfirmed: false,
+                    same_editor_tab: false,
+                },
+            });
+        });
+        self.common
+            .proxy
+            .get_definition(offset, path, position, move |result| {
+                if let Ok(ProxyResponse::GetDefinitionResponse {
+                    definition, ..
+                }) = result
+                {
+                    if let Some(location) =
+                        goto_response_locations(definition).into_iter().next()
+                    {
+                        send(location);
+                    }
+                }
+            });
+    }
+
+    /// Show `editor` embedded below the line of `offset`, replacing any
+    /// existing peek.
+    pub fn show_peek(&self, offset: usize, editor: Rc<EditorData>) {
+        self.peek.set(Some(PeekData { offset, editor }));
+    }
+
+    pub fn close_peek(&self) {
+        if self.peek.with_untracked(|peek| peek.is_some()) {
+            self.peek.set(None);
+        }
+    }
+
+    /// Open the location currently shown in the peek editor as a normal editor
+    pub fn open_peek(&self) {
+        let peek_editor = match self.peek.get_untracked() {
+            Some(peek) => peek.editor,
+            None => return,
+        };
+        let path = match peek_editor
+            .view
+            .doc
+            .get_untracked()
+            .content
+            .with_untracked(|c| c.path().cloned())
+        {
+            Some(path) => path,
+            None => return,
+        };
+        let offset = peek_editor.cursor.with_untracked(|c| c.offset());
+        self.close_peek();
+        self.common
+            .internal_command
+            .send(InternalCommand::JumpToLocation {
+                location: EditorLocation {
+                    path,
+                    position: Some(EditorPosition::Offset(offset)),
+                    scroll_offset: None,
+                    ignore_unconfirmed: false,
+                    same_editor_tab: false,
+                },
+            });
+    }
+
     fn page_move(&self, down: bool, mods: ModifiersState) {
         let config = self.common.config.get_untracked();
         let viewport = self.viewport.get_untracked();
@@ -1060,50 +1411,96 @@ fn select_completion(&self) {
             .completion
             .with_untracked(|c| c.current_item().cloned());
         self.cancel_completion();
-        let doc = self.view.doc.get_untracked();
         if let Some(item) = item {
-            if item.item.data.is_some() {
-                let editor = self.clone();
-                let rev = doc.buffer.with_untracked(|buffer| buffer.rev());
-                let path = doc.content.with_untracked(|c| c.path().cloned());
-                let offset = self.cursor.with_untracked(|c| c.offset());
-                let buffer = doc.buffer;
-                let content = doc.content;
-                let send = create_ext_action(self.scope, move |item| {
-                    if editor.cursor.with_untracked(|c| c.offset() != offset) {
-                        return;
-                    }
-                    if buffer.with_untracked(|b| b.rev()) != rev
-                        || content.with_untracked(|content| {
-                            content.path() != path.as_ref()
-                        })
-                    {
-                        return;
-                    }
-                    let _ = editor.apply_completion_item(&item);
-                });
-                self.common.proxy.completion_resolve(
-                    item.plugin_id,
-                    item.item.clone(),
-                    move |result| {
-                        let item =
-                            if let Ok(ProxyResponse::CompletionResolveResponse {
-                                item,
-                            }) = result
-                            {
-                                *item
-                            } else {
-                                item.item.clone()
-                            };
-                        send(item);
-                    },
-                );
-            } else {
-                let _ = self.apply_completion_item(&item.item);
+            self.resolve_and_apply_completion(item, None);
+        }
+    }
+
+    /// Accept the current completion item if `c` is one of its commit characters,
+    /// in which case the character is inserted after the item is applied, and it
+    /// returns true.
+    fn commit_completion(&self, c: &str) -> bool {
+        if !self.has_completions() {
+            return false;
+        }
+        let item = self.common.completion.with_untracked(|completion| {
+            completion
+                .current_item()
+                .filter(|item| {
+                    item.item
+                        .commit_characters
+                        .as_ref()
+                        .map(|chars| chars.iter().any(|commit| commit == c))
+                        .unwrap_or(false)
+                })
+                .cloned()
+        });
+        match item {
+            Some(item) => {
+                self.cancel_completion();
+                self.resolve_and_apply_completion(item, Some(c.to_string()));
+                true
             }
+            None => false,
         }
     }
 
+    /// Apply the completion item, resolving it first if it can be, as some
+    /// servers only send parts of it, like the additional text edits of an auto
+    /// import, in the resolve response. The commit character it was accepted
+    /// with is inserted after it.
+    fn resolve_and_apply_completion(
+        &self,
+        item: ScoredCompletionItem,
+        commit_char: Option<String>,
+    ) {
+        if item.item.data.is_none() {
+            let _ = self.apply_completion_item(&item.item);
+            if let Some(c) = commit_char {
+                self.receive_char(&c);
+            }
+            return;
+        }
+
+        let doc = self.view.doc.get_untracked();
+        let editor = self.clone();
+        let rev = doc.buffer.with_untracked(|buffer| buffer.rev());
+        let path = doc.content.with_untracked(|c| c.path().cloned());
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let buffer = doc.buffer;
+        let content = doc.content;
+        let send = create_ext_action(self.scope, move |item| {
+            if content.with_untracked(|content| content.path() != path.as_ref()) {
+                return;
+            }
+            if editor.cursor.with_untracked(|c| c.offset() == offset)
+                && buffer.with_untracked(|b| b.rev()) == rev
+            {
+                let _ = editor.apply_completion_item(&item);
+            }
+            // The character that was typed isn't lost, even if the item can't
+            // be applied anymore
+            if let Some(c) = commit_char.as_deref() {
+                editor.receive_char(c);
+            }
+        });
+        self.common.proxy.completion_resolve(
+            item.plugin_id,
+            item.item.clone(),
+            move |result| {
+                let item =
+                    if let Ok(ProxyResponse::CompletionResolveResponse { item }) =
+                        result
+                    {
+                        *item
+                    } else {
+                        item.item.clone()
+                    };
+                send(item);
+            },
+        );
+    }
+
     pub fn cancel_completion(&self) {
         if self.common.completion.with_untracked(|c| c.status)
             == CompletionStatus::Inactive
@@ -1161,6 +1558,7 @@ fn update_completion(&self, display_if_empty_input: bool) {
                 && completion.path == path
         }) {
             self.common.completion.update(|completion| {
+                let was_incomplete = completion.is_incomplete(&completion.input);
                 completion.update_input(input.clone());
 
                 let cursor_offset = self.cursor.with_untracked(|c| c.offset());
@@ -1176,19 +1574,28 @@ fn update_completion(&self, display_if_empty_input: bool) {
                         path.clone(),
                         "".to_string(),
                         start_pos,
+                        CompletionTriggerKind::INVOKED,
                     );
                 }
 
-                if !completion.input_items.contains_key(&input) {
+                if !completion.input_items.contains_key(&input)
+                    || completion.is_incomplete(&input)
+                {
                     let position = doc
                         .buffer
                         .with_untracked(|buffer| buffer.offset_to_position(offset));
+                    let trigger_kind = if was_incomplete {
+                        CompletionTriggerKind::TRIGGER_FOR_INCOMPLETE_COMPLETIONS
+                    } else {
+                        CompletionTriggerKind::INVOKED
+                    };
                     completion.request(
                         self.editor_id,
                         &self.common.proxy,
                         path,
                         input,
                         position,
+                        trigger_kind,
                     );
                 }
             });
@@ -1202,6 +1609,7 @@ fn update_completion(&self, display_if_empty_input: bool) {
             completion.input = input.clone();
             completion.status = CompletionStatus::Started;
             completion.input_items.clear();
+            completion.incomplete_inputs.clear();
             completion.request_id += 1;
             let start_pos = doc
                 .buffer
@@ -1212,6 +1620,7 @@ fn update_completion(&self, display_if_empty_input: bool) {
                 path.clone(),
                 "".to_string(),
                 start_pos,
+                CompletionTriggerKind::INVOKED,
             );
 
             if !input.is_empty() {
@@ -1224,6 +1633,7 @@ fn update_completion(&self, display_if_empty_input: bool) {
                     path,
                     input,
                     position,
+                    CompletionTriggerKind::INVOKED,
                 );
             }
         });
@@ -1260,43 +1670,56 @@ fn apply_completion_item(&self, item: &CompletionItem) -> Result<()> {
             .insert_text_format
             .unwrap_or(lsp_types::InsertTextFormat::PLAIN_TEXT);
         if let Some(edit) = &item.text_edit {
-            match edit {
+            let (range, new_text, replace) = match edit {
                 CompletionTextEdit::Edit(edit) => {
-                    let offset = cursor.offset();
-                    let start_offset = buffer.prev_code_boundary(offset);
-                    let end_offset = buffer.next_code_boundary(offset);
-                    let edit_start = buffer.offset_of_position(&edit.range.start);
-                    let edit_end = buffer.offset_of_position(&edit.range.end);
-
-                    let selection = lapce_core::selection::Selection::region(
-                        start_offset.min(edit_start),
-                        end_offset.max(edit_end),
+                    (&edit.range, &edit.new_text, true)
+                }
+                CompletionTextEdit::InsertAndReplace(edit) => {
+                    let replace = self
+                        .common
+                        .config
+                        .with_untracked(|config| config.editor.completion_replace);
+                    let range = if replace { &edit.replace } else { &edit.insert };
+                    (range, &edit.new_text, replace)
+                }
+            };
+            let offset = cursor.offset();
+            let start_offset = buffer.prev_code_boundary(offset);
+            // Inserting keeps the rest of the word after the cursor
+            let end_offset = if replace {
+                buffer.next_code_boundary(offset)
+            } else {
+                offset
+            };
+            let edit_start = buffer.offset_of_position(&range.start);
+            let edit_end = buffer.offset_of_position(&range.end);
+
+            let selection = lapce_core::selection::Selection::region(
+                start_offset.min(edit_start),
+                end_offset.max(edit_end),
+            );
+            match text_format {
+                lsp_types::InsertTextFormat::PLAIN_TEXT => {
+                    self.do_edit(
+                        &selection,
+                        &[
+                            &[(selection.clone(), new_text.as_str())][..],
+                            &additional_edit[..],
+                        ]
+                        .concat(),
                     );
-                    match text_format {
-                        lsp_types::InsertTextFormat::PLAIN_TEXT => {
-                            self.do_edit(
-                                &selection,
-                                &[
-                                    &[(selection.clone(), edit.new_text.as_str())][..],
-                                    &additional_edit[..],
-                                ]
-                                .concat(),
-                            );
-                            return Ok(());
-                        }
-                        lsp_types::InsertTextFormat::SNIPPET => {
-                            self.completion_apply_snippet(
-                                &edit.new_text,
-                                &selection,
-                                additional_edit,
-                                start_offset,
-                            )?;
-                            return Ok(());
-                        }
-                        _ => {}
-                    }
+                    return Ok(());
+                }
+                lsp_types::InsertTextFormat::SNIPPET => {
+                    self.completion_apply_snippet(
+                        new_text,
+                        &selection,
+                        additional_edit,
+                        start_offset,
+                    )?;
+                    return Ok(());
                 }
-                CompletionTextEdit::InsertAndReplace(_) => (),
+                _ => {}
             }
         }
 
@@ -1462,40 +1885,254 @@ fn apply_deltas(&self, deltas: &[(RopeDelta, InvalLines, SyntaxEdit)]) {
         for (delta, _, _) in deltas {
             // self.inactive_apply_delta(delta);
             self.update_snippet_offset(delta);
+            self.update_linked_editing_offset(delta);
+            self.update_inline_completion_offset(delta);
+            self.update_peek_offset(delta);
             // self.update_breakpoints(delta);
         }
-        // self.update_signature();
+        if !deltas.is_empty() {
+            self.get_semantic_range_styles();
+        }
     }
 
-    fn update_snippet_offset(&self, delta: &RopeDelta) {
-        if self.snippet.with_untracked(|s| s.is_some()) {
-            self.snippet.update(|snippet| {
-                let mut transformer = Transformer::new(delta);
-                *snippet = Some(
-                    snippet
-                        .as_ref()
-                        .unwrap()
-                        .iter()
-                        .map(|(tab, (start, end))| {
-                            (
-                                *tab,
-                                (
-                                    transformer.transform(*start, false),
-                                    transformer.transform(*end, true),
-                                ),
-                            )
-                        })
-                        .collect(),
-                );
+    /// Request the semantic styles of the visible lines, which arrive sooner
+    /// than the ones of the whole document while those are pending
+    fn get_semantic_range_styles(&self) {
+        let viewport = self.viewport.get_untracked();
+        let line_height = self.common.config.get_untracked().editor.line_height();
+        let start_line = (viewport.y0 / line_height as f64).floor() as usize;
+        let end_line = (viewport.y1 / line_height as f64).ceil() as usize;
+        self.view
+            .doc
+            .get_untracked()
+            .get_semantic_range_styles(start_line, end_line);
+    }
+
+    fn update_peek_offset(&self, delta: &RopeDelta) {
+        if self.peek.with_untracked(|p| p.is_some()) {
+            self.peek.update(|peek| {
+                if let Some(peek) = peek.as_mut() {
+                    let mut transformer = Transformer::new(delta);
+                    peek.offset = transformer.transform(peek.offset, false);
+                }
             });
         }
     }
 
-    fn do_go_to_location(
-        &self,
-        location: EditorLocation,
-        edits: Option<Vec<TextEdit>>,
-    ) {
+    fn update_linked_editing_offset(&self, delta: &RopeDelta) {
+        if self.linked_editing.with_untracked(|l| l.is_some()) {
+            self.linked_editing.update(|linked_editing| {
+                if let Some(linked_editing) = linked_editing.as_mut() {
+                    let mut transformer = Transformer::new(delta);
+                    for (start, end) in linked_editing.ranges.iter_mut() {
+                        *start = transformer.transform(*start, false);
+                        *end = transformer.transform(*end, true);
+                    }
+                }
+            });
+        }
+        if self.linked_editing_word.with_untracked(|w| w.is_some()) {
+            self.linked_editing_word.update(|word| {
+                if let Some((start, end)) = word.as_mut() {
+                    let mut transformer = Transformer::new(delta);
+                    *start = transformer.transform(*start, false);
+                    *end = transformer.transform(*end, true);
+                }
+            });
+        }
+    }
+
+    /// Keep the linked editing ranges in sync with the cursor. The mirrored
+    /// cursors are collapsed back to the primary one once the cursor leaves
+    /// the ranges or the text no longer matches the word pattern, and new
+    /// ranges are looked up when a single caret in insert mode enters a word.
+    fn update_linked_editing(&self) {
+        let selection = match self.cursor.with_untracked(|c| c.mode.clone()) {
+            CursorMode::Insert(selection) => selection,
+            _ => {
+                self.linked_editing.set(None);
+                self.linked_editing_word.set(None);
+                return;
+            }
+        };
+
+        if let Some(linked_editing) = self.linked_editing.get_untracked() {
+            let same_len = selection.len() == linked_editing.ranges.len();
+            if same_len
+                && selection
+                    .regions()
+                    .iter()
+                    .zip(linked_editing.ranges.iter())
+                    .all(|(region, (start, end))| {
+                        *start <= region.min() && region.max() <= *end
+                    })
+                && self
+                    .view
+                    .doc
+                    .get_untracked()
+                    .buffer
+                    .with_untracked(|buffer| linked_editing.is_valid(buffer))
+            {
+                return;
+            }
+
+            self.linked_editing.set(None);
+            if same_len {
+                if let Some(region) = selection.regions().get(linked_editing.index) {
+                    let mut primary = Selection::new();
+                    primary.add_region(*region);
+                    self.cursor.update(|cursor| cursor.set_insert(primary));
+                }
+            }
+            return;
+        }
+
+        if selection.len() != 1 {
+            self.linked_editing_word.set(None);
+            return;
+        }
+        let Some(region) = selection.last_inserted() else {
+            return;
+        };
+        let in_word = self.linked_editing_word.with_untracked(|word| {
+            word.map_or(false, |(start, end)| {
+                start <= region.min() && region.max() <= end
+            })
+        });
+        if in_word {
+            return;
+        }
+
+        let word = self
+            .view
+            .doc
+            .get_untracked()
+            .buffer
+            .with_untracked(|buffer| {
+                WordCursor::new(buffer.text(), region.end).select_word()
+            });
+        self.linked_editing_word.set(Some(word));
+        self.get_linked_editing_ranges(*region);
+    }
+
+    /// Ask the language server for the ranges linked to the one under the
+    /// cursor, falling back to the matching tag from tree-sitter.
+    fn get_linked_editing_ranges(&self, region: SelRegion) {
+        let doc = self.view.doc.get_untracked();
+        let path = match doc.content.with_untracked(|c| c.path().cloned()) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let offset = region.end;
+        let (position, rev) = doc
+            .buffer
+            .with_untracked(|buffer| (buffer.offset_to_position(offset), doc.rev()));
+
+        let cursor = self.cursor;
+        let linked_editing = self.linked_editing;
+        let send = create_ext_action(self.scope, move |result| {
+            if doc.rev() != rev || linked_editing.with_untracked(|l| l.is_some()) {
+                return;
+            }
+            let unchanged = cursor.with_untracked(|c| match &c.mode {
+                CursorMode::Insert(selection) => {
+                    selection.len() == 1
+                        && selection.last_inserted() == Some(&region)
+                }
+                _ => false,
+            });
+            if !unchanged {
+                return;
+            }
+
+            let (ranges, word_pattern) = match result {
+                Ok(ProxyResponse::LinkedEditingRange { ranges }) => {
+                    let offsets = doc.buffer.with_untracked(|buffer| {
+                        ranges
+                            .ranges
+                            .iter()
+                            .map(|range| {
+                                (
+                                    buffer.offset_of_position(&range.start),
+                                    buffer.offset_of_position(&range.end),
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    });
+                    let word_pattern = ranges
+                        .word_pattern
+                        .and_then(|pattern| Regex::new(&pattern).ok());
+                    (offsets, word_pattern)
+                }
+                _ => (
+                    doc.syntax
+                        .with_untracked(|syntax| {
+                            syntax.find_linked_tag_names(offset)
+                        })
+                        .map(|ranges| ranges.to_vec())
+                        .unwrap_or_default(),
+                    None,
+                ),
+            };
+            let mut ranges = ranges;
+            ranges.sort();
+            ranges.dedup();
+            if ranges.len() < 2 {
+                return;
+            }
+
+            let index = match ranges.iter().position(|(start, end)| {
+                *start <= region.min() && region.max() <= *end
+            }) {
+                Some(index) => index,
+                None => return,
+            };
+            let selection = Selection::mirrored(region, &ranges, index);
+            linked_editing.set(Some(LinkedEditing {
+                index,
+                ranges,
+                word_pattern: word_pattern
+                    .unwrap_or_else(|| DEFAULT_WORD_PATTERN.clone()),
+            }));
+            cursor.update(|cursor| cursor.set_insert(selection));
+        });
+        self.common
+            .proxy
+            .linked_editing_range(path, position, move |result| {
+                send(result);
+            });
+    }
+
+    fn update_snippet_offset(&self, delta: &RopeDelta) {
+        if self.snippet.with_untracked(|s| s.is_some()) {
+            self.snippet.update(|snippet| {
+                let mut transformer = Transformer::new(delta);
+                *snippet = Some(
+                    snippet
+                        .as_ref()
+                        .unwrap()
+                        .iter()
+                        .map(|(tab, (start, end))| {
+                            (
+                                *tab,
+                                (
+                                    transformer.transform(*start, false),
+                                    transformer.transform(*end, true),
+                                ),
+                            )
+                        })
+                        .collect(),
+                );
+            });
+        }
+    }
+
+    fn do_go_to_location(
+        &self,
+        location: EditorLocation,
+        edits: Option<Vec<TextEdit>>,
+    ) {
         if let Some(position) = location.position {
             self.go_to_position(position, location.scroll_offset, edits);
         } else if let Some(edits) = edits.as_ref() {
@@ -1709,6 +2346,77 @@ pub fn save(
         }
     }
 
+    /// Format the text between `start` and `end` with the range formatting
+    /// of the language server.
+    fn format_range(&self, start: usize, end: usize) {
+        let doc = self.view.doc.get_untracked();
+        let path = match if doc.loaded() {
+            doc.content.with_untracked(|c| c.path().cloned())
+        } else {
+            None
+        } {
+            Some(path) => path,
+            None => return,
+        };
+
+        let rev = doc.rev();
+        let range = doc.buffer.with_untracked(|buffer| Range {
+            start: buffer.offset_to_position(start),
+            end: buffer.offset_to_position(end),
+        });
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, move |edits: Vec<TextEdit>| {
+            let current_rev = editor.view.doc.with_untracked(|doc| doc.rev());
+            if current_rev == rev {
+                editor.do_text_edit(&edits);
+            }
+        });
+        self.common
+            .proxy
+            .get_range_formatting(path, range, move |result| {
+                if let Ok(ProxyResponse::GetRangeFormatting { edits }) = result {
+                    send(edits);
+                }
+            });
+    }
+
+    /// Ask the language server for the edits after typing `ch`. The server is
+    /// only asked if `ch` is one of its trigger characters.
+    fn on_type_formatting(&self, ch: &str) {
+        let doc = self.view.doc.get_untracked();
+        let path = match if doc.loaded() {
+            doc.content.with_untracked(|c| c.path().cloned())
+        } else {
+            None
+        } {
+            Some(path) => path,
+            None => return,
+        };
+
+        let rev = doc.rev();
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let position = doc
+            .buffer
+            .with_untracked(|buffer| buffer.offset_to_position(offset));
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, move |edits: Vec<TextEdit>| {
+            let current_rev = editor.view.doc.with_untracked(|doc| doc.rev());
+            if current_rev == rev {
+                editor.do_text_edit(&edits);
+            }
+        });
+        self.common.proxy.get_on_type_formatting(
+            path,
+            position,
+            ch.to_string(),
+            move |result| {
+                if let Ok(ProxyResponse::GetOnTypeFormatting { edits }) = result {
+                    send(edits);
+                }
+            },
+        );
+    }
+
     fn search_whole_word_forward(&self, mods: ModifiersState) {
         let offset = self.cursor.with_untracked(|c| c.offset());
         let (word, buffer) =
@@ -2031,6 +2739,60 @@ fn single_click(&self, pointer_event: &PointerInputEvent) {
                 pointer_event.modifiers.alt_key(),
             )
         });
+        self.update_linked_editing();
+        self.update_signature(None);
+        self.cancel_inline_completion();
+
+        let open_link = if cfg!(target_os = "macos") {
+            pointer_event.modifiers.super_key()
+        } else {
+            pointer_event.modifiers.control_key()
+        };
+        if open_link {
+            self.open_document_link(new_offset);
+        }
+    }
+
+    /// Open the link at the offset, or the color picker for the color at it
+    fn open_document_link(&self, offset: usize) {
+        let doc = self.view.doc.get_untracked();
+        if let Some(target) = doc.document_link_at(offset) {
+            match target {
+                DocumentLinkTarget::Uri(uri) => {
+                    self.common
+                        .internal_command
+                        .send(InternalCommand::OpenWebUri { uri });
+                }
+                DocumentLinkTarget::File { path, position } => {
+                    self.common.internal_command.send(
+                        InternalCommand::JumpToLocation {
+                            location: EditorLocation {
+                                path,
+                                position: position.map(EditorPosition::Position),
+                                scroll_offset: None,
+                                ignore_unconfirmed: false,
+                                same_editor_tab: false,
+                            },
+                        },
+                    );
+                }
+            }
+            return;
+        }
+
+        if let Some((interval, color)) = doc.document_color_at(offset) {
+            let path = match doc.content.with_untracked(|c| c.path().cloned()) {
+                Some(path) => path,
+                None => return,
+            };
+            let range = doc.buffer.with_untracked(|buffer| Range {
+                start: buffer.offset_to_position(interval.start),
+                end: buffer.offset_to_position(interval.end),
+            });
+            self.common
+                .internal_command
+                .send(InternalCommand::PaletteColorPicker { path, range, color });
+        }
     }
 
     fn double_click(&self, pointer_event: &PointerInputEvent) {
@@ -2092,7 +2854,9 @@ pub fn pointer_move(&self, pointer_event: &PointerMoveEvent) {
         }
         let hover_delay = self.common.config.get_untracked().editor.hover_delay;
         if hover_delay > 0 {
-            if is_inside {
+            // Past the end of the line, where the blame of the line is shown
+            let blame = !is_inside && self.has_inline_blame(offset);
+            if is_inside || blame {
                 let start_offset = self
                     .view
                     .doc
@@ -2107,7 +2871,11 @@ pub fn pointer_move(&self, pointer_event: &PointerMoveEvent) {
                         if mouse_hover_timer.try_get_untracked() == Some(token)
                             && editor.editor_tab_id.try_get_untracked().is_some()
                         {
-                            editor.update_hover(start_offset);
+                            if blame {
+                                editor.show_blame_hover(start_offset);
+                            } else {
+                                editor.update_hover(start_offset);
+                            }
                         }
                     });
                 mouse_hover_timer.set(timer_token);
@@ -2136,10 +2904,13 @@ fn right_click(&self, pointer_event: &PointerInputEvent) {
 
         let is_file = doc.content.with_untracked(|content| content.is_file());
         let mut menu = Menu::new("");
-        let cmds = if is_file {
+        let mut cmds = if is_file {
             vec![
                 Some(CommandKind::Focus(FocusCommand::GotoDefinition)),
                 Some(CommandKind::Focus(FocusCommand::GotoTypeDefinition)),
+                Some(CommandKind::Focus(FocusCommand::GotoImplementation)),
+                Some(CommandKind::Focus(FocusCommand::GotoDeclaration)),
+                Some(CommandKind::Focus(FocusCommand::PeekDefinition)),
                 None,
                 Some(CommandKind::Focus(FocusCommand::Rename)),
                 None,
@@ -2162,6 +2933,36 @@ fn right_click(&self, pointer_event: &PointerInputEvent) {
                 )),
             ]
         };
+        if is_file {
+            // Stage, unstage or revert the changes under the cursor, before the
+            // command palette entry
+            let mut source_control_cmds = Vec::new();
+            if self.cursor_diff_hunk().is_some() {
+                source_control_cmds.extend([
+                    Some(CommandKind::Focus(FocusCommand::StageHunk)),
+                    Some(CommandKind::Focus(FocusCommand::UnstageHunk)),
+                    Some(CommandKind::Focus(FocusCommand::RevertHunk)),
+                ]);
+            }
+            let has_selection = self.cursor.with_untracked(|c| match &c.mode {
+                CursorMode::Insert(selection) => !selection.is_caret(),
+                CursorMode::Visual { .. } => true,
+                CursorMode::Normal(_) => false,
+            });
+            if has_selection {
+                source_control_cmds.extend([
+                    Some(CommandKind::Focus(FocusCommand::StageSelectedLines)),
+                    Some(CommandKind::Focus(FocusCommand::UnstageSelectedLines)),
+                    Some(CommandKind::Focus(FocusCommand::RevertSelectedLines)),
+                ]);
+            }
+            if !source_control_cmds.is_empty() {
+                let palette = cmds.split_off(cmds.len() - 2);
+                cmds.push(None);
+                cmds.extend(source_control_cmds);
+                cmds.extend(palette);
+            }
+        }
         let lapce_command = self.common.lapce_command;
         for cmd in cmds {
             if let Some(cmd) = cmd {
@@ -2182,7 +2983,266 @@ fn right_click(&self, pointer_event: &PointerInputEvent) {
         show_context_menu(menu, None);
     }
 
+    /// Whether the blame of the line of the offset is shown at its end.
+    fn has_inline_blame(&self, offset: usize) -> bool {
+        if !self
+            .common
+            .config
+            .get_untracked()
+            .editor
+            .enable_inline_blame
+        {
+            return false;
+        }
+        let doc = self.view.doc.get_untracked();
+        let line = doc
+            .buffer
+            .with_untracked(|buffer| buffer.line_of_offset(offset));
+        doc.blame_line.get_untracked() == Some(line)
+            && doc.line_blame(line).is_some()
+    }
+
+    /// Show the full commit message of the commit that last changed the line
+    /// of the offset, from where its diff can be opened.
+    pub fn show_blame_hover(&self, offset: usize) {
+        let doc = self.view.doc.get_untracked();
+        let path = match doc.content.with_untracked(|c| c.path().cloned()) {
+            Some(path) => path,
+            None => return,
+        };
+        let line = doc
+            .buffer
+            .with_untracked(|buffer| buffer.line_of_offset(offset));
+        let hunk = match doc.line_blame(line) {
+            Some(hunk) => hunk,
+            None => return,
+        };
+        let config = self.common.config.get_untracked();
+        let hover_data = &self.common.hover;
+        hover_data.content.set(parse_markdown(
+            &blame_hover_text(&hunk),
+            1.5,
+            &config,
+        ));
+        hover_data.offset.set(offset);
+        hover_data.editor_id.set(self.editor_id);
+        hover_data.blame.set(Some((path, hunk)));
+        hover_data.active.set(true);
+    }
+
+    /// Move the cursor to the next hunk of changes since `head`, wrapping
+    /// around to the first one.
+    fn next_diff(&self) {
+        let doc = self.view.doc.get_untracked();
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let line = doc.buffer.with_untracked(|b| b.line_of_offset(offset));
+        let hunks = doc.diff_hunks();
+        let hunk = hunks
+            .iter()
+            .find(|hunk| hunk.start > line)
+            .or_else(|| hunks.first());
+        if let Some(hunk) = hunk {
+            self.go_to_position(EditorPosition::Line(hunk.start), None, None);
+        }
+    }
+
+    /// Move the cursor to the previous hunk of changes since `head`, wrapping
+    /// around to the last one.
+    fn previous_diff(&self) {
+        let doc = self.view.doc.get_untracked();
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let line = doc.buffer.with_untracked(|b| b.line_of_offset(offset));
+        let hunks = doc.diff_hunks();
+        let hunk = hunks
+            .iter()
+            .rev()
+            .find(|hunk| hunk.start < line && !hunk.contains(&line))
+            .or_else(|| hunks.last());
+        if let Some(hunk) = hunk {
+            self.go_to_position(EditorPosition::Line(hunk.start), None, None);
+        }
+    }
+
+    /// The lines of the hunk of changes the line is in, which includes the
+    /// line after the deleted lines of a hunk that only deletes.
+    fn diff_hunk_lines(&self, line: usize) -> Option<std::ops::Range<usize>> {
+        let hunk = self.view.doc.get_untracked().diff_hunk_at_line(line)?;
+        Some(hunk.start..hunk.end.max(hunk.start + 1))
+    }
+
+    fn cursor_diff_hunk(&self) -> Option<std::ops::Range<usize>> {
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        self.diff_hunk_lines(self.view.line_of_offset(offset))
+    }
+
+    /// Show the menu to stage, unstage or revert the hunk of changes at the
+    /// line, from the diff gutter.
+    pub fn diff_hunk_menu(&self, line: usize) {
+        let lines = match self.diff_hunk_lines(line) {
+            Some(lines) => lines,
+            None => return,
+        };
+        let stage = {
+            let editor = self.clone();
+            let lines = lines.clone();
+            move || editor.stage_lines(lines.clone())
+        };
+        let unstage = {
+            let editor = self.clone();
+            let lines = lines.clone();
+            move || editor.unstage_lines(lines.clone())
+        };
+        let revert = {
+            let editor = self.clone();
+            move || editor.revert_lines(lines.clone())
+        };
+        let menu = Menu::new("")
+            .entry(MenuItem::new("Stage Hunk").action(stage))
+            .entry(MenuItem::new("Unstage Hunk").action(unstage))
+            .entry(MenuItem::new("Revert Hunk").action(revert));
+        show_context_menu(menu, None);
+    }
+
+    /// The lines of the selection, or the line of the cursor without one.
+    fn selected_lines(&self) -> std::ops::Range<usize> {
+        let doc = self.view.doc.get_untracked();
+        doc.buffer.with_untracked(|buffer| {
+            let selection = self.cursor.with_untracked(|c| c.edit_selection(buffer));
+            let start = selection.min_offset();
+            let end = selection.max_offset();
+            let start_line = buffer.line_of_offset(start);
+            let mut end_line = buffer.line_of_offset(end);
+            // A selection that ends at the start of a line doesn't include it
+            if end > start && buffer.offset_of_line(end_line) == end {
+                end_line = end_line.saturating_sub(1).max(start_line);
+            }
+            start_line..end_line + 1
+        })
+    }
+
+    pub fn stage_lines(&self, lines: std::ops::Range<usize>) {
+        let doc = self.view.doc.get_untracked();
+        if let Some(path) = doc.content.with_untracked(|c| c.path().cloned()) {
+            self.common
+                .proxy
+                .git_stage_lines(path, lines.start, lines.end);
+        }
+    }
+
+    pub fn unstage_lines(&self, lines: std::ops::Range<usize>) {
+        let doc = self.view.doc.get_untracked();
+        if let Some(path) = doc.content.with_untracked(|c| c.path().cloned()) {
+            self.common
+                .proxy
+                .git_unstage_lines(path, lines.start, lines.end);
+        }
+    }
+
+    /// Revert the unstaged changes of the lines, as an edit of the buffer so
+    /// that it can be undone.
+    pub fn revert_lines(&self, lines: std::ops::Range<usize>) {
+        let doc = self.view.doc.get_untracked();
+        let path = match doc.content.with_untracked(|c| c.path().cloned()) {
+            Some(path) => path,
+            None => return,
+        };
+        let rev = doc.rev();
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, move |result| {
+            let content = match result {
+                Ok(ProxyResponse::GitRevertLinesResponse { content }) => content,
+                _ => return,
+            };
+            let doc = editor.view.doc.get_untracked();
+            if doc.rev() != rev {
+                return;
+            }
+            let (selection, old) = doc.buffer.with_untracked(|buffer| {
+                (
+                    editor.cursor.with_untracked(|c| c.edit_selection(buffer)),
+                    buffer.text().to_string(),
+                )
+            });
+            let (old_range, new_range) = changed_range(&old, &content);
+            if old_range.is_empty() && new_range.is_empty() {
+                return;
+            }
+            editor.do_edit(
+                &selection,
+                &[(
+                    Selection::region(old_range.start, old_range.end),
+                    &content[new_range],
+                )],
+            );
+        });
+        self.common.proxy.git_revert_lines(
+            path,
+            lines.start,
+            lines.end,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
     fn update_hover(&self, offset: usize) {
+        if let Some((dap_id, frame_id)) = self.common.paused_frame.get_untracked() {
+            self.update_debug_hover(offset, dap_id, frame_id);
+        } else {
+            self.update_lsp_hover(offset);
+        }
+    }
+
+    /// Show the value of the expression under the mouse in the paused debug
+    /// session, falling back to the language server hover if the debug adapter
+    /// can't evaluate it.
+    fn update_debug_hover(&self, offset: usize, dap_id: DapId, frame_id: usize) {
+        let expression =
+            self.view
+                .doc
+                .get_untracked()
+                .buffer
+                .with_untracked(|buffer| {
+                    let end = buffer.next_code_boundary(offset);
+                    buffer.slice_to_cow(offset..end).trim().to_string()
+                });
+        if expression.is_empty() {
+            self.update_lsp_hover(offset);
+            return;
+        }
+
+        let editor = self.clone();
+        let config = self.common.config;
+        let hover_data = self.common.hover.clone();
+        let editor_id = self.editor_id;
+        let hovered = expression.clone();
+        let send = create_ext_action(self.scope, move |resp| match resp {
+            Ok(ProxyResponse::DapEvaluateResponse { resp }) => {
+                let content = from_plaintext(
+                    &format!("{hovered} = {}", resp.result),
+                    1.5,
+                    &config.get_untracked(),
+                );
+                hover_data.content.set(content);
+                hover_data.offset.set(offset);
+                hover_data.editor_id.set(editor_id);
+                hover_data.blame.set(None);
+                hover_data.active.set(true);
+            }
+            _ => editor.update_lsp_hover(offset),
+        });
+        self.common.proxy.dap_evaluate(
+            dap_id,
+            expression,
+            Some(frame_id),
+            Some("hover".to_string()),
+            move |resp| {
+                send(resp);
+            },
+        );
+    }
+
+    fn update_lsp_hover(&self, offset: usize) {
         let doc = self.view.doc.get_untracked();
         let path = doc
             .content
@@ -2203,6 +3263,7 @@ fn update_hover(&self, offset: usize) {
                 hover_data.content.set(content);
                 hover_data.offset.set(offset);
                 hover_data.editor_id.set(editor_id);
+                hover_data.blame.set(None);
                 hover_data.active.set(true);
             }
         });
@@ -2211,6 +3272,374 @@ fn update_hover(&self, offset: usize) {
         });
     }
 
+    pub fn cancel_signature(&self) {
+        if self.common.signature.editor_id.get_untracked() == self.editor_id {
+            self.common.signature.cancel();
+        }
+    }
+
+    /// Request the signature help at the cursor. It's triggered by typing one
+    /// of the server's trigger characters, and while it's shown it is
+    /// retriggered by any edit or cursor movement, so that the active
+    /// parameter follows the cursor. `typed` is the text that was just typed,
+    /// if any.
+    fn update_signature(&self, typed: Option<&str>) {
+        let signature = self.common.signature.clone();
+        let active = signature.active.get_untracked()
+            && signature.editor_id.get_untracked() == self.editor_id;
+        // The proxy only passes a typed character on to the servers that have
+        // it as a trigger character, or as a retrigger character while the
+        // signature help is active
+        let trigger_character = match typed {
+            Some(c) if c.chars().count() == 1 => Some(c.to_string()),
+            _ if active => None,
+            _ => return,
+        };
+
+        if self.get_mode() != Mode::Insert
+            || !self
+                .common
+                .config
+                .with_untracked(|config| config.editor.show_signature)
+        {
+            self.cancel_signature();
+            return;
+        }
+
+        let doc = self.view.doc.get_untracked();
+        let path = match if doc.loaded() {
+            doc.content.with_untracked(|c| c.path().cloned())
+        } else {
+            None
+        } {
+            Some(path) => path,
+            None => return,
+        };
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let position = doc
+            .buffer
+            .with_untracked(|buffer| buffer.offset_to_position(offset));
+        let language = doc.syntax.with_untracked(|syntax| syntax.language);
+
+        let context = SignatureHelpContext {
+            trigger_kind: if trigger_character.is_some() {
+                SignatureHelpTriggerKind::TRIGGER_CHARACTER
+            } else {
+                SignatureHelpTriggerKind::CONTENT_CHANGE
+            },
+            trigger_character,
+            is_retrigger: active,
+            active_signature_help: if active {
+                signature.signature_help.get_untracked()
+            } else {
+                None
+            },
+        };
+
+        if !active {
+            // Hide the signature help shown in another editor
+            signature.cancel();
+            signature.editor_id.set(self.editor_id);
+        }
+        let request_id = signature.request_id.get_untracked() + 1;
+        signature.request_id.set(request_id);
+        let handle_result = {
+            let signature = signature.clone();
+            move |result: Result<ProxyResponse, RpcError>| {
+                if signature.request_id.get_untracked() != request_id {
+                    return;
+                }
+                match result {
+                    Ok(ProxyResponse::GetSignatureResponse {
+                        signature_help,
+                        ..
+                    }) if !signature_help.signatures.is_empty() => {
+                        if !active {
+                            signature.offset.set(offset);
+                        }
+                        signature.language.set(language);
+                        signature.signature_help.set(Some(signature_help));
+                        signature.active.set(true);
+                    }
+                    // The server has no signature here, e.g. after the closing
+                    // parenthesis of the call
+                    _ => signature.cancel(),
+                }
+            }
+        };
+        let retry = create_ext_action(self.scope, handle_result.clone());
+        let proxy = self.common.proxy.clone();
+        let retrigger = (active && context.trigger_character.is_some()).then(|| {
+            SignatureHelpContext {
+                trigger_kind: SignatureHelpTriggerKind::CONTENT_CHANGE,
+                trigger_character: None,
+                ..context.clone()
+            }
+        });
+        let retry_path = path.clone();
+        let send = create_ext_action(self.scope, move |result| {
+            match (retrigger, &result) {
+                // No server retriggers on the typed character, so the signature
+                // help is retriggered by the content change instead
+                (Some(context), Err(_))
+                    if signature.request_id.get_untracked() == request_id =>
+                {
+                    proxy.get_signature(
+                        request_id,
+                        retry_path,
+                        position,
+                        Some(context),
+                        move |result| {
+                            retry(result);
+                        },
+                    );
+                }
+                _ => handle_result(result),
+            }
+        });
+        self.common.proxy.get_signature(
+            request_id,
+            path,
+            position,
+            Some(context),
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    pub fn cancel_inline_completion(&self) {
+        if self
+            .common
+            .inline_completion
+            .with_untracked(|c| c.status == InlineCompletionStatus::Inactive)
+        {
+            return;
+        }
+        self.common.inline_completion.update(|c| c.cancel());
+        self.view.doc.get_untracked().clear_inline_completion();
+    }
+
+    /// Request inline completions at the cursor after a short delay, so that
+    /// typing quickly doesn't flood the providers with requests. In the
+    /// meantime the current suggestion keeps being shown, as long as it
+    /// matches what's being typed.
+    fn update_inline_completion(&self, trigger_kind: InlineCompletionTriggerKind) {
+        let enabled = self
+            .common
+            .config
+            .with_untracked(|config| config.editor.enable_inline_completion);
+        let single_caret = self.cursor.with_untracked(|cursor| match &cursor.mode {
+            CursorMode::Insert(selection) => {
+                selection.len() == 1 && selection.is_caret()
+            }
+            _ => false,
+        });
+        if !single_caret
+            || (!enabled && trigger_kind == InlineCompletionTriggerKind::AUTOMATIC)
+        {
+            self.cancel_inline_completion();
+            return;
+        }
+
+        self.show_inline_completion();
+
+        let editor = self.clone();
+        let delay = if trigger_kind == InlineCompletionTriggerKind::INVOKED {
+            0
+        } else {
+            INLINE_COMPLETION_DELAY
+        };
+        let timer = exec_after(Duration::from_millis(delay), move |token| {
+            if editor.editor_tab_id.try_get_untracked().is_some()
+                && editor
+                    .common
+                    .inline_completion
+                    .with_untracked(|c| c.timer == token)
+            {
+                editor.request_inline_completion(trigger_kind);
+            }
+        });
+        self.common.inline_completion.update(|c| {
+            c.timer = timer;
+            if c.status == InlineCompletionStatus::Inactive {
+                c.status = InlineCompletionStatus::Started;
+            }
+        });
+    }
+
+    fn request_inline_completion(&self, trigger_kind: InlineCompletionTriggerKind) {
+        let doc = self.view.doc.get_untracked();
+        let path = match if doc.loaded() {
+            doc.content.with_untracked(|c| c.path().cloned())
+        } else {
+            None
+        } {
+            Some(path) => path,
+            None => return,
+        };
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let position = doc
+            .buffer
+            .with_untracked(|buffer| buffer.offset_to_position(offset));
+        let rev = doc.rev();
+
+        let mut request_id = 0;
+        self.common.inline_completion.update(|c| {
+            if c.path != path {
+                c.items.clear();
+                c.path = path.clone();
+            }
+            c.request_id += 1;
+            request_id = c.request_id;
+        });
+
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, move |result| {
+            if let Ok(ProxyResponse::GetInlineCompletionsResponse {
+                request_id: response_id,
+                plugin_id,
+                items,
+            }) = result
+            {
+                let doc = editor.view.doc.get_untracked();
+                if editor
+                    .common
+                    .inline_completion
+                    .with_untracked(|c| c.request_id != response_id)
+                    || doc.rev() != rev
+                {
+                    return;
+                }
+                doc.buffer.with_untracked(|buffer| {
+                    editor
+                        .common
+                        .inline_completion
+                        .update(|c| c.set_items(plugin_id, items, buffer, offset));
+                });
+                editor.show_inline_completion();
+            }
+        });
+        self.common.proxy.get_inline_completions(
+            request_id,
+            path,
+            position,
+            trigger_kind,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// Show the part of the current inline completion that is yet to be typed
+    /// at the cursor.
+    fn show_inline_completion(&self) {
+        let doc = self.view.doc.get_untracked();
+        let path = doc.content.with_untracked(|c| c.path().cloned());
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let text = self.common.inline_completion.with_untracked(|c| {
+            if path.as_ref() != Some(&c.path) {
+                return None;
+            }
+            let item = c.current_item()?;
+            doc.buffer.with_untracked(|buffer| {
+                item.remaining_text(buffer, offset)
+                    .map(|text| text.to_string())
+            })
+        });
+        match text {
+            Some(text) => {
+                let (line, col) = doc
+                    .buffer
+                    .with_untracked(|buffer| buffer.offset_to_line_col(offset));
+                doc.set_inline_completion(text, line, col);
+            }
+            None => doc.clear_inline_completion(),
+        }
+    }
+
+    /// Accept the current inline completion as a whole, or only its next word
+    /// or line.
+    fn accept_inline_completion(&self, cmd: &FocusCommand) {
+        let doc = self.view.doc.get_untracked();
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let item = match self
+            .common
+            .inline_completion
+            .with_untracked(|c| c.current_item().cloned())
+        {
+            Some(item) => item,
+            None => return,
+        };
+        let remaining = match doc.buffer.with_untracked(|buffer| {
+            item.remaining_text(buffer, offset)
+                .map(|text| text.to_string())
+        }) {
+            Some(remaining) => remaining,
+            None => return,
+        };
+
+        match cmd {
+            FocusCommand::InlineCompletionAcceptWord
+            | FocusCommand::InlineCompletionAcceptLine => {
+                let len = if matches!(cmd, FocusCommand::InlineCompletionAcceptWord)
+                {
+                    next_word(&remaining).len()
+                } else {
+                    next_line(&remaining).len()
+                };
+                if len == 0 {
+                    return;
+                }
+                // The accepted part replaces the range of the item, like
+                // accepting the item as a whole does
+                let text = match doc.buffer.with_untracked(|buffer| {
+                    item.partial_text(buffer, offset, len)
+                        .map(|text| text.to_string())
+                }) {
+                    Some(text) => text,
+                    None => return,
+                };
+                let selection = Selection::region(item.start, item.end.max(offset));
+                self.do_edit(&selection, &[(selection.clone(), text.as_str())]);
+                self.show_inline_completion();
+            }
+            _ => {
+                self.cancel_inline_completion();
+                let selection = Selection::region(item.start, item.end.max(offset));
+                if let Some(snippet) = item.snippet.as_ref() {
+                    let _ = self.completion_apply_snippet(
+                        snippet,
+                        &selection,
+                        Vec::new(),
+                        item.start,
+                    );
+                } else {
+                    self.do_edit(
+                        &selection,
+                        &[(selection.clone(), item.text.as_str())],
+                    );
+                }
+                if let Some(command) = item.command {
+                    self.common.proxy.execute_command(item.plugin_id, command);
+                }
+            }
+        }
+    }
+
+    fn update_inline_completion_offset(&self, delta: &RopeDelta) {
+        if self
+            .common
+            .inline_completion
+            .with_untracked(|c| c.status == InlineCompletionStatus::Inactive)
+        {
+            return;
+        }
+        self.common
+            .inline_completion
+            .update(|c| c.update_offsets(delta));
+    }
+
     // reset the doc inside and move cursor back
     pub fn reset(&self) {
         let doc = self.view.doc.get_untracked();
@@ -2435,6 +3864,13 @@ fn check_condition(&self, condition: Condition) -> bool {
             Condition::ListFocus => self.has_completions(),
             Condition::CompletionFocus => self.has_completions(),
             Condition::InSnippet => self.snippet.with_untracked(|s| s.is_some()),
+            Condition::InlineCompletionVisible => self
+                .view
+                .doc
+                .get_untracked()
+                .inline_completion
+                .with_untracked(|c| c.is_some()),
+            Condition::ModalFocus => self.peek.with_untracked(|p| p.is_some()),
             Condition::EditorFocus => self
                 .view
                 .doc
@@ -2552,6 +3988,9 @@ fn receive_char(&self, c: &str) {
         } else {
             // normal editor receive char
             if self.get_mode() == Mode::Insert {
+                if self.commit_completion(c) {
+                    return;
+                }
                 let mut cursor = self.cursor.get_untracked();
                 let config = self.common.config.get_untracked();
                 let deltas =
@@ -2570,6 +4009,16 @@ fn receive_char(&self, c: &str) {
                     self.cancel_completion();
                 }
                 self.apply_deltas(&deltas);
+                if !deltas.is_empty() && c.chars().count() == 1 {
+                    self.on_type_formatting(c);
+                }
+                if !deltas.is_empty() {
+                    self.update_signature(Some(c));
+                    self.update_inline_completion(
+                        InlineCompletionTriggerKind::AUTOMATIC,
+                    );
+                }
+                self.update_linked_editing();
             } else if let Some(direction) = self.inline_find.get_untracked() {
                 self.inline_find(direction.clone(), c);
                 self.last_inline_find.set(Some((direction, c.to_string())));
@@ -2682,3 +4131,83 @@ fn parse_hover_resp(
         },
     }
 }
+
+/// The byte range of `old` that's replaced to get `new`, and the byte range of
+/// `new` that it's replaced with.
+fn changed_range(
+    old: &str,
+    new: &str,
+) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix)
+        || !new.is_char_boundary(new.len() - suffix)
+    {
+        suffix -= 1;
+    }
+    (prefix..old.len() - suffix, prefix..new.len() - suffix)
+}
+
+/// Flatten a goto response into its target locations. For location links the
+/// selection range is used, so the cursor lands on the symbol itself.
+fn goto_response_locations(response: GotoDefinitionResponse) -> Vec<Location> {
+    match response {
+        GotoDefinitionResponse::Scalar(location) => vec![location],
+        GotoDefinitionResponse::Array(locations) => locations,
+        GotoDefinitionResponse::Link(location_links) => location_links
+            .into_iter()
+            .map(|link| Location {
+                uri: link.target_uri,
+                range: link.target_selection_range,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::changed_range;
+
+    #[test]
+    fn changed_range_of_edits() {
+        assert_eq!(changed_range("abc", "abc"), (3..3, 3..3));
+        assert_eq!(changed_range("", "abc"), (0..0, 0..3));
+        assert_eq!(changed_range("abc", ""), (0..3, 0..0));
+        // Inserted
+        assert_eq!(changed_range("ac", "abc"), (1..1, 1..2));
+        // Deleted
+        assert_eq!(changed_range("abc", "ac"), (1..2, 1..1));
+        // Replaced
+        assert_eq!(changed_range("a\nb\nc", "a\nX\nc"), (2..3, 2..3));
+        // The prefix and the suffix don't overlap when the text repeats
+        assert_eq!(changed_range("aa", "aaa"), (2..2, 2..3));
+        assert_eq!(changed_range("aaa", "aa"), (2..3, 2..2));
+    }
+
+    #[test]
+    fn changed_range_on_char_boundaries() {
+        // "é" and "è" share their first byte, which isn't a char on its own
+        assert_eq!(changed_range("é", "è"), (0..2, 0..2));
+        assert_eq!(changed_range("aéb", "aèb"), (1..3, 1..3));
+        // "é" and "ũ" share their last byte
+        assert_eq!(changed_range("xé", "xũ"), (1..3, 1..3));
+        assert_eq!(changed_range("éy", "ũy"), (0..2, 0..2));
+        // A multibyte char inserted next to the same char
+        assert_eq!(changed_range("✓", "✓✓"), (3..3, 3..6));
+        assert_eq!(changed_range("a✓b", "a✓✗b"), (4..4, 4..7));
+    }
+}
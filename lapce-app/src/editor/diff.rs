@@ -76,12 +76,22 @@ pub fn to_data(
                                 doc.init_content(Rope::from(content));
                             }
                         });
-                        common.proxy.get_buffer_head(
-                            history.path.clone(),
-                            move |result| {
-                                send(result);
-                            },
-                        );
+                        if history.version == "head" {
+                            common.proxy.get_buffer_head(
+                                history.path.clone(),
+                                move |result| {
+                                    send(result);
+                                },
+                            );
+                        } else {
+                            common.proxy.get_buffer_revision(
+                                history.path.clone(),
+                                history.version.clone(),
+                                move |result| {
+                                    send(result);
+                                },
+                            );
+                        }
                     }
 
                     doc
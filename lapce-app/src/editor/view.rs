@@ -1,16 +1,18 @@
-use std::{collections::HashMap, rc::Rc, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, rc::Rc, sync::Arc};
 
 use floem::{
-    action::{set_ime_allowed, set_ime_cursor_area},
+    action::{set_ime_allowed, set_ime_cursor_area, show_context_menu},
     context::PaintCx,
     cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout},
     event::{Event, EventListener},
     id::Id,
     keyboard::ModifiersState,
+    menu::{Menu, MenuItem},
     peniko::{
         kurbo::{BezPath, Line, Point, Rect, Size},
         Color,
     },
+    pointer::PointerInputEvent,
     reactive::{
         create_effect, create_memo, create_rw_signal, Memo, ReadSignal, RwSignal,
     },
@@ -26,6 +28,7 @@
     mode::{Mode, VisualMode},
     selection::Selection,
 };
+use lapce_rpc::dap_types::{DapId, DebuggerCapabilities};
 use lapce_xi_rope::find::CaseMatching;
 
 use super::{
@@ -37,9 +40,12 @@
     app::clickable_icon,
     command::InternalCommand,
     config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    debug::{BreakpointField, LapceBreakpoint},
     doc::{DocContent, Document},
     keypress::KeyPressFocus,
+    listener::Listener,
     main_split::MainSplitData,
+    source_control::blame_gutter_text,
     text_input::text_input,
     window_tab::Focus,
     workspace::LapceWorkspace,
@@ -108,6 +114,8 @@ pub fn editor_view(
         hide_cursor.track();
         let occurrences = doc.with(|doc| doc.find_result.occurrences);
         occurrences.track();
+        let inline_completion = doc.with(|doc| doc.inline_completion);
+        inline_completion.track();
         id.request_paint();
     });
 
@@ -153,8 +161,30 @@ pub fn editor_view(
         rev
     });
 
-    let editor_window_origin = editor.window_origin;
+    // The visible lines get their semantic styles when the document is loaded
+    // or scrolled, without waiting for the ones of the whole document
+    let editor_viewport = editor.viewport;
+    let semantic_editor = editor.clone();
+    create_effect(move |_| {
+        let loaded = doc.with(|doc| doc.loaded);
+        if loaded.get() {
+            editor_viewport.track();
+            semantic_editor.get_semantic_range_styles();
+        }
+    });
+
+    let editor_view = editor.view.clone();
     let cursor = editor.cursor;
+    create_effect(move |_| {
+        if !is_active.get() {
+            return;
+        }
+        let offset = cursor.with(|c| c.offset());
+        let line = editor_view.line_of_offset(offset);
+        doc.get().set_blame_line(Some(line));
+    });
+
+    let editor_window_origin = editor.window_origin;
     let find_focus = editor.find_focus;
     let ime_allowed = editor.common.ime_allowed;
     let editor_viewport = editor.viewport;
@@ -697,6 +727,58 @@ fn paint_find(&self, cx: &mut PaintCx, screen_lines: &ScreenLines) {
         }
     }
 
+    /// Paint the lines of the inline completion after the first one, which is
+    /// phantom text, over the lines below the cursor.
+    fn paint_inline_completion(
+        &self,
+        cx: &mut PaintCx,
+        viewport: Rect,
+        screen_lines: &ScreenLines,
+    ) {
+        let doc = self.editor.view.doc.get_untracked();
+        let (text, line) = match doc.inline_completion.with_untracked(|c| c.clone())
+        {
+            Some((text, line, _)) => (text, line),
+            None => return,
+        };
+        let lines: Vec<&str> = text.lines().skip(1).collect();
+        if lines.is_empty() {
+            return;
+        }
+        let info = match screen_lines.info.get(&line) {
+            Some(info) => info,
+            None => return,
+        };
+
+        let config = self.editor.common.config.get_untracked();
+        let line_height = config.editor.line_height() as f64;
+        let y = info.y as f64 + line_height;
+
+        let rect = Size::new(viewport.width(), line_height * lines.len() as f64)
+            .to_rect()
+            .with_origin(Point::new(viewport.x0, y));
+        cx.fill(&rect, config.get_color(LapceColor::EDITOR_BACKGROUND), 0.0);
+
+        let family: Vec<FamilyOwned> =
+            FamilyOwned::parse_list(&config.editor.font_family).collect();
+        let attrs = Attrs::new()
+            .color(*config.get_color(LapceColor::COMPLETION_LENS_FOREGROUND))
+            .family(&family)
+            .font_size(config.editor.font_size() as f32);
+        for (i, line) in lines.into_iter().enumerate() {
+            let mut text_layout = TextLayout::new();
+            text_layout.set_text(line, AttrsList::new(attrs));
+            let height = text_layout.size().height;
+            cx.draw_text(
+                &text_layout,
+                Point::new(
+                    0.0,
+                    y + i as f64 * line_height + (line_height - height) / 2.0,
+                ),
+            );
+        }
+    }
+
     fn paint_sticky_headers(&self, cx: &mut PaintCx, viewport: Rect) {
         let config = self.editor.common.config.get_untracked();
         if !config.editor.sticky_header {
@@ -947,6 +1029,7 @@ fn paint(&mut self, cx: &mut PaintCx) {
         self.paint_diff_sections(cx, viewport, &screen_lines, &config);
         self.paint_find(cx, &screen_lines);
         self.paint_text(cx, viewport, &screen_lines);
+        self.paint_inline_completion(cx, viewport, &screen_lines);
         self.paint_sticky_headers(cx, viewport);
         self.paint_scroll_bar(cx, viewport, is_local, config);
     }
@@ -1309,6 +1392,7 @@ pub fn editor_container_view(
             container(|| {
                 stack(|| {
                     (
+                        editor_blame_gutter(editor),
                         editor_gutter(editor, is_active),
                         container(|| editor_content(editor, is_active))
                             .style(move |s| s.size_pct(100.0, 100.0)),
@@ -1329,6 +1413,7 @@ pub fn editor_container_view(
                                     |s| s.hide(),
                                 )
                         }),
+                        peek_view(editor),
                         find_view(
                             editor,
                             find_editor,
@@ -1374,22 +1459,124 @@ pub fn editor_container_view(
     .style(|s| s.flex_col().size_pct(100.0, 100.0))
 }
 
+/// The author and date of the commit that last changed each block of lines,
+/// shown when the blame gutter is toggled on
+fn editor_blame_gutter(editor: RwSignal<Rc<EditorData>>) -> impl View {
+    let (doc, viewport, scroll_delta, config, blame_gutter) =
+        editor.with_untracked(|e| {
+            (
+                e.view.doc,
+                e.viewport,
+                e.scroll_delta,
+                e.common.config,
+                e.common.blame_gutter,
+            )
+        });
+
+    clip(move || {
+        list(
+            move || {
+                if !blame_gutter.get() {
+                    return Vec::new();
+                }
+                let viewport = viewport.get();
+                let line_height = config.get().editor.line_height() as f64;
+                let min_vline = (viewport.y0 / line_height).floor() as usize;
+                let max_vline = (viewport.y1 / line_height).ceil() as usize;
+                let editor = editor.get_untracked();
+                doc.get().blame.with(|blame| {
+                    blame
+                        .iter()
+                        .filter(|hunk| {
+                            let start = editor.view.visual_line(hunk.start_line);
+                            let end = editor.view.visual_line(
+                                hunk.start_line + hunk.lines.max(1) - 1,
+                            );
+                            end >= min_vline && start <= max_vline
+                        })
+                        .map(|hunk| {
+                            // Keep the annotation of a block that's scrolled
+                            // partly out of view on its first visible line
+                            let vline = editor
+                                .view
+                                .visual_line(hunk.start_line)
+                                .max(min_vline);
+                            (vline, hunk.clone())
+                        })
+                        .collect::<Vec<_>>()
+                })
+            },
+            |(vline, hunk)| (*vline, hunk.start_line, hunk.commit_id.clone()),
+            move |(vline, hunk)| {
+                let text = blame_gutter_text(&hunk);
+                let start_line = hunk.start_line;
+                container(|| {
+                    label(move || text.clone()).style(|s| {
+                        s.width_pct(100.0).min_width_px(0.0).text_ellipsis()
+                    })
+                })
+                .on_click(move |_| {
+                    let editor = editor.get_untracked();
+                    let offset = editor.view.offset_of_line(start_line);
+                    editor.show_blame_hover(offset);
+                    true
+                })
+                .style(move |s| {
+                    let config = config.get();
+                    let viewport = viewport.get();
+                    let line_height = config.editor.line_height() as f32;
+                    s.absolute()
+                        .width_pct(100.0)
+                        .height_px(line_height)
+                        .margin_top_px(
+                            vline as f32 * line_height - viewport.y0 as f32,
+                        )
+                        .padding_horiz_px(10.0)
+                        .items_center()
+                        .cursor(CursorStyle::Pointer)
+                        .color(*config.get_color(LapceColor::EDITOR_DIM))
+                })
+            },
+        )
+        .on_event(EventListener::PointerWheel, move |event| {
+            if let Event::PointerWheel(pointer_event) = event {
+                scroll_delta.set(pointer_event.delta);
+            }
+            true
+        })
+        .style(|s| s.size_pct(100.0, 100.0))
+    })
+    .style(move |s| {
+        let config = config.get();
+        s.width_px(220.0)
+            .height_pct(100.0)
+            .border_right(1.0)
+            .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+            .background(*config.get_color(LapceColor::EDITOR_BACKGROUND))
+            .apply_if(!blame_gutter.get(), |s| s.hide())
+    })
+}
+
 fn editor_gutter(
     editor: RwSignal<Rc<EditorData>>,
     is_active: impl Fn(bool) -> bool + 'static + Copy,
 ) -> impl View {
-    let padding_left = 10.0;
+    let padding_left = 25.0;
     let padding_right = 30.0;
 
-    let (doc, cursor, viewport, scroll_delta, config) = editor.with_untracked(|e| {
-        (
-            e.view.doc,
-            e.cursor,
-            e.viewport,
-            e.scroll_delta,
-            e.common.config,
-        )
-    });
+    let (doc, cursor, viewport, scroll_delta, config, breakpoints) = editor
+        .with_untracked(|e| {
+            (
+                e.view.doc,
+                e.cursor,
+                e.viewport,
+                e.scroll_delta,
+                e.common.config,
+                e.common.breakpoints,
+            )
+        });
+    let doc_path =
+        create_memo(move |_| doc.get().content.with(|c| c.path().cloned()));
 
     let code_action_line = create_memo(move |_| {
         if is_active(true) {
@@ -1432,6 +1619,33 @@ fn editor_gutter(
                             .on_resize(move |rect| {
                                 gutter_rect.set(rect);
                             })
+                            .on_event(EventListener::PointerDown, move |event| {
+                                // The changes painted in the gutter can be
+                                // staged, unstaged or reverted from its menu
+                                if let Event::PointerDown(pointer_event) = event {
+                                    if pointer_event.button.is_secondary() {
+                                        let editor = editor.get_untracked();
+                                        if editor
+                                            .view
+                                            .kind
+                                            .get_untracked()
+                                            .is_normal()
+                                        {
+                                            let line_height = config
+                                                .get_untracked()
+                                                .editor
+                                                .line_height()
+                                                as f64;
+                                            let y = pointer_event.pos.y
+                                                + viewport.get_untracked().y0;
+                                            editor.diff_hunk_menu(
+                                                (y / line_height).floor() as usize,
+                                            );
+                                        }
+                                    }
+                                }
+                                false
+                            })
                             .on_event(EventListener::PointerWheel, move |event| {
                                 if let Event::PointerWheel(pointer_event) = event {
                                     scroll_delta.set(pointer_event.delta);
@@ -1502,11 +1716,185 @@ fn editor_gutter(
                     .padding_left_px(padding_left)
                     .padding_right_px(padding_right)
             }),
+            // The breakpoints, left of the line numbers, where they're toggled
+            // and edited
+            clip(|| {
+                stack(|| {
+                    (
+                        list(
+                            move || {
+                                let path = match doc_path.get() {
+                                    Some(path) => path,
+                                    None => return Vec::new(),
+                                };
+                                breakpoints.with(|breakpoints| {
+                                    breakpoints
+                                        .get(&path)
+                                        .map(|breakpoints| {
+                                            breakpoints
+                                                .iter()
+                                                .map(|b| (b.line, b.icon()))
+                                                .collect()
+                                        })
+                                        .unwrap_or_default()
+                                })
+                            },
+                            |(line, icon)| (*line, *icon),
+                            move |(line, icon)| {
+                                svg(move || config.get().ui_svg(icon)).style(
+                                    move |s| {
+                                        let config = config.get();
+                                        let viewport = viewport.get();
+                                        let size = config.ui.icon_size() as f32;
+                                        let line_height =
+                                            config.editor.line_height() as f32;
+                                        let visual_line = editor
+                                            .get_untracked()
+                                            .view
+                                            .visual_line(line);
+                                        s.absolute()
+                                            .size_px(size, size)
+                                            .margin_left_px(
+                                                (padding_left - size) / 2.0,
+                                            )
+                                            .margin_top_px(
+                                                visual_line as f32 * line_height
+                                                    - viewport.y0 as f32
+                                                    + (line_height - size) / 2.0,
+                                            )
+                                            .color(*config.get_color(
+                                                LapceColor::DEBUG_BREAKPOINT,
+                                            ))
+                                    },
+                                )
+                            },
+                        ),
+                        empty()
+                            .on_event(EventListener::PointerDown, move |event| {
+                                if let Event::PointerDown(pointer_event) = event {
+                                    breakpoint_pointer_down(
+                                        editor.get_untracked(),
+                                        pointer_event,
+                                    );
+                                }
+                                true
+                            })
+                            .on_event(EventListener::PointerWheel, move |event| {
+                                if let Event::PointerWheel(pointer_event) = event {
+                                    scroll_delta.set(pointer_event.delta);
+                                }
+                                true
+                            })
+                            .style(|s| s.absolute().size_pct(100.0, 100.0)),
+                    )
+                })
+                .style(|s| s.size_pct(100.0, 100.0))
+            })
+            .style(move |s| {
+                s.absolute()
+                    .width_px(padding_left)
+                    .height_pct(100.0)
+                    .cursor(CursorStyle::Pointer)
+            }),
         )
     })
     .style(|s| s.height_pct(100.0))
 }
 
+/// Toggle the breakpoint of the line that's clicked, or show the menu to edit
+/// it
+fn breakpoint_pointer_down(
+    editor: Rc<EditorData>,
+    pointer_event: &PointerInputEvent,
+) {
+    let path = match editor
+        .view
+        .doc
+        .get_untracked()
+        .content
+        .with_untracked(|content| content.path().cloned())
+    {
+        Some(path) => path,
+        None => return,
+    };
+    let line_height = editor.common.config.get_untracked().editor.line_height();
+    let visual_line = ((pointer_event.pos.y + editor.viewport.get_untracked().y0)
+        / line_height as f64)
+        .floor() as usize;
+    let line = editor
+        .view
+        .actual_line(visual_line, true)
+        .min(editor.view.last_line());
+    let internal_command = editor.common.internal_command;
+    if pointer_event.button.is_secondary() {
+        let breakpoint = editor.common.breakpoints.with_untracked(|breakpoints| {
+            breakpoints
+                .get(&path)
+                .and_then(|b| b.iter().find(|b| b.line == line).cloned())
+        });
+        let menu = breakpoint_menu(
+            path,
+            line,
+            breakpoint,
+            &editor.common.dap_capabilities.get_untracked(),
+            internal_command,
+        );
+        show_context_menu(menu, None);
+    } else {
+        internal_command.send(InternalCommand::ToggleBreakpoint { path, line });
+    }
+}
+
+fn breakpoint_menu(
+    path: PathBuf,
+    line: usize,
+    breakpoint: Option<LapceBreakpoint>,
+    capabilities: &im::HashMap<DapId, DebuggerCapabilities>,
+    internal_command: Listener<InternalCommand>,
+) -> Menu {
+    let toggle_title = if breakpoint.is_some() {
+        "Remove Breakpoint"
+    } else {
+        "Add Breakpoint"
+    };
+    let mut menu = Menu::new("").entry(MenuItem::new(toggle_title).action({
+        let path = path.clone();
+        move || {
+            internal_command.send(InternalCommand::ToggleBreakpoint {
+                path: path.clone(),
+                line,
+            });
+        }
+    }));
+    menu = menu.separator();
+    for field in [
+        BreakpointField::Condition,
+        BreakpointField::HitCondition,
+        BreakpointField::LogMessage,
+    ] {
+        let title = match (&breakpoint, field) {
+            (None, BreakpointField::Condition) => {
+                "Add Conditional Breakpoint...".to_string()
+            }
+            (None, BreakpointField::LogMessage) => "Add Logpoint...".to_string(),
+            _ => format!("Edit {}...", field.title()),
+        };
+        let path = path.clone();
+        menu = menu.entry(
+            MenuItem::new(title)
+                .enabled(field.is_supported(capabilities))
+                .action(move || {
+                    internal_command.send(InternalCommand::EditBreakpoint {
+                        path: path.clone(),
+                        line,
+                        field,
+                    });
+                }),
+        );
+    }
+    menu
+}
+
 fn editor_breadcrumbs(
     workspace: Arc<LapceWorkspace>,
     editor: Rc<EditorData>,
@@ -2049,6 +2437,143 @@ fn find_view(
     })
 }
 
+/// The number of lines of the peeked location that are visible at once
+const PEEK_LINES: usize = 12;
+
+/// The read-only editor shown below the host editor's line when peeking at
+/// a location.
+fn peek_view(editor: RwSignal<Rc<EditorData>>) -> impl View {
+    let (peek, host_view, viewport, config) = editor.with_untracked(|editor| {
+        (
+            editor.peek,
+            editor.view.clone(),
+            editor.viewport,
+            editor.common.config,
+        )
+    });
+
+    let peek_top = create_memo(move |_| {
+        let offset = peek.with(|peek| peek.as_ref().map(|peek| peek.offset))?;
+        let config = config.get();
+        let line_height = config.editor.line_height() as f64;
+        host_view.doc.track();
+        let line = host_view
+            .doc
+            .get_untracked()
+            .buffer
+            .with(|buffer| buffer.line_of_offset(offset));
+        let y = (host_view.visual_line(line) + 1) as f64 * line_height;
+        Some(y - viewport.get().y0)
+    });
+
+    list(
+        move || peek.get().into_iter(),
+        |peek| peek.editor.editor_id,
+        move |peek| {
+            let peek_editor = create_rw_signal(peek.editor.clone());
+            let peek_scope = peek.editor.scope;
+            let doc = peek.editor.view.doc;
+            let path = create_memo(move |_| {
+                doc.get().content.with(|content| content.path().cloned())
+            });
+            stack(|| {
+                (
+                    stack(|| {
+                        (
+                            label(move || {
+                                path.get()
+                                    .and_then(|path| {
+                                        Some(path.file_name()?.to_str()?.to_string())
+                                    })
+                                    .unwrap_or_default()
+                            })
+                            .style(|s| s.padding_horiz_px(10.0)),
+                            label(move || {
+                                path.get()
+                                    .and_then(|path| {
+                                        Some(path.parent()?.to_str()?.to_string())
+                                    })
+                                    .unwrap_or_default()
+                            })
+                            .style(move |s| {
+                                s.flex_grow(1.0).min_width_px(0.0).color(
+                                    *config.get().get_color(LapceColor::EDITOR_DIM),
+                                )
+                            }),
+                            clickable_icon(
+                                || LapceIcons::LOCATION_FORWARD,
+                                move || {
+                                    editor.get_untracked().open_peek();
+                                },
+                                || false,
+                                || false,
+                                config,
+                            )
+                            .style(|s| s.padding_left_px(6.0)),
+                            clickable_icon(
+                                || LapceIcons::CLOSE,
+                                move || {
+                                    editor.get_untracked().close_peek();
+                                },
+                                || false,
+                                || false,
+                                config,
+                            )
+                            .style(|s| s.padding_horiz_px(6.0)),
+                        )
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        s.items_center()
+                            .width_pct(100.0)
+                            .height_px(config.editor.line_height() as f32)
+                            .border_bottom(1.0)
+                            .border_color(
+                                *config.get_color(LapceColor::LAPCE_BORDER),
+                            )
+                            .background(
+                                *config.get_color(LapceColor::PANEL_BACKGROUND),
+                            )
+                    }),
+                    stack(|| {
+                        (
+                            editor_gutter(peek_editor, |_| false),
+                            container(|| editor_content(peek_editor, |_| false))
+                                .style(|s| s.size_pct(100.0, 100.0)),
+                        )
+                    })
+                    .style(|s| s.width_pct(100.0).flex_grow(1.0).min_height_px(0.0)),
+                )
+            })
+            .on_double_click(move |_| {
+                editor.get_untracked().open_peek();
+                true
+            })
+            .on_cleanup(move || {
+                peek_scope.dispose();
+            })
+            .style(move |s| {
+                let config = config.get();
+                let line_height = config.editor.line_height() as f32;
+                s.flex_col()
+                    .width_pct(100.0)
+                    .height_px(line_height * (PEEK_LINES + 1) as f32)
+                    .border_top(1.0)
+                    .border_bottom(1.0)
+                    .border_color(*config.get_color(LapceColor::EDITOR_CARET))
+                    .background(*config.get_color(LapceColor::EDITOR_BACKGROUND))
+                    .cursor(CursorStyle::Default)
+            })
+        },
+    )
+    .style(move |s| {
+        s.absolute()
+            .width_pct(100.0)
+            .margin_top_px(peek_top.get().unwrap_or(0.0) as f32)
+            .apply_if(peek_top.get().is_none(), |s| s.hide())
+    })
+}
+
 pub fn changes_colors(
     changes: im::Vector<DiffLines>,
     min_line: usize,
@@ -21,7 +21,10 @@
         location::EditorLocation,
         EditorData, EditorInfo,
     },
-    id::{DiffEditorId, EditorId, EditorTabId, KeymapId, SettingsId, SplitId},
+    id::{
+        DiffEditorId, DisassemblyId, EditorId, EditorTabId, GitLogId, KeymapId,
+        SettingsId, SplitId,
+    },
     main_split::MainSplitData,
     window_tab::WindowTabData,
 };
@@ -32,6 +35,8 @@ pub enum EditorTabChildInfo {
     DiffEditor(DiffEditorInfo),
     Settings,
     Keymap,
+    Disassembly,
+    GitLog,
 }
 
 impl EditorTabChildInfo {
@@ -53,6 +58,10 @@ pub fn to_data(
                 EditorTabChild::Settings(SettingsId::next())
             }
             EditorTabChildInfo::Keymap => EditorTabChild::Keymap(KeymapId::next()),
+            EditorTabChildInfo::Disassembly => {
+                EditorTabChild::Disassembly(DisassemblyId::next())
+            }
+            EditorTabChildInfo::GitLog => EditorTabChild::GitLog(GitLogId::next()),
         }
     }
 }
@@ -118,6 +127,8 @@ pub enum EditorTabChildSource {
     NewFileEditor,
     Settings,
     Keymap,
+    Disassembly,
+    GitLog,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -126,6 +137,8 @@ pub enum EditorTabChild {
     DiffEditor(DiffEditorId),
     Settings(SettingsId),
     Keymap(KeymapId),
+    Disassembly(DisassemblyId),
+    GitLog(GitLogId),
 }
 
 #[derive(PartialEq)]
@@ -144,6 +157,8 @@ pub fn id(&self) -> u64 {
             EditorTabChild::DiffEditor(id) => id.to_raw(),
             EditorTabChild::Settings(id) => id.to_raw(),
             EditorTabChild::Keymap(id) => id.to_raw(),
+            EditorTabChild::Disassembly(id) => id.to_raw(),
+            EditorTabChild::GitLog(id) => id.to_raw(),
         }
     }
 
@@ -175,6 +190,8 @@ pub fn child_info(&self, data: &WindowTabData) -> EditorTabChildInfo {
             }
             EditorTabChild::Settings(_) => EditorTabChildInfo::Settings,
             EditorTabChild::Keymap(_) => EditorTabChildInfo::Keymap,
+            EditorTabChild::Disassembly(_) => EditorTabChildInfo::Disassembly,
+            EditorTabChild::GitLog(_) => EditorTabChildInfo::GitLog,
         }
     }
 
@@ -253,7 +270,9 @@ pub fn view_info(
                     match content {
                         DocContent::File { path, .. } => Some((path, is_pristine)),
                         DocContent::Local => None,
-                        DocContent::History(_) => None,
+                        DocContent::History(history) => {
+                            Some((history.path, is_pristine))
+                        }
                         DocContent::Scratch { name, .. } => {
                             Some((PathBuf::from(name), is_pristine))
                         }
@@ -312,6 +331,26 @@ pub fn view_info(
                     is_pristine: true,
                 }
             }),
+            EditorTabChild::Disassembly(_) => create_memo(move |_| {
+                let config = config.get();
+                EditorTabChildViewInfo {
+                    icon: config.ui_svg(LapceIcons::DEBUG),
+                    color: Some(*config.get_color(LapceColor::LAPCE_ICON_ACTIVE)),
+                    path: "Disassembly".to_string(),
+                    confirmed: None,
+                    is_pristine: true,
+                }
+            }),
+            EditorTabChild::GitLog(_) => create_memo(move |_| {
+                let config = config.get();
+                EditorTabChildViewInfo {
+                    icon: config.ui_svg(LapceIcons::SCM),
+                    color: Some(*config.get_color(LapceColor::LAPCE_ICON_ACTIVE)),
+                    path: "Git Log".to_string(),
+                    confirmed: None,
+                    is_pristine: true,
+                }
+            }),
         }
     }
 }
@@ -0,0 +1,978 @@
+use std::{path::PathBuf, rc::Rc, sync::Arc, time::Duration};
+
+use floem::{
+    action::{exec_after, TimerToken},
+    ext_event::create_ext_action,
+    id::Id,
+    peniko::kurbo::{Circle, Line, Point},
+    reactive::{create_memo, ReadSignal, RwSignal, Scope},
+    style::{CursorStyle, Style},
+    view::{ChangeFlags, View},
+    views::{
+        container, label, list, scroll, stack, svg, virtual_list, Decorators,
+        VirtualListDirection, VirtualListItemSize,
+    },
+    Renderer, ViewContext,
+};
+use lapce_core::selection::Selection;
+use lapce_rpc::{
+    proxy::ProxyResponse,
+    source_control::{CommitInfo, FileDiff},
+};
+use lapce_xi_rope::Rope;
+
+use crate::{
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    editor::EditorData,
+    id::EditorId,
+    main_split::MainSplitData,
+    source_control::{commit_date, relative_time, short_commit_id},
+    text_input::text_input,
+    window_tab::CommonData,
+};
+
+/// The number of commits requested at a time
+const PAGE_SIZE: usize = 200;
+
+/// How long to wait after the last change of a filter before reloading
+const FILTER_DELAY: u64 = 300;
+
+/// The width of a lane of the commit graph
+const LANE_WIDTH: f64 = 14.0;
+
+const LANE_COLORS: [&str; 6] = [
+    LapceColor::TERMINAL_BLUE,
+    LapceColor::TERMINAL_GREEN,
+    LapceColor::TERMINAL_YELLOW,
+    LapceColor::TERMINAL_MAGENTA,
+    LapceColor::TERMINAL_CYAN,
+    LapceColor::TERMINAL_RED,
+];
+
+/// A line of the commit graph between two lanes
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GraphLine {
+    pub from: usize,
+    pub to: usize,
+}
+
+/// The part of the commit graph drawn in the row of a commit
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GraphRow {
+    /// The lane of the commit itself
+    pub lane: usize,
+    /// The lines from the top of the row to its middle
+    pub top: Vec<GraphLine>,
+    /// The lines from the middle of the row to its bottom
+    pub bottom: Vec<GraphLine>,
+    /// The number of lanes the row uses
+    pub lanes: usize,
+}
+
+#[derive(Clone)]
+pub struct GitLogData {
+    pub commits: RwSignal<im::Vector<CommitInfo>>,
+    /// Whether the commits were filtered, in which case they are linked one
+    /// after the other in the graph instead of to their parents
+    pub filtered: RwSignal<bool>,
+    pub has_more: RwSignal<bool>,
+    pub loading: RwSignal<bool>,
+    pub error: RwSignal<Option<String>>,
+    pub selected: RwSignal<Option<CommitInfo>>,
+    pub changes: RwSignal<Vec<FileDiff>>,
+    pub path_editor: EditorData,
+    pub author_editor: EditorData,
+    pub text_editor: EditorData,
+    request_id: RwSignal<u64>,
+    filter_timer: RwSignal<TimerToken>,
+    scope: Scope,
+    pub common: Rc<CommonData>,
+}
+
+impl GitLogData {
+    pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
+        let data = Self {
+            commits: cx.create_rw_signal(im::Vector::new()),
+            filtered: cx.create_rw_signal(false),
+            has_more: cx.create_rw_signal(false),
+            loading: cx.create_rw_signal(false),
+            error: cx.create_rw_signal(None),
+            selected: cx.create_rw_signal(None),
+            changes: cx.create_rw_signal(Vec::new()),
+            path_editor: EditorData::new_local(cx, EditorId::next(), common.clone()),
+            author_editor: EditorData::new_local(
+                cx,
+                EditorId::next(),
+                common.clone(),
+            ),
+            text_editor: EditorData::new_local(cx, EditorId::next(), common.clone()),
+            request_id: cx.create_rw_signal(0),
+            filter_timer: cx.create_rw_signal(TimerToken::INVALID),
+            scope: cx,
+            common,
+        };
+
+        {
+            let data = data.clone();
+            cx.create_effect(move |prev: Option<()>| {
+                for editor in
+                    [&data.path_editor, &data.author_editor, &data.text_editor]
+                {
+                    editor.view.doc.get().buffer.track();
+                }
+                if prev.is_none() {
+                    return;
+                }
+                let log = data.clone();
+                let timer =
+                    exec_after(Duration::from_millis(FILTER_DELAY), move |token| {
+                        if log.filter_timer.try_get_untracked() == Some(token) {
+                            log.load();
+                        }
+                    });
+                data.filter_timer.set(timer);
+            });
+        }
+
+        data
+    }
+
+    /// Reload the log from the first commit with the current filters.
+    pub fn load(&self) {
+        // A reload with the current filters makes a pending one redundant
+        self.filter_timer.set(TimerToken::INVALID);
+        self.request(0);
+    }
+
+    /// Load the next page of commits after the ones already loaded.
+    pub fn load_more(&self) {
+        if self.loading.get_untracked() || !self.has_more.get_untracked() {
+            return;
+        }
+        self.request(self.commits.with_untracked(|commits| commits.len()));
+    }
+
+    /// Show the commits that changed the file, or a file in the directory.
+    pub fn show_file_history(&self, path: PathBuf) {
+        let path = self
+            .common
+            .workspace
+            .path
+            .as_ref()
+            .and_then(|workspace_path| path.strip_prefix(workspace_path).ok())
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+        set_editor_text(&self.path_editor, path);
+        self.load();
+    }
+
+    pub fn select(&self, commit: CommitInfo) {
+        let commit_id = commit.id.clone();
+        self.selected.set(Some(commit));
+        self.changes.set(Vec::new());
+
+        let requested = commit_id.clone();
+        let selected = self.selected;
+        let changes = self.changes;
+        let send = create_ext_action(self.scope, move |result| {
+            if selected.with_untracked(|selected| {
+                selected.as_ref().map(|c| &c.id) != Some(&requested)
+            }) {
+                return;
+            }
+            if let Ok(ProxyResponse::GitCommitChangesResponse { diffs }) = result {
+                changes.set(diffs);
+            }
+        });
+        self.common
+            .proxy
+            .git_commit_changes(commit_id, move |result| {
+                send(result);
+            });
+    }
+
+    fn request(&self, skip: usize) {
+        let request_id = self.request_id.get_untracked() + 1;
+        self.request_id.set(request_id);
+        self.loading.set(true);
+
+        let path = editor_text(&self.path_editor);
+        let author = editor_text(&self.author_editor);
+        let text = editor_text(&self.text_editor);
+        let filtered = !path.is_empty() || !author.is_empty() || !text.is_empty();
+        let path = if path.is_empty() {
+            None
+        } else {
+            Some(match self.common.workspace.path.as_ref() {
+                Some(workspace_path) => workspace_path.join(path),
+                None => PathBuf::from(path),
+            })
+        };
+
+        let data = self.clone();
+        let send = create_ext_action(self.scope, move |result| {
+            if data.request_id.get_untracked() != request_id {
+                return;
+            }
+            data.loading.set(false);
+            match result {
+                Ok(ProxyResponse::GitLogResponse { commits }) => {
+                    data.has_more.set(commits.len() >= PAGE_SIZE);
+                    data.filtered.set(filtered);
+                    data.error.set(None);
+                    data.commits.update(|c| {
+                        if skip == 0 {
+                            c.clear();
+                        }
+                        c.extend(commits);
+                    });
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    data.has_more.set(false);
+                    data.error.set(Some(err.message));
+                    if skip == 0 {
+                        data.commits.set(im::Vector::new());
+                    }
+                }
+            }
+        });
+        self.common.proxy.git_log(
+            path,
+            author,
+            text,
+            skip,
+            PAGE_SIZE,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+}
+
+fn editor_text(editor: &EditorData) -> String {
+    editor
+        .view
+        .doc
+        .get_untracked()
+        .buffer
+        .with_untracked(|b| b.to_string().trim().to_string())
+}
+
+fn set_editor_text(editor: &EditorData, text: String) {
+    let len = text.len();
+    let doc = editor.view.doc.get_untracked();
+    doc.reload(Rope::from(text), true);
+    editor
+        .cursor
+        .update(|cursor| cursor.set_insert(Selection::region(0, len)));
+}
+
+/// Lay out the commit graph, giving every commit a lane and drawing lines to
+/// the lanes of its parents. The lanes of parents that aren't loaded yet
+/// continue to the bottom of the last row.
+pub fn commit_graph(
+    commits: &im::Vector<CommitInfo>,
+    filtered: bool,
+    has_more: bool,
+) -> Vec<GraphRow> {
+    // The id of the commit every lane is heading to
+    let mut lanes: Vec<Option<String>> = Vec::new();
+    let mut rows = Vec::with_capacity(commits.len());
+    for (i, commit) in commits.iter().enumerate() {
+        let lane = lanes
+            .iter()
+            .position(|id| id.as_ref() == Some(&commit.id))
+            .or_else(|| lanes.iter().position(|id| id.is_none()))
+            .unwrap_or(lanes.len());
+        if lane == lanes.len() {
+            lanes.push(None);
+        }
+
+        let mut row = GraphRow {
+            lane,
+            ..Default::default()
+        };
+        for (j, id) in lanes.iter_mut().enumerate() {
+            if let Some(id_ref) = id.as_ref() {
+                if id_ref == &commit.id {
+                    row.top.push(GraphLine { from: j, to: lane });
+                    *id = None;
+                } else {
+                    row.top.push(GraphLine { from: j, to: j });
+                    row.bottom.push(GraphLine { from: j, to: j });
+                }
+            }
+        }
+
+        let parents = if filtered {
+            match commits.get(i + 1) {
+                Some(next) => vec![next.id.clone()],
+                None if has_more => vec![String::new()],
+                None => Vec::new(),
+            }
+        } else {
+            commit.parents.clone()
+        };
+        for parent in parents {
+            let target =
+                match lanes.iter().position(|id| id.as_ref() == Some(&parent)) {
+                    Some(j) => j,
+                    None => {
+                        let j = if lanes[lane].is_none() {
+                            lane
+                        } else if let Some(j) =
+                            lanes.iter().position(|id| id.is_none())
+                        {
+                            j
+                        } else {
+                            lanes.push(None);
+                            lanes.len() - 1
+                        };
+                        lanes[j] = Some(parent);
+                        j
+                    }
+                };
+            row.bottom.push(GraphLine {
+                from: lane,
+                to: target,
+            });
+        }
+
+        while lanes.last().map(|id| id.is_none()).unwrap_or(false) {
+            lanes.pop();
+        }
+        row.lanes = row
+            .top
+            .iter()
+            .chain(row.bottom.iter())
+            .map(|line| line.from.max(line.to))
+            .chain(std::iter::once(lane))
+            .max()
+            .unwrap_or(0)
+            + 1;
+        rows.push(row);
+    }
+    rows
+}
+
+fn commit_graph_row(
+    row: GraphRow,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> CommitGraphRow {
+    let cx = ViewContext::get_current();
+    let id = cx.new_id();
+    CommitGraphRow { id, row, config }
+}
+
+pub struct CommitGraphRow {
+    id: Id,
+    row: GraphRow,
+    config: ReadSignal<Arc<LapceConfig>>,
+}
+
+impl View for CommitGraphRow {
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn child(&self, _id: Id) -> Option<&dyn View> {
+        None
+    }
+
+    fn child_mut(&mut self, _id: Id) -> Option<&mut dyn View> {
+        None
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        Vec::new()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        Vec::new()
+    }
+
+    fn update(
+        &mut self,
+        _cx: &mut floem::context::UpdateCx,
+        _state: Box<dyn std::any::Any>,
+    ) -> ChangeFlags {
+        ChangeFlags::empty()
+    }
+
+    fn layout(
+        &mut self,
+        cx: &mut floem::context::LayoutCx,
+    ) -> floem::taffy::prelude::Node {
+        cx.layout_node(self.id, false, |_cx| Vec::new())
+    }
+
+    fn event(
+        &mut self,
+        _cx: &mut floem::context::EventCx,
+        _id_path: Option<&[Id]>,
+        _event: floem::event::Event,
+    ) -> bool {
+        false
+    }
+
+    fn paint(&mut self, cx: &mut floem::context::PaintCx) {
+        let layout = cx.get_layout(self.id).unwrap();
+        let height = layout.size.height as f64;
+        let middle = height / 2.0;
+        let config = self.config.get_untracked();
+        let color =
+            |lane: usize| *config.get_color(LANE_COLORS[lane % LANE_COLORS.len()]);
+        let x = |lane: usize| lane as f64 * LANE_WIDTH + LANE_WIDTH / 2.0;
+
+        for line in &self.row.top {
+            cx.stroke(
+                &Line::new(
+                    Point::new(x(line.from), 0.0),
+                    Point::new(x(line.to), middle),
+                ),
+                color(line.from),
+                1.5,
+            );
+        }
+        for line in &self.row.bottom {
+            cx.stroke(
+                &Line::new(
+                    Point::new(x(line.from), middle),
+                    Point::new(x(line.to), height),
+                ),
+                color(line.to),
+                1.5,
+            );
+        }
+        cx.fill(
+            &Circle::new(Point::new(x(self.row.lane), middle), 4.0),
+            color(self.row.lane),
+            0.0,
+        );
+    }
+}
+
+pub fn git_log_view(main_split: MainSplitData) -> impl View {
+    let git_log = main_split.git_log.clone();
+    let config = git_log.common.config;
+    let ui_line_height = git_log.common.ui_line_height;
+    let commits = git_log.commits;
+    let filtered = git_log.filtered;
+    let has_more = git_log.has_more;
+    let loading = git_log.loading;
+    let error = git_log.error;
+    let selected = git_log.selected;
+
+    let rows = create_memo(move |_| {
+        commits.with(|commits| {
+            commit_graph(commits, filtered.get(), has_more.get())
+                .into_iter()
+                .zip(commits.iter().cloned())
+                .map(|(row, commit)| (commit, row))
+                .collect::<im::Vector<(CommitInfo, GraphRow)>>()
+        })
+    });
+    let graph_width = create_memo(move |_| {
+        rows.with(|rows| rows.iter().map(|(_, row)| row.lanes).max().unwrap_or(1))
+    });
+
+    let filter_input = move |editor: EditorData, placeholder: &'static str| {
+        text_input(editor, || false)
+            .placeholder(move || placeholder.to_string())
+            .keyboard_navigatable()
+            .style(move |s| {
+                s.flex_grow(1.0)
+                    .flex_basis_px(0.0)
+                    .min_width_px(0.0)
+                    .margin_right_px(10.0)
+                    .border_radius(6.0)
+                    .border(1.0)
+                    .border_color(*config.get().get_color(LapceColor::LAPCE_BORDER))
+            })
+    };
+
+    let view_fn = {
+        let git_log = git_log.clone();
+        move |(commit, row): (CommitInfo, GraphRow)| {
+            let git_log = git_log.clone();
+            let commit_id = commit.id.clone();
+            let summary = commit.summary.clone();
+            let author = commit.author.clone();
+            let date = commit_date(commit.time);
+            let short_id = short_commit_id(&commit.id).to_string();
+            stack(|| {
+                (
+                    commit_graph_row(row, config).style(move |s| {
+                        s.min_width_px(
+                            (graph_width.get() as f64 * LANE_WIDTH) as f32,
+                        )
+                        .height_pct(100.0)
+                        .margin_right_px(6.0)
+                    }),
+                    label(move || summary.clone()).style(|s| {
+                        s.text_ellipsis()
+                            .flex_grow(1.0)
+                            .flex_basis_px(0.0)
+                            .min_width_px(0.0)
+                            .margin_right_px(10.0)
+                    }),
+                    label(move || author.clone()).style(|s| {
+                        s.text_ellipsis().width_px(150.0).margin_right_px(10.0)
+                    }),
+                    label(move || date.clone()).style(move |s| {
+                        s.width_px(90.0)
+                            .margin_right_px(10.0)
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                    }),
+                    label(move || short_id.clone()).style(move |s| {
+                        let config = config.get();
+                        s.width_px(70.0)
+                            .font_family(config.editor.font_family.clone())
+                            .color(*config.get_color(LapceColor::EDITOR_DIM))
+                    }),
+                )
+            })
+            .on_click(move |_| {
+                git_log.select(commit.clone());
+                true
+            })
+            .style(move |s| {
+                let config = config.get();
+                let is_selected =
+                    selected.with(|c| c.as_ref().map(|c| &c.id) == Some(&commit_id));
+                s.items_center()
+                    .padding_horiz_px(10.0)
+                    .height_px(ui_line_height.get() as f32)
+                    .width_pct(100.0)
+                    .apply_if(is_selected, |s| {
+                        s.background(
+                            *config.get_color(LapceColor::PANEL_CURRENT_BACKGROUND),
+                        )
+                    })
+            })
+            .hover_style(move |s| {
+                s.cursor(CursorStyle::Pointer).background(
+                    *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            })
+        }
+    };
+
+    let load_more = git_log.clone();
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    filter_input(git_log.path_editor.clone(), "Filter by Path"),
+                    filter_input(git_log.author_editor.clone(), "Filter by Author"),
+                    filter_input(
+                        git_log.text_editor.clone(),
+                        "Filter by Message or Commit",
+                    ),
+                )
+            })
+            .style(move |s| {
+                s.width_pct(100.0)
+                    .padding_vert_px(6.0)
+                    .padding_left_px(10.0)
+                    .border_bottom(1.0)
+                    .border_color(*config.get().get_color(LapceColor::LAPCE_BORDER))
+            }),
+            stack(move || {
+                (
+                    container(move || {
+                        scroll(move || {
+                            stack(move || {
+                                (
+                                    virtual_list(
+                                        VirtualListDirection::Vertical,
+                                        VirtualListItemSize::Fixed(Box::new(
+                                            move || ui_line_height.get(),
+                                        )),
+                                        move || rows.get(),
+                                        |(commit, row): &(CommitInfo, GraphRow)| {
+                                            (commit.id.clone(), row.clone())
+                                        },
+                                        view_fn,
+                                    )
+                                    .style(|s| s.flex_col().width_pct(100.0)),
+                                    label(move || {
+                                        if let Some(error) = error.get() {
+                                            error
+                                        } else if loading.get() {
+                                            "Loading...".to_string()
+                                        } else if has_more.get() {
+                                            "Load More".to_string()
+                                        } else if commits.with(|c| c.is_empty()) {
+                                            "No Commits".to_string()
+                                        } else {
+                                            String::new()
+                                        }
+                                    })
+                                    .on_click(move |_| {
+                                        load_more.load_more();
+                                        true
+                                    })
+                                    .style(
+                                        move |s| {
+                                            let config = config.get();
+                                            let clickable = has_more.get()
+                                                && !loading.get()
+                                                && error.with(|e| e.is_none());
+                                            s.padding_horiz_px(10.0)
+                                                .padding_vert_px(6.0)
+                                                .color(*config.get_color(
+                                                    LapceColor::EDITOR_DIM,
+                                                ))
+                                                .apply_if(clickable, |s| {
+                                                    s.color(*config.get_color(
+                                                        LapceColor::EDITOR_LINK,
+                                                    ))
+                                                    .cursor(CursorStyle::Pointer)
+                                                })
+                                        },
+                                    ),
+                                )
+                            })
+                            .style(|s| s.flex_col().width_pct(100.0))
+                        })
+                        .style(|s| s.absolute().size_pct(100.0, 100.0))
+                    })
+                    .style(|s| {
+                        s.height_pct(100.0)
+                            .flex_grow(1.0)
+                            .flex_basis_px(0.0)
+                            .min_width_px(0.0)
+                    }),
+                    commit_details_view(main_split.clone()),
+                )
+            })
+            .style(|s| s.width_pct(100.0).flex_grow(1.0).flex_basis_px(0.0)),
+        )
+    })
+    .style(|s| s.flex_col().size_pct(100.0, 100.0))
+}
+
+/// The message of the selected commit and the files it changed, which open
+/// the diff of the file in the commit when clicked.
+fn commit_details_view(main_split: MainSplitData) -> impl View {
+    let git_log = main_split.git_log.clone();
+    let config = git_log.common.config;
+    let workspace = git_log.common.workspace.clone();
+    let selected = git_log.selected;
+    let changes = git_log.changes;
+
+    let header = move || {
+        selected.with(|commit| {
+            commit
+                .as_ref()
+                .map(|commit| {
+                    format!(
+                        "{} <{}>\n{} ({})\n{}",
+                        commit.author,
+                        commit.email,
+                        commit_date(commit.time),
+                        relative_time(commit.time),
+                        commit.id
+                    )
+                })
+                .unwrap_or_else(|| "No Commit Selected".to_string())
+        })
+    };
+    let message = move || {
+        selected.with(|commit| {
+            commit
+                .as_ref()
+                .map(|commit| commit.message.trim().to_string())
+                .unwrap_or_default()
+        })
+    };
+
+    let view_fn = move |diff: FileDiff| {
+        let main_split = main_split.clone();
+        let (full_path, old_path) = match &diff {
+            FileDiff::Modified(path)
+            | FileDiff::Added(path)
+            | FileDiff::Deleted(path) => (path.clone(), None),
+            FileDiff::Renamed(path, old_path) => {
+                (path.clone(), Some(old_path.clone()))
+            }
+        };
+        let path = workspace
+            .path
+            .as_ref()
+            .and_then(|workspace_path| full_path.strip_prefix(workspace_path).ok())
+            .unwrap_or(&full_path)
+            .to_path_buf();
+        let file_name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_string();
+        let folder = path
+            .parent()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_string();
+        let style_path = path.clone();
+        let diff_for_style = diff.clone();
+        stack(|| {
+            (
+                svg(move || config.get().file_svg(&path).0).style(move |s| {
+                    let config = config.get();
+                    let size = config.ui.icon_size() as f32;
+                    let color = config.file_svg(&style_path).1.copied();
+                    s.min_width_px(size)
+                        .size_px(size, size)
+                        .margin_right_px(6.0)
+                        .apply_opt(color, Style::color)
+                }),
+                label(move || file_name.clone())
+                    .style(|s| s.text_ellipsis().margin_right_px(6.0)),
+                label(move || folder.clone()).style(move |s| {
+                    s.text_ellipsis()
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .min_width_px(0.0)
+                        .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                }),
+                svg(move || {
+                    let svg = match &diff {
+                        FileDiff::Modified(_) => LapceIcons::SCM_DIFF_MODIFIED,
+                        FileDiff::Added(_) => LapceIcons::SCM_DIFF_ADDED,
+                        FileDiff::Deleted(_) => LapceIcons::SCM_DIFF_REMOVED,
+                        FileDiff::Renamed(_, _) => LapceIcons::SCM_DIFF_RENAMED,
+                    };
+                    config.get().ui_svg(svg)
+                })
+                .style(move |s| {
+                    let config = config.get();
+                    let size = config.ui.icon_size() as f32;
+                    let color = match &diff_for_style {
+                        FileDiff::Modified(_) | FileDiff::Renamed(_, _) => {
+                            LapceColor::SOURCE_CONTROL_MODIFIED
+                        }
+                        FileDiff::Added(_) => LapceColor::SOURCE_CONTROL_ADDED,
+                        FileDiff::Deleted(_) => LapceColor::SOURCE_CONTROL_REMOVED,
+                    };
+                    s.min_width_px(size)
+                        .size_px(size, size)
+                        .margin_left_px(6.0)
+                        .color(*config.get_color(color))
+                }),
+            )
+        })
+        .on_click(move |_| {
+            let commit_id = selected
+                .with_untracked(|commit| commit.as_ref().map(|c| c.id.clone()));
+            if let Some(commit_id) = commit_id {
+                main_split.open_commit_file_changes(
+                    full_path.clone(),
+                    old_path.clone(),
+                    commit_id,
+                );
+            }
+            true
+        })
+        .style(|s| {
+            s.items_center()
+                .width_pct(100.0)
+                .padding_horiz_px(10.0)
+                .padding_vert_px(2.0)
+        })
+        .hover_style(move |s| {
+            s.cursor(CursorStyle::Pointer).background(
+                *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+            )
+        })
+    };
+
+    container(move || {
+        scroll(move || {
+            stack(move || {
+                (
+                    label(header).style(move |s| {
+                        s.padding_horiz_px(10.0)
+                            .padding_vert_px(6.0)
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                    }),
+                    label(message).style(move |s| {
+                        s.padding_horiz_px(10.0)
+                            .padding_bottom_px(6.0)
+                            .apply_if(selected.with(|c| c.is_none()), |s| s.hide())
+                    }),
+                    list(move || changes.get(), |diff| diff.clone(), view_fn).style(
+                        move |s| {
+                            s.flex_col()
+                                .width_pct(100.0)
+                                .padding_top_px(6.0)
+                                .border_top(1.0)
+                                .border_color(
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::LAPCE_BORDER),
+                                )
+                                .apply_if(selected.with(|c| c.is_none()), |s| {
+                                    s.hide()
+                                })
+                        },
+                    ),
+                )
+            })
+            .style(|s| s.flex_col().width_pct(100.0))
+        })
+        .style(|s| s.absolute().size_pct(100.0, 100.0))
+    })
+    .style(move |s| {
+        s.width_px(400.0)
+            .height_pct(100.0)
+            .border_left(1.0)
+            .border_color(*config.get().get_color(LapceColor::LAPCE_BORDER))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::source_control::CommitInfo;
+
+    use super::{commit_graph, GraphLine, GraphRow};
+
+    fn commit(id: &str, parents: &[&str]) -> CommitInfo {
+        CommitInfo {
+            id: id.to_string(),
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            author: "author".to_string(),
+            email: "author@example.com".to_string(),
+            time: 0,
+            summary: id.to_string(),
+            message: id.to_string(),
+        }
+    }
+
+    fn row(
+        lane: usize,
+        top: &[(usize, usize)],
+        bottom: &[(usize, usize)],
+        lanes: usize,
+    ) -> GraphRow {
+        let lines = |lines: &[(usize, usize)]| {
+            lines
+                .iter()
+                .map(|(from, to)| GraphLine {
+                    from: *from,
+                    to: *to,
+                })
+                .collect()
+        };
+        GraphRow {
+            lane,
+            top: lines(top),
+            bottom: lines(bottom),
+            lanes,
+        }
+    }
+
+    #[test]
+    fn linear_history() {
+        let commits =
+            im::vector![commit("c", &["b"]), commit("b", &["a"]), commit("a", &[]),];
+        assert_eq!(
+            commit_graph(&commits, false, false),
+            vec![
+                row(0, &[], &[(0, 0)], 1),
+                row(0, &[(0, 0)], &[(0, 0)], 1),
+                row(0, &[(0, 0)], &[], 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn branch_and_merge() {
+        let commits = im::vector![
+            commit("merge", &["a", "b"]),
+            commit("a", &["base"]),
+            commit("b", &["base"]),
+            commit("base", &[]),
+        ];
+        assert_eq!(
+            commit_graph(&commits, false, false),
+            vec![
+                // The second parent gets a lane of its own
+                row(0, &[], &[(0, 0), (0, 1)], 2),
+                row(0, &[(0, 0), (1, 1)], &[(1, 1), (0, 0)], 2),
+                // Its lane joins the first one at their common parent
+                row(1, &[(0, 0), (1, 1)], &[(0, 0), (1, 0)], 2),
+                row(0, &[(0, 0)], &[], 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn branch_tips() {
+        // Two branches from the same commit, neither merged
+        let commits = im::vector![
+            commit("feature", &["base"]),
+            commit("main", &["base"]),
+            commit("base", &[]),
+        ];
+        assert_eq!(
+            commit_graph(&commits, false, false),
+            vec![
+                row(0, &[], &[(0, 0)], 1),
+                row(1, &[(0, 0)], &[(0, 0), (1, 0)], 2),
+                row(0, &[(0, 0)], &[], 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn octopus_merge() {
+        let commits = im::vector![
+            commit("merge", &["a", "b", "c"]),
+            commit("a", &["base"]),
+            commit("b", &["base"]),
+            commit("c", &["base"]),
+            commit("base", &[]),
+        ];
+        assert_eq!(
+            commit_graph(&commits, false, false),
+            vec![
+                row(0, &[], &[(0, 0), (0, 1), (0, 2)], 3),
+                row(0, &[(0, 0), (1, 1), (2, 2)], &[(1, 1), (2, 2), (0, 0)], 3),
+                row(1, &[(0, 0), (1, 1), (2, 2)], &[(0, 0), (2, 2), (1, 0)], 3),
+                // The lane of `b` ended, so nothing is drawn in it
+                row(2, &[(0, 0), (2, 2)], &[(0, 0), (2, 0)], 3),
+                row(0, &[(0, 0)], &[], 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn parents_not_loaded() {
+        // The lane of a parent that isn't loaded continues to the bottom
+        let commits = im::vector![commit("c", &["b"]), commit("b", &["a"])];
+        assert_eq!(
+            commit_graph(&commits, false, true),
+            vec![row(0, &[], &[(0, 0)], 1), row(0, &[(0, 0)], &[(0, 0)], 1),]
+        );
+    }
+
+    #[test]
+    fn filtered_commits() {
+        // The filtered commits are linked one after the other, whatever their
+        // parents are, and to the ones after them if there are more
+        let commits = im::vector![commit("c", &["x", "y"]), commit("b", &["z"]),];
+        let more = commit_graph(&commits, true, true);
+        assert_eq!(
+            more,
+            vec![row(0, &[], &[(0, 0)], 1), row(0, &[(0, 0)], &[(0, 0)], 1),]
+        );
+        let all = commit_graph(&commits, true, false);
+        assert_eq!(
+            all,
+            vec![row(0, &[], &[(0, 0)], 1), row(0, &[(0, 0)], &[], 1)]
+        );
+    }
+}
@@ -1,7 +1,10 @@
+use std::path::PathBuf;
+
 use floem::{
     peniko::kurbo::Rect,
     reactive::{RwSignal, Scope},
 };
+use lapce_rpc::source_control::BlameHunk;
 
 use crate::{id::EditorId, markdown::MarkdownContent};
 
@@ -11,6 +14,9 @@ pub struct HoverData {
     pub offset: RwSignal<usize>,
     pub editor_id: RwSignal<EditorId>,
     pub content: RwSignal<Vec<MarkdownContent>>,
+    /// The file and the blame of the line, when the hover is for the git
+    /// blame, whose commit diff can be opened from it
+    pub blame: RwSignal<Option<(PathBuf, BlameHunk)>>,
     pub layout_rect: RwSignal<Rect>,
 }
 
@@ -20,6 +26,7 @@ pub fn new(cx: Scope) -> Self {
             active: cx.create_rw_signal(false),
             offset: cx.create_rw_signal(0),
             content: cx.create_rw_signal(Vec::new()),
+            blame: cx.create_rw_signal(None),
             editor_id: cx.create_rw_signal(EditorId::next()),
             layout_rect: cx.create_rw_signal(Rect::ZERO),
         }
@@ -20,6 +20,10 @@ pub fn to_raw(self) -> u64 {
 pub type EditorTabId = Id;
 pub type SettingsId = Id;
 pub type KeymapId = Id;
+pub type DisassemblyId = Id;
+pub type GitLogId = Id;
 pub type EditorId = Id;
 pub type DiffEditorId = Id;
 pub type TerminalTabId = Id;
+pub type DapVariableId = Id;
+pub type DebugConsoleEntryId = Id;
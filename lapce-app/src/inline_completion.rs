@@ -0,0 +1,327 @@
+use std::{path::PathBuf, str::FromStr};
+
+use floem::action::TimerToken;
+use lapce_core::buffer::{rope_text::RopeText, Buffer};
+use lapce_rpc::{
+    inline_completion::{self, InlineCompletionText},
+    plugin::PluginId,
+};
+use lapce_xi_rope::{RopeDelta, Transformer};
+use lsp_types::Command;
+
+use crate::snippet::Snippet;
+
+/// The time to wait after an edit before requesting inline completions, in
+/// milliseconds
+pub const INLINE_COMPLETION_DELAY: u64 = 150;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum InlineCompletionStatus {
+    Inactive,
+    Started,
+    Active,
+}
+
+#[derive(Clone)]
+pub struct InlineCompletionItem {
+    /// The start offset of the text that's replaced when the item is accepted
+    pub start: usize,
+    /// The end offset of the text that's replaced when the item is accepted
+    pub end: usize,
+    /// The text of the item, with the placeholders of a snippet filled in
+    pub text: String,
+    /// The snippet to insert when the item is accepted as a whole
+    pub snippet: Option<String>,
+    /// The text that what's typed is checked against, defaults to `text`
+    pub filter_text: String,
+    /// The command to run after the item is accepted as a whole
+    pub command: Option<Command>,
+    /// The plugin that provided the item, which runs the command
+    pub plugin_id: PluginId,
+}
+
+impl InlineCompletionItem {
+    /// The length of the start of the text that's already typed, if what's
+    /// been typed since the start of the item matches its text or its filter
+    /// text. When it only matches the filter text, none of the text counts as
+    /// typed, as what's typed is replaced by the text.
+    fn typed_len(&self, typed: &str) -> Option<usize> {
+        if self.text.starts_with(typed) {
+            Some(typed.len())
+        } else if self.filter_text.starts_with(typed) {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    /// The part of the item that is yet to be typed at the offset.
+    pub fn remaining_text(&self, buffer: &Buffer, offset: usize) -> Option<&str> {
+        if offset < self.start || offset > buffer.len() {
+            return None;
+        }
+        let typed = buffer.slice_to_cow(self.start..offset);
+        let remaining = &self.text[self.typed_len(&typed)?..];
+        (!remaining.is_empty()).then_some(remaining)
+    }
+
+    /// The text to replace the range of the item with, to accept `len` more
+    /// bytes of the remaining text at the offset.
+    pub fn partial_text(
+        &self,
+        buffer: &Buffer,
+        offset: usize,
+        len: usize,
+    ) -> Option<&str> {
+        let remaining = self.remaining_text(buffer, offset)?;
+        let typed_len = self.text.len() - remaining.len();
+        self.text.get(..typed_len + len.min(remaining.len()))
+    }
+}
+
+#[derive(Clone)]
+pub struct InlineCompletionData {
+    pub status: InlineCompletionStatus,
+    pub request_id: usize,
+    pub timer: TimerToken,
+    pub path: PathBuf,
+    pub items: Vec<InlineCompletionItem>,
+    pub active: usize,
+}
+
+impl Default for InlineCompletionData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InlineCompletionData {
+    pub fn new() -> Self {
+        Self {
+            status: InlineCompletionStatus::Inactive,
+            request_id: 0,
+            timer: TimerToken::INVALID,
+            path: PathBuf::new(),
+            items: Vec::new(),
+            active: 0,
+        }
+    }
+
+    /// Drop the items and ignore the responses that are still in flight.
+    pub fn cancel(&mut self) {
+        self.status = InlineCompletionStatus::Inactive;
+        self.request_id += 1;
+        self.timer = TimerToken::INVALID;
+        self.items.clear();
+        self.active = 0;
+    }
+
+    pub fn current_item(&self) -> Option<&InlineCompletionItem> {
+        if self.status != InlineCompletionStatus::Active {
+            return None;
+        }
+        self.items.get(self.active)
+    }
+
+    pub fn next(&mut self) {
+        if !self.items.is_empty() {
+            self.active = (self.active + 1) % self.items.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.items.is_empty() {
+            self.active = (self.active + self.items.len() - 1) % self.items.len();
+        }
+    }
+
+    /// Replace the items with the ones received for the cursor `offset`.
+    pub fn set_items(
+        &mut self,
+        plugin_id: PluginId,
+        items: Vec<inline_completion::InlineCompletionItem>,
+        buffer: &Buffer,
+        offset: usize,
+    ) {
+        self.items = items
+            .into_iter()
+            .filter_map(|item| {
+                let (start, end) = item
+                    .range
+                    .map(|range| {
+                        (
+                            buffer.offset_of_position(&range.start),
+                            buffer.offset_of_position(&range.end),
+                        )
+                    })
+                    .unwrap_or((offset, offset));
+                let (text, snippet) = match item.insert_text {
+                    InlineCompletionText::String(text) => (text, None),
+                    InlineCompletionText::Snippet { value, .. } => {
+                        let text = Snippet::from_str(&value).ok()?.text();
+                        (text, Some(value))
+                    }
+                };
+                Some(InlineCompletionItem {
+                    start,
+                    end,
+                    filter_text: item.filter_text.unwrap_or_else(|| text.clone()),
+                    text,
+                    snippet,
+                    command: item.command,
+                    plugin_id,
+                })
+            })
+            .filter(|item| !item.text.is_empty())
+            .collect();
+        self.active = 0;
+        self.status = if self.items.is_empty() {
+            InlineCompletionStatus::Inactive
+        } else {
+            InlineCompletionStatus::Active
+        };
+    }
+
+    /// Shift the ranges of the items after an edit, so they can keep being
+    /// shown while typing.
+    pub fn update_offsets(&mut self, delta: &RopeDelta) {
+        let mut transformer = Transformer::new(delta);
+        for item in self.items.iter_mut() {
+            item.start = transformer.transform(item.start, false);
+            item.end = transformer.transform(item.end, true);
+        }
+    }
+}
+
+/// The next word of the remaining text to accept, with the whitespace in front
+/// of it.
+pub fn next_word(text: &str) -> &str {
+    let start = text
+        .char_indices()
+        .find(|(_, c)| !c.is_whitespace())
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let word = &text[start..];
+    let len = match word.chars().next() {
+        Some(c) if is_word_char(c) => word
+            .char_indices()
+            .find(|(_, c)| !is_word_char(*c))
+            .map(|(i, _)| i)
+            .unwrap_or(word.len()),
+        Some(c) => c.len_utf8(),
+        None => 0,
+    };
+    &text[..start + len]
+}
+
+/// The rest of the current line of the remaining text to accept. When the
+/// text starts with a line break, it's the next line.
+pub fn next_line(text: &str) -> &str {
+    let skip = if text.starts_with("\r\n") {
+        2
+    } else if text.starts_with('\n') {
+        1
+    } else {
+        0
+    };
+    let end = text[skip..]
+        .find(['\r', '\n'])
+        .map(|i| i + skip)
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::buffer::Buffer;
+    use lapce_rpc::plugin::PluginId;
+
+    use super::{next_line, next_word, InlineCompletionItem};
+
+    fn item(
+        start: usize,
+        end: usize,
+        text: &str,
+        filter_text: &str,
+    ) -> InlineCompletionItem {
+        InlineCompletionItem {
+            start,
+            end,
+            text: text.to_string(),
+            snippet: None,
+            filter_text: filter_text.to_string(),
+            command: None,
+            plugin_id: PluginId(0),
+        }
+    }
+
+    #[test]
+    fn remaining_text_strips_what_is_typed() {
+        let buffer = Buffer::new("let x = foo");
+        let item = item(8, 8, "foobar()", "foobar()");
+        assert_eq!(item.remaining_text(&buffer, 8), Some("foobar()"));
+        assert_eq!(item.remaining_text(&buffer, 11), Some("bar()"));
+        // Before the start of the item
+        assert_eq!(item.remaining_text(&buffer, 7), None);
+    }
+
+    #[test]
+    fn remaining_text_stops_when_typing_something_else() {
+        let buffer = Buffer::new("let x = fob");
+        let item = item(8, 8, "foobar()", "foobar()");
+        assert_eq!(item.remaining_text(&buffer, 11), None);
+    }
+
+    #[test]
+    fn remaining_text_is_none_when_all_is_typed() {
+        let buffer = Buffer::new("foobar()");
+        let item = item(0, 0, "foobar()", "foobar()");
+        assert_eq!(item.remaining_text(&buffer, 8), None);
+    }
+
+    #[test]
+    fn remaining_text_with_filter_text() {
+        // What's typed only matches the filter text, so the whole text is left
+        // to replace it
+        let buffer = Buffer::new("bar");
+        let item = item(0, 3, "self.bar()", "bar");
+        assert_eq!(item.remaining_text(&buffer, 2), Some("self.bar()"));
+        assert_eq!(item.remaining_text(&buffer, 3), Some("self.bar()"));
+
+        let buffer = Buffer::new("baz");
+        assert_eq!(item.remaining_text(&buffer, 3), None);
+    }
+
+    #[test]
+    fn partial_text_includes_what_is_typed() {
+        let buffer = Buffer::new("foo");
+        let item = item(0, 3, "foo.bar(baz)", "foo.bar(baz)");
+        assert_eq!(item.partial_text(&buffer, 3, 4), Some("foo.bar"));
+        assert_eq!(item.partial_text(&buffer, 3, 100), Some("foo.bar(baz)"));
+
+        let item = self::item(0, 3, "self.foo()", "foo");
+        assert_eq!(item.partial_text(&buffer, 3, 4), Some("self"));
+    }
+
+    #[test]
+    fn next_word_of_text() {
+        assert_eq!(next_word("bar(baz)"), "bar");
+        assert_eq!(next_word("  bar baz"), "  bar");
+        assert_eq!(next_word("_foo_1 bar"), "_foo_1");
+        assert_eq!(next_word("(baz)"), "(");
+        assert_eq!(next_word(" héllo wörld"), " héllo");
+        assert_eq!(next_word("   "), "   ");
+        assert_eq!(next_word(""), "");
+    }
+
+    #[test]
+    fn next_line_of_text() {
+        assert_eq!(next_line("foo\nbar"), "foo");
+        assert_eq!(next_line("foo\r\nbar"), "foo");
+        assert_eq!(next_line("\nbar\nbaz"), "\nbar");
+        assert_eq!(next_line("\r\nbar\r\nbaz"), "\r\nbar");
+        assert_eq!(next_line("foo"), "foo");
+        assert_eq!(next_line(""), "");
+    }
+}
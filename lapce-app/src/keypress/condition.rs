@@ -53,6 +53,8 @@ pub enum Condition {
     ModalFocus,
     #[strum(serialize = "in_snippet")]
     InSnippet,
+    #[strum(serialize = "inline_completion_visible")]
+    InlineCompletionVisible,
     #[strum(serialize = "terminal_focus")]
     TerminalFocus,
     #[strum(serialize = "source_control_focus")]
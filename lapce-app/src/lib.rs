@@ -7,16 +7,19 @@
 pub mod config;
 pub mod db;
 pub mod debug;
+pub mod disassembly;
 pub mod doc;
 pub mod editor;
 pub mod editor_tab;
 pub mod file_explorer;
 pub mod find;
 pub mod focus_text;
+pub mod git_log;
 pub mod global_search;
 pub mod history;
 pub mod hover;
 pub mod id;
+pub mod inline_completion;
 pub mod keymap;
 pub mod keypress;
 pub mod listener;
@@ -28,6 +31,7 @@
 pub mod proxy;
 pub mod rename;
 pub mod settings;
+pub mod signature;
 pub mod snippet;
 pub mod source_control;
 pub mod status;
@@ -37,7 +37,11 @@
     editor_tab::{
         EditorTabChild, EditorTabChildSource, EditorTabData, EditorTabInfo,
     },
-    id::{DiffEditorId, EditorId, EditorTabId, KeymapId, SettingsId, SplitId},
+    git_log::GitLogData,
+    id::{
+        DiffEditorId, DisassemblyId, EditorId, EditorTabId, GitLogId, KeymapId,
+        SettingsId, SplitId,
+    },
     keypress::{EventRef, KeyPressData},
     window_tab::{CommonData, Focus, WindowTabData},
 };
@@ -229,6 +233,7 @@ pub struct MainSplitData {
     pub locations: RwSignal<im::Vector<EditorLocation>>,
     pub current_location: RwSignal<usize>,
     pub width: RwSignal<f64>,
+    pub git_log: GitLogData,
     pub common: Rc<CommonData>,
 }
 
@@ -252,6 +257,7 @@ pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
             EditorData::new_local(cx, EditorId::next(), common.clone());
         let replace_editor =
             EditorData::new_local(cx, EditorId::next(), common.clone());
+        let git_log = GitLogData::new(cx, common.clone());
 
         let active_editor = cx.create_memo(move |_| -> Option<Rc<EditorData>> {
             let active_editor_tab = active_editor_tab.get()?;
@@ -307,6 +313,7 @@ pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
             locations,
             current_location,
             width: cx.create_rw_signal(0.0),
+            git_log,
             common,
         }
     }
@@ -348,6 +355,8 @@ pub fn key_down<'a>(
             }
             EditorTabChild::Settings(_) => None,
             EditorTabChild::Keymap(_) => None,
+            EditorTabChild::Disassembly(_) => None,
+            EditorTabChild::GitLog(_) => None,
         }
     }
 
@@ -500,6 +509,34 @@ pub fn go_to_location(
         }
     }
 
+    /// Show `location` in a read-only editor embedded in the editor of
+    /// `editor_id`, below the line of `offset`.
+    pub fn peek_location(
+        &self,
+        editor_id: EditorId,
+        offset: usize,
+        location: EditorLocation,
+    ) {
+        let editor = match self
+            .editors
+            .with_untracked(|editors| editors.get(&editor_id).cloned())
+        {
+            Some(editor) => editor,
+            None => return,
+        };
+        let (doc, new_doc) = self.get_doc(location.path.clone());
+        let peek_editor = EditorData::new(
+            editor.scope,
+            None,
+            None,
+            EditorId::next(),
+            doc,
+            self.common.clone(),
+        );
+        peek_editor.go_to_location(location, new_doc, None);
+        editor.show_peek(offset, Rc::new(peek_editor));
+    }
+
     pub fn open_file_changes(&self, path: PathBuf) {
         let (right, _) = self.get_doc(path.clone());
         let left = Document::new_hisotry(
@@ -532,6 +569,57 @@ pub fn open_file_changes(&self, path: PathBuf) {
         );
     }
 
+    /// Opens a diff editor with the changes a commit made to the file, which
+    /// was at `old_path` before the commit if the commit renamed it
+    pub fn open_commit_file_changes(
+        &self,
+        path: PathBuf,
+        old_path: Option<PathBuf>,
+        commit_id: String,
+    ) {
+        let load = |path: PathBuf, version: String| {
+            let doc = Rc::new(Document::new_hisotry(
+                self.scope,
+                DocContent::History(DocHistory {
+                    path: path.clone(),
+                    version: version.clone(),
+                }),
+                self.common.clone(),
+            ));
+            let send = {
+                let doc = doc.clone();
+                create_ext_action(self.scope, move |result| {
+                    // The file doesn't exist in that revision if it was
+                    // added or deleted by the commit
+                    let content = match result {
+                        Ok(ProxyResponse::BufferHeadResponse {
+                            content, ..
+                        }) => content,
+                        _ => String::new(),
+                    };
+                    doc.init_content(Rope::from(content));
+                })
+            };
+            self.common
+                .proxy
+                .get_buffer_revision(path, version, move |result| {
+                    send(result);
+                });
+            doc
+        };
+        let left = load(
+            old_path.unwrap_or_else(|| path.clone()),
+            format!("{commit_id}^"),
+        );
+        let right = load(path, commit_id);
+
+        self.get_editor_tab_child(
+            EditorTabChildSource::DiffEditor { left, right },
+            false,
+            false,
+        );
+    }
+
     fn new_editor_tab(
         &self,
         editor_tab_id: EditorTabId,
@@ -682,6 +770,8 @@ fn get_editor_tab_child(
                         }
                         EditorTabChild::Settings(_) => true,
                         EditorTabChild::Keymap(_) => true,
+                        EditorTabChild::Disassembly(_) => true,
+                        EditorTabChild::GitLog(_) => true,
                     };
 
                     if can_be_selected {
@@ -796,6 +886,50 @@ fn get_editor_tab_child(
                         })
                     }
                 }
+                EditorTabChildSource::Disassembly => {
+                    if let Some(index) =
+                        active_editor_tab.with_untracked(|editor_tab| {
+                            editor_tab.children.iter().position(|(_, _, child)| {
+                                matches!(child, EditorTabChild::Disassembly(_))
+                            })
+                        })
+                    {
+                        Some(index)
+                    } else if ignore_unconfirmed {
+                        None
+                    } else {
+                        active_editor_tab.with_untracked(|editor_tab| {
+                            editor_tab
+                                .get_unconfirmed_editor_tab_child(
+                                    &editors,
+                                    &diff_editors,
+                                )
+                                .map(|(i, _)| i)
+                        })
+                    }
+                }
+                EditorTabChildSource::GitLog => {
+                    if let Some(index) =
+                        active_editor_tab.with_untracked(|editor_tab| {
+                            editor_tab.children.iter().position(|(_, _, child)| {
+                                matches!(child, EditorTabChild::GitLog(_))
+                            })
+                        })
+                    {
+                        Some(index)
+                    } else if ignore_unconfirmed {
+                        None
+                    } else {
+                        active_editor_tab.with_untracked(|editor_tab| {
+                            editor_tab
+                                .get_unconfirmed_editor_tab_child(
+                                    &editors,
+                                    &diff_editors,
+                                )
+                                .map(|(i, _)| i)
+                        })
+                    }
+                }
             }
         };
 
@@ -854,6 +988,12 @@ fn get_editor_tab_child(
                 EditorTabChildSource::Keymap => {
                     EditorTabChild::Keymap(KeymapId::next())
                 }
+                EditorTabChildSource::Disassembly => {
+                    EditorTabChild::Disassembly(DisassemblyId::next())
+                }
+                EditorTabChildSource::GitLog => {
+                    EditorTabChild::GitLog(GitLogId::next())
+                }
                 EditorTabChildSource::DiffEditor { left, right } => {
                     let diff_editor_id = DiffEditorId::next();
                     let diff_editor = DiffEditorData::new(
@@ -885,6 +1025,8 @@ fn get_editor_tab_child(
                         EditorTabChild::DiffEditor(_) => {}
                         EditorTabChild::Settings(_) => {}
                         EditorTabChild::Keymap(_) => {}
+                        EditorTabChild::Disassembly(_) => {}
+                        EditorTabChild::GitLog(_) => {}
                     }
                     (editor_tab_id, current_child.clone())
                 });
@@ -949,6 +1091,8 @@ fn get_editor_tab_child(
                 }
                 EditorTabChild::Settings(_) => {}
                 EditorTabChild::Keymap(_) => {}
+                EditorTabChild::Disassembly(_) => {}
+                EditorTabChild::GitLog(_) => {}
             }
 
             // Now loading the new child
@@ -1003,6 +1147,18 @@ fn get_editor_tab_child(
                                 .position(|(_, _, child)| {
                                     matches!(child, EditorTabChild::Keymap(_))
                                 }),
+                            EditorTabChildSource::Disassembly => editor_tab
+                                .children
+                                .iter()
+                                .position(|(_, _, child)| {
+                                    matches!(child, EditorTabChild::Disassembly(_))
+                                }),
+                            EditorTabChildSource::GitLog => editor_tab
+                                .children
+                                .iter()
+                                .position(|(_, _, child)| {
+                                    matches!(child, EditorTabChild::GitLog(_))
+                                }),
                             EditorTabChildSource::NewFileEditor => None,
                         })
                     {
@@ -1291,6 +1447,10 @@ fn split_editor_tab(
                 EditorTabChild::Settings(SettingsId::next())
             }
             EditorTabChild::Keymap(_) => EditorTabChild::Keymap(KeymapId::next()),
+            EditorTabChild::Disassembly(_) => {
+                EditorTabChild::Disassembly(DisassemblyId::next())
+            }
+            EditorTabChild::GitLog(_) => EditorTabChild::GitLog(GitLogId::next()),
         };
 
         let editor_tab = {
@@ -1629,6 +1789,8 @@ fn editor_tab_child_close_warning(
             EditorTabChild::DiffEditor(_) => None,
             EditorTabChild::Settings(_) => None,
             EditorTabChild::Keymap(_) => None,
+            EditorTabChild::Disassembly(_) => None,
+            EditorTabChild::GitLog(_) => None,
         }
     }
 
@@ -1806,6 +1968,8 @@ pub fn editor_tab_child_close(
             }
             EditorTabChild::Settings(_) => {}
             EditorTabChild::Keymap(_) => {}
+            EditorTabChild::Disassembly(_) => {}
+            EditorTabChild::GitLog(_) => {}
         }
 
         if editor_tab_children_len == 0 {
@@ -1980,6 +2144,20 @@ pub fn get_diagnostic_data(&self, path: &Path) -> DiagnosticData {
         }
     }
 
+    /// The file of the active editor, with the line the cursor is on.
+    pub fn active_editor_line(&self) -> Option<(PathBuf, usize)> {
+        let editor = self.active_editor.get_untracked()?;
+        let doc = editor.view.doc.get_untracked();
+        let path = doc
+            .content
+            .with_untracked(|content| content.path().cloned())?;
+        let offset = editor.cursor.with_untracked(|cursor| cursor.offset());
+        let line = doc
+            .buffer
+            .with_untracked(|buffer| buffer.line_of_offset(offset));
+        Some((path, line))
+    }
+
     pub fn open_file_changed(&self, path: &Path, content: &str) {
         let doc = self.docs.with_untracked(|docs| docs.get(path).cloned());
         let doc = match doc {
@@ -2018,6 +2196,14 @@ pub fn open_keymap(&self) {
         self.get_editor_tab_child(EditorTabChildSource::Keymap, false, false);
     }
 
+    pub fn open_disassembly(&self) {
+        self.get_editor_tab_child(EditorTabChildSource::Disassembly, false, false);
+    }
+
+    pub fn open_git_log(&self) {
+        self.get_editor_tab_child(EditorTabChildSource::GitLog, false, false);
+    }
+
     pub fn new_file(&self) {
         self.get_editor_tab_child(EditorTabChildSource::NewFileEditor, false, false);
     }
@@ -2246,6 +2432,8 @@ fn editor_tab_child_set_parent(
             }
             EditorTabChild::Settings(_) => {}
             EditorTabChild::Keymap(_) => {}
+            EditorTabChild::Disassembly(_) => {}
+            EditorTabChild::GitLog(_) => {}
         }
         Some(())
     }
@@ -22,9 +22,12 @@
     buffer::rope_text::RopeText, command::FocusCommand, language::LapceLanguage,
     mode::Mode, movement::Movement, selection::Selection, syntax::Syntax,
 };
-use lapce_rpc::proxy::ProxyResponse;
+use lapce_rpc::{
+    dap_types::{DebugRequest, RunDebugConfig},
+    proxy::ProxyResponse,
+};
 use lapce_xi_rope::Rope;
-use lsp_types::DocumentSymbolResponse;
+use lsp_types::{DocumentSymbolResponse, Range, TextEdit, Url, WorkspaceEdit};
 use nucleo::Utf32Str;
 use strum::{EnumMessage, IntoEnumIterator};
 
@@ -96,6 +99,10 @@ pub struct PaletteData {
     pub executed_run_configs: Rc<RefCell<HashMap<(RunDebugMode, String), Instant>>>,
     pub main_split: MainSplitData,
     pub references: RwSignal<Vec<EditorLocation>>,
+    /// The color in a document that the color picker replaces
+    pub color_picker: RwSignal<Option<(PathBuf, Range, lsp_types::Color)>>,
+    /// The attach config that a process is picked for
+    pub attach_config: RwSignal<Option<RunDebugConfig>>,
     pub source_control: SourceControlData,
     pub common: Rc<CommonData>,
 }
@@ -113,6 +120,8 @@ pub fn new(
         let items = cx.create_rw_signal(im::Vector::new());
         let index = cx.create_rw_signal(0);
         let references = cx.create_rw_signal(Vec::new());
+        let color_picker = cx.create_rw_signal(None);
+        let attach_config = cx.create_rw_signal(None);
         let input = cx.create_rw_signal(PaletteInput {
             input: "".to_string(),
             kind: PaletteKind::File,
@@ -202,6 +211,8 @@ pub fn new(
             executed_commands: Rc::new(RefCell::new(HashMap::new())),
             executed_run_configs: Rc::new(RefCell::new(HashMap::new())),
             references,
+            color_picker,
+            attach_config,
             source_control,
             common,
         };
@@ -261,10 +272,13 @@ pub fn new(
                         .unwrap();
                     if let Some(new_kind) = new_kind {
                         palette.run_inner(new_kind);
-                    } else if input
-                        .with_untracked(|i| i.kind == PaletteKind::WorkspaceSymbol)
-                    {
-                        palette.run_inner(PaletteKind::WorkspaceSymbol);
+                    } else if input.with_untracked(|i| {
+                        matches!(
+                            i.kind,
+                            PaletteKind::WorkspaceSymbol | PaletteKind::ColorPicker
+                        )
+                    }) {
+                        palette.run_inner(input.with_untracked(|i| i.kind));
                     }
                 }
                 Some(new_input)
@@ -373,6 +387,12 @@ fn run_inner(&self, kind: PaletteKind) {
             PaletteKind::SCMReferences => {
                 self.get_scm_references();
             }
+            PaletteKind::ColorPicker => {
+                self.get_color_presentations();
+            }
+            PaletteKind::AttachProcess => {
+                self.get_processes();
+            }
         }
     }
 
@@ -750,6 +770,22 @@ fn get_run_configs(&self) {
         let mut items = Vec::new();
         if let Some(configs) = configs.as_ref() {
             for config in &configs.configs {
+                if config.request == DebugRequest::Attach {
+                    items.push((
+                        executed_run_configs
+                            .get(&(RunDebugMode::Debug, config.name.clone())),
+                        PaletteItem {
+                            content: PaletteItemContent::RunAndDebug {
+                                mode: RunDebugMode::Debug,
+                                config: config.clone(),
+                            },
+                            filter_text: format!("Attach {}", config.name),
+                            score: 0,
+                            indices: vec![],
+                        },
+                    ));
+                    continue;
+                }
                 items.push((
                     executed_run_configs
                         .get(&(RunDebugMode::Run, config.name.clone())),
@@ -787,6 +823,43 @@ fn get_run_configs(&self) {
                     },
                 ));
             }
+
+            for compound in &configs.compounds {
+                let compound_configs: Vec<RunDebugConfig> = compound
+                    .configs
+                    .iter()
+                    .filter_map(|name| {
+                        configs.configs.iter().find(|config| &config.name == name)
+                    })
+                    .cloned()
+                    .collect();
+                if compound_configs.is_empty() {
+                    continue;
+                }
+                // Attaching needs the debugger, so those can't only be run
+                let modes = if compound_configs
+                    .iter()
+                    .any(|config| config.request == DebugRequest::Attach)
+                {
+                    vec![RunDebugMode::Debug]
+                } else {
+                    vec![RunDebugMode::Run, RunDebugMode::Debug]
+                };
+                for mode in modes {
+                    items.push((
+                        executed_run_configs.get(&(mode, compound.name.clone())),
+                        PaletteItem {
+                            content: PaletteItemContent::RunAndDebugCompound {
+                                mode,
+                                configs: compound_configs.clone(),
+                            },
+                            filter_text: format!("{mode} {}", compound.name),
+                            score: 0,
+                            indices: vec![],
+                        },
+                    ));
+                }
+            }
         }
 
         items.sort_by_key(|(executed, _item)| std::cmp::Reverse(executed.copied()));
@@ -867,6 +940,93 @@ fn get_scm_references(&self) {
         self.items.set(items);
     }
 
+    /// Initialize the palette with the presentations of the color being picked, or of the
+    /// color typed into the palette, which can replace the color in the document.
+    fn get_color_presentations(&self) {
+        let (path, range, color) = match self.color_picker.get_untracked() {
+            Some(color_picker) => color_picker,
+            None => return,
+        };
+        let input = self
+            .input_editor
+            .view
+            .doc
+            .get_untracked()
+            .buffer
+            .with_untracked(|buffer| buffer.to_string());
+        let color = parse_hex_color(input.trim()).unwrap_or(color);
+
+        let item_path = path.clone();
+        let set_items = self.items.write_only();
+        let send = create_ext_action(self.common.scope, move |result| {
+            if let Ok(ProxyResponse::GetColorPresentations { presentations }) =
+                result
+            {
+                let items: im::Vector<PaletteItem> = presentations
+                    .into_iter()
+                    .map(|presentation| PaletteItem {
+                        filter_text: presentation.label.clone(),
+                        content: PaletteItemContent::ColorPresentation {
+                            path: item_path.clone(),
+                            range,
+                            presentation,
+                        },
+                        score: 0,
+                        indices: Vec::new(),
+                    })
+                    .collect();
+                set_items.set(items);
+            } else {
+                set_items.update(|items| items.clear());
+            }
+        });
+
+        self.common.proxy.get_color_presentations(
+            path,
+            color,
+            range,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// Initialize the palette with the running processes, to pick the one that
+    /// the debugger attaches to.
+    fn get_processes(&self) {
+        let config = match self.attach_config.get_untracked() {
+            Some(config) => config,
+            None => return,
+        };
+        let set_items = self.items.write_only();
+        let send = create_ext_action(self.common.scope, move |result| {
+            if let Ok(ProxyResponse::ListProcessesResponse { processes }) = result {
+                let items: im::Vector<PaletteItem> = processes
+                    .into_iter()
+                    .map(|process| {
+                        let mut config = config.clone();
+                        config.pid = Some(process.pid);
+                        PaletteItem {
+                            content: PaletteItemContent::AttachProcess { config },
+                            filter_text: format!(
+                                "{} {}",
+                                process.pid, process.command
+                            ),
+                            score: 0,
+                            indices: Vec::new(),
+                        }
+                    })
+                    .collect();
+                set_items.set(items);
+            } else {
+                set_items.update(|items| items.clear());
+            }
+        });
+        self.common.proxy.list_processes(move |result| {
+            send(result);
+        });
+    }
+
     fn preselect_matching(&self, matching: &str) {
         let Some((idx, _)) = self
             .items
@@ -1002,6 +1162,24 @@ fn select(&self) {
                         },
                     );
                 }
+                PaletteItemContent::RunAndDebugCompound { mode, configs } => {
+                    for config in configs {
+                        self.common.internal_command.send(
+                            InternalCommand::RunAndDebug {
+                                mode: *mode,
+                                config: config.clone(),
+                            },
+                        );
+                    }
+                }
+                PaletteItemContent::AttachProcess { config } => {
+                    self.common.internal_command.send(
+                        InternalCommand::RunAndDebug {
+                            mode: RunDebugMode::Debug,
+                            config: config.clone(),
+                        },
+                    );
+                }
                 PaletteItemContent::ColorTheme { name } => self
                     .common
                     .internal_command
@@ -1035,6 +1213,31 @@ fn select(&self) {
                     }
                     doc.trigger_syntax_change(None);
                 }
+                PaletteItemContent::ColorPresentation {
+                    path,
+                    range,
+                    presentation,
+                } => {
+                    let mut edits =
+                        vec![presentation.text_edit.clone().unwrap_or(TextEdit {
+                            range: *range,
+                            new_text: presentation.label.clone(),
+                        })];
+                    if let Some(additional_edits) =
+                        presentation.additional_text_edits.as_ref()
+                    {
+                        edits.extend(additional_edits.iter().cloned());
+                    }
+                    if let Ok(url) = Url::from_file_path(path) {
+                        self.common.internal_command.send(
+                            InternalCommand::ApplyWorkspaceEdit {
+                                edit: WorkspaceEdit::new(HashMap::from([(
+                                    url, edits,
+                                )])),
+                            },
+                        );
+                    }
+                }
                 PaletteItemContent::SCMReference { name } => {
                     self.common
                         .lapce_command
@@ -1105,6 +1308,7 @@ fn preview(&self, _cx: Scope) {
                 PaletteItemContent::Command { .. } => {}
                 PaletteItemContent::Workspace { .. } => {}
                 PaletteItemContent::RunAndDebug { .. } => {}
+                PaletteItemContent::RunAndDebugCompound { .. } => {}
                 PaletteItemContent::SshHost { .. } => {}
                 PaletteItemContent::Language { .. } => {}
                 PaletteItemContent::Reference { location, .. } => {
@@ -1172,7 +1376,9 @@ fn preview(&self, _cx: Scope) {
                         name: name.clone(),
                         save: false,
                     }),
-                PaletteItemContent::SCMReference { .. } => {}
+                PaletteItemContent::SCMReference { .. }
+                | PaletteItemContent::ColorPresentation { .. }
+                | PaletteItemContent::AttachProcess { .. } => {}
             }
         }
     }
@@ -1393,3 +1599,54 @@ fn receive_char(&self, c: &str) {
         self.input_editor.receive_char(c);
     }
 }
+
+/// Parse a color written as `#rgb`, `#rrggbb` or `#rrggbbaa`
+fn parse_hex_color(s: &str) -> Option<lsp_types::Color> {
+    let hex = s.strip_prefix('#')?;
+    let hex = if hex.len() == 3 {
+        hex.chars().flat_map(|c| [c, c]).collect::<String>()
+    } else {
+        hex.to_string()
+    };
+    if hex.len() != 6 && hex.len() != 8 {
+        return None;
+    }
+
+    let component = |i: usize| -> Option<f32> {
+        let value = u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()?;
+        Some(value as f32 / 255.0)
+    };
+    Some(lsp_types::Color {
+        red: component(0)?,
+        green: component(2)?,
+        blue: component(4)?,
+        alpha: if hex.len() == 8 { component(6)? } else { 1.0 },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_hex_color;
+
+    #[test]
+    fn hex_color() {
+        let color = parse_hex_color("#ff8000").unwrap();
+        assert_eq!(color.red, 1.0);
+        assert_eq!(color.green, 128.0 / 255.0);
+        assert_eq!(color.blue, 0.0);
+        assert_eq!(color.alpha, 1.0);
+
+        let color = parse_hex_color("#f80").unwrap();
+        assert_eq!(color.red, 1.0);
+        assert_eq!(color.green, 136.0 / 255.0);
+        assert_eq!(color.blue, 0.0);
+
+        let color = parse_hex_color("#00000080").unwrap();
+        assert_eq!(color.alpha, 128.0 / 255.0);
+
+        assert!(parse_hex_color("ff8000").is_none());
+        assert!(parse_hex_color("#ff80").is_none());
+        assert!(parse_hex_color("#gg8000").is_none());
+        assert!(parse_hex_color("#é8000").is_none());
+    }
+}
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use lapce_rpc::dap_types::RunDebugConfig;
-use lsp_types::{Range, SymbolKind};
+use lsp_types::{ColorPresentation, Range, SymbolKind};
 
 use crate::{
     command::{LapceCommand, LapceWorkbenchCommand},
@@ -60,6 +60,11 @@ pub enum PaletteItemContent {
         mode: RunDebugMode,
         config: RunDebugConfig,
     },
+    /// The configs of a compound, which are run or debugged together
+    RunAndDebugCompound {
+        mode: RunDebugMode,
+        configs: Vec<RunDebugConfig>,
+    },
     ColorTheme {
         name: String,
     },
@@ -72,4 +77,13 @@ pub enum PaletteItemContent {
     SCMReference {
         name: String,
     },
+    ColorPresentation {
+        path: PathBuf,
+        range: Range,
+        presentation: ColorPresentation,
+    },
+    /// The config to debug with, which attaches to the process of the item
+    AttachProcess {
+        config: RunDebugConfig,
+    },
 }
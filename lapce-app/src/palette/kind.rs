@@ -18,6 +18,8 @@ pub enum PaletteKind {
     IconTheme,
     Language,
     SCMReferences,
+    ColorPicker,
+    AttachProcess,
 }
 
 impl PaletteKind {
@@ -38,7 +40,9 @@ pub fn symbol(&self) -> &'static str {
             | PaletteKind::ColorTheme
             | PaletteKind::IconTheme
             | PaletteKind::Language
-            | PaletteKind::SCMReferences => "",
+            | PaletteKind::SCMReferences
+            | PaletteKind::ColorPicker
+            | PaletteKind::AttachProcess => "",
         }
     }
 
@@ -80,6 +84,8 @@ pub fn command(self) -> Option<LapceWorkbenchCommand> {
             PaletteKind::SCMReferences => {
                 Some(LapceWorkbenchCommand::PaletteSCMReferences)
             }
+            PaletteKind::ColorPicker => None, // InternalCommand::PaletteColorPicker
+            PaletteKind::AttachProcess => None, // RunAndDebug of an attach config
         }
     }
 
@@ -103,7 +109,8 @@ pub fn get_input<'a>(&self, input: &'a str) -> &'a str {
             | PaletteKind::ColorTheme
             | PaletteKind::IconTheme
             | PaletteKind::Language
-            | PaletteKind::SCMReferences => input,
+            | PaletteKind::SCMReferences
+            | PaletteKind::AttachProcess => input,
             PaletteKind::PaletteHelp
             | PaletteKind::Command
             | PaletteKind::Workspace
@@ -112,6 +119,9 @@ pub fn get_input<'a>(&self, input: &'a str) -> &'a str {
             | PaletteKind::Line
             // | PaletteType::GlobalSearch
              => input.get(1..).unwrap_or(""),
+            // The input is the color to pick rather than a filter, so that all the
+            // presentations of the color are shown
+            PaletteKind::ColorPicker => "",
         }
     }
 
@@ -1,29 +1,41 @@
-use std::{rc::Rc, sync::Arc};
+use std::{path::PathBuf, rc::Rc, sync::Arc};
 
 use floem::{
     cosmic_text::Style as FontStyle,
+    event::EventListener,
+    peniko::{kurbo::Point, Color},
     reactive::{ReadSignal, RwSignal},
     style::CursorStyle,
     view::View,
-    views::{container, container_box, label, list, scroll, stack, svg, Decorators},
+    views::{
+        container, container_box, empty, label, list, scroll, stack, svg, Decorators,
+    },
 };
 use lapce_rpc::{
-    dap_types::{DapId, ThreadId},
+    dap_types::{DapId, ExceptionBreakpointsFilter, ThreadId},
     terminal::TermId,
 };
 
-use super::{position::PanelPosition, view::panel_header};
+use super::{kind::PanelKind, position::PanelPosition, view::panel_header};
 use crate::{
     app::clickable_icon,
     command::InternalCommand,
     config::{color::LapceColor, icon::LapceIcons, LapceConfig},
-    debug::{RunDebugMode, StackTraceData},
+    debug::{
+        BreakpointField, DapVariable, DebugConsoleEntry, DebugConsoleKind,
+        DebugInput, LapceBreakpoint, RunDebugData, RunDebugMode, StackTraceData,
+    },
     editor::location::{EditorLocation, EditorPosition},
     listener::Listener,
+    settings::checkbox,
     terminal::panel::TerminalPanelData,
-    window_tab::WindowTabData,
+    text_input::text_input,
+    window_tab::{Focus, WindowTabData},
 };
 
+/// The number of completions shown above the input of the debug console
+const MAX_SHOWN_COMPLETIONS: usize = 8;
+
 pub fn debug_panel(
     window_tab_data: Rc<WindowTabData>,
     position: PanelPosition,
@@ -44,10 +56,85 @@ pub fn debug_panel(
                 })
                 .style(|s| s.width_pct(100.0).flex_col().height_px(150.0))
             },
+            {
+                let terminal = terminal.clone();
+                stack(move || {
+                    (
+                        panel_header("Stack Frames".to_string(), config),
+                        debug_stack_traces(terminal, internal_command, config),
+                    )
+                })
+                .style(|s| {
+                    s.width_pct(100.0)
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .flex_col()
+                })
+            },
+            {
+                let terminal = terminal.clone();
+                stack(move || {
+                    (
+                        panel_header("Variables".to_string(), config),
+                        debug_variables(terminal, config),
+                    )
+                })
+                .style(|s| {
+                    s.width_pct(100.0)
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .flex_col()
+                })
+            },
+            {
+                let terminal = terminal.clone();
+                stack(move || {
+                    (
+                        panel_header("Watch".to_string(), config),
+                        debug_watches(terminal, config),
+                    )
+                })
+                .style(|s| {
+                    s.width_pct(100.0)
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .flex_col()
+                })
+            },
+            {
+                let terminal = terminal.clone();
+                stack(move || {
+                    (
+                        panel_header("Memory".to_string(), config),
+                        debug_memory(terminal, config),
+                    )
+                })
+                .style(|s| {
+                    s.width_pct(100.0)
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .flex_col()
+                })
+            },
+            {
+                let terminal = terminal.clone();
+                stack(move || {
+                    (
+                        panel_header("Breakpoints".to_string(), config),
+                        debug_breakpoints(terminal, internal_command, config),
+                    )
+                })
+                .style(|s| {
+                    s.width_pct(100.0)
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .flex_col()
+                })
+            },
             stack(move || {
                 (
-                    panel_header("Stack Frames".to_string(), config),
-                    debug_stack_traces(terminal, internal_command, config),
+                    panel_header("Debug Console".to_string(), config),
+                    debug_console(terminal, config),
                 )
             })
             .style(|s| {
@@ -154,6 +241,71 @@ fn debug_process_icons(
                         )
                         .style(|s| s.margin_right_px(6.0))
                     },
+                    {
+                        let terminal = terminal.clone();
+                        clickable_icon(
+                            || LapceIcons::DEBUG_STEP_OVER,
+                            move || {
+                                terminal.dap_step_over(term_id);
+                            },
+                            || false,
+                            move || !paused() || stopped,
+                            config,
+                        )
+                        .style(|s| s.margin_right_px(6.0))
+                    },
+                    {
+                        let terminal = terminal.clone();
+                        clickable_icon(
+                            || LapceIcons::DEBUG_STEP_INTO,
+                            move || {
+                                terminal.dap_step_into(term_id);
+                            },
+                            || false,
+                            move || !paused() || stopped,
+                            config,
+                        )
+                        .style(|s| s.margin_right_px(6.0))
+                    },
+                    {
+                        let terminal = terminal.clone();
+                        clickable_icon(
+                            || LapceIcons::DEBUG_STEP_OUT,
+                            move || {
+                                terminal.dap_step_out(term_id);
+                            },
+                            || false,
+                            move || !paused() || stopped,
+                            config,
+                        )
+                        .style(|s| s.margin_right_px(6.0))
+                    },
+                    {
+                        let terminal = terminal.clone();
+                        let dap_capabilities = terminal.common.dap_capabilities;
+                        // Only debug adapters that record the execution can step back
+                        let supports_step_back = move || {
+                            dap_capabilities.with(|capabilities| {
+                                capabilities
+                                    .get(&dap_id)
+                                    .and_then(|c| c.supports_step_back)
+                                    .unwrap_or(false)
+                            })
+                        };
+                        clickable_icon(
+                            || LapceIcons::DEBUG_STEP_BACK,
+                            move || {
+                                terminal.dap_step_back(term_id);
+                            },
+                            || false,
+                            move || !paused() || stopped,
+                            config,
+                        )
+                        .style(move |s| {
+                            s.margin_right_px(6.0)
+                                .apply_if(!supports_step_back(), |s| s.hide())
+                        })
+                    },
                     {
                         let terminal = terminal.clone();
                         clickable_icon(
@@ -207,87 +359,215 @@ fn debug_processes(
         let terminal = terminal.clone();
         let local_terminal = terminal.clone();
         list(
-            move || local_terminal.run_debug_process(true),
+            move || {
+                // The child sessions that have their own terminal are shown
+                // under their parent
+                let daps = local_terminal.debug.daps.get();
+                local_terminal
+                    .run_debug_process(true)
+                    .into_iter()
+                    .filter(|(_, p)| {
+                        p.config
+                            .parent
+                            .map(|parent| !daps.contains_key(&parent))
+                            .unwrap_or(true)
+                    })
+                    .collect::<Vec<_>>()
+            },
             |(term_id, p)| (*term_id, p.stopped),
             move |(term_id, p)| {
                 let terminal = terminal.clone();
-                let is_active =
-                    move || terminal.debug.active_term.get() == Some(term_id);
+                let process_dap_id = p.config.dap_id;
+                let is_active = {
+                    let terminal = terminal.clone();
+                    move || {
+                        terminal.debug.active_term.get() == Some(term_id)
+                            && terminal
+                                .get_dap(term_id, true)
+                                .map(|dap| dap.dap_id)
+                                .unwrap_or(process_dap_id)
+                                == process_dap_id
+                    }
+                };
                 let local_terminal = terminal.clone();
+                let children_terminal = terminal.clone();
                 stack(move || {
                     (
-                        {
-                            let svg_str = match (&p.mode, p.stopped) {
-                                (RunDebugMode::Run, false) => LapceIcons::START,
-                                (RunDebugMode::Run, true) => LapceIcons::RUN_ERRORS,
-                                (RunDebugMode::Debug, false) => LapceIcons::DEBUG,
-                                (RunDebugMode::Debug, true) => {
-                                    LapceIcons::DEBUG_DISCONNECT
-                                }
-                            };
-                            svg(move || config.get().ui_svg(svg_str)).style(
-                                move |s| {
-                                    let config = config.get();
-                                    let size = config.ui.icon_size() as f32;
-                                    s.size_px(size, size)
-                                        .margin_horiz_px(10.0)
-                                        .color(*config.get_color(
-                                            LapceColor::LAPCE_ICON_ACTIVE,
-                                        ))
+                        stack(move || {
+                            (
+                                {
+                                    let svg_str = match (&p.mode, p.stopped) {
+                                        (RunDebugMode::Run, false) => {
+                                            LapceIcons::START
+                                        }
+                                        (RunDebugMode::Run, true) => {
+                                            LapceIcons::RUN_ERRORS
+                                        }
+                                        (RunDebugMode::Debug, false) => {
+                                            LapceIcons::DEBUG
+                                        }
+                                        (RunDebugMode::Debug, true) => {
+                                            LapceIcons::DEBUG_DISCONNECT
+                                        }
+                                    };
+                                    svg(move || config.get().ui_svg(svg_str)).style(
+                                        move |s| {
+                                            let config = config.get();
+                                            let size = config.ui.icon_size() as f32;
+                                            s.size_px(size, size)
+                                                .margin_horiz_px(10.0)
+                                                .color(*config.get_color(
+                                                    LapceColor::LAPCE_ICON_ACTIVE,
+                                                ))
+                                        },
+                                    )
                                 },
+                                label(move || p.config.name.clone()).style(|s| {
+                                    s.flex_grow(1.0)
+                                        .flex_basis_px(0.0)
+                                        .min_width_px(0.0)
+                                        .text_ellipsis()
+                                }),
+                                debug_process_icons(
+                                    terminal.clone(),
+                                    term_id,
+                                    p.config.dap_id,
+                                    p.mode,
+                                    p.stopped,
+                                    config,
+                                ),
+                            )
+                        })
+                        .on_click(move |_| {
+                            local_terminal.debug.active_term.set(Some(term_id));
+                            local_terminal.debug.active_session.set(None);
+                            local_terminal.focus_terminal(term_id);
+                            true
+                        })
+                        .style(move |s| {
+                            s.padding_vert_px(6.0)
+                                .width_pct(100.0)
+                                .items_center()
+                                .apply_if(is_active(), |s| {
+                                    s.background(*config.get().get_color(
+                                        LapceColor::PANEL_CURRENT_BACKGROUND,
+                                    ))
+                                })
+                        })
+                        .hover_style(move |s| {
+                            s.cursor(CursorStyle::Pointer).background(
+                                (*config.get().get_color(
+                                    LapceColor::PANEL_HOVERED_BACKGROUND,
+                                ))
+                                .with_alpha_factor(0.3),
                             )
-                        },
-                        label(move || p.config.name.clone()).style(|s| {
-                            s.flex_grow(1.0)
-                                .flex_basis_px(0.0)
-                                .min_width_px(0.0)
-                                .text_ellipsis()
                         }),
-                        debug_process_icons(
-                            terminal.clone(),
-                            term_id,
-                            p.config.dap_id,
-                            p.mode,
-                            p.stopped,
-                            config,
-                        ),
-                    )
-                })
-                .on_click(move |_| {
-                    local_terminal.debug.active_term.set(Some(term_id));
-                    local_terminal.focus_terminal(term_id);
-                    true
-                })
-                .style(move |s| {
-                    s.padding_vert_px(6.0)
-                        .width_pct(100.0)
-                        .items_center()
-                        .apply_if(is_active(), |s| {
-                            s.background(
-                                *config
-                                    .get()
-                                    .get_color(LapceColor::PANEL_CURRENT_BACKGROUND),
+                        {
+                            let local_terminal = children_terminal.clone();
+                            list(
+                                move || {
+                                    local_terminal
+                                        .debug
+                                        .child_sessions(process_dap_id)
+                                },
+                                |(depth, dap)| (*depth, dap.dap_id),
+                                move |(depth, dap)| {
+                                    debug_child_session(
+                                        children_terminal.clone(),
+                                        depth,
+                                        dap.dap_id,
+                                        dap.term_id,
+                                        dap.name,
+                                        dap.stopped,
+                                        config,
+                                    )
+                                },
                             )
-                        })
-                })
-                .hover_style(move |s| {
-                    s.cursor(CursorStyle::Pointer).background(
-                        (*config
-                            .get()
-                            .get_color(LapceColor::PANEL_HOVERED_BACKGROUND))
-                        .with_alpha_factor(0.3),
+                            .style(|s| s.width_pct(100.0).flex_col())
+                        },
                     )
                 })
+                .style(|s| s.width_pct(100.0).flex_col())
             },
         )
         .style(|s| s.width_pct(100.0).flex_col())
     })
 }
 
+/// A session that was started by the debug adapter of another one, indented
+/// under it in the session tree
+fn debug_child_session(
+    terminal: TerminalPanelData,
+    depth: usize,
+    dap_id: DapId,
+    term_id: TermId,
+    name: String,
+    stopped: RwSignal<bool>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debug = terminal.debug.clone();
+    let is_active = move || {
+        debug.active_term.get() == Some(term_id)
+            && debug.active_session.get() == Some(dap_id)
+    };
+    stack(move || {
+        (
+            svg(move || {
+                let svg_str = if stopped.get() {
+                    LapceIcons::DEBUG_PAUSE
+                } else {
+                    LapceIcons::DEBUG
+                };
+                config.get().ui_svg(svg_str)
+            })
+            .style(move |s| {
+                let config = config.get();
+                let size = config.ui.icon_size() as f32;
+                s.size_px(size, size)
+                    .margin_left_px(10.0 + depth as f32 * 16.0)
+                    .margin_right_px(10.0)
+                    .color(*config.get_color(LapceColor::LAPCE_ICON_ACTIVE))
+            }),
+            label(move || name.clone()).style(|s| {
+                s.flex_grow(1.0)
+                    .flex_basis_px(0.0)
+                    .min_width_px(0.0)
+                    .text_ellipsis()
+            }),
+        )
+    })
+    .on_click(move |_| {
+        terminal.debug.active_term.set(Some(term_id));
+        terminal.debug.active_session.set(Some(dap_id));
+        terminal.focus_terminal(term_id);
+        true
+    })
+    .style(move |s| {
+        s.padding_vert_px(6.0)
+            .width_pct(100.0)
+            .items_center()
+            .apply_if(is_active(), |s| {
+                s.background(
+                    *config.get().get_color(LapceColor::PANEL_CURRENT_BACKGROUND),
+                )
+            })
+    })
+    .hover_style(move |s| {
+        s.cursor(CursorStyle::Pointer).background(
+            (*config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND))
+                .with_alpha_factor(0.3),
+        )
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 fn debug_stack_frames(
+    terminal: TerminalPanelData,
+    dap_id: DapId,
     thread_id: ThreadId,
     stack_trace: StackTraceData,
     stopped: RwSignal<bool>,
+    active_frame: RwSignal<Option<usize>>,
     internal_command: Listener<InternalCommand>,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
@@ -320,6 +600,9 @@ fn debug_stack_frames(
                 },
                 |frame| frame.id,
                 move |frame| {
+                    let terminal = terminal.clone();
+                    let frame_id = frame.id;
+                    let is_active = move || active_frame.get() == Some(frame_id);
                     let full_path =
                         frame.source.as_ref().and_then(|s| s.path.clone());
                     let line = frame.line.saturating_sub(1);
@@ -360,6 +643,7 @@ fn debug_stack_frames(
                         })
                     })
                     .on_click(move |_| {
+                        terminal.dap_select_frame(dap_id, frame_id);
                         if let Some(path) = full_path.clone() {
                             internal_command.send(InternalCommand::JumpToLocation {
                                 location: EditorLocation {
@@ -387,6 +671,13 @@ fn debug_stack_frames(
                                     *config.get().get_color(LapceColor::EDITOR_DIM),
                                 )
                             })
+                            .apply_if(is_active(), |s| {
+                                s.background(
+                                    *config.get().get_color(
+                                        LapceColor::PANEL_CURRENT_BACKGROUND,
+                                    ),
+                                )
+                            })
                     })
                     .hover_style(move |s| {
                         s.background(
@@ -430,23 +721,34 @@ fn debug_stack_traces(
                         let mut traces = stack_traces
                             .into_iter()
                             .map(|(thread_id, stack_trace)| {
-                                (dap.dap_id, dap.stopped, thread_id, stack_trace)
+                                (
+                                    dap.dap_id,
+                                    dap.stopped,
+                                    dap.active_frame,
+                                    thread_id,
+                                    stack_trace,
+                                )
                             })
                             .collect::<Vec<_>>();
-                        traces.sort_by_key(|(_, _, id, _)| main_thread != Some(*id));
+                        traces.sort_by_key(|(_, _, _, id, _)| {
+                            main_thread != Some(*id)
+                        });
                         traces
                     } else {
                         Vec::new()
                     }
                 },
-                |(dap_id, stopped, thread_id, _)| {
+                |(dap_id, stopped, _, thread_id, _)| {
                     (*dap_id, *thread_id, stopped.get_untracked())
                 },
-                move |(_, stopped, thread_id, stack_trace)| {
+                move |(dap_id, stopped, active_frame, thread_id, stack_trace)| {
                     debug_stack_frames(
+                        terminal.clone(),
+                        dap_id,
                         thread_id,
                         stack_trace,
                         stopped,
+                        active_frame,
                         internal_command,
                         config,
                     )
@@ -463,3 +765,1007 @@ fn debug_stack_traces(
             .flex_basis_px(0.0)
     })
 }
+
+fn debug_variables(
+    terminal: TerminalPanelData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    container(move || {
+        scroll(move || {
+            let local_terminal = terminal.clone();
+            list(
+                move || {
+                    let dap = local_terminal.get_active_dap(true);
+                    match dap {
+                        Some(dap) if dap.stopped.get() => dap
+                            .scopes
+                            .get()
+                            .into_iter()
+                            .map(|scope| (dap.dap_id, scope))
+                            .collect::<Vec<_>>(),
+                        _ => Vec::new(),
+                    }
+                },
+                |(_, scope)| scope.id,
+                move |(dap_id, scope)| {
+                    debug_variable(terminal.clone(), dap_id, scope, 0, config)
+                },
+            )
+            .style(|s| s.flex_col().min_width_pct(100.0))
+        })
+        .style(|s| s.absolute().size_pct(100.0, 100.0))
+    })
+    .style(|s| {
+        s.width_pct(100.0)
+            .line_height(1.6)
+            .flex_grow(1.0)
+            .flex_basis_px(0.0)
+    })
+}
+
+/// A scope or variable, with its children below it when it's expanded
+fn debug_variable(
+    terminal: TerminalPanelData,
+    dap_id: DapId,
+    variable: DapVariable,
+    level: usize,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debug = terminal.debug.clone();
+    let focus = terminal.common.focus;
+    let expanded = variable.expanded;
+    let children = variable.children;
+    let has_children = variable.has_children();
+    let variable_id = variable.id;
+    let editing_variable = debug.editing_variable;
+    let is_editing = move || {
+        editing_variable.with(|v| v.as_ref().map(|v| v.id) == Some(variable_id))
+    };
+    let name = variable.name.clone();
+    let is_scope = variable.value.is_none();
+    let value = variable.value.clone().unwrap_or_default();
+    let ty = variable.ty.clone().unwrap_or_default();
+    let click_variable = variable.clone();
+    let double_click_variable = variable;
+    let click_terminal = terminal.clone();
+    let double_click_debug = debug.clone();
+    let dap_capabilities = terminal.common.dap_capabilities;
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    svg(move || {
+                        let svg_str = match expanded.get() {
+                            true => LapceIcons::ITEM_OPENED,
+                            false => LapceIcons::ITEM_CLOSED,
+                        };
+                        config.get().ui_svg(svg_str)
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        let size = config.ui.icon_size() as f32;
+                        let color = if has_children {
+                            *config.get_color(LapceColor::LAPCE_ICON_ACTIVE)
+                        } else {
+                            Color::TRANSPARENT
+                        };
+                        s.size_px(size, size).margin_right_px(6.0).color(color)
+                    }),
+                    label(move || {
+                        if is_scope {
+                            name.clone()
+                        } else {
+                            format!("{name}:")
+                        }
+                    }),
+                    label(move || value.clone()).style(move |s| {
+                        s.margin_left_px(6.0)
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                            .apply_if(is_scope || is_editing(), |s| s.hide())
+                    }),
+                    list(
+                        move || {
+                            if is_editing() {
+                                vec![variable_id]
+                            } else {
+                                Vec::new()
+                            }
+                        },
+                        |id| *id,
+                        move |_| {
+                            text_input(debug.variable_editor.clone(), move || {
+                                focus.get() == Focus::Panel(PanelKind::Debug)
+                            })
+                            .style(move |s| {
+                                let config = config.get();
+                                s.margin_left_px(6.0)
+                                    .padding_horiz_px(4.0)
+                                    .min_width_px(100.0)
+                                    .background(
+                                        *config.get_color(
+                                            LapceColor::EDITOR_BACKGROUND,
+                                        ),
+                                    )
+                                    .border(1.0)
+                                    .border_color(
+                                        *config.get_color(LapceColor::LAPCE_BORDER),
+                                    )
+                            })
+                        },
+                    ),
+                    label(move || ty.clone()).style(move |s| {
+                        s.margin_left_px(10.0)
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                            .font_style(FontStyle::Italic)
+                            .apply_if(is_scope || is_editing(), |s| s.hide())
+                    }),
+                )
+            })
+            .on_click(move |_| {
+                if has_children {
+                    click_variable
+                        .toggle_expanded(&click_terminal.common.proxy, dap_id);
+                }
+                true
+            })
+            .on_double_click(move |_| {
+                if dap_capabilities.with_untracked(|capabilities| {
+                    double_click_variable.can_set_value(capabilities.get(&dap_id))
+                }) {
+                    double_click_debug
+                        .start_editing_variable(double_click_variable.clone());
+                    focus.set(Focus::Panel(PanelKind::Debug));
+                }
+                true
+            })
+            .style(move |s| {
+                s.items_center()
+                    .padding_left_px((level * 10 + 10) as f32)
+                    .padding_right_px(10.0)
+                    .min_width_pct(100.0)
+            })
+            .hover_style(move |s| {
+                s.background(
+                    *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+                .apply_if(has_children, |s| s.cursor(CursorStyle::Pointer))
+            }),
+            list(
+                move || {
+                    if expanded.get() {
+                        children.get().unwrap_or_default()
+                    } else {
+                        im::Vector::new()
+                    }
+                },
+                |variable| variable.id,
+                move |variable| {
+                    let terminal = terminal.clone();
+                    container_box(move || {
+                        Box::new(debug_variable(
+                            terminal,
+                            dap_id,
+                            variable,
+                            level + 1,
+                            config,
+                        ))
+                    })
+                },
+            )
+            .style(|s| s.flex_col().min_width_pct(100.0)),
+        )
+    })
+    .style(|s| s.flex_col().min_width_pct(100.0))
+}
+
+fn debug_watches(
+    terminal: TerminalPanelData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debug = terminal.debug.clone();
+    let focus = terminal.common.focus;
+    let watches = debug.watches;
+    let editing_variable = debug.editing_variable;
+    let editing_breakpoint = debug.editing_breakpoint;
+    let active_input = debug.active_input;
+    let watch_editor = debug.watch_editor.clone();
+    let is_focused = move || {
+        focus.get() == Focus::Panel(PanelKind::Debug)
+            && editing_variable.with(|v| v.is_none())
+            && editing_breakpoint.with(|b| b.is_none())
+            && active_input.get() == DebugInput::Watch
+    };
+
+    stack(move || {
+        (
+            container(move || {
+                scroll(move || {
+                    list(
+                        move || {
+                            watches
+                                .get()
+                                .into_iter()
+                                .map(|w| (w.expression, w.value.get()))
+                                .collect::<Vec<_>>()
+                        },
+                        |(expression, value)| {
+                            let value = match value {
+                                Some(Ok(variable)) => Ok(Some(variable.id)),
+                                Some(Err(err)) => Err(err.clone()),
+                                None => Ok(None),
+                            };
+                            (expression.clone(), value)
+                        },
+                        move |(expression, value)| {
+                            debug_watch(terminal.clone(), expression, value, config)
+                        },
+                    )
+                    .style(|s| s.flex_col().min_width_pct(100.0))
+                })
+                .style(|s| s.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|s| {
+                s.width_pct(100.0)
+                    .line_height(1.6)
+                    .flex_grow(1.0)
+                    .flex_basis_px(0.0)
+            }),
+            container(|| {
+                text_input(watch_editor, is_focused)
+                    .on_event(EventListener::PointerDown, move |_| {
+                        editing_variable.set(None);
+                        editing_breakpoint.set(None);
+                        active_input.set(DebugInput::Watch);
+                        focus.set(Focus::Panel(PanelKind::Debug));
+                        false
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        s.width_pct(100.0)
+                            .padding_vert_px(4.0)
+                            .padding_horiz_px(10.0)
+                            .cursor(CursorStyle::Text)
+                            .background(
+                                *config.get_color(LapceColor::EDITOR_BACKGROUND),
+                            )
+                            .border(1.0)
+                            .border_radius(6.0)
+                            .border_color(
+                                *config.get_color(LapceColor::LAPCE_BORDER),
+                            )
+                    })
+            })
+            .style(|s| s.padding_px(10.0).width_pct(100.0)),
+        )
+    })
+    .style(|s| s.flex_col().size_pct(100.0, 100.0))
+}
+
+fn debug_memory(
+    terminal: TerminalPanelData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debug = terminal.debug.clone();
+    let focus = terminal.common.focus;
+    let memory = debug.memory;
+    let editing_variable = debug.editing_variable;
+    let editing_breakpoint = debug.editing_breakpoint;
+    let active_input = debug.active_input;
+    let memory_editor = debug.memory_editor.clone();
+    let is_focused = move || {
+        focus.get() == Focus::Panel(PanelKind::Debug)
+            && editing_variable.with(|v| v.is_none())
+            && editing_breakpoint.with(|b| b.is_none())
+            && active_input.get() == DebugInput::Memory
+    };
+
+    stack(move || {
+        (
+            container(|| {
+                text_input(memory_editor, is_focused)
+                    .placeholder(|| "Memory reference".to_string())
+                    .on_event(EventListener::PointerDown, move |_| {
+                        editing_variable.set(None);
+                        editing_breakpoint.set(None);
+                        active_input.set(DebugInput::Memory);
+                        focus.set(Focus::Panel(PanelKind::Debug));
+                        false
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        s.width_pct(100.0)
+                            .padding_vert_px(4.0)
+                            .padding_horiz_px(10.0)
+                            .cursor(CursorStyle::Text)
+                            .background(
+                                *config.get_color(LapceColor::EDITOR_BACKGROUND),
+                            )
+                            .border(1.0)
+                            .border_radius(6.0)
+                            .border_color(
+                                *config.get_color(LapceColor::LAPCE_BORDER),
+                            )
+                    })
+            })
+            .style(|s| s.padding_px(10.0).width_pct(100.0)),
+            container(move || {
+                scroll(move || {
+                    stack(move || {
+                        (
+                            label(move || match memory.get() {
+                                Some(Err(err)) => err,
+                                _ => String::new(),
+                            })
+                            .style(move |s| {
+                                s.padding_horiz_px(10.0)
+                                    .color(
+                                        *config
+                                            .get()
+                                            .get_color(LapceColor::EDITOR_DIM),
+                                    )
+                                    .apply_if(
+                                        !memory.with(|m| matches!(m, Some(Err(_)))),
+                                        |s| s.hide(),
+                                    )
+                            }),
+                            list(
+                                move || match memory.get() {
+                                    Some(Ok(dump)) => dump.rows(),
+                                    _ => Vec::new(),
+                                },
+                                |row| row.clone(),
+                                move |(address, hex, ascii)| {
+                                    hex_row(address, hex, ascii, config)
+                                },
+                            )
+                            .style(|s| s.flex_col().min_width_pct(100.0)),
+                        )
+                    })
+                    .style(|s| s.flex_col().min_width_pct(100.0))
+                })
+                .style(|s| s.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|s| {
+                s.width_pct(100.0)
+                    .line_height(1.6)
+                    .flex_grow(1.0)
+                    .flex_basis_px(0.0)
+            }),
+        )
+    })
+    .style(|s| s.flex_col().size_pct(100.0, 100.0))
+}
+
+/// A row of the hex layout, with the address, the bytes in hex and the bytes
+/// as ASCII, in the editor font so that the columns line up.
+fn hex_row(
+    address: String,
+    hex: String,
+    ascii: String,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    stack(|| {
+        (
+            label(move || address.clone()).style(move |s| {
+                s.margin_right_px(12.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+            label(move || hex.clone()).style(|s| s.margin_right_px(12.0)),
+            label(move || ascii.clone()),
+        )
+    })
+    .style(move |s| {
+        let config = config.get();
+        s.padding_horiz_px(10.0)
+            .font_family(config.editor.font_family.clone())
+            .font_size(config.editor.font_size() as f32)
+    })
+}
+
+fn debug_watch(
+    terminal: TerminalPanelData,
+    expression: String,
+    value: Option<Result<DapVariable, String>>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debug = terminal.debug.clone();
+    let paused_frame = terminal.common.paused_frame.get_untracked();
+    let watch = match (value, paused_frame) {
+        (Some(Ok(variable)), Some((dap_id, _))) => container_box(move || {
+            Box::new(debug_variable(terminal, dap_id, variable, 0, config))
+        }),
+        (value, _) => {
+            let expression = expression.clone();
+            let message = match value {
+                Some(Err(err)) => err,
+                _ => "not available".to_string(),
+            };
+            container_box(move || {
+                Box::new(
+                    stack(|| {
+                        (
+                            label(move || format!("{expression}:")),
+                            label(move || message.clone()).style(move |s| {
+                                s.margin_left_px(6.0).color(
+                                    *config.get().get_color(LapceColor::EDITOR_DIM),
+                                )
+                            }),
+                        )
+                    })
+                    .style(|s| s.padding_left_px(26.0)),
+                )
+            })
+        }
+    };
+
+    stack(|| {
+        (
+            watch.style(|s| s.flex_grow(1.0).flex_basis_px(0.0).min_width_px(0.0)),
+            clickable_icon(
+                || LapceIcons::CLOSE,
+                move || {
+                    debug.remove_watch(&expression);
+                },
+                || false,
+                || false,
+                config,
+            )
+            .style(|s| s.margin_right_px(6.0)),
+        )
+    })
+    .style(|s| s.min_width_pct(100.0))
+}
+
+fn debug_console(
+    terminal: TerminalPanelData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debug = terminal.debug.clone();
+    let focus = terminal.common.focus;
+    let editing_variable = debug.editing_variable;
+    let editing_breakpoint = debug.editing_breakpoint;
+    let active_input = debug.active_input;
+    let console_editor = debug.console_editor.clone();
+    let completions = debug.console_completions;
+    let completion_index = debug.console_completion_index;
+    let is_focused = move || {
+        focus.get() == Focus::Panel(PanelKind::Debug)
+            && editing_variable.with(|v| v.is_none())
+            && editing_breakpoint.with(|b| b.is_none())
+            && active_input.get() == DebugInput::Console
+    };
+    let pre_launch_output = debug.pre_launch_output;
+    let content_height = terminal.cx.create_rw_signal(0.0);
+    let local_terminal = terminal.clone();
+
+    stack(move || {
+        (
+            container(move || {
+                scroll(move || {
+                    list(
+                        move || {
+                            let dap = local_terminal.get_active_dap(true);
+                            let mut entries = match dap {
+                                Some(dap) => dap
+                                    .console
+                                    .get()
+                                    .into_iter()
+                                    .map(|entry| (dap.dap_id, entry))
+                                    .collect::<Vec<_>>(),
+                                None => Vec::new(),
+                            };
+                            // The sessions whose pre-launch command is running
+                            pre_launch_output.with(|pre_launch_output| {
+                                for (dap_id, output) in pre_launch_output {
+                                    entries.extend(
+                                        output
+                                            .iter()
+                                            .map(|entry| (*dap_id, entry.clone())),
+                                    );
+                                }
+                            });
+                            entries
+                        },
+                        |(_, entry)| (entry.id, entry.text.len()),
+                        move |(dap_id, entry)| {
+                            debug_console_entry(
+                                terminal.clone(),
+                                dap_id,
+                                entry,
+                                config,
+                            )
+                        },
+                    )
+                    .on_resize(move |rect| {
+                        content_height.set(rect.height());
+                    })
+                    .style(|s| s.flex_col().min_width_pct(100.0))
+                })
+                // Follow the output as it comes
+                .on_scroll_to(move || Some(Point::new(0.0, content_height.get())))
+                .style(|s| s.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|s| {
+                s.width_pct(100.0)
+                    .line_height(1.6)
+                    .flex_grow(1.0)
+                    .flex_basis_px(0.0)
+            }),
+            list(
+                move || {
+                    // Only a window of the completions around the selected
+                    // one is shown
+                    let index = completion_index.get();
+                    let start = index.saturating_sub(MAX_SHOWN_COMPLETIONS - 1);
+                    completions.with(|completions| {
+                        completions
+                            .iter()
+                            .enumerate()
+                            .skip(start)
+                            .take(MAX_SHOWN_COMPLETIONS)
+                            .map(|(i, item)| (i, item.label.clone()))
+                            .collect::<Vec<_>>()
+                    })
+                },
+                |(i, text)| (*i, text.clone()),
+                move |(i, text)| {
+                    let debug = debug.clone();
+                    container(move || label(move || text.clone()))
+                        .on_click(move |_| {
+                            debug.apply_console_completion(i);
+                            true
+                        })
+                        .style(move |s| {
+                            let config = config.get();
+                            s.width_pct(100.0)
+                                .padding_horiz_px(10.0)
+                                .cursor(CursorStyle::Pointer)
+                                .apply_if(completion_index.get() == i, |s| {
+                                    s.background(*config.get_color(
+                                        LapceColor::PANEL_CURRENT_BACKGROUND,
+                                    ))
+                                })
+                        })
+                        .hover_style(move |s| {
+                            s.background(
+                                *config
+                                    .get()
+                                    .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                            )
+                        })
+                },
+            )
+            .style(move |s| {
+                s.flex_col()
+                    .width_pct(100.0)
+                    .line_height(1.6)
+                    .border_top(1.0)
+                    .border_color(*config.get().get_color(LapceColor::LAPCE_BORDER))
+                    .apply_if(completions.with(|c| c.is_empty()), |s| s.hide())
+            }),
+            container(|| {
+                text_input(console_editor, is_focused)
+                    .on_event(EventListener::PointerDown, move |_| {
+                        editing_variable.set(None);
+                        editing_breakpoint.set(None);
+                        active_input.set(DebugInput::Console);
+                        focus.set(Focus::Panel(PanelKind::Debug));
+                        false
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        s.width_pct(100.0)
+                            .padding_vert_px(4.0)
+                            .padding_horiz_px(10.0)
+                            .cursor(CursorStyle::Text)
+                            .background(
+                                *config.get_color(LapceColor::EDITOR_BACKGROUND),
+                            )
+                            .border(1.0)
+                            .border_radius(6.0)
+                            .border_color(
+                                *config.get_color(LapceColor::LAPCE_BORDER),
+                            )
+                    })
+            })
+            .style(|s| s.padding_px(10.0).width_pct(100.0)),
+        )
+    })
+    .style(|s| s.flex_col().size_pct(100.0, 100.0))
+}
+
+fn debug_console_entry(
+    terminal: TerminalPanelData,
+    dap_id: DapId,
+    entry: DebugConsoleEntry,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let kind = entry.kind;
+    let view = match entry.variable {
+        Some(variable) => container_box(move || {
+            Box::new(debug_variable(terminal, dap_id, variable, 0, config))
+        }),
+        None => {
+            let text = entry.text.trim_end_matches('\n').to_string();
+            let text = match kind {
+                DebugConsoleKind::Input => format!("> {text}"),
+                _ => text,
+            };
+            container_box(move || {
+                Box::new(label(move || text.clone()).style(move |s| {
+                    let config = config.get();
+                    let color = match kind {
+                        DebugConsoleKind::Stderr | DebugConsoleKind::Error => {
+                            Some(*config.get_color(LapceColor::LAPCE_ERROR))
+                        }
+                        DebugConsoleKind::Important => {
+                            Some(*config.get_color(LapceColor::LAPCE_WARN))
+                        }
+                        DebugConsoleKind::Console => {
+                            Some(*config.get_color(LapceColor::EDITOR_DIM))
+                        }
+                        DebugConsoleKind::Stdout
+                        | DebugConsoleKind::Input
+                        | DebugConsoleKind::Result => None,
+                    };
+                    s.padding_horiz_px(10.0)
+                        .apply_opt(color, |s, color| s.color(color))
+                }))
+            })
+        }
+    };
+    view.style(|s| s.min_width_pct(100.0))
+}
+
+fn debug_breakpoints(
+    terminal: TerminalPanelData,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debug = terminal.debug.clone();
+    let focus = terminal.common.focus;
+    let breakpoints = debug.breakpoints;
+    let function_breakpoints = debug.function_breakpoints;
+    let editing_variable = debug.editing_variable;
+    let editing_breakpoint = debug.editing_breakpoint;
+    let active_input = debug.active_input;
+    let function_breakpoint_editor = debug.function_breakpoint_editor.clone();
+    let is_focused = move || {
+        focus.get() == Focus::Panel(PanelKind::Debug)
+            && editing_variable.with(|v| v.is_none())
+            && editing_breakpoint.with(|b| b.is_none())
+            && active_input.get() == DebugInput::FunctionBreakpoint
+    };
+
+    stack(move || {
+        (
+            container(move || {
+                scroll(move || {
+                    stack(move || {
+                        (
+                            {
+                                let debug = debug.clone();
+                                let terminal = terminal.clone();
+                                list(
+                                    move || debug.exception_breakpoint_filters(),
+                                    |filter| filter.filter.clone(),
+                                    move |filter| {
+                                        debug_exception_filter(
+                                            terminal.clone(),
+                                            filter,
+                                            config,
+                                        )
+                                    },
+                                )
+                                .style(|s| s.flex_col().min_width_pct(100.0))
+                            },
+                            {
+                                let debug = debug.clone();
+                                list(
+                                    move || function_breakpoints.get(),
+                                    |name| name.clone(),
+                                    move |name| {
+                                        debug_function_breakpoint(
+                                            debug.clone(),
+                                            name,
+                                            config,
+                                        )
+                                    },
+                                )
+                                .style(|s| s.flex_col().min_width_pct(100.0))
+                            },
+                            list(
+                                move || {
+                                    breakpoints.with(|breakpoints| {
+                                        breakpoints
+                                            .iter()
+                                            .flat_map(|(path, breakpoints)| {
+                                                breakpoints
+                                                    .iter()
+                                                    .map(|b| {
+                                                        (path.clone(), b.clone())
+                                                    })
+                                                    .collect::<Vec<_>>()
+                                            })
+                                            .collect::<Vec<_>>()
+                                    })
+                                },
+                                |(path, breakpoint)| {
+                                    (
+                                        path.clone(),
+                                        breakpoint.line,
+                                        breakpoint.condition.clone(),
+                                        breakpoint.hit_condition.clone(),
+                                        breakpoint.log_message.clone(),
+                                    )
+                                },
+                                move |(path, breakpoint)| {
+                                    debug_breakpoint(
+                                        terminal.clone(),
+                                        path,
+                                        breakpoint,
+                                        internal_command,
+                                        config,
+                                    )
+                                },
+                            )
+                            .style(|s| s.flex_col().min_width_pct(100.0)),
+                        )
+                    })
+                    .style(|s| s.flex_col().min_width_pct(100.0))
+                })
+                .style(|s| s.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|s| {
+                s.width_pct(100.0)
+                    .line_height(1.6)
+                    .flex_grow(1.0)
+                    .flex_basis_px(0.0)
+            }),
+            container(|| {
+                text_input(function_breakpoint_editor, is_focused)
+                    .on_event(EventListener::PointerDown, move |_| {
+                        editing_variable.set(None);
+                        editing_breakpoint.set(None);
+                        active_input.set(DebugInput::FunctionBreakpoint);
+                        focus.set(Focus::Panel(PanelKind::Debug));
+                        false
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        s.width_pct(100.0)
+                            .padding_vert_px(4.0)
+                            .padding_horiz_px(10.0)
+                            .cursor(CursorStyle::Text)
+                            .background(
+                                *config.get_color(LapceColor::EDITOR_BACKGROUND),
+                            )
+                            .border(1.0)
+                            .border_radius(6.0)
+                            .border_color(
+                                *config.get_color(LapceColor::LAPCE_BORDER),
+                            )
+                    })
+            })
+            .style(|s| s.padding_px(10.0).width_pct(100.0)),
+        )
+    })
+    .style(|s| s.flex_col().size_pct(100.0, 100.0))
+}
+
+/// An exception filter of the debug adapters, which is turned on or off by
+/// clicking it
+fn debug_exception_filter(
+    terminal: TerminalPanelData,
+    filter: ExceptionBreakpointsFilter,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debug = terminal.debug;
+    let label_text = filter.label.clone();
+    let description = filter.description.clone().unwrap_or_default();
+    let checked = {
+        let debug = debug.clone();
+        let filter = filter.clone();
+        move || debug.is_exception_filter_enabled(&filter)
+    };
+
+    stack(|| {
+        (
+            checkbox(checked, config).style(|s| s.margin_right_px(6.0)),
+            label(move || label_text.clone()),
+            label(move || description.clone()).style(move |s| {
+                s.margin_left_px(10.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+        )
+    })
+    .on_click(move |_| {
+        debug.toggle_exception_filter(&filter);
+        true
+    })
+    .style(|s| {
+        s.items_center()
+            .padding_left_px(10.0)
+            .padding_right_px(6.0)
+            .min_width_pct(100.0)
+    })
+    .hover_style(move |s| {
+        s.cursor(CursorStyle::Pointer).background(
+            *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+        )
+    })
+}
+
+fn debug_function_breakpoint(
+    debug: RunDebugData,
+    name: String,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let label_text = name.clone();
+
+    stack(|| {
+        (
+            svg(move || config.get().ui_svg(LapceIcons::SYMBOL_KIND_FUNCTION))
+                .style(move |s| {
+                    let config = config.get();
+                    let size = config.ui.icon_size() as f32;
+                    s.size_px(size, size)
+                        .margin_right_px(6.0)
+                        .color(*config.get_color(LapceColor::DEBUG_BREAKPOINT))
+                }),
+            label(move || label_text.clone()),
+            empty().style(|s| s.flex_grow(1.0)),
+            clickable_icon(
+                || LapceIcons::CLOSE,
+                move || {
+                    debug.remove_function_breakpoint(&name);
+                },
+                || false,
+                || false,
+                config,
+            ),
+        )
+    })
+    .style(|s| {
+        s.items_center()
+            .padding_left_px(10.0)
+            .padding_right_px(6.0)
+            .min_width_pct(100.0)
+    })
+}
+
+/// A breakpoint with what's set on it, and the input to change it below it
+/// while it's edited
+fn debug_breakpoint(
+    terminal: TerminalPanelData,
+    path: PathBuf,
+    breakpoint: LapceBreakpoint,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debug = terminal.debug.clone();
+    let focus = terminal.common.focus;
+    let editing_breakpoint = debug.editing_breakpoint;
+    let line = breakpoint.line;
+    let icon = breakpoint.icon();
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+    let details = [
+        BreakpointField::Condition,
+        BreakpointField::HitCondition,
+        BreakpointField::LogMessage,
+    ]
+    .into_iter()
+    .filter_map(|field| {
+        breakpoint
+            .field(field)
+            .map(|value| format!("{}: {value}", field.title()))
+    })
+    .collect::<Vec<_>>()
+    .join(", ");
+    let editing_path = path.clone();
+    let editing_field = move || {
+        editing_breakpoint.with(|b| {
+            b.as_ref()
+                .filter(|(path, l, _)| path == &editing_path && *l == line)
+                .map(|(_, _, field)| *field)
+        })
+    };
+    let click_path = path.clone();
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    svg(move || config.get().ui_svg(icon)).style(move |s| {
+                        let config = config.get();
+                        let size = config.ui.icon_size() as f32;
+                        s.size_px(size, size)
+                            .margin_right_px(6.0)
+                            .color(*config.get_color(LapceColor::DEBUG_BREAKPOINT))
+                    }),
+                    label(move || format!("{file_name}:{}", line + 1)),
+                    label(move || details.clone()).style(move |s| {
+                        s.margin_left_px(10.0)
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                    }),
+                    empty().style(|s| s.flex_grow(1.0)),
+                    clickable_icon(
+                        || LapceIcons::CLOSE,
+                        move || {
+                            debug.remove_breakpoint(path.clone(), line);
+                        },
+                        || false,
+                        || false,
+                        config,
+                    ),
+                )
+            })
+            .on_click(move |_| {
+                internal_command.send(InternalCommand::JumpToLocation {
+                    location: EditorLocation {
+                        path: click_path.clone(),
+                        position: Some(EditorPosition::Line(line)),
+                        scroll_offset: None,
+                        ignore_unconfirmed: false,
+                        same_editor_tab: false,
+                    },
+                });
+                true
+            })
+            .style(move |s| {
+                s.items_center()
+                    .padding_left_px(10.0)
+                    .padding_right_px(6.0)
+                    .min_width_pct(100.0)
+            })
+            .hover_style(move |s| {
+                s.cursor(CursorStyle::Pointer).background(
+                    *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            }),
+            list(
+                move || editing_field().into_iter().collect::<Vec<_>>(),
+                |field| *field,
+                move |field| {
+                    let debug = terminal.debug.clone();
+                    stack(|| {
+                        (
+                            label(move || format!("{}:", field.title())),
+                            text_input(debug.breakpoint_editor.clone(), move || {
+                                focus.get() == Focus::Panel(PanelKind::Debug)
+                            })
+                            .style(move |s| {
+                                let config = config.get();
+                                s.flex_grow(1.0)
+                                    .margin_left_px(6.0)
+                                    .padding_horiz_px(4.0)
+                                    .background(
+                                        *config.get_color(
+                                            LapceColor::EDITOR_BACKGROUND,
+                                        ),
+                                    )
+                                    .border(1.0)
+                                    .border_color(
+                                        *config.get_color(LapceColor::LAPCE_BORDER),
+                                    )
+                            }),
+                        )
+                    })
+                    .style(|s| {
+                        s.items_center()
+                            .padding_left_px(30.0)
+                            .padding_right_px(10.0)
+                            .width_pct(100.0)
+                    })
+                },
+            )
+            .style(|s| s.flex_col().min_width_pct(100.0)),
+        )
+    })
+    .style(|s| s.flex_col().min_width_pct(100.0))
+}
@@ -1,11 +1,11 @@
-use std::{path::PathBuf, rc::Rc};
+use std::{path::PathBuf, rc::Rc, sync::Arc};
 
 use floem::{
     action::show_context_menu,
     event::{Event, EventListener},
     menu::{Menu, MenuItem},
     peniko::kurbo::{Point, Rect, Size},
-    reactive::{create_memo, create_rw_signal},
+    reactive::{create_memo, create_rw_signal, ReadSignal},
     style::{CursorStyle, Style},
     view::View,
     views::{container, label, list, scroll, stack, svg, Decorators},
@@ -13,12 +13,12 @@
 use lapce_core::buffer::rope_text::RopeText;
 use lapce_rpc::source_control::FileDiff;
 
-use super::{kind::PanelKind, position::PanelPosition, view::panel_header};
+use super::{kind::PanelKind, position::PanelPosition};
 use crate::{
+    app::clickable_icon,
     command::{CommandKind, InternalCommand, LapceCommand, LapceWorkbenchCommand},
-    config::{color::LapceColor, icon::LapceIcons},
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
     editor::view::{cursor_caret, editor_view, CursorRender},
-    settings::checkbox,
     source_control::SourceControlData,
     window_tab::{Focus, WindowTabData},
 };
@@ -201,13 +201,53 @@ pub fn source_control_panel(
                 )
             })
             .style(|s| s.flex_col().width_pct(100.0).padding_px(10.0)),
+            {
+                let source_control = source_control.clone();
+                let staged_diffs = source_control.staged_diffs;
+                stack(|| {
+                    (
+                        file_diffs_header(
+                            "Staged Changes".to_string(),
+                            LapceIcons::SCM_CHANGE_REMOVE,
+                            {
+                                let source_control = source_control.clone();
+                                move || source_control.unstage_all()
+                            },
+                            config,
+                        ),
+                        file_diffs_view(source_control, true),
+                    )
+                })
+                .style(move |s| {
+                    s.flex_col()
+                        .width_pct(100.0)
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .apply_if(staged_diffs.with(|diffs| diffs.is_empty()), |s| {
+                            s.hide()
+                        })
+                })
+            },
             stack(|| {
                 (
-                    panel_header("Changes".to_string(), config),
-                    file_diffs_view(source_control),
+                    file_diffs_header(
+                        "Changes".to_string(),
+                        LapceIcons::SCM_CHANGE_ADD,
+                        {
+                            let source_control = source_control.clone();
+                            move || source_control.stage_all()
+                        },
+                        config,
+                    ),
+                    file_diffs_view(source_control, false),
                 )
             })
-            .style(|s| s.flex_col().size_pct(100.0, 100.0)),
+            .style(|s| {
+                s.flex_col()
+                    .width_pct(100.0)
+                    .flex_grow(1.0)
+                    .flex_basis_px(0.0)
+            }),
         )
     })
     .on_event(EventListener::PointerDown, move |_| {
@@ -219,8 +259,38 @@ pub fn source_control_panel(
     .style(|s| s.flex_col().size_pct(100.0, 100.0))
 }
 
-fn file_diffs_view(source_control: SourceControlData) -> impl View {
-    let file_diffs = source_control.file_diffs;
+/// The header of the staged or unstaged changes, with the icon to stage or
+/// unstage them all
+fn file_diffs_header(
+    header: String,
+    icon: &'static str,
+    on_click: impl Fn() + 'static,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    stack(|| {
+        (
+            label(move || header.clone())
+                .style(|s| s.flex_grow(1.0).min_width_px(0.0).text_ellipsis()),
+            clickable_icon(move || icon, on_click, || false, || false, config),
+        )
+    })
+    .style(move |s| {
+        s.padding_left_px(10.0)
+            .padding_right_px(6.0)
+            .padding_vert_px(2.0)
+            .width_pct(100.0)
+            .items_center()
+            .background(*config.get().get_color(LapceColor::EDITOR_BACKGROUND))
+    })
+}
+
+/// The list of the staged changes, or of the changes that aren't staged
+fn file_diffs_view(source_control: SourceControlData, staged: bool) -> impl View {
+    let file_diffs = if staged {
+        source_control.staged_diffs
+    } else {
+        source_control.file_diffs
+    };
     let config = source_control.common.config;
     let workspace = source_control.common.workspace.clone();
     let panel_rect = create_rw_signal(Rect::ZERO);
@@ -228,11 +298,14 @@ fn file_diffs_view(source_control: SourceControlData) -> impl View {
     let lapce_command = source_control.common.lapce_command;
     let internal_command = source_control.common.internal_command;
 
-    let view_fn = move |(path, (diff, checked)): (PathBuf, (FileDiff, bool))| {
+    let view_fn = move |(path, diff): (PathBuf, FileDiff)| {
         let diff_for_style = diff.clone();
         let full_path = path.clone();
         let diff_for_menu = diff.clone();
+        let diff_for_stage = diff.clone();
         let path_for_click = full_path.clone();
+        let source_control = source_control.clone();
+        let source_control_for_menu = source_control.clone();
 
         let path = if let Some(workspace_path) = workspace.path.as_ref() {
             path.strip_prefix(workspace_path)
@@ -254,16 +327,6 @@ fn file_diffs_view(source_control: SourceControlData) -> impl View {
         let style_path = path.clone();
         stack(|| {
             (
-                checkbox(move || checked, config)
-                    .on_click(move |_| {
-                        file_diffs.update(|diffs| {
-                            if let Some((_, checked)) = diffs.get_mut(&full_path) {
-                                *checked = !*checked;
-                            }
-                        });
-                        true
-                    })
-                    .hover_style(|s| s.cursor(CursorStyle::Pointer)),
                 svg(move || config.get().file_svg(&path).0).style(move |s| {
                     let config = config.get();
                     let size = config.ui.icon_size() as f32;
@@ -280,11 +343,12 @@ fn file_diffs_view(source_control: SourceControlData) -> impl View {
                         - 10.0
                         - size
                         - 6.0
-                        - size
                         - 6.0
                         - 10.0
                         - size
-                        - 6.0;
+                        - 6.0
+                        - size
+                        - 16.0;
                     s.text_ellipsis()
                         .margin_right_px(6.0)
                         .max_width_px(max_width)
@@ -296,6 +360,26 @@ fn file_diffs_view(source_control: SourceControlData) -> impl View {
                         .color(*config.get().get_color(LapceColor::EDITOR_DIM))
                         .min_width_px(0.0)
                 }),
+                clickable_icon(
+                    move || {
+                        if staged {
+                            LapceIcons::SCM_CHANGE_REMOVE
+                        } else {
+                            LapceIcons::SCM_CHANGE_ADD
+                        }
+                    },
+                    move || {
+                        if staged {
+                            source_control.unstage(&diff_for_stage);
+                        } else {
+                            source_control.stage(&diff_for_stage);
+                        }
+                    },
+                    || false,
+                    || false,
+                    config,
+                )
+                .style(|s| s.margin_right_px(4.0)),
                 container(|| {
                     svg(move || {
                         let svg = match &diff {
@@ -341,21 +425,32 @@ fn file_diffs_view(source_control: SourceControlData) -> impl View {
             true
         })
         .on_event(EventListener::PointerDown, move |event| {
-            let diff_for_menu = diff_for_menu.clone();
-
-            let discard = move || {
-                lapce_command.send(LapceCommand {
-                    kind: CommandKind::Workbench(
-                        LapceWorkbenchCommand::SourceControlDiscardTargetFileChanges,
-                    ),
-                    data: Some(serde_json::json!(diff_for_menu.clone())),
-                });
-            };
-
             if let Event::PointerDown(pointer_event) = event {
                 if pointer_event.button.is_secondary() {
-                    let menu = Menu::new("")
-                        .entry(MenuItem::new("Discard Changes").action(discard));
+                    let menu = if staged {
+                        let source_control = source_control_for_menu.clone();
+                        let diff = diff_for_menu.clone();
+                        Menu::new("").entry(
+                            MenuItem::new("Unstage Changes")
+                                .action(move || source_control.unstage(&diff)),
+                        )
+                    } else {
+                        let source_control = source_control_for_menu.clone();
+                        let diff = diff_for_menu.clone();
+                        let stage = move || source_control.stage(&diff);
+                        let diff = diff_for_menu.clone();
+                        let discard = move || {
+                            lapce_command.send(LapceCommand {
+                                kind: CommandKind::Workbench(
+                                    LapceWorkbenchCommand::SourceControlDiscardTargetFileChanges,
+                                ),
+                                data: Some(serde_json::json!(diff.clone())),
+                            });
+                        };
+                        Menu::new("")
+                            .entry(MenuItem::new("Stage Changes").action(stage))
+                            .entry(MenuItem::new("Discard Changes").action(discard))
+                    };
                     show_context_menu(menu, None);
                 }
             }
@@ -380,9 +475,7 @@ fn file_diffs_view(source_control: SourceControlData) -> impl View {
         scroll(|| {
             list(
                 move || file_diffs.get(),
-                |(path, (diff, checked))| {
-                    (path.to_path_buf(), diff.clone(), *checked)
-                },
+                |(path, diff)| (path.to_path_buf(), diff.clone()),
                 view_fn,
             )
             .style(|s| s.line_height(1.6).flex_col().width_pct(100.0))
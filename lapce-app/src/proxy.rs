@@ -4,10 +4,11 @@
 use floem::{ext_event::create_signal_from_channel, reactive::ReadSignal};
 use lapce_proxy::dispatch::Dispatcher;
 use lapce_rpc::{
-    core::{CoreHandler, CoreNotification, CoreRpcHandler},
+    core::{CoreHandler, CoreNotification, CoreRequest, CoreRpcHandler},
     plugin::VoltID,
     proxy::{ProxyRpcHandler, ProxyStatus},
     terminal::TermId,
+    RequestId,
 };
 use lsp_types::Url;
 use tracing::error;
@@ -25,6 +26,7 @@
 
 pub struct Proxy {
     pub tx: Sender<CoreNotification>,
+    pub request_tx: Sender<(RequestId, CoreRequest)>,
     pub term_tx: Sender<(TermId, TermEvent)>,
 }
 
@@ -33,6 +35,9 @@ pub struct ProxyData {
     pub proxy_rpc: ProxyRpcHandler,
     pub core_rpc: CoreRpcHandler,
     pub notification: ReadSignal<Option<CoreNotification>>,
+    /// The requests from the proxy, which are responded to through the
+    /// `core_rpc` once they're handled
+    pub request: ReadSignal<Option<(RequestId, CoreRequest)>>,
 }
 
 impl ProxyData {
@@ -107,10 +112,15 @@ pub fn new_proxy(
     }
 
     let (tx, rx) = crossbeam_channel::unbounded();
+    let (request_tx, request_rx) = crossbeam_channel::unbounded();
     {
         let core_rpc = core_rpc.clone();
         std::thread::spawn(move || {
-            let mut proxy = Proxy { tx, term_tx };
+            let mut proxy = Proxy {
+                tx,
+                request_tx,
+                term_tx,
+            };
             core_rpc.mainloop(&mut proxy);
             core_rpc.notification(CoreNotification::ProxyStatus {
                 status: ProxyStatus::Connected,
@@ -119,11 +129,13 @@ pub fn new_proxy(
     };
 
     let notification = create_signal_from_channel(rx);
+    let request = create_signal_from_channel(request_rx);
 
     ProxyData {
         proxy_rpc,
         core_rpc,
         notification,
+        request,
     }
 }
 
@@ -138,11 +150,8 @@ fn handle_notification(&mut self, rpc: lapce_rpc::core::CoreNotification) {
         let _ = self.tx.send(rpc);
     }
 
-    fn handle_request(
-        &mut self,
-        _id: lapce_rpc::RequestId,
-        _rpc: lapce_rpc::core::CoreRequest,
-    ) {
+    fn handle_request(&mut self, id: RequestId, rpc: CoreRequest) {
+        let _ = self.request_tx.send((id, rpc));
     }
 }
 
@@ -0,0 +1,186 @@
+use floem::{
+    cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout, Weight},
+    peniko::kurbo::Rect,
+    reactive::{RwSignal, Scope},
+};
+use lapce_core::language::LapceLanguage;
+use lsp_types::{
+    Documentation, MarkupKind, ParameterLabel, SignatureHelp, SignatureInformation,
+};
+
+use crate::{
+    config::{color::LapceColor, LapceConfig},
+    id::EditorId,
+    markdown::{from_plaintext, highlight_as_code, parse_markdown, MarkdownContent},
+};
+
+#[derive(Clone)]
+pub struct SignatureData {
+    pub active: RwSignal<bool>,
+    pub request_id: RwSignal<usize>,
+    /// The offset where the signature help was first triggered, which is kept
+    /// while retriggering so the popup doesn't move as the arguments are typed
+    pub offset: RwSignal<usize>,
+    pub editor_id: RwSignal<EditorId>,
+    pub language: RwSignal<LapceLanguage>,
+    pub signature_help: RwSignal<Option<SignatureHelp>>,
+    pub layout_rect: RwSignal<Rect>,
+}
+
+impl SignatureData {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            active: cx.create_rw_signal(false),
+            request_id: cx.create_rw_signal(0),
+            offset: cx.create_rw_signal(0),
+            editor_id: cx.create_rw_signal(EditorId::next()),
+            language: cx.create_rw_signal(LapceLanguage::Plaintext),
+            signature_help: cx.create_rw_signal(None),
+            layout_rect: cx.create_rw_signal(Rect::ZERO),
+        }
+    }
+
+    /// Hide the signature help and ignore the responses still in flight.
+    pub fn cancel(&self) {
+        self.request_id.update(|id| *id += 1);
+        if self.active.get_untracked() {
+            self.active.set(false);
+            self.signature_help.set(None);
+        }
+    }
+
+    /// Show the next or previous signature, wrapping around at the ends. The
+    /// choice is sent back to the language server when retriggering.
+    pub fn cycle_signature(&self, forward: bool) {
+        self.signature_help.update(|help| {
+            if let Some(help) = help.as_mut() {
+                let len = help.signatures.len();
+                if len == 0 {
+                    return;
+                }
+                let current = active_signature_index(help);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                help.active_signature = Some(next as u32);
+            }
+        });
+    }
+}
+
+/// The index of the signature to show, as the server's choice may be out of
+/// bounds.
+pub fn active_signature_index(help: &SignatureHelp) -> usize {
+    (help.active_signature.unwrap_or(0) as usize)
+        .min(help.signatures.len().saturating_sub(1))
+}
+
+/// The byte range of the active parameter in the label of the signature.
+fn active_parameter_range(
+    help: &SignatureHelp,
+    signature: &SignatureInformation,
+) -> Option<(usize, usize)> {
+    let index = signature.active_parameter.or(help.active_parameter)? as usize;
+    let parameter = signature.parameters.as_ref()?.get(index)?;
+    let label = &signature.label;
+    match &parameter.label {
+        ParameterLabel::Simple(name) => {
+            // Skip the function name, which could contain the parameter name
+            let search_start = label.find('(').unwrap_or(0);
+            let start = label[search_start..].find(name.as_str())? + search_start;
+            Some((start, start + name.len()))
+        }
+        ParameterLabel::LabelOffsets([start, end]) => {
+            // The offsets are in UTF-16 code units
+            let mut utf16_offset = 0;
+            let mut range = (None, None);
+            for (i, c) in label.char_indices() {
+                if utf16_offset == *start as usize {
+                    range.0 = Some(i);
+                }
+                if utf16_offset == *end as usize {
+                    range.1 = Some(i);
+                }
+                utf16_offset += c.len_utf16();
+            }
+            if utf16_offset == *end as usize {
+                range.1 = Some(label.len());
+            }
+            Some((range.0?, range.1?))
+        }
+    }
+}
+
+/// The label of the active signature with its active parameter highlighted.
+pub fn signature_label(
+    help: &SignatureHelp,
+    language: LapceLanguage,
+    config: &LapceConfig,
+) -> Option<TextLayout> {
+    let signature = help.signatures.get(active_signature_index(help))?;
+    let label = &signature.label;
+
+    let font_family: Vec<FamilyOwned> =
+        FamilyOwned::parse_list(&config.editor.font_family).collect();
+    let attrs = Attrs::new()
+        .color(*config.get_color(LapceColor::EDITOR_FOREGROUND))
+        .family(&font_family)
+        .font_size(config.editor.font_size() as f32);
+    let mut attrs_list = AttrsList::new(attrs);
+    if config.editor.signature_label_code_block {
+        highlight_as_code(&mut attrs_list, attrs, Some(language), label, 0, config);
+    }
+    if let Some((start, end)) = active_parameter_range(help, signature) {
+        attrs_list.add_span(
+            start..end,
+            attrs
+                .color(*config.get_color(LapceColor::EDITOR_FOCUS))
+                .weight(Weight::BOLD),
+        );
+    }
+
+    let mut text_layout = TextLayout::new();
+    text_layout.set_text(label, attrs_list);
+    Some(text_layout)
+}
+
+/// The documentation of the active parameter followed by the one of the
+/// active signature.
+pub fn signature_documentation(
+    help: &SignatureHelp,
+    config: &LapceConfig,
+) -> Vec<MarkdownContent> {
+    let signature = match help.signatures.get(active_signature_index(help)) {
+        Some(signature) => signature,
+        None => return Vec::new(),
+    };
+    let parameter_documentation = signature
+        .active_parameter
+        .or(help.active_parameter)
+        .and_then(|index| signature.parameters.as_ref()?.get(index as usize))
+        .and_then(|parameter| parameter.documentation.as_ref());
+
+    let mut content = Vec::new();
+    for documentation in parameter_documentation
+        .into_iter()
+        .chain(signature.documentation.as_ref())
+    {
+        let documentation = match documentation {
+            Documentation::String(text) => from_plaintext(text, 1.5, config),
+            Documentation::MarkupContent(content) => match content.kind {
+                MarkupKind::PlainText => from_plaintext(&content.value, 1.5, config),
+                MarkupKind::Markdown => parse_markdown(&content.value, 1.5, config),
+            },
+        };
+        if documentation.is_empty() {
+            continue;
+        }
+        if !content.is_empty() {
+            content.push(MarkdownContent::Separator);
+        }
+        content.extend(documentation);
+    }
+    content
+}
@@ -1,12 +1,13 @@
 use std::{path::PathBuf, rc::Rc};
 
+use chrono::{Local, TimeZone};
 use floem::{
     keyboard::ModifiersState,
     reactive::{RwSignal, Scope},
 };
 use indexmap::IndexMap;
 use lapce_core::mode::Mode;
-use lapce_rpc::source_control::FileDiff;
+use lapce_rpc::source_control::{BlameHunk, FileDiff};
 
 use crate::{
     command::{CommandExecuted, CommandKind},
@@ -16,10 +17,15 @@
     window_tab::CommonData,
 };
 
+/// The commit ids are shortened to this length where they're shown
+const SHORT_COMMIT_ID_LEN: usize = 8;
+
 #[derive(Clone)]
 pub struct SourceControlData {
-    // VCS modified files & whether they should be included in the next commit
-    pub file_diffs: RwSignal<IndexMap<PathBuf, (FileDiff, bool)>>,
+    // VCS modified files that aren't staged
+    pub file_diffs: RwSignal<IndexMap<PathBuf, FileDiff>>,
+    // VCS modified files that are staged, which make up the next commit
+    pub staged_diffs: RwSignal<IndexMap<PathBuf, FileDiff>>,
     pub branch: RwSignal<String>,
     pub branches: RwSignal<im::Vector<String>>,
     pub tags: RwSignal<im::Vector<String>>,
@@ -64,6 +70,7 @@ impl SourceControlData {
     pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
         Self {
             file_diffs: cx.create_rw_signal(IndexMap::new()),
+            staged_diffs: cx.create_rw_signal(IndexMap::new()),
             branch: cx.create_rw_signal("".to_string()),
             branches: cx.create_rw_signal(im::Vector::new()),
             tags: cx.create_rw_signal(im::Vector::new()),
@@ -77,22 +84,15 @@ pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
     }
 
     pub fn commit(&self) {
-        let diffs: Vec<FileDiff> = self.file_diffs.with_untracked(|file_diffs| {
-            file_diffs
-                .iter()
-                .filter_map(
-                    |(_, (diff, checked))| {
-                        if *checked {
-                            Some(diff)
-                        } else {
-                            None
-                        }
-                    },
-                )
-                .cloned()
-                .collect()
-        });
-        if diffs.is_empty() {
+        // Commit what's staged, or all the changes if nothing is
+        let has_staged = self.staged_diffs.with_untracked(|diffs| !diffs.is_empty());
+        let diffs: Vec<FileDiff> = if has_staged {
+            Vec::new()
+        } else {
+            self.file_diffs
+                .with_untracked(|file_diffs| file_diffs.values().cloned().collect())
+        };
+        if !has_staged && diffs.is_empty() {
             return;
         }
 
@@ -111,4 +111,202 @@ pub fn commit(&self) {
         self.editor.reset();
         self.common.proxy.git_commit(message.to_string(), diffs);
     }
+
+    pub fn stage(&self, diff: &FileDiff) {
+        self.common.proxy.git_stage_files(diff_paths(diff));
+    }
+
+    pub fn unstage(&self, diff: &FileDiff) {
+        self.common.proxy.git_unstage_files(diff_paths(diff));
+    }
+
+    pub fn stage_all(&self) {
+        let files = self.file_diffs.with_untracked(|diffs| {
+            diffs.values().flat_map(diff_paths).collect::<Vec<_>>()
+        });
+        if !files.is_empty() {
+            self.common.proxy.git_stage_files(files);
+        }
+    }
+
+    pub fn unstage_all(&self) {
+        let files = self.staged_diffs.with_untracked(|diffs| {
+            diffs.values().flat_map(diff_paths).collect::<Vec<_>>()
+        });
+        if !files.is_empty() {
+            self.common.proxy.git_unstage_files(files);
+        }
+    }
+}
+
+/// The paths changed by the diff, which are both paths of a rename.
+fn diff_paths(diff: &FileDiff) -> Vec<PathBuf> {
+    match diff {
+        FileDiff::Modified(p) | FileDiff::Added(p) | FileDiff::Deleted(p) => {
+            vec![p.clone()]
+        }
+        FileDiff::Renamed(new, old) => vec![new.clone(), old.clone()],
+    }
+}
+
+/// The hunk of the blame that the line, starting at 0, is in.
+pub fn line_blame(blame: &im::Vector<BlameHunk>, line: usize) -> Option<&BlameHunk> {
+    blame
+        .iter()
+        .find(|hunk| hunk.start_line <= line && line < hunk.start_line + hunk.lines)
+}
+
+/// The blame of a line shown at its end, with the author, how long ago the
+/// commit was made and its summary.
+pub fn blame_annotation(hunk: &BlameHunk) -> String {
+    if !hunk.is_committed() {
+        return "You, Uncommitted changes".to_string();
+    }
+    format!(
+        "{}, {} \u{2022} {}",
+        hunk.author,
+        relative_time(hunk.time),
+        hunk.summary
+    )
+}
+
+/// The blame of a line shown in the blame gutter, with the date of the commit
+/// and its author.
+pub fn blame_gutter_text(hunk: &BlameHunk) -> String {
+    if !hunk.is_committed() {
+        return "Uncommitted changes".to_string();
+    }
+    format!("{} {}", commit_date(hunk.time), hunk.author)
+}
+
+/// The markdown of the hover of a blamed line, with the full commit message.
+pub fn blame_hover_text(hunk: &BlameHunk) -> String {
+    if !hunk.is_committed() {
+        return "Uncommitted changes".to_string();
+    }
+    format!(
+        "**{}** <{}>, {} ({})\n\n`{}`\n\n{}",
+        hunk.author,
+        hunk.email,
+        commit_date(hunk.time),
+        relative_time(hunk.time),
+        short_commit_id(&hunk.commit_id),
+        hunk.message.trim()
+    )
+}
+
+pub fn short_commit_id(commit_id: &str) -> &str {
+    &commit_id[..commit_id.len().min(SHORT_COMMIT_ID_LEN)]
+}
+
+/// The date of a commit in the local time zone, from its time in seconds
+/// since the Unix epoch.
+pub fn commit_date(time: i64) -> String {
+    Local
+        .timestamp_opt(time, 0)
+        .single()
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// How long ago the time in seconds since the Unix epoch was, like `3 days
+/// ago`.
+pub fn relative_time(time: i64) -> String {
+    relative_time_from(time, Local::now().timestamp())
+}
+
+/// How long before `now` the time was, both in seconds since the Unix epoch.
+fn relative_time_from(time: i64, now: i64) -> String {
+    let seconds = (now - time).max(0);
+    let (count, unit) = match seconds {
+        s if s < 60 => return "just now".to_string(),
+        s if s < 60 * 60 => (s / 60, "minute"),
+        s if s < 60 * 60 * 24 => (s / (60 * 60), "hour"),
+        s if s < 60 * 60 * 24 * 30 => (s / (60 * 60 * 24), "day"),
+        s if s < 60 * 60 * 24 * 365 => (s / (60 * 60 * 24 * 30), "month"),
+        s => (s / (60 * 60 * 24 * 365), "year"),
+    };
+    if count == 1 {
+        format!("1 {unit} ago")
+    } else {
+        format!("{count} {unit}s ago")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveDate, TimeZone};
+    use lapce_rpc::source_control::BlameHunk;
+
+    use super::{commit_date, line_blame, relative_time, relative_time_from};
+
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+
+    fn hunk(commit_id: &str, start_line: usize, lines: usize) -> BlameHunk {
+        BlameHunk {
+            commit_id: commit_id.to_string(),
+            author: "author".to_string(),
+            email: "author@example.com".to_string(),
+            time: 0,
+            summary: "summary".to_string(),
+            message: "summary".to_string(),
+            start_line,
+            lines,
+        }
+    }
+
+    #[test]
+    fn relative_times() {
+        let now = 1_700_000_000;
+        assert_eq!(relative_time_from(now, now), "just now");
+        assert_eq!(relative_time_from(now - 59, now), "just now");
+        // A time in the future, from a clock that's off
+        assert_eq!(relative_time_from(now + HOUR, now), "just now");
+        assert_eq!(relative_time_from(now - MINUTE, now), "1 minute ago");
+        assert_eq!(relative_time_from(now - 59 * MINUTE, now), "59 minutes ago");
+        assert_eq!(relative_time_from(now - HOUR, now), "1 hour ago");
+        assert_eq!(relative_time_from(now - 23 * HOUR, now), "23 hours ago");
+        assert_eq!(relative_time_from(now - DAY, now), "1 day ago");
+        assert_eq!(relative_time_from(now - 29 * DAY, now), "29 days ago");
+        assert_eq!(relative_time_from(now - 30 * DAY, now), "1 month ago");
+        assert_eq!(relative_time_from(now - 364 * DAY, now), "12 months ago");
+        assert_eq!(relative_time_from(now - 365 * DAY, now), "1 year ago");
+        assert_eq!(relative_time_from(now - 3 * 365 * DAY, now), "3 years ago");
+
+        assert_eq!(relative_time(Local::now().timestamp()), "just now");
+    }
+
+    #[test]
+    fn commit_dates() {
+        let date = NaiveDate::from_ymd_opt(2023, 6, 15)
+            .and_then(|date| date.and_hms_opt(12, 30, 0))
+            .unwrap();
+        let time = Local
+            .from_local_datetime(&date)
+            .single()
+            .unwrap()
+            .timestamp();
+        assert_eq!(commit_date(time), "2023-06-15");
+        // Out of the range of the dates
+        assert_eq!(commit_date(i64::MAX), "");
+    }
+
+    #[test]
+    fn line_blames() {
+        let blame = im::vector![hunk("a", 0, 2), hunk("b", 2, 1), hunk("c", 5, 3)];
+        let commit =
+            |line| line_blame(&blame, line).map(|hunk| hunk.commit_id.as_str());
+
+        assert_eq!(commit(0), Some("a"));
+        assert_eq!(commit(1), Some("a"));
+        assert_eq!(commit(2), Some("b"));
+        // A gap between the hunks
+        assert_eq!(commit(3), None);
+        assert_eq!(commit(7), Some("c"));
+        // After the last hunk
+        assert_eq!(commit(8), None);
+        assert_eq!(line_blame(&im::Vector::new(), 0), None);
+    }
 }
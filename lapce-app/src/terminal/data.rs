@@ -17,7 +17,10 @@
     movement::{LinePosition, Movement},
     register::Clipboard,
 };
-use lapce_rpc::{dap_types::RunDebugConfig, terminal::TermId};
+use lapce_rpc::{
+    dap_types::{DebugRequest, RunDebugConfig},
+    terminal::TermId,
+};
 use parking_lot::RwLock;
 
 use super::{
@@ -365,6 +368,9 @@ pub fn new_raw_terminal(
 
             if let Some(debug_command) = run_debug.debug_command.as_ref() {
                 debug_command.clone()
+            } else if run_debug.request == DebugRequest::Attach {
+                // The process that's debugged is already running elsewhere
+                common.config.get_untracked().terminal.shell.clone()
             } else {
                 format!("{} {}", run_debug.program, run_debug.args.join(" "))
             }
@@ -1,9 +1,12 @@
-use std::{collections::HashMap, rc::Rc, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, rc::Rc, sync::Arc};
 
 use floem::reactive::{RwSignal, Scope};
 use lapce_core::mode::Mode;
 use lapce_rpc::{
-    dap_types::{DapId, RunDebugConfig, StackFrame, Stopped, ThreadId},
+    dap_types::{
+        DapId, DebugRequest, DebuggerCapabilities, Output, RunDebugConfig,
+        SourceBreakpoint, StackFrame, Stopped, ThreadId,
+    },
     terminal::TermId,
 };
 
@@ -47,7 +50,7 @@ pub fn new(
         let tab_info = TerminalTabInfo { active: 0, tabs };
         let tab_info = cx.create_rw_signal(tab_info);
 
-        let debug = RunDebugData::new(cx);
+        let debug = RunDebugData::new(cx, common.clone());
 
         Self {
             cx,
@@ -363,9 +366,12 @@ pub fn restart_run_debug(&self, term_id: TermId) -> Option<()> {
                     terminal.run_debug.get_untracked().as_ref()?.config.dap_id;
                 let daps = self.debug.daps.get_untracked();
                 let dap = daps.get(&dap_id)?;
-                self.common
-                    .proxy
-                    .dap_restart(dap.dap_id, self.debug.source_breakpoints());
+                self.common.proxy.dap_restart(
+                    dap.dap_id,
+                    self.debug.source_breakpoints(),
+                    self.debug.exception_filters.get_untracked(),
+                    self.debug.source_function_breakpoints(),
+                );
                 term_id
             }
         };
@@ -463,7 +469,9 @@ pub fn set_process_id(&self, term_id: &TermId, process_id: Option<u32>) {
         if let Some(terminal) = self.get_terminal(term_id) {
             terminal.run_debug.with_untracked(|run_debug| {
                 if let Some(run_debug) = run_debug.as_ref() {
-                    if run_debug.config.debug_command.is_some() {
+                    if run_debug.config.debug_command.is_some()
+                        || run_debug.config.request == DebugRequest::Attach
+                    {
                         let dap_id = run_debug.config.dap_id;
                         self.common
                             .proxy
@@ -474,6 +482,95 @@ pub fn set_process_id(&self, term_id: &TermId, process_id: Option<u32>) {
         }
     }
 
+    /// The session of the terminal that the debug controls act on, which is
+    /// the child session selected in the session tree if it runs there
+    fn term_dap_id(&self, term_id: TermId, tracked: bool) -> Option<DapId> {
+        let active_session = if tracked {
+            self.debug.active_session.get()
+        } else {
+            self.debug.active_session.get_untracked()
+        };
+        if let Some(dap_id) = active_session {
+            let in_terminal = |daps: &im::HashMap<DapId, DapData>| {
+                daps.get(&dap_id)
+                    .map(|dap| dap.term_id == term_id)
+                    .unwrap_or(false)
+            };
+            let in_terminal = if tracked {
+                self.debug.daps.with(in_terminal)
+            } else {
+                self.debug.daps.with_untracked(in_terminal)
+            };
+            if in_terminal {
+                return Some(dap_id);
+            }
+        }
+
+        let terminal = self.get_terminal(&term_id)?;
+        if tracked {
+            terminal
+                .run_debug
+                .with(|r| r.as_ref().map(|r| r.config.dap_id))
+        } else {
+            terminal
+                .run_debug
+                .with_untracked(|r| r.as_ref().map(|r| r.config.dap_id))
+        }
+    }
+
+    /// Add the session that the debug adapter started to the session tree,
+    /// under its parent and in the parent's terminal.
+    pub fn dap_child_session(&self, dap_id: DapId, parent: DapId, name: String) {
+        let term_id = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(&parent).map(|dap| dap.term_id));
+        let term_id = match term_id {
+            Some(term_id) => term_id,
+            None => return,
+        };
+        self.debug.daps.update(|daps| {
+            daps.insert(
+                dap_id,
+                DapData::new(self.cx, dap_id, term_id, Some(parent), name),
+            );
+        });
+    }
+
+    /// Clear the variables of a session that ended, and remove it from the
+    /// session tree if it's a child session.
+    pub fn dap_terminated(&self, dap_id: &DapId) {
+        let dap = match self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(dap_id).cloned())
+        {
+            Some(dap) => dap,
+            None => return,
+        };
+        if dap.parent.is_none() {
+            dap.clear_scopes();
+            return;
+        }
+        self.debug.daps.update(|daps| {
+            daps.remove(dap_id);
+        });
+        dap.dispose_variables();
+        if self.debug.active_session.get_untracked() == Some(*dap_id) {
+            self.debug.active_session.set(None);
+        }
+        if self
+            .common
+            .paused_frame
+            .get_untracked()
+            .map(|(paused_dap, _)| &paused_dap == dap_id)
+            .unwrap_or(false)
+        {
+            self.common.paused_frame.set(None);
+            self.common.instruction_pointer.set(None);
+        }
+    }
+
     pub fn dap_continued(&self, dap_id: &DapId) {
         let dap = self
             .debug
@@ -481,9 +578,26 @@ pub fn dap_continued(&self, dap_id: &DapId) {
             .with_untracked(|daps| daps.get(dap_id).cloned());
         if let Some(dap) = dap {
             dap.stopped.set(false);
+            dap.clear_scopes();
+        }
+        if self
+            .common
+            .paused_frame
+            .get_untracked()
+            .map(|(paused_dap, _)| &paused_dap == dap_id)
+            .unwrap_or(false)
+        {
+            self.common.paused_frame.set(None);
+            self.common.instruction_pointer.set(None);
+            self.debug.editing_variable.set(None);
+            self.debug.evaluate_watches();
         }
     }
 
+    pub fn dap_output(&self, dap_id: &DapId, output: &Output) {
+        self.debug.output(dap_id, output);
+    }
+
     pub fn dap_stopped(
         &self,
         dap_id: &DapId,
@@ -496,37 +610,164 @@ pub fn dap_stopped(
             .with_untracked(|daps| daps.get(dap_id).cloned());
         if let Some(dap) = dap {
             dap.stopped(self.cx, stopped, stack_frames);
+            // Show the session that stopped when it's a child session
+            self.debug
+                .active_session
+                .set(dap.parent.map(|_| dap.dap_id));
+            if let Some(frame_id) = dap.top_frame() {
+                self.dap_select_frame(*dap_id, frame_id);
+            }
+
+            // Remove the breakpoint that was added to run to the cursor
+            if let Some(path) = dap.temporary_breakpoint.get_untracked() {
+                dap.temporary_breakpoint.set(None);
+                let breakpoints = self.debug.path_source_breakpoints(&path);
+                self.common
+                    .proxy
+                    .dap_set_breakpoints(dap.dap_id, path, breakpoints);
+            }
         }
     }
 
-    pub fn dap_continue(&self, term_id: TermId) -> Option<()> {
-        let terminal = self.get_terminal(&term_id)?;
-        let dap_id = terminal
-            .run_debug
-            .with_untracked(|r| r.as_ref().map(|r| r.config.dap_id))?;
+    /// Inspect the variables of the stack frame, and evaluate the watch
+    /// expressions in it.
+    pub fn dap_select_frame(&self, dap_id: DapId, frame_id: usize) {
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(&dap_id).cloned());
+        if let Some(dap) = dap {
+            dap.select_frame(&self.common.proxy, frame_id);
+            self.common
+                .instruction_pointer
+                .set(dap.frame_instruction_pointer(frame_id));
+            self.common.paused_frame.set(Some((dap_id, frame_id)));
+            self.debug.evaluate_watches();
+            self.debug.read_memory();
+        }
+    }
+
+    /// The debug adapter of the terminal, with the thread that's being debugged.
+    fn dap_thread(&self, term_id: TermId) -> Option<(DapId, ThreadId)> {
+        let dap_id = self.term_dap_id(term_id, false)?;
         let thread_id = self.debug.daps.with_untracked(|daps| {
             daps.get(&dap_id)
                 .and_then(|dap| dap.thread_id.get_untracked())
         });
         let thread_id = thread_id.unwrap_or_default();
+        Some((dap_id, thread_id))
+    }
+
+    /// Whether the debug adapter has the capability, which it reports when
+    /// it's initialized.
+    pub fn dap_supports(
+        &self,
+        dap_id: DapId,
+        capability: impl Fn(&DebuggerCapabilities) -> Option<bool>,
+    ) -> bool {
+        self.common.dap_capabilities.with_untracked(|capabilities| {
+            capabilities
+                .get(&dap_id)
+                .and_then(capability)
+                .unwrap_or(false)
+        })
+    }
+
+    pub fn dap_continue(&self, term_id: TermId) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_thread(term_id)?;
         self.common.proxy.dap_continue(dap_id, thread_id);
         Some(())
     }
 
     pub fn dap_pause(&self, term_id: TermId) -> Option<()> {
-        let terminal = self.get_terminal(&term_id)?;
-        let dap_id = terminal
-            .run_debug
-            .with_untracked(|r| r.as_ref().map(|r| r.config.dap_id))?;
-        let thread_id = self.debug.daps.with_untracked(|daps| {
-            daps.get(&dap_id)
-                .and_then(|dap| dap.thread_id.get_untracked())
-        });
-        let thread_id = thread_id.unwrap_or_default();
+        let (dap_id, thread_id) = self.dap_thread(term_id)?;
         self.common.proxy.dap_pause(dap_id, thread_id);
         Some(())
     }
 
+    pub fn dap_step_over(&self, term_id: TermId) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_thread(term_id)?;
+        self.common.proxy.dap_step_over(dap_id, thread_id);
+        Some(())
+    }
+
+    pub fn dap_step_into(&self, term_id: TermId) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_thread(term_id)?;
+        self.common.proxy.dap_step_into(dap_id, thread_id);
+        Some(())
+    }
+
+    pub fn dap_step_out(&self, term_id: TermId) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_thread(term_id)?;
+        self.common.proxy.dap_step_out(dap_id, thread_id);
+        Some(())
+    }
+
+    pub fn dap_step_back(&self, term_id: TermId) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_thread(term_id)?;
+        if !self.dap_supports(dap_id, |c| c.supports_step_back) {
+            return None;
+        }
+        self.common.proxy.dap_step_back(dap_id, thread_id);
+        Some(())
+    }
+
+    pub fn dap_step_instruction(&self, term_id: TermId) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_thread(term_id)?;
+        if !self.dap_supports(dap_id, |c| c.supports_stepping_granularity) {
+            return None;
+        }
+        self.common.proxy.dap_step_instruction(dap_id, thread_id);
+        Some(())
+    }
+
+    /// Continue until the line, starting at 0, is reached, by adding a breakpoint
+    /// on it that is removed again when the debuggee stops.
+    pub fn dap_run_to_cursor(
+        &self,
+        term_id: TermId,
+        path: PathBuf,
+        line: usize,
+    ) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_thread(term_id)?;
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(&dap_id).cloned())?;
+
+        let mut breakpoints = self.debug.path_source_breakpoints(&path);
+        if !breakpoints.iter().any(|b| b.line == line + 1) {
+            breakpoints.push(SourceBreakpoint {
+                line: line + 1,
+                ..Default::default()
+            });
+            dap.temporary_breakpoint.set(Some(path.clone()));
+        }
+        self.common
+            .proxy
+            .dap_set_breakpoints(dap_id, path, breakpoints);
+        self.common.proxy.dap_continue(dap_id, thread_id);
+        Some(())
+    }
+
+    /// Move the execution to the line, starting at 0, without running the code in
+    /// between.
+    pub fn dap_jump_to_cursor(
+        &self,
+        term_id: TermId,
+        path: PathBuf,
+        line: usize,
+    ) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_thread(term_id)?;
+        if !self.dap_supports(dap_id, |c| c.supports_goto_targets_request) {
+            return None;
+        }
+        self.common
+            .proxy
+            .dap_goto(dap_id, thread_id, path, line + 1);
+        Some(())
+    }
+
     pub fn get_active_dap(&self, tracked: bool) -> Option<DapData> {
         let active_term = if tracked {
             self.debug.active_term.get()?
@@ -537,16 +778,7 @@ pub fn get_active_dap(&self, tracked: bool) -> Option<DapData> {
     }
 
     pub fn get_dap(&self, term_id: TermId, tracked: bool) -> Option<DapData> {
-        let terminal = self.get_terminal(&term_id)?;
-        let dap_id = if tracked {
-            terminal
-                .run_debug
-                .with(|r| r.as_ref().map(|r| r.config.dap_id))?
-        } else {
-            terminal
-                .run_debug
-                .with_untracked(|r| r.as_ref().map(|r| r.config.dap_id))?
-        };
+        let dap_id = self.term_dap_id(term_id, tracked)?;
 
         if tracked {
             self.debug.daps.with(|daps| daps.get(&dap_id).cloned())
@@ -4,6 +4,8 @@
     view::View,
     views::{label, Decorators},
 };
+use once_cell::sync::Lazy;
+use regex::Regex;
 
 use crate::{command::InternalCommand, listener::Listener};
 
@@ -21,3 +23,14 @@ pub fn web_link(
         .base_style(move |s| s.color(color()))
         .hover_style(move |s| s.cursor(CursorStyle::Pointer))
 }
+
+/// Find the web addresses in the text, returning their byte ranges. Trailing
+/// punctuation is left out, so that a link at the end of a sentence or in
+/// parentheses doesn't include it.
+pub fn find_web_links(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    static URL_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"https?://[^\s<>"'`]*[^\s<>"'`.,;:)\]}]"#).unwrap()
+    });
+
+    URL_REGEX.find_iter(text).map(|m| (m.start(), m.end()))
+}
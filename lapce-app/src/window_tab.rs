@@ -1,4 +1,11 @@
-use std::{collections::HashSet, env, path::Path, rc::Rc, sync::Arc, time::Instant};
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+    time::Instant,
+};
 
 use crossbeam_channel::Sender;
 use floem::{
@@ -18,12 +25,13 @@
     register::Register,
 };
 use lapce_rpc::{
-    core::CoreNotification,
-    dap_types::RunDebugConfig,
+    core::{CoreNotification, CoreRequest, CoreResponse},
+    dap_types::{DapId, DebuggerCapabilities, RunDebugConfig},
     file::PathObject,
-    proxy::{ProxyRpcHandler, ProxyStatus},
+    proxy::{ProxyResponse, ProxyRpcHandler, ProxyStatus},
     source_control::FileDiff,
     terminal::TermId,
+    RequestId,
 };
 use lsp_types::{ProgressParams, ProgressToken, ShowMessageParams};
 use serde_json::Value;
@@ -40,7 +48,10 @@
     completion::{CompletionData, CompletionStatus},
     config::LapceConfig,
     db::LapceDb,
-    debug::{DapData, RunDebugMode, RunDebugProcess},
+    debug::{
+        resolve_config_variables, DapData, LapceBreakpoint, RunDebugMode,
+        RunDebugProcess,
+    },
     doc::{DocContent, EditorDiagnostic},
     editor::{
         location::{EditorLocation, EditorPosition},
@@ -51,7 +62,8 @@
     find::Find,
     global_search::GlobalSearchData,
     hover::HoverData,
-    id::WindowTabId,
+    id::{EditorId, WindowTabId},
+    inline_completion::InlineCompletionData,
     keypress::{condition::Condition, EventRef, KeyPressData, KeyPressFocus},
     listener::Listener,
     main_split::{MainSplitData, SplitData, SplitDirection, SplitMoveDirection},
@@ -64,6 +76,7 @@
     plugin::PluginData,
     proxy::{new_proxy, path_from_url, ProxyData},
     rename::RenameData,
+    signature::SignatureData,
     source_control::SourceControlData,
     terminal::{
         event::{terminal_update_process, TermEvent, TermNotification},
@@ -110,7 +123,9 @@ pub struct CommonData {
     pub focus: RwSignal<Focus>,
     pub keypress: RwSignal<KeyPressData>,
     pub completion: RwSignal<CompletionData>,
+    pub inline_completion: RwSignal<InlineCompletionData>,
     pub hover: HoverData,
+    pub signature: SignatureData,
     pub register: RwSignal<Register>,
     pub find: Find,
     pub workbench_size: RwSignal<Size>,
@@ -131,6 +146,17 @@ pub struct CommonData {
     pub mouse_hover_timer: RwSignal<TimerToken>,
     pub hide_cursor: RwSignal<bool>,
     pub ime_allowed: RwSignal<bool>,
+    /// The debug session and the stack frame whose variables are inspected,
+    /// while the debuggee is paused
+    pub paused_frame: RwSignal<Option<(DapId, usize)>>,
+    /// The memory reference of the instruction the paused frame is at, if the
+    /// debug adapter reports it
+    pub instruction_pointer: RwSignal<Option<String>>,
+    /// Whether the git blame of the lines is shown left of the line numbers
+    pub blame_gutter: RwSignal<bool>,
+    pub breakpoints: RwSignal<BTreeMap<PathBuf, Vec<LapceBreakpoint>>>,
+    /// The capabilities of the debug adapters that were started
+    pub dap_capabilities: RwSignal<im::HashMap<DapId, DebuggerCapabilities>>,
 }
 
 #[derive(Clone)]
@@ -300,7 +326,9 @@ pub fn new(
 
         let focus = cx.create_rw_signal(Focus::Workbench);
         let completion = cx.create_rw_signal(CompletionData::new(cx, config));
+        let inline_completion = cx.create_rw_signal(InlineCompletionData::new());
         let hover = HoverData::new(cx);
+        let signature = SignatureData::new(cx);
 
         let register = cx.create_rw_signal(Register::default());
         let view_id = cx.create_rw_signal(floem::id::Id::next());
@@ -327,7 +355,9 @@ pub fn new(
             keypress,
             focus,
             completion,
+            inline_completion,
             hover,
+            signature,
             register,
             find,
             window_command,
@@ -348,6 +378,16 @@ pub fn new(
             hide_cursor: cx.create_rw_signal(false),
             window_origin: cx.create_rw_signal(Point::ZERO),
             ime_allowed,
+            paused_frame: cx.create_rw_signal(None),
+            instruction_pointer: cx.create_rw_signal(None),
+            blame_gutter: cx.create_rw_signal(false),
+            breakpoints: cx.create_rw_signal(
+                workspace_info
+                    .as_ref()
+                    .map(|info| info.breakpoints.clone())
+                    .unwrap_or_default(),
+            ),
+            dap_capabilities: cx.create_rw_signal(im::HashMap::new()),
         });
 
         let main_split = MainSplitData::new(cx, common.clone());
@@ -532,6 +572,13 @@ pub fn new(
             });
         }
 
+        {
+            let window_tab_data = window_tab_data.clone();
+            cx.create_effect(move |last_path: Option<Option<PathBuf>>| {
+                window_tab_data.update_inline_values(last_path.flatten())
+            });
+        }
+
         {
             let window_tab_data = window_tab_data.clone();
             let notification = window_tab_data.proxy.notification;
@@ -544,6 +591,18 @@ pub fn new(
             });
         }
 
+        {
+            let window_tab_data = window_tab_data.clone();
+            let request = window_tab_data.proxy.request;
+            cx.create_effect(move |_| {
+                request.with(|request| {
+                    if let Some((id, rpc)) = request.as_ref() {
+                        window_tab_data.handle_core_request(*id, rpc);
+                    }
+                });
+            });
+        }
+
         window_tab_data
     }
 
@@ -561,6 +620,15 @@ pub fn reload_config(&self) {
         self.common.keypress.update(|keypress| {
             keypress.update_keymaps(&config);
         });
+        if self
+            .common
+            .config
+            .with_untracked(|old| old.plugins != config.plugins)
+        {
+            self.common
+                .proxy
+                .update_plugin_configs(config.plugins.clone());
+        }
         self.set_config.set(Arc::new(config));
     }
 
@@ -981,6 +1049,60 @@ pub fn run_workbench_command(
                     self.terminal.stop_run_debug(term_id);
                 }
             }
+            DebugStepOver => {
+                let active_term = self.terminal.debug.active_term.get_untracked();
+                if let Some(term_id) = active_term {
+                    self.terminal.dap_step_over(term_id);
+                }
+            }
+            DebugStepInto => {
+                let active_term = self.terminal.debug.active_term.get_untracked();
+                if let Some(term_id) = active_term {
+                    self.terminal.dap_step_into(term_id);
+                }
+            }
+            DebugStepOut => {
+                let active_term = self.terminal.debug.active_term.get_untracked();
+                if let Some(term_id) = active_term {
+                    self.terminal.dap_step_out(term_id);
+                }
+            }
+            DebugStepBack => {
+                let active_term = self.terminal.debug.active_term.get_untracked();
+                if let Some(term_id) = active_term {
+                    self.terminal.dap_step_back(term_id);
+                }
+            }
+            DebugStepInstruction => {
+                let active_term = self.terminal.debug.active_term.get_untracked();
+                if let Some(term_id) = active_term {
+                    self.terminal.dap_step_instruction(term_id);
+                }
+            }
+            DebugOpenDisassembly => {
+                self.main_split.open_disassembly();
+            }
+            DebugToggleBreakpoint => {
+                if let Some((path, line)) = self.main_split.active_editor_line() {
+                    self.terminal.debug.toggle_breakpoint(path, line);
+                }
+            }
+            DebugRunToCursor => {
+                let active_term = self.terminal.debug.active_term.get_untracked();
+                if let (Some(term_id), Some((path, line))) =
+                    (active_term, self.main_split.active_editor_line())
+                {
+                    self.terminal.dap_run_to_cursor(term_id, path, line);
+                }
+            }
+            DebugJumpToCursor => {
+                let active_term = self.terminal.debug.active_term.get_untracked();
+                if let (Some(term_id), Some((path, line))) =
+                    (active_term, self.main_split.active_editor_line())
+                {
+                    self.terminal.dap_jump_to_cursor(term_id, path, line);
+                }
+            }
 
             // ==== UI ====
             ZoomIn => {
@@ -1116,6 +1238,39 @@ pub fn run_workbench_command(
             SourceControlDiscardActiveFileChanges => {
                 // TODO:
             }
+            SourceControlToggleBlame => {
+                let blame_gutter = !self.common.blame_gutter.get_untracked();
+                self.common.blame_gutter.set(blame_gutter);
+                if blame_gutter {
+                    let docs = self.main_split.docs.get_untracked();
+                    for (_, doc) in docs {
+                        doc.get_blame();
+                    }
+                }
+            }
+            SourceControlShowLog => {
+                self.main_split.git_log.load();
+                self.main_split.open_git_log();
+            }
+            SourceControlShowFileHistory => {
+                let path = self.main_split.active_editor.get_untracked().and_then(
+                    |editor| match editor
+                        .view
+                        .doc
+                        .get_untracked()
+                        .content
+                        .get_untracked()
+                    {
+                        DocContent::File { path, .. } => Some(path),
+                        DocContent::History(history) => Some(history.path),
+                        _ => None,
+                    },
+                );
+                if let Some(path) = path {
+                    self.main_split.git_log.show_file_history(path);
+                    self.main_split.open_git_log();
+                }
+            }
             SourceControlDiscardTargetFileChanges => {
                 if let Some(diff) = data
                     .and_then(|data| serde_json::from_value::<FileDiff>(data).ok())
@@ -1262,6 +1417,17 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
                 self.palette.references.set(references);
                 self.palette.run(PaletteKind::Reference);
             }
+            InternalCommand::PaletteColorPicker { path, range, color } => {
+                self.palette.color_picker.set(Some((path, range, color)));
+                self.palette.run(PaletteKind::ColorPicker);
+            }
+            InternalCommand::PeekLocation {
+                editor_id,
+                offset,
+                location,
+            } => {
+                self.main_split.peek_location(editor_id, offset, location);
+            }
             InternalCommand::Split {
                 direction,
                 editor_tab_id,
@@ -1417,6 +1583,21 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
             } => {
                 self.show_alert(title, msg, buttons);
             }
+            InternalCommand::ShowMessage { title, message } => {
+                self.show_message(&title, &message);
+            }
+            InternalCommand::ToggleBreakpoint { path, line } => {
+                self.terminal.debug.toggle_breakpoint(path, line);
+            }
+            InternalCommand::EditBreakpoint { path, line, field } => {
+                self.terminal
+                    .debug
+                    .start_editing_breakpoint(path, line, field);
+                if !self.panel.is_panel_visible(&PanelKind::Debug) {
+                    self.panel.show_panel(&PanelKind::Debug);
+                }
+                self.common.focus.set(Focus::Panel(PanelKind::Debug));
+            }
             InternalCommand::HideAlert => {
                 self.alert_data.active.set(false);
             }
@@ -1429,6 +1610,18 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
         }
     }
 
+    fn handle_core_request(&self, id: RequestId, rpc: &CoreRequest) {
+        match rpc {
+            CoreRequest::ApplyWorkspaceEdit { edit } => {
+                self.main_split.apply_workspace_edit(edit);
+                self.proxy.core_rpc.handle_response(
+                    id,
+                    Ok(CoreResponse::ApplyWorkspaceEditResponse { applied: true }),
+                );
+            }
+        }
+    }
+
     fn handle_core_notification(&self, rpc: &CoreNotification) {
         let cx = self.scope;
         match rpc {
@@ -1443,23 +1636,25 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                 self.source_control
                     .tags
                     .set(diff.tags.iter().cloned().collect());
-                self.source_control.file_diffs.update(|file_diffs| {
-                    *file_diffs = diff
-                        .diffs
+                self.source_control.file_diffs.set(
+                    diff.diffs
                         .iter()
                         .cloned()
-                        .map(|diff| {
-                            let checked = file_diffs
-                                .get(diff.path())
-                                .map_or(true, |(_, c)| *c);
-                            (diff.path().clone(), (diff, checked))
-                        })
-                        .collect();
-                });
+                        .map(|diff| (diff.path().clone(), diff))
+                        .collect(),
+                );
+                self.source_control.staged_diffs.set(
+                    diff.staged
+                        .iter()
+                        .cloned()
+                        .map(|diff| (diff.path().clone(), diff))
+                        .collect(),
+                );
 
                 let docs = self.main_split.docs.get_untracked();
                 for (_, doc) in docs {
                     doc.retrieve_head();
+                    doc.get_blame();
                 }
             }
             CoreNotification::CompletionResponse {
@@ -1469,14 +1664,36 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                 plugin_id,
             } => {
                 self.common.completion.update(|completion| {
-                    completion.receive(*request_id, input, resp, *plugin_id);
-
                     let editor_data = completion.latest_editor_id.and_then(|id| {
                         self.main_split
                             .editors
                             .with_untracked(|tabs| tabs.get(&id).cloned())
                     });
 
+                    match editor_data.as_ref() {
+                        Some(editor_data) => editor_data
+                            .view
+                            .doc
+                            .get_untracked()
+                            .buffer
+                            .with_untracked(|buffer| {
+                                completion.receive(
+                                    *request_id,
+                                    input,
+                                    resp,
+                                    *plugin_id,
+                                    Some(buffer),
+                                )
+                            }),
+                        None => completion.receive(
+                            *request_id,
+                            input,
+                            resp,
+                            *plugin_id,
+                            None,
+                        ),
+                    }
+
                     if let Some(editor_data) = editor_data {
                         let cursor_offset =
                             editor_data.cursor.with_untracked(|c| c.offset());
@@ -1552,6 +1769,28 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
             CoreNotification::DapContinued { dap_id } => {
                 self.terminal.dap_continued(dap_id);
             }
+            CoreNotification::DapOutput { dap_id, output } => {
+                self.terminal.dap_output(dap_id, output);
+            }
+            CoreNotification::DapChildSession {
+                dap_id,
+                parent,
+                name,
+            } => {
+                self.terminal
+                    .dap_child_session(*dap_id, *parent, name.clone());
+            }
+            CoreNotification::DapTerminated { dap_id } => {
+                self.terminal.dap_terminated(dap_id);
+            }
+            CoreNotification::DapCapabilities {
+                dap_id,
+                capabilities,
+            } => {
+                self.common.dap_capabilities.update(|c| {
+                    c.insert(*dap_id, capabilities.clone());
+                });
+            }
             CoreNotification::OpenFileChanged { path, content } => {
                 self.main_split.open_file_changed(path, content);
             }
@@ -1625,6 +1864,9 @@ pub fn key_down<'a>(&self, event: impl Into<EventRef<'a>> + Copy) -> bool {
             Focus::Panel(PanelKind::SourceControl) => {
                 keypress.key_down(event, &self.source_control)
             }
+            Focus::Panel(PanelKind::Debug) => {
+                keypress.key_down(event, &self.terminal.debug)
+            }
             _ => false,
         };
 
@@ -1646,6 +1888,7 @@ pub fn workspace_info(&self) -> WorkspaceInfo {
         WorkspaceInfo {
             split: main_split_data.get_untracked().split_info(self),
             panel: self.panel.panel_info(),
+            breakpoints: self.common.breakpoints.get_untracked(),
         }
     }
 
@@ -1654,7 +1897,33 @@ pub fn hover_origin(&self) -> Option<Point> {
             return None;
         }
 
-        let editor_id = self.common.hover.editor_id.get_untracked();
+        self.popup_origin(
+            self.common.hover.editor_id.get_untracked(),
+            self.common.hover.offset.get_untracked(),
+            self.common.hover.layout_rect.get().size(),
+        )
+    }
+
+    pub fn signature_origin(&self) -> Option<Point> {
+        if !self.common.signature.active.get_untracked() {
+            return None;
+        }
+
+        self.popup_origin(
+            self.common.signature.editor_id.get_untracked(),
+            self.common.signature.offset.get_untracked(),
+            self.common.signature.layout_rect.get().size(),
+        )
+    }
+
+    /// The origin of a popup of `size` shown above the offset in the editor,
+    /// or below it if there's no room above.
+    fn popup_origin(
+        &self,
+        editor_id: EditorId,
+        offset: usize,
+        size: Size,
+    ) -> Option<Point> {
         let editor = self
             .main_split
             .editors
@@ -1663,25 +1932,23 @@ pub fn hover_origin(&self) -> Option<Point> {
         let (window_origin, viewport, view) =
             (editor.window_origin, editor.viewport, editor.view.clone());
 
-        let (point_above, point_below) =
-            view.points_of_offset(self.common.hover.offset.get_untracked());
+        let (point_above, point_below) = view.points_of_offset(offset);
 
         let window_origin =
             window_origin.get() - self.common.window_origin.get().to_vec2();
         let viewport = viewport.get();
-        let hover_size = self.common.hover.layout_rect.get().size();
         let tab_size = self.layout_rect.get().size();
 
         let mut origin = window_origin
             + Vec2::new(
                 point_below.x - viewport.x0,
-                (point_above.y - viewport.y0) - hover_size.height,
+                (point_above.y - viewport.y0) - size.height,
             );
         if origin.y < 0.0 {
             origin.y = window_origin.y + point_below.y - viewport.y0;
         }
-        if origin.x + hover_size.width + 1.0 > tab_size.width {
-            origin.x = tab_size.width - hover_size.width - 1.0;
+        if origin.x + size.width + 1.0 > tab_size.width {
+            origin.x = tab_size.width - size.width - 1.0;
         }
         if origin.x <= 0.0 {
             origin.x = 0.0;
@@ -1957,19 +2224,110 @@ fn run_and_debug(
         mode: &RunDebugMode,
         config: &RunDebugConfig,
     ) {
+        let mut config = config.clone();
+        resolve_config_variables(
+            &mut config,
+            self.workspace.path.as_deref(),
+            self.main_split
+                .active_editor_line()
+                .map(|(path, _)| path)
+                .as_deref(),
+        );
+        let config = &config;
+        let has_pre_launch = config
+            .pre_launch
+            .as_ref()
+            .map(|pre_launch| !pre_launch.trim().is_empty())
+            .unwrap_or(false);
+        if has_pre_launch {
+            // Drop what an earlier run of the pre-launch command printed, and
+            // show what it prints this time
+            self.terminal.debug.take_pre_launch_output(&config.dap_id);
+            if !self.panel.is_panel_visible(&PanelKind::Debug) {
+                self.panel.show_panel(&PanelKind::Debug);
+            }
+        }
         match mode {
             RunDebugMode::Run => {
-                self.run_in_terminal(cx, mode, config);
+                if !has_pre_launch {
+                    self.run_in_terminal(cx, mode, config);
+                    return;
+                }
+                let window_tab_data = self.clone();
+                let mode = *mode;
+                let run_config = config.clone();
+                let send = create_ext_action(cx, move |result| {
+                    if let Ok(ProxyResponse::Success {}) = result {
+                        window_tab_data.run_in_terminal(cx, &mode, &run_config);
+                    }
+                });
+                self.common
+                    .proxy
+                    .run_pre_launch(config.clone(), move |result| {
+                        send(result);
+                    });
             }
             RunDebugMode::Debug => {
+                if config.needs_process() {
+                    self.palette.attach_config.set(Some(config.clone()));
+                    self.palette.run(PaletteKind::AttachProcess);
+                    return;
+                }
                 self.common.proxy.dap_start(
                     config.clone(),
                     self.terminal.debug.source_breakpoints(),
+                    self.terminal.debug.exception_filters.get_untracked(),
+                    self.terminal.debug.source_function_breakpoints(),
                 );
             }
         }
     }
 
+    /// Show the values of the variables of the paused stack frame in its file,
+    /// and clear the ones shown in the file of the frame before. It returns
+    /// the file that the values are shown in.
+    fn update_inline_values(&self, last_path: Option<PathBuf>) -> Option<PathBuf> {
+        let enabled = self
+            .common
+            .config
+            .with(|config| config.editor.enable_inline_values);
+        let frame = self.common.paused_frame.get().filter(|_| enabled).and_then(
+            |(dap_id, frame_id)| {
+                let dap = self
+                    .terminal
+                    .debug
+                    .daps
+                    .with(|daps| daps.get(&dap_id).cloned())?;
+                dap.frame_values(frame_id)
+            },
+        );
+        let path = frame.as_ref().map(|(path, _, _)| path.clone());
+
+        if let Some(last_path) = last_path.filter(|last| Some(last) != path.as_ref())
+        {
+            if let Some(doc) = self
+                .main_split
+                .docs
+                .with_untracked(|docs| docs.get(&last_path).cloned())
+            {
+                doc.clear_inline_values();
+            }
+        }
+
+        if let Some((path, line, values)) = frame {
+            // The file may be opened, and loaded, after the debuggee stopped
+            if let Some(doc) =
+                self.main_split.docs.with(|docs| docs.get(&path).cloned())
+            {
+                if doc.loaded.get() {
+                    doc.set_inline_values(line, &values);
+                }
+            }
+        }
+
+        path
+    }
+
     fn run_in_terminal(
         &self,
         cx: Scope,
@@ -2000,9 +2358,25 @@ fn run_in_terminal(
         self.terminal.focus_terminal(term_id);
 
         self.terminal.debug.active_term.set(Some(term_id));
+        let dap = DapData::new(
+            cx,
+            config.dap_id,
+            term_id,
+            config.parent,
+            config.name.clone(),
+        );
+        if let Some(output) =
+            self.terminal.debug.take_pre_launch_output(&config.dap_id)
+        {
+            dap.console.set(output);
+        }
+        let mut old_dap = None;
         self.terminal.debug.daps.update(|daps| {
-            daps.insert(config.dap_id, DapData::new(cx, config.dap_id, term_id));
+            old_dap = daps.insert(config.dap_id, dap);
         });
+        if let Some(old_dap) = old_dap {
+            old_dap.dispose_variables();
+        }
 
         if !self.panel.is_panel_visible(&PanelKind::Terminal) {
             self.panel.show_panel(&PanelKind::Terminal);
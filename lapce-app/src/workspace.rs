@@ -1,8 +1,8 @@
-use std::{fmt::Display, path::PathBuf};
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{main_split::SplitInfo, panel::data::PanelInfo};
+use crate::{debug::LapceBreakpoint, main_split::SplitInfo, panel::data::PanelInfo};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub struct SshHost {
@@ -140,4 +140,6 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 pub struct WorkspaceInfo {
     pub split: SplitInfo,
     pub panel: PanelInfo,
+    #[serde(default)]
+    pub breakpoints: BTreeMap<PathBuf, Vec<LapceBreakpoint>>,
 }
@@ -294,6 +294,24 @@ pub enum FocusCommand {
     GetCompletion,
     #[strum(serialize = "get_signature")]
     GetSignature,
+    #[strum(message = "Trigger Inline Completion")]
+    #[strum(serialize = "inline_completion.invoke")]
+    InlineCompletionInvoke,
+    #[strum(message = "Accept Inline Completion")]
+    #[strum(serialize = "inline_completion.accept")]
+    InlineCompletionAccept,
+    #[strum(message = "Accept Next Word of Inline Completion")]
+    #[strum(serialize = "inline_completion.accept_word")]
+    InlineCompletionAcceptWord,
+    #[strum(message = "Accept Next Line of Inline Completion")]
+    #[strum(serialize = "inline_completion.accept_line")]
+    InlineCompletionAcceptLine,
+    #[strum(message = "Show Next Inline Completion")]
+    #[strum(serialize = "inline_completion.next")]
+    InlineCompletionNext,
+    #[strum(message = "Show Previous Inline Completion")]
+    #[strum(serialize = "inline_completion.previous")]
+    InlineCompletionPrevious,
     /// This will close a modal, such as the settings window or completion
     #[strum(message = "Close Modal")]
     #[strum(serialize = "modal.close")]
@@ -304,6 +322,15 @@ pub enum FocusCommand {
     #[strum(message = "Go to Type Definition")]
     #[strum(serialize = "goto_type_definition")]
     GotoTypeDefinition,
+    #[strum(message = "Go to Implementation")]
+    #[strum(serialize = "goto_implementation")]
+    GotoImplementation,
+    #[strum(message = "Go to Declaration")]
+    #[strum(serialize = "goto_declaration")]
+    GotoDeclaration,
+    #[strum(message = "Peek Definition")]
+    #[strum(serialize = "peek_definition")]
+    PeekDefinition,
     #[strum(message = "Show Hover")]
     #[strum(serialize = "show_hover")]
     ShowHover,
@@ -313,6 +340,24 @@ pub enum FocusCommand {
     #[strum(message = "Go to Previous Difference")]
     #[strum(serialize = "previous_diff")]
     PreviousDiff,
+    #[strum(message = "Stage Hunk")]
+    #[strum(serialize = "stage_hunk")]
+    StageHunk,
+    #[strum(message = "Unstage Hunk")]
+    #[strum(serialize = "unstage_hunk")]
+    UnstageHunk,
+    #[strum(message = "Revert Hunk")]
+    #[strum(serialize = "revert_hunk")]
+    RevertHunk,
+    #[strum(message = "Stage Selected Lines")]
+    #[strum(serialize = "stage_selected_lines")]
+    StageSelectedLines,
+    #[strum(message = "Unstage Selected Lines")]
+    #[strum(serialize = "unstage_selected_lines")]
+    UnstageSelectedLines,
+    #[strum(message = "Revert Selected Lines")]
+    #[strum(serialize = "revert_selected_lines")]
+    RevertSelectedLines,
     #[strum(message = "Toggle Code Lens")]
     #[strum(serialize = "toggle_code_lens")]
     ToggleCodeLens,
@@ -322,6 +367,9 @@ pub enum FocusCommand {
     #[strum(serialize = "format_document")]
     #[strum(message = "Format Document")]
     FormatDocument,
+    #[strum(serialize = "format_selection")]
+    #[strum(message = "Format Selection")]
+    FormatSelection,
     #[strum(serialize = "search")]
     Search,
     #[strum(serialize = "focus_replace_editor")]
@@ -162,6 +162,50 @@ pub fn region(start: usize, end: usize) -> Selection {
         }
     }
 
+    /// Creates a [`Selection`] that mirrors `region`, which lies within `ranges[index]`,
+    /// at the same relative position in each of the other `ranges`.
+    /// The given region is added last so it stays the primary one.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// # use lapce_core::selection::{Selection, SelRegion};
+    /// let ranges = [(1, 4), (7, 10)];
+    /// let selection = Selection::mirrored(SelRegion::caret(3), &ranges, 0);
+    /// assert_eq!(selection.regions(), vec![
+    ///     SelRegion::caret(3),
+    ///     SelRegion::caret(9)
+    /// ]);
+    /// assert_eq!(selection.last_inserted(), Some(&SelRegion::caret(3)));
+    /// ```
+    pub fn mirrored(
+        region: SelRegion,
+        ranges: &[(usize, usize)],
+        index: usize,
+    ) -> Selection {
+        let mut selection = Selection::new();
+        let origin = match ranges.get(index) {
+            Some((origin, _)) => *origin,
+            None => {
+                selection.add_region(region);
+                return selection;
+            }
+        };
+        let start = region.start.saturating_sub(origin);
+        let end = region.end.saturating_sub(origin);
+        for (i, (range_start, range_end)) in ranges.iter().enumerate() {
+            if i != index {
+                selection.add_region(SelRegion::new(
+                    (range_start + start).min(*range_end),
+                    (range_start + end).min(*range_end),
+                    None,
+                ));
+            }
+        }
+        selection.add_region(region);
+        selection
+    }
+
     /// Returns whether this [`Selection`], contains the given `offset` position or not.
     ///
     /// **Example:**
@@ -689,11 +689,11 @@ pub fn find_matching_pair(&self, offset: usize) -> Option<usize> {
         let char = node.kind().chars().next()?;
         let tag: &'static str = matching_bracket_general(char)?;
 
-        if let Some(offset) = self.find_tag_in_siblings(node, true, tag) {
-            return Some(offset);
+        if let Some(sibling) = self.find_tag_in_siblings(node, true, tag) {
+            return Some(sibling.start_byte());
         }
-        if let Some(offset) = self.find_tag_in_siblings(node, false, tag) {
-            return Some(offset);
+        if let Some(sibling) = self.find_tag_in_siblings(node, false, tag) {
+            return Some(sibling.start_byte());
         }
         None
     }
@@ -717,31 +717,107 @@ pub fn find_tag(
         let node = tree
             .root_node()
             .descendant_for_byte_range(offset, offset + 1)?;
+        self.find_tag_node(node, previous, tag)
+            .map(|node| node.start_byte())
+    }
 
-        if let Some(offset) = self.find_tag_in_siblings(node, previous, tag) {
-            return Some(offset);
+    /// Find the node of the kind `tag` in the siblings of the node, in its
+    /// children, and then in the siblings of its ancestors.
+    fn find_tag_node<'a>(
+        &self,
+        node: Node<'a>,
+        previous: bool,
+        tag: &str,
+    ) -> Option<Node<'a>> {
+        if let Some(sibling) = self.find_tag_in_siblings(node, previous, tag) {
+            return Some(sibling);
         }
 
-        if let Some(offset) = self.find_tag_in_children(node, tag) {
-            return Some(offset);
+        if let Some(child) = self.find_tag_in_children(node, tag) {
+            return Some(child);
         }
 
         let mut node = node;
         while let Some(parent) = node.parent() {
-            if let Some(offset) = self.find_tag_in_siblings(parent, previous, tag) {
-                return Some(offset);
+            if let Some(sibling) = self.find_tag_in_siblings(parent, previous, tag) {
+                return Some(sibling);
             }
             node = parent;
         }
         None
     }
 
-    fn find_tag_in_siblings(
+    /// Find the name of the tag at `offset` and the name of its matching
+    /// opening or closing tag, so that both can be edited together.
+    /// The range under the offset comes first.
+    pub fn find_linked_tag_names(
         &self,
-        node: Node,
+        offset: usize,
+    ) -> Option<[(usize, usize); 2]> {
+        const TAG_PAIRS: [(&str, &str); 2] = [
+            ("start_tag", "end_tag"),
+            ("jsx_opening_element", "jsx_closing_element"),
+        ];
+
+        let tree = self.layers.as_ref()?.try_tree()?;
+        let tag_name = |tag: Node<'_>| -> Option<(usize, usize)> {
+            let name = tag.child_by_field_name("name").or_else(|| {
+                (0..tag.child_count())
+                    .filter_map(|i| tag.child(i))
+                    .find(|child| child.kind() == "tag_name")
+            })?;
+            Some((name.start_byte(), name.end_byte()))
+        };
+
+        for node_offset in [offset, offset.saturating_sub(1)] {
+            let mut node = match tree
+                .root_node()
+                .descendant_for_byte_range(node_offset, node_offset)
+            {
+                Some(node) => node,
+                None => continue,
+            };
+            // the name can be nested, e.g. a member expression in jsx
+            for _ in 0..3 {
+                let parent = match node.parent() {
+                    Some(parent) => parent,
+                    None => break,
+                };
+                let pair = TAG_PAIRS.iter().find_map(|(open, close)| {
+                    if parent.kind() == *open {
+                        Some((false, *close))
+                    } else if parent.kind() == *close {
+                        Some((true, *open))
+                    } else {
+                        None
+                    }
+                });
+                if let Some((previous, counterpart)) = pair {
+                    let name = tag_name(parent)?;
+                    if name.0 > offset || name.1 < offset {
+                        break;
+                    }
+                    let other = self.find_tag_node(parent, previous, counterpart)?;
+                    let other = tag_name(other)?;
+                    if self.text.slice_to_cow(name.0..name.1)
+                        != self.text.slice_to_cow(other.0..other.1)
+                    {
+                        return None;
+                    }
+                    return Some([name, other]);
+                }
+                node = parent;
+            }
+        }
+        None
+    }
+
+    fn find_tag_in_siblings<'a>(
+        &self,
+        node: Node<'a>,
         previous: bool,
         tag: &str,
-    ) -> Option<usize> {
+    ) -> Option<Node<'a>> {
         let mut node = node;
         while let Some(sibling) = if previous {
             node.prev_sibling()
@@ -749,20 +825,22 @@ fn find_tag_in_siblings(
             node.next_sibling()
         } {
             if sibling.kind() == tag {
-                let offset = sibling.start_byte();
-                return Some(offset);
+                return Some(sibling);
             }
             node = sibling;
         }
         None
     }
 
-    fn find_tag_in_children(&self, node: Node, tag: &str) -> Option<usize> {
+    fn find_tag_in_children<'a>(
+        &self,
+        node: Node<'a>,
+        tag: &str,
+    ) -> Option<Node<'a>> {
         for i in 0..node.child_count() {
             if let Some(child) = node.child(i) {
                 if child.kind() == tag {
-                    let offset = child.start_byte();
-                    return Some(offset);
+                    return Some(child);
                 }
             }
         }
@@ -787,6 +865,35 @@ pub fn sticky_headers(&self, offset: usize) -> Option<Vec<usize>> {
         Some(offsets)
     }
 
+    /// The byte ranges of the identifiers between the offsets, in the order
+    /// they appear in. Grammars name their identifiers differently, like
+    /// `identifier`, `field_identifier` or `variable_name`, so these are the
+    /// named tokens that aren't a literal or a comment.
+    pub fn find_identifiers(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Option<Vec<(usize, usize)>> {
+        let tree = self.layers.as_ref()?.try_tree()?;
+        let mut identifiers = Vec::new();
+        let mut cursor = tree.root_node().walk();
+        loop {
+            let node = cursor.node();
+            let overlaps = node.start_byte() < end && node.end_byte() > start;
+            if overlaps && is_identifier_token(&node) {
+                identifiers.push((node.start_byte(), node.end_byte()));
+            }
+            if overlaps && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return Some(identifiers);
+                }
+            }
+        }
+    }
+
     pub fn find_enclosing_parentheses(
         &self,
         offset: usize,
@@ -859,6 +966,16 @@ pub fn find_enclosing_pair(&self, offset: usize) -> Option<(usize, usize)> {
     }
 }
 
+/// Whether the node is a token that can be a variable name
+fn is_identifier_token(node: &Node) -> bool {
+    let kind = node.kind();
+    node.is_named()
+        && node.child_count() == 0
+        && !["comment", "string", "literal"]
+            .iter()
+            .any(|k| kind.contains(k))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -895,4 +1012,70 @@ fn test_lens_iter() {
         assert_eq!(Some((90, 25)), iter.next());
         assert_eq!(None, iter.next());
     }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_find_identifiers() {
+        let text = r#"fn f(p: Point) -> i32 {
+    // x
+    let x = p.x + 1;
+    println!("x");
+    x
+}
+"#;
+        let mut syntax = Syntax::from_language(LapceLanguage::Rust);
+        syntax.parse(0, Rope::from(text), None);
+
+        let start = text.find("let").unwrap();
+        let end = text.find("println").unwrap();
+        let identifiers = syntax
+            .find_identifiers(start, end)
+            .unwrap()
+            .into_iter()
+            .map(|(s, e)| &text[s..e])
+            .collect::<Vec<_>>();
+        // The field identifier is found, but not the number
+        assert_eq!(identifiers, vec!["x", "p", "x"]);
+
+        // The comment and the string are skipped
+        let count = syntax
+            .find_identifiers(0, text.len())
+            .unwrap()
+            .into_iter()
+            .filter(|(s, e)| &text[*s..*e] == "x")
+            .count();
+        assert_eq!(count, 3);
+    }
+
+    #[cfg(feature = "lang-javascript")]
+    #[test]
+    fn test_find_linked_tag_names() {
+        let text = "const a = <div><p>hi</p></div>;";
+        let mut syntax = Syntax::from_language(LapceLanguage::Javascript);
+        syntax.parse(0, Rope::from(text), None);
+
+        let open = text.find("div").unwrap();
+        let close = text.rfind("div").unwrap();
+        // In the name, and right after it
+        for offset in [open + 1, open + 3] {
+            assert_eq!(
+                syntax.find_linked_tag_names(offset),
+                Some([(open, open + 3), (close, close + 3)])
+            );
+        }
+        assert_eq!(
+            syntax.find_linked_tag_names(close),
+            Some([(close, close + 3), (open, open + 3)])
+        );
+
+        let open = text.find("p>").unwrap();
+        let close = text.rfind("p>").unwrap();
+        assert_eq!(
+            syntax.find_linked_tag_names(open),
+            Some([(open, open + 1), (close, close + 1)])
+        );
+
+        // Not in a tag name
+        assert_eq!(syntax.find_linked_tag_names(text.find("hi").unwrap()), None);
+    }
 }
@@ -20,23 +20,32 @@
 use indexmap::IndexMap;
 use lapce_rpc::{
     core::{CoreNotification, CoreRpcHandler},
+    dap_types::ProcessInfo,
     file::FileNodeItem,
+    plugin::PluginId,
     proxy::{
         ProxyHandler, ProxyNotification, ProxyRequest, ProxyResponse,
         ProxyRpcHandler, SearchMatch,
     },
-    source_control::{DiffInfo, FileDiff},
+    source_control::{BlameHunk, CommitInfo, DiffInfo, FileDiff},
     style::{LineStyle, SemanticStyles},
     terminal::TermId,
     RequestId, RpcError,
 };
 use lapce_xi_rope::Rope;
-use lsp_types::{Position, Range, TextDocumentItem, Url};
+use lsp_types::{
+    FileChangeType, FileEvent, MessageType, Position, Range, SemanticTokens,
+    SemanticTokensDelta, SemanticTokensFullDeltaResult, SemanticTokensRangeResult,
+    ShowMessageParams, TextDocumentItem, Url,
+};
 use parking_lot::Mutex;
 
 use crate::{
     buffer::{get_mod_time, load_file, Buffer},
-    plugin::{catalog::PluginCatalog, remove_volt, PluginCatalogRpcHandler},
+    plugin::{
+        catalog::PluginCatalog, dap::run_pre_launch, remove_volt,
+        PluginCatalogRpcHandler,
+    },
     terminal::Terminal,
     watcher::{FileWatcher, Notify, WatchToken},
 };
@@ -50,6 +59,9 @@ pub struct Dispatcher {
     core_rpc: CoreRpcHandler,
     catalog_rpc: PluginCatalogRpcHandler,
     buffers: HashMap<PathBuf, Buffer>,
+    /// The last full semantic tokens of each file and the plugin that gave
+    /// them, which are the base of the delta requests
+    semantic_tokens: Arc<Mutex<HashMap<PathBuf, (PluginId, SemanticTokens)>>>,
     #[allow(deprecated)]
     terminals: HashMap<TermId, mio::channel::Sender<Msg>>,
     file_watcher: FileWatcher,
@@ -75,6 +87,7 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                     self.workspace.clone(),
                     self.core_rpc.clone(),
                     self.proxy_rpc.clone(),
+                    self.catalog_rpc.clone(),
                 ));
                 if let Some(workspace) = self.workspace.as_ref() {
                     self.file_watcher
@@ -122,16 +135,18 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                 path,
                 input,
                 position,
+                trigger_kind,
             } => {
-                self.catalog_rpc
-                    .completion(request_id, &path, input, position);
+                self.catalog_rpc.completion(
+                    request_id,
+                    &path,
+                    input,
+                    position,
+                    trigger_kind,
+                );
             }
-            SignatureHelp {
-                request_id,
-                path,
-                position,
-            } => {
-                self.catalog_rpc.signature_help(request_id, &path, position);
+            ExecuteCommand { plugin_id, command } => {
+                self.catalog_rpc.execute_command(plugin_id, command);
             }
             Shutdown {} => {
                 self.catalog_rpc.shutdown();
@@ -216,8 +231,15 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
             DapStart {
                 config,
                 breakpoints,
+                exception_filters,
+                function_breakpoints,
             } => {
-                let _ = self.catalog_rpc.dap_start(config, breakpoints);
+                let _ = self.catalog_rpc.dap_start(
+                    config,
+                    breakpoints,
+                    exception_filters,
+                    function_breakpoints,
+                );
             }
             DapProcessId {
                 dap_id,
@@ -232,6 +254,29 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
             DapPause { dap_id, thread_id } => {
                 let _ = self.catalog_rpc.dap_pause(dap_id, thread_id);
             }
+            DapStepOver { dap_id, thread_id } => {
+                let _ = self.catalog_rpc.dap_step_over(dap_id, thread_id);
+            }
+            DapStepInto { dap_id, thread_id } => {
+                let _ = self.catalog_rpc.dap_step_into(dap_id, thread_id);
+            }
+            DapStepOut { dap_id, thread_id } => {
+                let _ = self.catalog_rpc.dap_step_out(dap_id, thread_id);
+            }
+            DapStepBack { dap_id, thread_id } => {
+                let _ = self.catalog_rpc.dap_step_back(dap_id, thread_id);
+            }
+            DapStepInstruction { dap_id, thread_id } => {
+                let _ = self.catalog_rpc.dap_step_instruction(dap_id, thread_id);
+            }
+            DapGoto {
+                dap_id,
+                thread_id,
+                path,
+                line,
+            } => {
+                let _ = self.catalog_rpc.dap_goto(dap_id, thread_id, path, line);
+            }
             DapStop { dap_id } => {
                 let _ = self.catalog_rpc.dap_stop(dap_id);
             }
@@ -241,8 +286,15 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
             DapRestart {
                 dap_id,
                 breakpoints,
+                exception_filters,
+                function_breakpoints,
             } => {
-                let _ = self.catalog_rpc.dap_restart(dap_id, breakpoints);
+                let _ = self.catalog_rpc.dap_restart(
+                    dap_id,
+                    breakpoints,
+                    exception_filters,
+                    function_breakpoints,
+                );
             }
             DapSetBreakpoints {
                 dap_id,
@@ -253,6 +305,22 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                     self.catalog_rpc
                         .dap_set_breakpoints(dap_id, path, breakpoints);
             }
+            DapSetExceptionBreakpoints {
+                dap_id,
+                exception_filters,
+            } => {
+                let _ = self
+                    .catalog_rpc
+                    .dap_set_exception_breakpoints(dap_id, exception_filters);
+            }
+            DapSetFunctionBreakpoints {
+                dap_id,
+                breakpoints,
+            } => {
+                let _ = self
+                    .catalog_rpc
+                    .dap_set_function_breakpoints(dap_id, breakpoints);
+            }
             InstallVolt { volt } => {
                 let catalog_rpc = self.catalog_rpc.clone();
                 let _ = catalog_rpc.install_volt(volt);
@@ -316,6 +384,64 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                     }
                 }
             }
+            GitStageFiles { files } => {
+                if let Some(workspace) = self.workspace.as_ref() {
+                    match git_stage_files(workspace, &files) {
+                        Ok(()) => (),
+                        Err(e) => eprintln!("{e:?}"),
+                    }
+                }
+            }
+            GitUnstageFiles { files } => {
+                if let Some(workspace) = self.workspace.as_ref() {
+                    match git_unstage_files(workspace, &files) {
+                        Ok(()) => (),
+                        Err(e) => eprintln!("{e:?}"),
+                    }
+                }
+            }
+            GitStageLines {
+                path,
+                start_line,
+                end_line,
+            } => {
+                if let Some(workspace) = self.workspace.as_ref() {
+                    let content = self
+                        .buffers
+                        .get(&path)
+                        .map(|buffer| buffer.rope.to_string());
+                    match git_stage_lines(
+                        workspace,
+                        &path,
+                        content,
+                        start_line..end_line,
+                    ) {
+                        Ok(()) => (),
+                        Err(e) => eprintln!("{e:?}"),
+                    }
+                }
+            }
+            GitUnstageLines {
+                path,
+                start_line,
+                end_line,
+            } => {
+                if let Some(workspace) = self.workspace.as_ref() {
+                    let content = self
+                        .buffers
+                        .get(&path)
+                        .map(|buffer| buffer.rope.to_string());
+                    match git_unstage_lines(
+                        workspace,
+                        &path,
+                        content,
+                        start_line..end_line,
+                    ) {
+                        Ok(()) => (),
+                        Err(e) => eprintln!("{e:?}"),
+                    }
+                }
+            }
         }
     }
 
@@ -339,12 +465,16 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     Ok(ProxyResponse::NewBufferResponse { content, read_only }),
                 );
             }
-            BufferHead { path } => {
+            BufferHead { path, revision } => {
                 let result = if let Some(workspace) = self.workspace.as_ref() {
-                    let result = file_get_head(workspace, &path);
+                    let result = file_get_revision(
+                        workspace,
+                        &path,
+                        revision.as_deref().unwrap_or("HEAD"),
+                    );
                     if let Ok((_blob_id, content)) = result {
                         Ok(ProxyResponse::BufferHeadResponse {
-                            version: "head".to_string(),
+                            version: revision.unwrap_or_else(|| "head".to_string()),
                             content,
                         })
                     } else {
@@ -435,7 +565,51 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     proxy_rpc.handle_response(id, result);
                 });
             }
-            GetSignature { .. } => {}
+            GetSignature {
+                request_id,
+                path,
+                position,
+                context,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.signature_help(
+                    &path,
+                    position,
+                    context,
+                    move |_, result| {
+                        let result = result.map(|signature_help| {
+                            ProxyResponse::GetSignatureResponse {
+                                request_id,
+                                signature_help,
+                            }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            GetInlineCompletions {
+                request_id,
+                path,
+                position,
+                trigger_kind,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_inline_completions(
+                    &path,
+                    position,
+                    trigger_kind,
+                    move |plugin_id, result| {
+                        let result = result.map(|response| {
+                            ProxyResponse::GetInlineCompletionsResponse {
+                                request_id,
+                                plugin_id,
+                                items: response.into_items(),
+                            }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
             GetReferences { path, position } => {
                 let proxy_rpc = self.proxy_rpc.clone();
                 self.catalog_rpc.get_references(
@@ -460,6 +634,117 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     }
                 }
             }
+            GitBlame { path } => {
+                let workspace = self.workspace.clone();
+                // Blame what's in the buffer, which can have unsaved changes
+                let content = self
+                    .buffers
+                    .get(&path)
+                    .map(|buffer| buffer.rope.to_string());
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = match workspace {
+                        Some(workspace) => {
+                            git_blame(&workspace, &path, content.as_deref())
+                                .map(|hunks| ProxyResponse::GitBlameResponse {
+                                    hunks,
+                                })
+                                .map_err(|e| RpcError {
+                                    code: 0,
+                                    message: e.to_string(),
+                                })
+                        }
+                        None => Err(RpcError {
+                            code: 0,
+                            message: "no workspace set".to_string(),
+                        }),
+                    };
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            GitLog {
+                path,
+                author,
+                text,
+                skip,
+                limit,
+            } => {
+                let workspace = self.workspace.clone();
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = match workspace {
+                        Some(workspace) => git_log(
+                            &workspace,
+                            path.as_deref(),
+                            &author,
+                            &text,
+                            skip,
+                            limit,
+                        )
+                        .map(|commits| ProxyResponse::GitLogResponse { commits })
+                        .map_err(|e| RpcError {
+                            code: 0,
+                            message: e.to_string(),
+                        }),
+                        None => Err(RpcError {
+                            code: 0,
+                            message: "no workspace set".to_string(),
+                        }),
+                    };
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            GitCommitChanges { commit_id } => {
+                let workspace = self.workspace.clone();
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = match workspace {
+                        Some(workspace) => {
+                            git_commit_changes(&workspace, &commit_id)
+                                .map(|diffs| {
+                                    ProxyResponse::GitCommitChangesResponse { diffs }
+                                })
+                                .map_err(|e| RpcError {
+                                    code: 0,
+                                    message: e.to_string(),
+                                })
+                        }
+                        None => Err(RpcError {
+                            code: 0,
+                            message: "no workspace set".to_string(),
+                        }),
+                    };
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            GitRevertLines {
+                path,
+                start_line,
+                end_line,
+            } => {
+                let content = self
+                    .buffers
+                    .get(&path)
+                    .map(|buffer| buffer.rope.to_string());
+                let result = match self.workspace.as_ref() {
+                    Some(workspace) => git_revert_lines(
+                        workspace,
+                        &path,
+                        content,
+                        start_line..end_line,
+                    )
+                    .map(|content| ProxyResponse::GitRevertLinesResponse { content })
+                    .map_err(|e| RpcError {
+                        code: 0,
+                        message: e.to_string(),
+                    }),
+                    None => Err(RpcError {
+                        code: 0,
+                        message: "no workspace set".to_string(),
+                    }),
+                };
+                self.proxy_rpc.handle_response(id, result);
+            }
             GetDefinition {
                 request_id,
                 path,
@@ -500,6 +785,46 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     },
                 );
             }
+            GetImplementation {
+                request_id,
+                path,
+                position,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_implementation(
+                    &path,
+                    position,
+                    move |_, result| {
+                        let result = result.map(|implementation| {
+                            ProxyResponse::GetImplementationResponse {
+                                request_id,
+                                implementation,
+                            }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            GetDeclaration {
+                request_id,
+                path,
+                position,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_declaration(
+                    &path,
+                    position,
+                    move |_, result| {
+                        let result = result.map(|declaration| {
+                            ProxyResponse::GetDeclarationResponse {
+                                request_id,
+                                declaration,
+                            }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
             GetInlayHints { path } => {
                 let proxy_rpc = self.proxy_rpc.clone();
                 let buffer = self.buffers.get(&path).unwrap();
@@ -514,6 +839,39 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                         proxy_rpc.handle_response(id, result);
                     });
             }
+            GetDocumentLinks { path } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc
+                    .get_document_links(&path, move |_, result| {
+                        let result = result
+                            .map(|links| ProxyResponse::GetDocumentLinks { links });
+                        proxy_rpc.handle_response(id, result);
+                    });
+            }
+            GetDocumentColors { path } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc
+                    .get_document_colors(&path, move |_, result| {
+                        let result = result.map(|colors| {
+                            ProxyResponse::GetDocumentColors { colors }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    });
+            }
+            GetColorPresentations { path, color, range } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_color_presentations(
+                    &path,
+                    color,
+                    range,
+                    move |_, result| {
+                        let result = result.map(|presentations| {
+                            ProxyResponse::GetColorPresentations { presentations }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
             GetSemanticTokens { path } => {
                 let buffer = self.buffers.get(&path).unwrap();
                 let text = buffer.rope.clone();
@@ -543,21 +901,124 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                         }
                     };
 
-                let proxy_rpc = self.proxy_rpc.clone();
-                self.catalog_rpc.get_semantic_tokens(
-                    &path,
-                    move |plugin_id, result| match result {
-                        Ok(result) => {
-                            catalog_rpc.format_semantic_tokens(
-                                plugin_id,
-                                result,
+                let semantic_tokens = self.semantic_tokens.clone();
+                let previous = semantic_tokens.lock().get(&path).and_then(
+                    |(plugin_id, tokens)| {
+                        Some((*plugin_id, tokens.result_id.clone()?))
+                    },
+                );
+                let Some((plugin_id, previous_result_id)) = previous else {
+                    get_full_semantic_tokens(
+                        catalog_rpc,
+                        semantic_tokens,
+                        path,
+                        text,
+                        handle_tokens,
+                    );
+                    return;
+                };
+
+                self.catalog_rpc.get_semantic_tokens_delta(
+                    plugin_id,
+                    &path.clone(),
+                    previous_result_id.clone(),
+                    move |result| {
+                        let tokens = match result {
+                            Ok(SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                                Some(tokens)
+                            }
+                            Ok(SemanticTokensFullDeltaResult::TokensDelta(
+                                delta,
+                            )) => semantic_tokens
+                                .lock()
+                                .get(&path)
+                                .filter(|(_, tokens)| {
+                                    tokens.result_id.as_ref()
+                                        == Some(&previous_result_id)
+                                })
+                                .and_then(|(_, tokens)| {
+                                    apply_semantic_tokens_delta(tokens, delta)
+                                }),
+                            _ => None,
+                        };
+                        match tokens {
+                            Some(tokens) => {
+                                semantic_tokens
+                                    .lock()
+                                    .insert(path, (plugin_id, tokens.clone()));
+                                catalog_rpc.format_semantic_tokens(
+                                    plugin_id,
+                                    tokens,
+                                    text,
+                                    Box::new(handle_tokens),
+                                );
+                            }
+                            // the delta couldn't be applied on what we have,
+                            // so start over with the full tokens
+                            None => get_full_semantic_tokens(
+                                catalog_rpc,
+                                semantic_tokens,
+                                path,
                                 text,
-                                Box::new(handle_tokens),
+                                handle_tokens,
+                            ),
+                        }
+                    },
+                );
+            }
+            GetSemanticTokensRange { path, range } => {
+                let buffer = self.buffers.get(&path).unwrap();
+                let text = buffer.rope.clone();
+                let rev = buffer.rev;
+                let len = buffer.len();
+                let local_path = path.clone();
+                let proxy_rpc = self.proxy_rpc.clone();
+                let catalog_rpc = self.catalog_rpc.clone();
+
+                let handle_tokens =
+                    move |result: Result<Vec<LineStyle>, RpcError>| match result {
+                        Ok(styles) => {
+                            proxy_rpc.handle_response(
+                                id,
+                                Ok(ProxyResponse::GetSemanticTokensRange {
+                                    styles: SemanticStyles {
+                                        rev,
+                                        path: local_path,
+                                        styles,
+                                        len,
+                                    },
+                                }),
                             );
                         }
                         Err(e) => {
                             proxy_rpc.handle_response(id, Err(e));
                         }
+                    };
+
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_semantic_tokens_range(
+                    &path,
+                    range,
+                    move |plugin_id, result| {
+                        let tokens = match result {
+                            Ok(SemanticTokensRangeResult::Tokens(tokens)) => tokens,
+                            Ok(SemanticTokensRangeResult::Partial(partial)) => {
+                                SemanticTokens {
+                                    result_id: None,
+                                    data: partial.data,
+                                }
+                            }
+                            Err(e) => {
+                                proxy_rpc.handle_response(id, Err(e));
+                                return;
+                            }
+                        };
+                        catalog_rpc.format_semantic_tokens(
+                            plugin_id,
+                            tokens,
+                            text,
+                            Box::new(handle_tokens),
+                        );
                     },
                 );
             }
@@ -608,31 +1069,71 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                         proxy_rpc.handle_response(id, result);
                     });
             }
-            PrepareRename { path, position } => {
+            GetRangeFormatting { path, range } => {
                 let proxy_rpc = self.proxy_rpc.clone();
-                self.catalog_rpc.prepare_rename(
+                self.catalog_rpc.get_range_formatting(
                     &path,
-                    position,
+                    range,
                     move |_, result| {
-                        let result =
-                            result.map(|resp| ProxyResponse::PrepareRename { resp });
+                        let result = result.map(|edits| {
+                            ProxyResponse::GetRangeFormatting { edits }
+                        });
                         proxy_rpc.handle_response(id, result);
                     },
                 );
             }
-            Rename {
-                path,
-                position,
-                new_name,
-            } => {
+            GetOnTypeFormatting { path, position, ch } => {
                 let proxy_rpc = self.proxy_rpc.clone();
-                self.catalog_rpc.rename(
+                self.catalog_rpc.get_on_type_formatting(
                     &path,
                     position,
-                    new_name,
+                    ch,
                     move |_, result| {
-                        let result =
-                            result.map(|edit| ProxyResponse::Rename { edit });
+                        let result = result.map(|edits| {
+                            ProxyResponse::GetOnTypeFormatting { edits }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            PrepareRename { path, position } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.prepare_rename(
+                    &path,
+                    position,
+                    move |_, result| {
+                        let result =
+                            result.map(|resp| ProxyResponse::PrepareRename { resp });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            LinkedEditingRange { path, position } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.linked_editing_range(
+                    &path,
+                    position,
+                    move |_, result| {
+                        let result = result.map(|ranges| {
+                            ProxyResponse::LinkedEditingRange { ranges }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            Rename {
+                path,
+                position,
+                new_name,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.rename(
+                    &path,
+                    position,
+                    new_name,
+                    move |_, result| {
+                        let result =
+                            result.map(|edit| ProxyResponse::Rename { edit });
                         proxy_rpc.handle_response(id, result);
                     },
                 );
@@ -765,31 +1266,40 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                         std::fs::OpenOptions::new()
                             .write(true)
                             .create_new(true)
-                            .open(path)
+                            .open(&path)
                     })
                     .map(|_| ProxyResponse::Success {})
                     .map_err(|e| RpcError {
                         code: 0,
                         message: e.to_string(),
                     });
+                if result.is_ok() {
+                    self.catalog_rpc.did_create_files(&path);
+                }
                 self.respond_rpc(id, result);
             }
             CreateDirectory { path } => {
-                let result = std::fs::create_dir_all(path)
+                let result = std::fs::create_dir_all(&path)
                     .map(|_| ProxyResponse::Success {})
                     .map_err(|e| RpcError {
                         code: 0,
                         message: e.to_string(),
                     });
+                if result.is_ok() {
+                    self.catalog_rpc.did_create_files(&path);
+                }
                 self.respond_rpc(id, result);
             }
             TrashPath { path } => {
-                let result = trash::delete(path)
+                let result = trash::delete(&path)
                     .map(|_| ProxyResponse::Success {})
                     .map_err(|e| RpcError {
                         code: 0,
                         message: e.to_string(),
                     });
+                if result.is_ok() {
+                    self.catalog_rpc.did_delete_files(&path);
+                }
                 self.respond_rpc(id, result);
             }
             DuplicatePath {
@@ -826,20 +1336,47 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
             RenamePath { from, to } => {
                 // We first check if the destination already exists, because rename can overwrite it
                 // and that's not the default behavior we want for when a user renames a document.
-                let result = if to.exists() {
-                    Err(RpcError {
+                if to.exists() {
+                    let result = Err(RpcError {
                         code: 0,
                         message: format!("{to:?} already exists"),
-                    })
-                } else {
-                    std::fs::rename(from, to)
-                        .map(|_| ProxyResponse::Success {})
-                        .map_err(|e| RpcError {
-                            code: 0,
-                            message: e.to_string(),
-                        })
-                };
-                self.respond_rpc(id, result);
+                    });
+                    self.respond_rpc(id, result);
+                    return;
+                }
+
+                // The language servers get to update the references to the path,
+                // such as imports, before it's renamed.
+                let proxy_rpc = self.proxy_rpc.clone();
+                let core_rpc = self.core_rpc.clone();
+                let catalog_rpc = self.catalog_rpc.clone();
+                self.catalog_rpc.will_rename_files(
+                    &from.clone(),
+                    &to.clone(),
+                    move |edit| {
+                        // The edits can be to the renamed file itself, so the
+                        // rename waits for the editor to have applied them.
+                        thread::spawn(move || {
+                            if let Some(edit) = edit {
+                                if let Err(e) =
+                                    core_rpc.apply_workspace_edit_sync(edit)
+                                {
+                                    eprintln!("{e:?}");
+                                }
+                            }
+                            let result = std::fs::rename(&from, &to)
+                                .map(|_| ProxyResponse::Success {})
+                                .map_err(|e| RpcError {
+                                    code: 0,
+                                    message: e.to_string(),
+                                });
+                            if result.is_ok() {
+                                catalog_rpc.did_rename_files(&from, &to);
+                            }
+                            proxy_rpc.handle_response(id, result);
+                        });
+                    },
+                );
             }
             GetSelectionRange { positions, path } => {
                 let proxy_rpc = self.proxy_rpc.clone();
@@ -872,6 +1409,175 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     },
                 );
             }
+            DapScopes { dap_id, frame_id } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc
+                    .dap_scopes(dap_id, frame_id, move |result| {
+                        let result =
+                            result.map(|resp| ProxyResponse::DapScopesResponse {
+                                scopes: resp.scopes,
+                            });
+                        proxy_rpc.handle_response(id, result);
+                    });
+            }
+            DapVariables {
+                dap_id,
+                variables_reference,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_variables(
+                    dap_id,
+                    variables_reference,
+                    move |result| {
+                        let result =
+                            result.map(|resp| ProxyResponse::DapVariablesResponse {
+                                variables: resp.variables,
+                            });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapEvaluate {
+                dap_id,
+                expression,
+                frame_id,
+                context,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_evaluate(
+                    dap_id,
+                    expression,
+                    frame_id,
+                    context,
+                    move |result| {
+                        let result = result
+                            .map(|resp| ProxyResponse::DapEvaluateResponse { resp });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapSetVariable {
+                dap_id,
+                variables_reference,
+                name,
+                value,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_set_variable(
+                    dap_id,
+                    variables_reference,
+                    name,
+                    value,
+                    move |result| {
+                        let result = result.map(|resp| {
+                            ProxyResponse::DapSetVariableResponse { resp }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapCompletions {
+                dap_id,
+                frame_id,
+                text,
+                column,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_completions(
+                    dap_id,
+                    frame_id,
+                    text,
+                    column,
+                    move |result| {
+                        let result = result.map(|resp| {
+                            ProxyResponse::DapCompletionsResponse {
+                                targets: resp.targets,
+                            }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapDisassemble {
+                dap_id,
+                memory_reference,
+                instruction_offset,
+                instruction_count,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_disassemble(
+                    dap_id,
+                    memory_reference,
+                    instruction_offset,
+                    instruction_count,
+                    move |result| {
+                        let result = result.map(|resp| {
+                            ProxyResponse::DapDisassembleResponse {
+                                instructions: resp.instructions,
+                            }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapReadMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                count,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_read_memory(
+                    dap_id,
+                    memory_reference,
+                    offset,
+                    count,
+                    move |result| {
+                        let result = result.map(|resp| {
+                            ProxyResponse::DapReadMemoryResponse { resp }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            RunPreLaunch { config } => {
+                let workspace = self.workspace.clone();
+                let core_rpc = self.core_rpc.clone();
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result =
+                        run_pre_launch(&config, workspace.as_deref(), &core_rpc)
+                            .map(|_| ProxyResponse::Success {})
+                            .map_err(|e| RpcError {
+                                code: 0,
+                                message: e.to_string(),
+                            });
+                    if let Err(err) = result.as_ref() {
+                        core_rpc.show_message(
+                            "Pre-launch".to_string(),
+                            ShowMessageParams {
+                                typ: MessageType::ERROR,
+                                message: err.message.clone(),
+                            },
+                        );
+                    }
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            ListProcesses {} => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = list_processes()
+                        .map(|processes| ProxyResponse::ListProcessesResponse {
+                            processes,
+                        })
+                        .map_err(|e| RpcError {
+                            code: 0,
+                            message: e.to_string(),
+                        });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
         }
     }
 }
@@ -889,6 +1595,7 @@ pub fn new(core_rpc: CoreRpcHandler, proxy_rpc: ProxyRpcHandler) -> Self {
             core_rpc,
             catalog_rpc: plugin_rpc,
             buffers: HashMap::new(),
+            semantic_tokens: Arc::new(Mutex::new(HashMap::new())),
             terminals: HashMap::new(),
             file_watcher,
             window_id: 1,
@@ -901,9 +1608,71 @@ fn respond_rpc(&self, id: RequestId, result: Result<ProxyResponse, RpcError>) {
     }
 }
 
+/// Request the full semantic tokens of the file, and keep them for the delta
+/// requests that follow
+fn get_full_semantic_tokens(
+    catalog_rpc: PluginCatalogRpcHandler,
+    semantic_tokens: Arc<Mutex<HashMap<PathBuf, (PluginId, SemanticTokens)>>>,
+    path: PathBuf,
+    text: Rope,
+    f: impl FnOnce(Result<Vec<LineStyle>, RpcError>) + Clone + Send + 'static,
+) {
+    let plugin_catalog = catalog_rpc.clone();
+    plugin_catalog.get_semantic_tokens(&path.clone(), move |plugin_id, result| {
+        match result {
+            Ok(tokens) => {
+                semantic_tokens
+                    .lock()
+                    .insert(path, (plugin_id, tokens.clone()));
+                catalog_rpc.format_semantic_tokens(
+                    plugin_id,
+                    tokens,
+                    text,
+                    Box::new(f),
+                );
+            }
+            Err(e) => {
+                semantic_tokens.lock().remove(&path);
+                f(Err(e));
+            }
+        }
+    });
+}
+
+/// Apply the edits of a delta response to the tokens it was made against.
+///
+/// Returns `None` if an edit doesn't fall on whole tokens or is out of bounds,
+/// in which case the full tokens need to be requested again.
+fn apply_semantic_tokens_delta(
+    previous: &SemanticTokens,
+    delta: SemanticTokensDelta,
+) -> Option<SemanticTokens> {
+    let mut data = previous.data.clone();
+    let mut edits = delta.edits;
+    // the edits all refer to the previous tokens, so apply them from the end
+    edits.sort_by(|a, b| b.start.cmp(&a.start));
+    for edit in edits {
+        // the edit offsets count integers, and each token is five of them
+        if edit.start % 5 != 0 || edit.delete_count % 5 != 0 {
+            return None;
+        }
+        let start = edit.start as usize / 5;
+        let end = start + edit.delete_count as usize / 5;
+        if end > data.len() {
+            return None;
+        }
+        data.splice(start..end, edit.data.unwrap_or_default());
+    }
+    Some(SemanticTokens {
+        result_id: delta.result_id,
+        data,
+    })
+}
+
 struct FileWatchNotifier {
     core_rpc: CoreRpcHandler,
     proxy_rpc: ProxyRpcHandler,
+    catalog_rpc: PluginCatalogRpcHandler,
     workspace: Option<PathBuf>,
     workspace_fs_change_handler: Arc<Mutex<Option<Sender<bool>>>>,
     last_diff: Arc<Mutex<DiffInfo>>,
@@ -920,11 +1689,13 @@ fn new(
         workspace: Option<PathBuf>,
         core_rpc: CoreRpcHandler,
         proxy_rpc: ProxyRpcHandler,
+        catalog_rpc: PluginCatalogRpcHandler,
     ) -> Self {
         let notifier = Self {
             workspace,
             core_rpc,
             proxy_rpc,
+            catalog_rpc,
             workspace_fs_change_handler: Arc::new(Mutex::new(None)),
             last_diff: Arc::new(Mutex::new(DiffInfo::default())),
         };
@@ -963,6 +1734,8 @@ fn handle_open_file_fs_event(&self, event: notify::Event) {
     }
 
     fn handle_workspace_fs_event(&self, event: notify::Event) {
+        self.notify_watched_files(&event);
+
         let explorer_change = match &event.kind {
             notify::EventKind::Create(_)
             | notify::EventKind::Remove(_)
@@ -1016,6 +1789,59 @@ fn handle_workspace_fs_event(&self, event: notify::Event) {
         });
         *handler = Some(sender);
     }
+
+    /// Let the language servers know about the changes of the files they watch
+    fn notify_watched_files(&self, event: &notify::Event) {
+        use notify::event::{ModifyKind, RenameMode};
+
+        let changes: Vec<(&PathBuf, FileChangeType)> = match &event.kind {
+            notify::EventKind::Create(_) => event
+                .paths
+                .iter()
+                .map(|path| (path, FileChangeType::CREATED))
+                .collect(),
+            notify::EventKind::Remove(_) => event
+                .paths
+                .iter()
+                .map(|path| (path, FileChangeType::DELETED))
+                .collect(),
+            notify::EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => event
+                .paths
+                .iter()
+                .zip([FileChangeType::DELETED, FileChangeType::CREATED])
+                .collect(),
+            notify::EventKind::Modify(ModifyKind::Name(_)) => event
+                .paths
+                .iter()
+                .map(|path| {
+                    if path.exists() {
+                        (path, FileChangeType::CREATED)
+                    } else {
+                        (path, FileChangeType::DELETED)
+                    }
+                })
+                .collect(),
+            notify::EventKind::Modify(_) => event
+                .paths
+                .iter()
+                .map(|path| (path, FileChangeType::CHANGED))
+                .collect(),
+            _ => return,
+        };
+
+        let changes: Vec<FileEvent> = changes
+            .into_iter()
+            .filter_map(|(path, typ)| {
+                Some(FileEvent {
+                    uri: Url::from_file_path(path).ok()?,
+                    typ,
+                })
+            })
+            .collect();
+        if !changes.is_empty() {
+            self.catalog_rpc.did_change_watched_files(changes);
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -1161,7 +1987,7 @@ fn git_diff_new(workspace_path: &Path) -> Option<DiffInfo> {
         }
     }
 
-    let mut deltas = Vec::new();
+    let mut unstaged = Vec::new();
     let mut diff_options = DiffOptions::new();
     let diff = repo
         .diff_index_to_workdir(
@@ -1175,9 +2001,10 @@ fn git_diff_new(workspace_path: &Path) -> Option<DiffInfo> {
         .ok()?;
     for delta in diff.deltas() {
         if let Some(delta) = git_delta_format(workspace_path, &delta) {
-            deltas.push(delta);
+            unstaged.push(delta);
         }
     }
+    let mut staged = Vec::new();
     let cached_diff = repo
         .diff_tree_to_index(
             repo.find_tree(repo.revparse_single("HEAD^{tree}").ok()?.id())
@@ -1189,9 +2016,22 @@ fn git_diff_new(workspace_path: &Path) -> Option<DiffInfo> {
         .ok()?;
     for delta in cached_diff.deltas() {
         if let Some(delta) = git_delta_format(workspace_path, &delta) {
-            deltas.push(delta);
+            staged.push(delta);
         }
     }
+
+    Some(DiffInfo {
+        head: name,
+        branches,
+        tags,
+        diffs: git_file_diffs(&unstaged),
+        staged: git_file_diffs(&staged),
+    })
+}
+
+/// The file diffs of the deltas, where a file added with the same content as
+/// a deleted one is a rename.
+fn git_file_diffs(deltas: &[(git2::Delta, git2::Oid, PathBuf)]) -> Vec<FileDiff> {
     let mut renames = Vec::new();
     let mut renamed_deltas = HashSet::new();
 
@@ -1233,19 +2073,17 @@ fn git_diff_new(workspace_path: &Path) -> Option<DiffInfo> {
         | FileDiff::Renamed(p, _)
         | FileDiff::Deleted(p) => p.clone(),
     });
-    Some(DiffInfo {
-        head: name,
-        branches,
-        tags,
-        diffs: file_diffs,
-    })
+    file_diffs
 }
 
-fn file_get_head(workspace_path: &Path, path: &Path) -> Result<(String, String)> {
+fn file_get_revision(
+    workspace_path: &Path,
+    path: &Path,
+    revision: &str,
+) -> Result<(String, String)> {
     let repo = Repository::discover(workspace_path)?;
-    let head = repo.head()?;
-    let tree = head.peel_to_tree()?;
-    let tree_entry = tree.get_path(path.strip_prefix(workspace_path)?)?;
+    let tree = repo.revparse_single(revision)?.peel_to_tree()?;
+    let tree_entry = tree.get_path(&git_relative_path(&repo, path)?)?;
     let blob = repo.find_blob(tree_entry.id())?;
     let id = blob.id().to_string();
     let content = std::str::from_utf8(blob.content())
@@ -1254,6 +2092,519 @@ fn file_get_head(workspace_path: &Path, path: &Path) -> Result<(String, String)>
     Ok((id, content))
 }
 
+/// The path of the file relative to the root of the repository, which can be
+/// above the workspace.
+fn git_relative_path(repo: &Repository, path: &Path) -> Result<PathBuf> {
+    let workdir = repo
+        .workdir()
+        .ok_or(anyhow!("the repository has no working directory"))?;
+    Ok(path.strip_prefix(workdir)?.to_path_buf())
+}
+
+fn git_stage_files(workspace_path: &Path, files: &[PathBuf]) -> Result<()> {
+    let repo = Repository::discover(workspace_path)?;
+    let mut index = repo.index()?;
+    for path in files {
+        let relative_path = git_relative_path(&repo, path)?;
+        if path.exists() {
+            index.add_path(&relative_path)?;
+        } else {
+            index.remove_path(&relative_path)?;
+        }
+    }
+    index.write()?;
+    Ok(())
+}
+
+fn git_unstage_files(workspace_path: &Path, files: &[PathBuf]) -> Result<()> {
+    let repo = Repository::discover(workspace_path)?;
+    let relative_paths = files
+        .iter()
+        .map(|path| git_relative_path(&repo, path))
+        .collect::<Result<Vec<_>>>()?;
+    match repo
+        .head()
+        .and_then(|head| head.peel(git2::ObjectType::Commit))
+    {
+        Ok(head) => {
+            repo.reset_default(Some(&head), relative_paths)?;
+        }
+        // Without any commit, unstaging is removing the files from the index
+        Err(_) => {
+            let mut index = repo.index()?;
+            for path in relative_paths {
+                index.remove_path(&path)?;
+            }
+            index.write()?;
+        }
+    }
+    Ok(())
+}
+
+/// Stage the changes of the lines of the file, where `content` is what's in
+/// the buffer, if it's open, instead of what's on disk.
+fn git_stage_lines(
+    workspace_path: &Path,
+    path: &Path,
+    content: Option<String>,
+    lines: std::ops::Range<usize>,
+) -> Result<()> {
+    let repo = Repository::discover(workspace_path)?;
+    let relative_path = git_relative_path(&repo, path)?;
+    let content = git_working_content(path, content)?;
+    let mut index = repo.index()?;
+    let (entry, staged) = git_index_content(&repo, &index, &relative_path)?;
+
+    let staged = git_apply_lines(&staged, &content, |line| lines.contains(&line))?;
+    git_index_set_content(&mut index, entry, &relative_path, &staged)?;
+    Ok(())
+}
+
+/// Unstage the changes of the lines of the file, where `content` is what's in
+/// the buffer, if it's open, instead of what's on disk.
+fn git_unstage_lines(
+    workspace_path: &Path,
+    path: &Path,
+    content: Option<String>,
+    lines: std::ops::Range<usize>,
+) -> Result<()> {
+    let repo = Repository::discover(workspace_path)?;
+    let relative_path = git_relative_path(&repo, path)?;
+    let content = git_working_content(path, content)?;
+    let mut index = repo.index()?;
+    let (entry, staged) = git_index_content(&repo, &index, &relative_path)?;
+    let head = git_head_content(&repo, &relative_path)?;
+
+    // The lines are of the working content, so they're mapped to the lines of
+    // the staged content first
+    let patch = git2::Patch::from_buffers(
+        &staged,
+        None,
+        &content,
+        None,
+        Some(DiffOptions::new().context_lines(0)),
+    )?;
+    let start = git_old_line(&patch, lines.start, false)?;
+    let end = git_old_line(&patch, lines.end, true)?;
+    let lines = start..end.max(start + 1);
+
+    let is_new = head.is_none();
+    let staged = git_apply_lines(&head.unwrap_or_default(), &staged, |line| {
+        !lines.contains(&line)
+    })?;
+    if is_new && staged.is_empty() {
+        // Everything of a new file is unstaged
+        index.remove_path(&relative_path)?;
+        index.write()?;
+        return Ok(());
+    }
+    git_index_set_content(&mut index, entry, &relative_path, &staged)?;
+    Ok(())
+}
+
+/// The content of the file with the unstaged changes of the lines reverted,
+/// where `content` is what's in the buffer, if it's open, instead of what's on
+/// disk.
+fn git_revert_lines(
+    workspace_path: &Path,
+    path: &Path,
+    content: Option<String>,
+    lines: std::ops::Range<usize>,
+) -> Result<String> {
+    let repo = Repository::discover(workspace_path)?;
+    let relative_path = git_relative_path(&repo, path)?;
+    let content = git_working_content(path, content)?;
+    let index = repo.index()?;
+    let (_, staged) = git_index_content(&repo, &index, &relative_path)?;
+
+    let content = git_apply_lines(&staged, &content, |line| !lines.contains(&line))?;
+    Ok(String::from_utf8(content).with_context(|| "content bytes to string")?)
+}
+
+fn git_working_content(path: &Path, content: Option<String>) -> Result<Vec<u8>> {
+    match content {
+        Some(content) => Ok(content.into_bytes()),
+        None => Ok(fs::read(path)?),
+    }
+}
+
+/// The index entry of the file, if it's in the index, and its staged content,
+/// which is empty if it isn't.
+fn git_index_content(
+    repo: &Repository,
+    index: &git2::Index,
+    relative_path: &Path,
+) -> Result<(Option<git2::IndexEntry>, Vec<u8>)> {
+    match index.get_path(relative_path, 0) {
+        Some(entry) => {
+            let content = repo.find_blob(entry.id)?.content().to_vec();
+            Ok((Some(entry), content))
+        }
+        None => Ok((None, Vec::new())),
+    }
+}
+
+/// The content of the file in the `HEAD` commit, if it's there.
+fn git_head_content(
+    repo: &Repository,
+    relative_path: &Path,
+) -> Result<Option<Vec<u8>>> {
+    let tree = match repo.head().and_then(|head| head.peel_to_tree()) {
+        Ok(tree) => tree,
+        Err(_) => return Ok(None),
+    };
+    let entry = match tree.get_path(relative_path) {
+        Ok(entry) => entry,
+        Err(_) => return Ok(None),
+    };
+    Ok(Some(repo.find_blob(entry.id())?.content().to_vec()))
+}
+
+/// Write the staged content of the file to the index, reusing its entry if it
+/// already has one.
+fn git_index_set_content(
+    index: &mut git2::Index,
+    entry: Option<git2::IndexEntry>,
+    relative_path: &Path,
+    content: &[u8],
+) -> Result<()> {
+    // The stat of a reused entry is of the file on disk, which no longer has
+    // the staged content, so it's cleared for the file to be compared by its
+    // content again instead of being taken as unchanged
+    let entry = entry.map(|entry| git2::IndexEntry {
+        ctime: git2::IndexTime::new(0, 0),
+        mtime: git2::IndexTime::new(0, 0),
+        file_size: 0,
+        ..entry
+    });
+    let entry = entry.unwrap_or_else(|| git2::IndexEntry {
+        ctime: git2::IndexTime::new(0, 0),
+        mtime: git2::IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode: 0o100644,
+        uid: 0,
+        gid: 0,
+        file_size: 0,
+        id: git2::Oid::zero(),
+        flags: 0,
+        flags_extended: 0,
+        path: relative_path
+            .to_string_lossy()
+            .replace('\\', "/")
+            .into_bytes(),
+    });
+    index.add_frombuffer(&entry, content)?;
+    index.write()?;
+    Ok(())
+}
+
+/// Apply the changes from `old` to `new` that are selected, where `selected`
+/// is given the line of `new` that's added, or the line of `new` before which
+/// the lines of `old` are deleted.
+fn git_apply_lines(
+    old: &[u8],
+    new: &[u8],
+    selected: impl Fn(usize) -> bool,
+) -> Result<Vec<u8>> {
+    let patch = git2::Patch::from_buffers(
+        old,
+        None,
+        new,
+        None,
+        Some(DiffOptions::new().context_lines(0)),
+    )?;
+    let old_lines = old.split_inclusive(|b| *b == b'\n').collect::<Vec<_>>();
+    // The last line of a side can be without its newline, which it needs when
+    // other lines are added after it
+    let push_line = |content: &mut Vec<u8>, line: &[u8]| {
+        if matches!(content.last(), Some(b) if *b != b'\n') {
+            content.push(b'\n');
+        }
+        content.extend_from_slice(line);
+    };
+
+    let mut content = Vec::with_capacity(new.len());
+    let mut old_line = 0;
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, lines_in_hunk) = patch.hunk(hunk_idx)?;
+        // The starts are 1 based, except when the hunk has no lines on that
+        // side, where they're the line before it
+        let hunk_old_start = if hunk.old_lines() == 0 {
+            hunk.old_start()
+        } else {
+            hunk.old_start() - 1
+        } as usize;
+        let mut new_line = if hunk.new_lines() == 0 {
+            hunk.new_start()
+        } else {
+            hunk.new_start() - 1
+        } as usize;
+
+        for line in &old_lines[old_line..hunk_old_start] {
+            content.extend_from_slice(line);
+        }
+        old_line = hunk_old_start;
+
+        for line_idx in 0..lines_in_hunk {
+            let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+            match line.origin() {
+                '-' => {
+                    if !selected(new_line) {
+                        push_line(&mut content, line.content());
+                    }
+                    old_line += 1;
+                }
+                '+' => {
+                    if selected(new_line) {
+                        push_line(&mut content, line.content());
+                    }
+                    new_line += 1;
+                }
+                ' ' => {
+                    push_line(&mut content, line.content());
+                    old_line += 1;
+                    new_line += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+    for line in &old_lines[old_line.min(old_lines.len())..] {
+        content.extend_from_slice(line);
+    }
+    Ok(content)
+}
+
+/// The line of the old side of the patch that corresponds to the line of the
+/// new side. The lines only in the new side correspond to the start of the
+/// hunk, or its end if `is_end` is set.
+fn git_old_line(
+    patch: &git2::Patch,
+    new_line: usize,
+    is_end: bool,
+) -> Result<usize> {
+    let mut old_line = new_line as i64;
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(hunk_idx)?;
+        let hunk_old_start = if hunk.old_lines() == 0 {
+            hunk.old_start()
+        } else {
+            hunk.old_start() - 1
+        } as usize;
+        let hunk_new_start = if hunk.new_lines() == 0 {
+            hunk.new_start()
+        } else {
+            hunk.new_start() - 1
+        } as usize;
+        if new_line < hunk_new_start {
+            break;
+        }
+        if new_line < hunk_new_start + hunk.new_lines() as usize {
+            return Ok(if is_end {
+                hunk_old_start + hunk.old_lines() as usize
+            } else {
+                hunk_old_start
+            });
+        }
+        old_line += hunk.old_lines() as i64 - hunk.new_lines() as i64;
+    }
+    Ok(old_line.max(0) as usize)
+}
+
+/// Blame the lines of the file, where `content` is what's in the buffer, if
+/// it's open, instead of what's on disk.
+fn git_blame(
+    workspace_path: &Path,
+    path: &Path,
+    content: Option<&str>,
+) -> Result<Vec<BlameHunk>> {
+    let repo = Repository::discover(workspace_path)?;
+    let blame = repo.blame_file(&git_relative_path(&repo, path)?, None)?;
+    let blame = match content {
+        Some(content) => blame.blame_buffer(content.as_bytes())?,
+        None => blame,
+    };
+
+    // The hunks of the same commit share everything but the lines
+    let mut commits: HashMap<git2::Oid, BlameHunk> = HashMap::new();
+    let mut hunks = Vec::new();
+    for hunk in blame.iter() {
+        let id = hunk.final_commit_id();
+        let commit = match commits.get(&id) {
+            Some(commit) => commit.clone(),
+            None => {
+                let commit = if id.is_zero() {
+                    BlameHunk {
+                        commit_id: id.to_string(),
+                        author: String::new(),
+                        email: String::new(),
+                        time: 0,
+                        summary: String::new(),
+                        message: String::new(),
+                        start_line: 0,
+                        lines: 0,
+                    }
+                } else {
+                    let commit = repo.find_commit(id)?;
+                    let author = commit.author();
+                    BlameHunk {
+                        commit_id: id.to_string(),
+                        author: author.name().unwrap_or_default().to_string(),
+                        email: author.email().unwrap_or_default().to_string(),
+                        time: commit.time().seconds(),
+                        summary: commit.summary().unwrap_or_default().to_string(),
+                        message: commit.message().unwrap_or_default().to_string(),
+                        start_line: 0,
+                        lines: 0,
+                    }
+                };
+                commits.insert(id, commit.clone());
+                commit
+            }
+        };
+        hunks.push(BlameHunk {
+            start_line: hunk.final_start_line().saturating_sub(1),
+            lines: hunk.lines_in_hunk(),
+            ..commit
+        });
+    }
+    Ok(hunks)
+}
+
+fn git_log(
+    workspace_path: &Path,
+    path: Option<&Path>,
+    author: &str,
+    text: &str,
+    skip: usize,
+    limit: usize,
+) -> Result<Vec<CommitInfo>> {
+    let repo = Repository::discover(workspace_path)?;
+    let relative_path = path
+        .map(|path| git_relative_path(&repo, path))
+        .transpose()?;
+    let author = author.to_lowercase();
+    let text = text.to_lowercase();
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    revwalk.push_head()?;
+
+    let mut skipped = 0;
+    let mut commits = Vec::new();
+    for id in revwalk {
+        let commit = repo.find_commit(id?)?;
+        let commit_author = commit.author();
+        if !author.is_empty()
+            && !commit_author
+                .name()
+                .unwrap_or_default()
+                .to_lowercase()
+                .contains(&author)
+            && !commit_author
+                .email()
+                .unwrap_or_default()
+                .to_lowercase()
+                .contains(&author)
+        {
+            continue;
+        }
+        if !text.is_empty()
+            && !commit
+                .message()
+                .unwrap_or_default()
+                .to_lowercase()
+                .contains(&text)
+            && !commit.id().to_string().starts_with(&text)
+        {
+            continue;
+        }
+        if let Some(relative_path) = relative_path.as_ref() {
+            if !git_commit_changes_path(&repo, &commit, relative_path)? {
+                continue;
+            }
+        }
+
+        if skipped < skip {
+            skipped += 1;
+            continue;
+        }
+        commits.push(CommitInfo {
+            id: commit.id().to_string(),
+            parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+            author: commit_author.name().unwrap_or_default().to_string(),
+            email: commit_author.email().unwrap_or_default().to_string(),
+            time: commit.time().seconds(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            message: commit.message().unwrap_or_default().to_string(),
+        });
+        if commits.len() >= limit {
+            break;
+        }
+    }
+    Ok(commits)
+}
+
+/// Whether the commit changed the file, or a file in the directory, from its
+/// first parent.
+fn git_commit_changes_path(
+    repo: &Repository,
+    commit: &git2::Commit,
+    relative_path: &Path,
+) -> Result<bool> {
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let mut diff_options = DiffOptions::new();
+    diff_options.pathspec(relative_path);
+    let diff = repo.diff_tree_to_tree(
+        parent_tree.as_ref(),
+        Some(&tree),
+        Some(&mut diff_options),
+    )?;
+    Ok(diff.deltas().len() > 0)
+}
+
+fn git_commit_changes(
+    workspace_path: &Path,
+    commit_id: &str,
+) -> Result<Vec<FileDiff>> {
+    let repo = Repository::discover(workspace_path)?;
+    let workdir = repo
+        .workdir()
+        .ok_or(anyhow!("the repository has no working directory"))?
+        .to_path_buf();
+    let commit = repo.find_commit(git2::Oid::from_str(commit_id)?)?;
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let mut diff =
+        repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    diff.find_similar(None)?;
+
+    let mut diffs = Vec::new();
+    for delta in diff.deltas() {
+        let old_path = delta.old_file().path().map(|p| workdir.join(p));
+        let new_path = delta.new_file().path().map(|p| workdir.join(p));
+        let diff = match (delta.status(), old_path, new_path) {
+            (git2::Delta::Added, _, Some(path)) => FileDiff::Added(path),
+            (git2::Delta::Deleted, Some(path), _) => FileDiff::Deleted(path),
+            (git2::Delta::Renamed, Some(old_path), Some(new_path)) => {
+                FileDiff::Renamed(new_path, old_path)
+            }
+            (_, _, Some(path)) => FileDiff::Modified(path),
+            _ => continue,
+        };
+        diffs.push(diff);
+    }
+    Ok(diffs)
+}
+
 fn git_get_remote_file_url(workspace_path: &Path, file: &Path) -> Result<String> {
     let repo = Repository::discover(workspace_path)?;
     let head = repo.head()?;
@@ -1299,6 +2650,50 @@ fn git_get_remote_file_url(workspace_path: &Path, file: &Path) -> Result<String>
     Ok(url)
 }
 
+/// The processes that are running on this machine, as listed by `ps`, or by
+/// `tasklist` on Windows
+fn list_processes() -> Result<Vec<ProcessInfo>> {
+    #[cfg(not(target_os = "windows"))]
+    let processes = {
+        let output = std::process::Command::new("ps")
+            .args(["-A", "-o", "pid=,args="])
+            .output()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (pid, command) = line.trim_start().split_once(' ')?;
+                Some(ProcessInfo {
+                    pid: pid.parse().ok()?,
+                    command: command.trim().to_string(),
+                })
+            })
+            .collect()
+    };
+
+    #[cfg(target_os = "windows")]
+    let processes = {
+        let mut command = std::process::Command::new("tasklist");
+        command.args(["/FO", "CSV", "/NH"]);
+        // CREATE_NO_WINDOW
+        std::os::windows::process::CommandExt::creation_flags(
+            &mut command,
+            0x08000000,
+        );
+        let output = command.output()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.trim_matches('"').split("\",\"");
+                let command = fields.next()?.to_string();
+                let pid = fields.next()?.parse().ok()?;
+                Some(ProcessInfo { pid, command })
+            })
+            .collect()
+    };
+
+    Ok(processes)
+}
+
 fn search_in_path(
     id: u64,
     current_id: &AtomicU64,
@@ -1380,3 +2775,271 @@ fn search_in_path(
 
     Ok(ProxyResponse::GlobalSearchResponse { matches })
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{
+        SemanticToken, SemanticTokens, SemanticTokensDelta, SemanticTokensEdit,
+    };
+
+    use std::{fs, path::Path};
+
+    use git2::{DiffOptions, Repository, Status};
+
+    use super::{
+        apply_semantic_tokens_delta, git_apply_lines, git_old_line,
+        git_unstage_lines,
+    };
+
+    fn token(delta_line: u32) -> SemanticToken {
+        SemanticToken {
+            delta_line,
+            delta_start: 0,
+            length: 1,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    fn tokens(lines: &[u32]) -> SemanticTokens {
+        SemanticTokens {
+            result_id: Some("1".to_string()),
+            data: lines.iter().map(|line| token(*line)).collect(),
+        }
+    }
+
+    fn delta(edits: Vec<SemanticTokensEdit>) -> SemanticTokensDelta {
+        SemanticTokensDelta {
+            result_id: Some("2".to_string()),
+            edits,
+        }
+    }
+
+    #[test]
+    fn semantic_tokens_delta_insert() {
+        let result = apply_semantic_tokens_delta(
+            &tokens(&[0, 1, 2]),
+            delta(vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 0,
+                data: Some(vec![token(7), token(8)]),
+            }]),
+        )
+        .unwrap();
+        assert_eq!(result.result_id.as_deref(), Some("2"));
+        assert_eq!(result.data, tokens(&[0, 7, 8, 1, 2]).data);
+    }
+
+    #[test]
+    fn semantic_tokens_delta_delete() {
+        let result = apply_semantic_tokens_delta(
+            &tokens(&[0, 1, 2, 3]),
+            delta(vec![
+                SemanticTokensEdit {
+                    start: 0,
+                    delete_count: 5,
+                    data: None,
+                },
+                SemanticTokensEdit {
+                    start: 10,
+                    delete_count: 10,
+                    data: Some(vec![token(9)]),
+                },
+            ]),
+        )
+        .unwrap();
+        assert_eq!(result.data, tokens(&[1, 9]).data);
+    }
+
+    #[test]
+    fn semantic_tokens_delta_invalid() {
+        // an edit that doesn't start on a token
+        assert!(apply_semantic_tokens_delta(
+            &tokens(&[0, 1, 2]),
+            delta(vec![SemanticTokensEdit {
+                start: 3,
+                delete_count: 0,
+                data: Some(vec![token(7)]),
+            }]),
+        )
+        .is_none());
+        // an edit that deletes part of a token
+        assert!(apply_semantic_tokens_delta(
+            &tokens(&[0, 1, 2]),
+            delta(vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 4,
+                data: None,
+            }]),
+        )
+        .is_none());
+        // an edit past the end of the tokens
+        assert!(apply_semantic_tokens_delta(
+            &tokens(&[0, 1, 2]),
+            delta(vec![SemanticTokensEdit {
+                start: 10,
+                delete_count: 10,
+                data: None,
+            }]),
+        )
+        .is_none());
+    }
+
+    fn apply(old: &str, new: &str, selected: impl Fn(usize) -> bool) -> String {
+        String::from_utf8(
+            git_apply_lines(old.as_bytes(), new.as_bytes(), selected).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn apply_modified_lines() {
+        let old = "a\nb\nc\nd\ne\n";
+        let new = "A\nb\nc\nd\nE\n";
+        assert_eq!(apply(old, new, |_| true), new);
+        assert_eq!(apply(old, new, |_| false), old);
+        assert_eq!(apply(old, new, |line| line == 0), "A\nb\nc\nd\ne\n");
+        assert_eq!(apply(old, new, |line| line == 4), "a\nb\nc\nd\nE\n");
+    }
+
+    #[test]
+    fn apply_inserted_lines() {
+        // The old start of a hunk with only added lines is the line before it,
+        // which is 0 at the start of the file
+        assert_eq!(apply("b\nc\n", "a\nb\nc\n", |line| line == 0), "a\nb\nc\n");
+        assert_eq!(apply("b\nc\n", "a\nb\nc\n", |_| false), "b\nc\n");
+        assert_eq!(apply("a\nc\n", "a\nb\nc\n", |line| line == 1), "a\nb\nc\n");
+        assert_eq!(apply("a\nb\n", "a\nb\nc\n", |line| line == 2), "a\nb\nc\n");
+        // Only some of the added lines
+        assert_eq!(
+            apply("a\nd\n", "a\nb\nc\nd\n", |line| line == 2),
+            "a\nc\nd\n"
+        );
+    }
+
+    #[test]
+    fn apply_deleted_lines() {
+        // The new start of a hunk with only deleted lines is the line before
+        // it, so the deletion is selected by the line after it
+        assert_eq!(apply("a\nb\nc\n", "a\nc\n", |line| line == 1), "a\nc\n");
+        assert_eq!(apply("a\nb\nc\n", "a\nc\n", |_| false), "a\nb\nc\n");
+        assert_eq!(apply("a\nb\n", "b\n", |line| line == 0), "b\n");
+        assert_eq!(apply("a\nb\n", "a\n", |line| line == 1), "a\n");
+        assert_eq!(apply("a\nb\n", "", |_| true), "");
+    }
+
+    #[test]
+    fn apply_without_trailing_newline() {
+        assert_eq!(apply("a\nb", "a\nc", |_| true), "a\nc");
+        assert_eq!(apply("a\nb", "a\nc", |_| false), "a\nb");
+        assert_eq!(apply("a\nb", "a\nb\n", |_| true), "a\nb\n");
+        assert_eq!(apply("a\nb\n", "a\nb", |_| true), "a\nb");
+        assert_eq!(apply("a\nb\n", "a\nb", |_| false), "a\nb\n");
+        assert_eq!(apply("a", "a\nb", |line| line == 1), "a\nb");
+    }
+
+    #[test]
+    fn apply_multibyte_lines() {
+        let old = "héllo\nwörld\n✓\n";
+        let new = "héllo\nwörld!\n✗\n✓\n";
+        assert_eq!(apply(old, new, |_| true), new);
+        assert_eq!(apply(old, new, |line| line == 1), "héllo\nwörld!\n✓\n");
+        assert_eq!(apply(old, new, |line| line == 2), "héllo\nwörld\n✗\n✓\n");
+    }
+
+    fn diff<'a>(old: &'a str, new: &'a str) -> git2::Patch<'a> {
+        git2::Patch::from_buffers(
+            old.as_bytes(),
+            None,
+            new.as_bytes(),
+            None,
+            Some(DiffOptions::new().context_lines(0)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn old_line_of_modified_lines() {
+        let patch = diff("a\nb\nc\nd\n", "a\nX\nY\nc\nd\n");
+        assert_eq!(git_old_line(&patch, 0, false).unwrap(), 0);
+        assert_eq!(git_old_line(&patch, 1, false).unwrap(), 1);
+        assert_eq!(git_old_line(&patch, 1, true).unwrap(), 2);
+        assert_eq!(git_old_line(&patch, 2, false).unwrap(), 1);
+        assert_eq!(git_old_line(&patch, 2, true).unwrap(), 2);
+        assert_eq!(git_old_line(&patch, 3, false).unwrap(), 2);
+        assert_eq!(git_old_line(&patch, 4, false).unwrap(), 3);
+        // Past the end
+        assert_eq!(git_old_line(&patch, 5, false).unwrap(), 4);
+    }
+
+    #[test]
+    fn old_line_of_inserted_lines() {
+        let patch = diff("a\nc\n", "a\nb\nc\n");
+        assert_eq!(git_old_line(&patch, 1, false).unwrap(), 1);
+        assert_eq!(git_old_line(&patch, 1, true).unwrap(), 1);
+        assert_eq!(git_old_line(&patch, 2, false).unwrap(), 1);
+
+        let patch = diff("b\n", "a\nb\n");
+        assert_eq!(git_old_line(&patch, 0, false).unwrap(), 0);
+        assert_eq!(git_old_line(&patch, 0, true).unwrap(), 0);
+        assert_eq!(git_old_line(&patch, 1, false).unwrap(), 0);
+    }
+
+    #[test]
+    fn old_line_of_deleted_lines() {
+        let patch = diff("a\nb\nc\n", "a\nc\n");
+        assert_eq!(git_old_line(&patch, 0, false).unwrap(), 0);
+        assert_eq!(git_old_line(&patch, 1, false).unwrap(), 2);
+        assert_eq!(git_old_line(&patch, 1, true).unwrap(), 2);
+
+        let patch = diff("a\nb\n", "b\n");
+        assert_eq!(git_old_line(&patch, 0, false).unwrap(), 1);
+    }
+
+    #[test]
+    fn old_line_without_trailing_newline() {
+        let patch = diff("a\nb", "a\nb\nc");
+        assert_eq!(git_old_line(&patch, 1, false).unwrap(), 1);
+        assert_eq!(git_old_line(&patch, 1, true).unwrap(), 2);
+        assert_eq!(git_old_line(&patch, 2, false).unwrap(), 1);
+        assert_eq!(git_old_line(&patch, 2, true).unwrap(), 2);
+    }
+
+    #[test]
+    fn unstage_lines_keeps_file_modified() {
+        let dir = std::env::temp_dir()
+            .join(format!("lapce-unstage-lines-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dir = fs::canonicalize(dir).unwrap();
+        let repo = Repository::init(&dir).unwrap();
+        let path = dir.join("file.txt");
+        let relative_path = Path::new("file.txt");
+
+        fs::write(&path, "a\nb\nc\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(relative_path).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+
+        // The entry gets the stat of the file, and the content that's left
+        // staged has the same size as the file
+        fs::write(&path, "A\nb\nC\n").unwrap();
+        index.add_path(relative_path).unwrap();
+        index.write().unwrap();
+        git_unstage_lines(&dir, &path, None, 2..3).unwrap();
+
+        let index = repo.index().unwrap();
+        let entry = index.get_path(relative_path, 0).unwrap();
+        let staged = repo.find_blob(entry.id).unwrap();
+        assert_eq!(staged.content(), b"A\nb\nc\n");
+        let status = repo.status_file(relative_path).unwrap();
+        assert!(status.contains(Status::INDEX_MODIFIED));
+        assert!(status.contains(Status::WT_MODIFIED));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -9,7 +9,9 @@
 };
 
 use lapce_rpc::{
-    dap_types::{DapId, DapServer, SetBreakpointsResponse},
+    dap_types::{
+        DapId, DapServer, DapTransport, DebugAdapterConfig, SetBreakpointsResponse,
+    },
     plugin::{PluginId, VoltID, VoltMetadata},
     proxy::ProxyResponse,
     style::LineStyle,
@@ -17,15 +19,15 @@
 };
 use lapce_xi_rope::{Rope, RopeDelta};
 use lsp_types::{
-    notification::DidOpenTextDocument, DidOpenTextDocumentParams, SemanticTokens,
-    TextDocumentIdentifier, TextDocumentItem, VersionedTextDocumentIdentifier,
+    MessageType, SemanticTokens, ShowMessageParams, TextDocumentIdentifier,
+    TextDocumentItem, VersionedTextDocumentIdentifier,
 };
 use parking_lot::Mutex;
 use psp_types::Notification;
 use serde_json::Value;
 
 use super::{
-    dap::{DapClient, DapRpcHandler},
+    dap::{run_pre_launch, DapClient, DapRpcHandler},
     psp::{ClonableCallback, PluginServerRpc, PluginServerRpcHandler, RpcCallback},
     wasi::{load_all_volts, start_volt},
     PluginCatalogNotification, PluginCatalogRpcHandler,
@@ -273,17 +275,10 @@ pub fn handle_did_open_text_document(&mut self, document: TextDocumentItem) {
             .collect();
         self.start_unactivated_volts(to_be_activated);
 
-        let path = document.uri.to_file_path().ok();
         for (_, plugin) in self.plugins.iter() {
-            plugin.server_notification(
-                DidOpenTextDocument::METHOD,
-                DidOpenTextDocumentParams {
-                    text_document: document.clone(),
-                },
-                Some(document.language_id.clone()),
-                path.clone(),
-                true,
-            );
+            plugin.handle_rpc(PluginServerRpc::DidOpenTextDocument {
+                document: document.clone(),
+            });
         }
     }
 
@@ -357,25 +352,25 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
                 self.check_unactivated_volts();
             }
             UpdatePluginConfigs(configs) => {
+                for (_, plugin) in self.plugins.iter() {
+                    let name = &plugin.volt_id.name;
+                    let configurations = configs.get(name);
+                    if configurations != self.plugin_configurations.get(name) {
+                        plugin.handle_rpc(PluginServerRpc::DidChangeConfiguration {
+                            configurations: configurations.cloned(),
+                        });
+                    }
+                }
                 self.plugin_configurations = configs;
             }
             PluginServerLoaded(plugin) => {
-                // TODO: check if the server has did open registered
                 if let Ok(ProxyResponse::GetOpenFilesContentResponse { items }) =
                     self.plugin_rpc.proxy_rpc.get_open_files_content()
                 {
                     for item in items {
-                        let language_id = Some(item.language_id.clone());
-                        let path = item.uri.to_file_path().ok();
-                        plugin.server_notification(
-                            DidOpenTextDocument::METHOD,
-                            DidOpenTextDocumentParams {
-                                text_document: item,
-                            },
-                            language_id,
-                            path,
-                            true,
-                        );
+                        plugin.handle_rpc(PluginServerRpc::DidOpenTextDocument {
+                            document: item,
+                        });
                     }
                 }
                 self.plugins.insert(plugin.plugin_id, plugin);
@@ -433,24 +428,37 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
             DapStart {
                 config,
                 breakpoints,
+                exception_filters,
+                function_breakpoints,
             } => {
                 let workspace = self.workspace.clone();
                 let plugin_rpc = self.plugin_rpc.clone();
                 thread::spawn(move || {
+                    if let Err(err) = run_pre_launch(
+                        &config,
+                        workspace.as_deref(),
+                        &plugin_rpc.core_rpc,
+                    ) {
+                        plugin_rpc.core_rpc.show_message(
+                            "Pre-launch".to_string(),
+                            ShowMessageParams {
+                                typ: MessageType::ERROR,
+                                message: err.to_string(),
+                            },
+                        );
+                        return;
+                    }
                     if let Ok(dap_rpc) = DapClient::start(
-                        DapServer {
-                            program: "/opt/homebrew/opt/llvm@14/bin/lldb-vscode"
-                                .to_string(),
-                            args: Vec::new(),
-                            cwd: workspace,
-                        },
+                        dap_server(config.adapter.as_ref(), workspace),
                         config.clone(),
                         breakpoints,
+                        exception_filters,
+                        function_breakpoints,
                         plugin_rpc.clone(),
                     ) {
                         let _ = plugin_rpc.dap_loaded(dap_rpc.clone());
 
-                        let _ = dap_rpc.launch(&config);
+                        let _ = dap_rpc.launch_or_attach(&config);
                     }
                 });
             }
@@ -480,6 +488,77 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
                     });
                 }
             }
+            DapStepOver { dap_id, thread_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
+                    thread::spawn(move || {
+                        if dap.next(thread_id).is_ok() {
+                            plugin_rpc.core_rpc.dap_continued(dap_id);
+                        }
+                    });
+                }
+            }
+            DapStepInto { dap_id, thread_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
+                    thread::spawn(move || {
+                        if dap.step_in(thread_id).is_ok() {
+                            plugin_rpc.core_rpc.dap_continued(dap_id);
+                        }
+                    });
+                }
+            }
+            DapStepOut { dap_id, thread_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
+                    thread::spawn(move || {
+                        if dap.step_out(thread_id).is_ok() {
+                            plugin_rpc.core_rpc.dap_continued(dap_id);
+                        }
+                    });
+                }
+            }
+            DapStepBack { dap_id, thread_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
+                    thread::spawn(move || {
+                        if dap.step_back(thread_id).is_ok() {
+                            plugin_rpc.core_rpc.dap_continued(dap_id);
+                        }
+                    });
+                }
+            }
+            DapStepInstruction { dap_id, thread_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
+                    thread::spawn(move || {
+                        if dap.step_instruction(thread_id).is_ok() {
+                            plugin_rpc.core_rpc.dap_continued(dap_id);
+                        }
+                    });
+                }
+            }
+            DapGoto {
+                dap_id,
+                thread_id,
+                path,
+                line,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
+                    thread::spawn(move || {
+                        if let Err(err) = dap.goto(thread_id, path, line) {
+                            plugin_rpc.core_rpc.show_message(
+                                "Jump to Cursor".to_string(),
+                                ShowMessageParams {
+                                    typ: MessageType::ERROR,
+                                    message: err.to_string(),
+                                },
+                            );
+                        }
+                    });
+                }
+            }
             DapStop { dap_id } => {
                 if let Some(dap) = self.daps.get(&dap_id) {
                     dap.stop();
@@ -495,9 +574,31 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
             DapRestart {
                 dap_id,
                 breakpoints,
+                exception_filters,
+                function_breakpoints,
             } => {
                 if let Some(dap) = self.daps.get(&dap_id) {
-                    dap.restart(breakpoints);
+                    dap.restart(
+                        breakpoints,
+                        exception_filters,
+                        function_breakpoints,
+                    );
+                }
+            }
+            DapSetExceptionBreakpoints {
+                dap_id,
+                exception_filters,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.update_exception_breakpoints(exception_filters);
+                }
+            }
+            DapSetFunctionBreakpoints {
+                dap_id,
+                breakpoints,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.update_function_breakpoints(breakpoints);
                 }
             }
             DapSetBreakpoints {
@@ -522,6 +623,117 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
                     );
                 }
             }
+            DapScopes {
+                dap_id,
+                frame_id,
+                f,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.scopes_async(frame_id, move |result| f.call(result));
+                } else {
+                    f.call(Err(dap_not_found()));
+                }
+            }
+            DapVariables {
+                dap_id,
+                variables_reference,
+                f,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.variables_async(variables_reference, move |result| {
+                        f.call(result)
+                    });
+                } else {
+                    f.call(Err(dap_not_found()));
+                }
+            }
+            DapEvaluate {
+                dap_id,
+                expression,
+                frame_id,
+                context,
+                f,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.evaluate_async(
+                        expression,
+                        frame_id,
+                        context,
+                        move |result| f.call(result),
+                    );
+                } else {
+                    f.call(Err(dap_not_found()));
+                }
+            }
+            DapSetVariable {
+                dap_id,
+                variables_reference,
+                name,
+                value,
+                f,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.set_variable_async(
+                        variables_reference,
+                        name,
+                        value,
+                        move |result| f.call(result),
+                    );
+                } else {
+                    f.call(Err(dap_not_found()));
+                }
+            }
+            DapCompletions {
+                dap_id,
+                frame_id,
+                text,
+                column,
+                f,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.completions_async(frame_id, text, column, move |result| {
+                        f.call(result)
+                    });
+                } else {
+                    f.call(Err(dap_not_found()));
+                }
+            }
+            DapDisassemble {
+                dap_id,
+                memory_reference,
+                instruction_offset,
+                instruction_count,
+                f,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.disassemble_async(
+                        memory_reference,
+                        instruction_offset,
+                        instruction_count,
+                        move |result| f.call(result),
+                    );
+                } else {
+                    f.call(Err(dap_not_found()));
+                }
+            }
+            DapReadMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                count,
+                f,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.read_memory_async(
+                        memory_reference,
+                        offset,
+                        count,
+                        move |result| f.call(result),
+                    );
+                } else {
+                    f.call(Err(dap_not_found()));
+                }
+            }
             Shutdown => {
                 for (_, plugin) in self.plugins.iter() {
                     plugin.shutdown();
@@ -530,3 +742,47 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
         }
     }
 }
+
+fn dap_not_found() -> RpcError {
+    RpcError {
+        code: 0,
+        message: "debug session doesn't exist".to_string(),
+    }
+}
+
+/// The debug adapter of a run config, or the default one if the config
+/// doesn't have one
+fn dap_server(
+    adapter: Option<&DebugAdapterConfig>,
+    workspace: Option<PathBuf>,
+) -> DapServer {
+    let default_program = || "/opt/homebrew/opt/llvm@14/bin/lldb-vscode".to_string();
+    let adapter = match adapter {
+        Some(adapter) => adapter,
+        None => {
+            return DapServer {
+                program: default_program(),
+                args: Vec::new(),
+                cwd: workspace,
+                transport: DapTransport::Stdio,
+            }
+        }
+    };
+    let transport = match adapter.port {
+        Some(port) => DapTransport::Tcp {
+            host: adapter
+                .host
+                .clone()
+                .unwrap_or_else(|| "127.0.0.1".to_string()),
+            port,
+            spawn: adapter.program.is_some(),
+        },
+        None => DapTransport::Stdio,
+    };
+    DapServer {
+        program: adapter.program.clone().unwrap_or_else(default_program),
+        args: adapter.args.clone(),
+        cwd: workspace,
+        transport,
+    }
+}
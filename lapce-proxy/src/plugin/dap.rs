@@ -1,31 +1,47 @@
 use std::{
     collections::HashMap,
-    io::{BufReader, BufWriter, Write},
-    path::PathBuf,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
     process::{Child, Command, Stdio},
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc,
     },
     thread,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Result};
 use crossbeam_channel::{Receiver, Sender};
 use lapce_rpc::{
+    core::CoreRpcHandler,
     dap_types::{
-        self, ConfigurationDone, Continue, ContinueArguments, ContinueResponse,
-        DapEvent, DapId, DapPayload, DapRequest, DapResponse, DapServer,
-        DebuggerCapabilities, Disconnect, Initialize, Launch, Pause, PauseArguments,
+        self, Attach, Completions, CompletionsArguments, CompletionsResponse,
+        ConfigurationDone, Continue, ContinueArguments, ContinueResponse, DapEvent,
+        DapId, DapPayload, DapRequest, DapResponse, DapServer, DapTransport,
+        DebugRequest, DebuggerCapabilities, Disassemble, DisassembleArguments,
+        DisassembleResponse, Disconnect, Evaluate, EvaluateArguments,
+        EvaluateResponse, FunctionBreakpoint, Goto, GotoArguments, GotoTargets,
+        GotoTargetsArguments, Initialize, Launch, Next, NextArguments, Pause,
+        PauseArguments, ReadMemory, ReadMemoryArguments, ReadMemoryResponse,
         Request, RunDebugConfig, RunInTerminal, RunInTerminalArguments,
-        RunInTerminalResponse, SetBreakpoints, SetBreakpointsArguments,
-        SetBreakpointsResponse, Source, SourceBreakpoint, StackTrace,
-        StackTraceArguments, StackTraceResponse, Terminate, ThreadId, Threads,
-        ThreadsResponse,
+        RunInTerminalResponse, Scopes, ScopesArguments, ScopesResponse,
+        SetBreakpoints, SetBreakpointsArguments, SetBreakpointsResponse,
+        SetExceptionBreakpoints, SetExceptionBreakpointsArguments,
+        SetExceptionBreakpointsResponse, SetFunctionBreakpoints,
+        SetFunctionBreakpointsArguments, SetFunctionBreakpointsResponse,
+        SetVariable, SetVariableArguments, SetVariableResponse, Source,
+        SourceBreakpoint, StackTrace, StackTraceArguments, StackTraceResponse,
+        StartDebugging, StartDebuggingRequestArguments, StepBack, StepBackArguments,
+        StepIn, StepInArguments, StepOut, StepOutArguments, SteppingGranularity,
+        Terminate, ThreadId, Threads, ThreadsResponse, Variables,
+        VariablesArguments, VariablesResponse,
     },
     terminal::TermId,
     RpcError,
 };
+use lsp_types::{MessageType, ShowMessageParams};
 use parking_lot::Mutex;
 use serde_json::Value;
 
@@ -34,13 +50,25 @@
     PluginCatalogRpcHandler,
 };
 
+/// How long connecting to an adapter over TCP is retried, while it starts
+/// listening
+const DAP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DAP_CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct DapClient {
     plugin_rpc: PluginCatalogRpcHandler,
     pub(crate) dap_rpc: DapRpcHandler,
     dap_server: DapServer,
+    /// The adapter that was spawned to listen on a port, which is killed when
+    /// the session ends
+    process: Option<Child>,
     config: RunDebugConfig,
     breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+    exception_filters: HashMap<String, bool>,
+    function_breakpoints: Vec<FunctionBreakpoint>,
     term_id: Option<TermId>,
+    /// The sessions that the debug adapter started from this one
+    children: Vec<DapRpcHandler>,
     capabilities: Option<DebuggerCapabilities>,
     terminated: bool,
     disconnected: bool,
@@ -52,6 +80,8 @@ pub fn new(
         dap_server: DapServer,
         config: RunDebugConfig,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
         plugin_rpc: PluginCatalogRpcHandler,
     ) -> Result<Self> {
         let dap_rpc = DapRpcHandler::new(config.dap_id);
@@ -59,10 +89,14 @@ pub fn new(
         Ok(Self {
             plugin_rpc,
             dap_server,
+            process: None,
             config,
             dap_rpc,
             breakpoints,
+            exception_filters,
+            function_breakpoints,
             term_id: None,
+            children: Vec::new(),
             capabilities: None,
             terminated: false,
             disconnected: false,
@@ -74,9 +108,18 @@ pub fn start(
         dap_server: DapServer,
         config: RunDebugConfig,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
         plugin_rpc: PluginCatalogRpcHandler,
     ) -> Result<DapRpcHandler> {
-        let mut dap = Self::new(dap_server, config, breakpoints, plugin_rpc)?;
+        let mut dap = Self::new(
+            dap_server,
+            config,
+            breakpoints,
+            exception_filters,
+            function_breakpoints,
+            plugin_rpc,
+        )?;
         dap.start_process()?;
 
         let dap_rpc = dap.dap_rpc.clone();
@@ -92,21 +135,90 @@ pub fn start(
         Ok(dap_rpc)
     }
 
-    fn start_process(&self) -> Result<()> {
-        let program = self.dap_server.program.clone();
-        let mut process = Self::process(
-            &program,
-            &self.dap_server.args,
-            self.dap_server.cwd.as_ref(),
-        )?;
-        let stdin = process.stdin.take().unwrap();
-        let stdout = process.stdout.take().unwrap();
-        // let stderr = process.stderr.take().unwrap();
+    fn start_process(&mut self) -> Result<()> {
+        match &self.dap_server.transport {
+            DapTransport::Stdio => {
+                let program = self.dap_server.program.clone();
+                let mut process = Self::process(
+                    &program,
+                    &self.dap_server.args,
+                    self.dap_server.cwd.as_ref(),
+                    true,
+                )?;
+                let stdin = process.stdin.take().unwrap();
+                let stdout = process.stdout.take().unwrap();
+                // let stderr = process.stderr.take().unwrap();
+                self.start_io(stdin, stdout, program);
+            }
+            DapTransport::Tcp { host, port, spawn } => {
+                let (host, port, spawn) = (host.clone(), *port, *spawn);
+                if spawn {
+                    // The adapter of the session before it was restarted
+                    self.kill_process();
+                    self.process = Some(Self::process(
+                        &self.dap_server.program,
+                        &self.dap_server.args,
+                        self.dap_server.cwd.as_ref(),
+                        false,
+                    )?);
+                }
+                let stream = Self::connect(&host, port).and_then(|stream| {
+                    let reader = stream.try_clone()?;
+                    Ok((stream, reader))
+                });
+                let (stream, reader) = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        self.kill_process();
+                        return Err(err);
+                    }
+                };
+                self.start_io(stream, reader, format!("{host}:{port}"));
+            }
+        }
+
+        Ok(())
+    }
 
+    /// Kill the adapter that was spawned to listen on a port, if there's one.
+    fn kill_process(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+    }
+
+    /// Connect to the adapter listening on the address, retrying while it's
+    /// still starting up.
+    fn connect(host: &str, port: u16) -> Result<TcpStream> {
+        let started = Instant::now();
+        loop {
+            match TcpStream::connect((host, port)) {
+                Ok(stream) => return Ok(stream),
+                Err(_) if started.elapsed() < DAP_CONNECT_TIMEOUT => {
+                    thread::sleep(DAP_CONNECT_RETRY_INTERVAL);
+                }
+                Err(err) => {
+                    return Err(anyhow!(
+                        "can't connect to the dap server at {host}:{port}: {err}"
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Write the messages to the adapter and read the ones it sends back, with
+    /// the same framing whatever the transport is.
+    fn start_io(
+        &self,
+        writer: impl Write + Send + 'static,
+        reader: impl Read + Send + 'static,
+        name: String,
+    ) {
         let dap_rpc = self.dap_rpc.clone();
         let io_rx = self.dap_rpc.io_rx.clone();
         let io_tx = self.dap_rpc.io_tx.clone();
-        let mut writer = Box::new(BufWriter::new(stdin));
+        let mut writer = Box::new(BufWriter::new(writer));
         thread::spawn(move || -> Result<()> {
             for msg in io_rx {
                 if let Ok(msg) = serde_json::to_string(&msg) {
@@ -122,7 +234,7 @@ fn start_process(&self) -> Result<()> {
         {
             let plugin_rpc = self.plugin_rpc.clone();
             thread::spawn(move || {
-                let mut reader = Box::new(BufReader::new(stdout));
+                let mut reader = Box::new(BufReader::new(reader));
                 loop {
                     match crate::plugin::lsp::read_message(&mut reader) {
                         Ok(message_str) => {
@@ -134,7 +246,7 @@ fn start_process(&self) -> Result<()> {
                             ));
                             plugin_rpc.core_rpc.log(
                                 tracing::Level::ERROR,
-                                format!("dap server {program} stopped!"),
+                                format!("dap server {name} stopped!"),
                             );
 
                             dap_rpc.disconnected();
@@ -144,14 +256,14 @@ fn start_process(&self) -> Result<()> {
                 }
             });
         }
-
-        Ok(())
     }
 
+    /// Spawn the adapter, with piped stdio when it's used to talk to it.
     fn process(
         server: &str,
         args: &[String],
         cwd: Option<&PathBuf>,
+        piped: bool,
     ) -> Result<Child> {
         let mut process = Command::new(server);
         if let Some(cwd) = cwd {
@@ -168,10 +280,11 @@ fn process(
             &mut process,
             0x08000000,
         );
+        let stdio = || if piped { Stdio::piped() } else { Stdio::null() };
         let child = process
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
+            .stdin(stdio())
+            .stdout(stdio())
+            .stderr(stdio())
             .spawn()?;
         Ok(child)
     }
@@ -199,10 +312,90 @@ fn handle_host_request(&mut self, req: &DapRequest) -> Result<Value> {
                 let resp = serde_json::to_value(resp)?;
                 Ok(resp)
             }
+            StartDebugging::COMMAND => {
+                let value = req
+                    .arguments
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("no arguments"))?;
+                let args: StartDebuggingRequestArguments =
+                    serde_json::from_value(value.clone())?;
+                self.start_child(args);
+                Ok(Value::Null)
+            }
             _ => Err(anyhow!("not implemented")),
         }
     }
 
+    /// Start a child session with the configuration that the debug adapter
+    /// sent. It connects to the same adapter when that's listening on a port,
+    /// and spawns another one otherwise.
+    fn start_child(&self, args: StartDebuggingRequestArguments) {
+        let mut config = self.config.clone();
+        config.dap_id = DapId::next();
+        config.parent = Some(self.config.dap_id);
+        if let Some(name) = args.configuration.get("name").and_then(|n| n.as_str()) {
+            config.name = name.to_string();
+        }
+        config.program = String::new();
+        config.args = Vec::new();
+        config.pre_launch = None;
+        config.pid = None;
+        config.host = None;
+        config.port = None;
+        config.debug_command = None;
+        config.request = args.request;
+        match args.request {
+            DebugRequest::Launch => {
+                config.launch = Some(args.configuration);
+                config.attach = None;
+            }
+            DebugRequest::Attach => {
+                config.attach = Some(args.configuration);
+                config.launch = None;
+            }
+        }
+
+        let mut dap_server = self.dap_server.clone();
+        if let DapTransport::Tcp { spawn, .. } = &mut dap_server.transport {
+            *spawn = false;
+        }
+
+        self.plugin_rpc.core_rpc.dap_child_session(
+            config.dap_id,
+            self.config.dap_id,
+            config.name.clone(),
+        );
+
+        let parent_rpc = self.dap_rpc.clone();
+        let plugin_rpc = self.plugin_rpc.clone();
+        let breakpoints = self.breakpoints.clone();
+        let exception_filters = self.exception_filters.clone();
+        let function_breakpoints = self.function_breakpoints.clone();
+        thread::spawn(move || {
+            match DapClient::start(
+                dap_server,
+                config.clone(),
+                breakpoints,
+                exception_filters,
+                function_breakpoints,
+                plugin_rpc.clone(),
+            ) {
+                Ok(dap_rpc) => {
+                    let _ = plugin_rpc.dap_loaded(dap_rpc.clone());
+                    parent_rpc.child_started(dap_rpc.clone());
+                    let _ = dap_rpc.launch_or_attach(&config);
+                }
+                Err(err) => {
+                    plugin_rpc.core_rpc.log(
+                        tracing::Level::ERROR,
+                        format!("can't start the child session: {err}"),
+                    );
+                    plugin_rpc.core_rpc.dap_terminated(config.dap_id);
+                }
+            }
+        });
+    }
+
     fn handle_host_event(&mut self, event: &DapEvent) -> Result<()> {
         match event {
             DapEvent::Initialized(_) => {
@@ -217,6 +410,8 @@ fn handle_host_event(&mut self, event: &DapEvent) -> Result<()> {
                         );
                     }
                 }
+                let _ = self.send_exception_breakpoints();
+                let _ = self.send_function_breakpoints();
                 // send dap configurations here
                 let _ = self.dap_rpc.request::<ConfigurationDone>(());
             }
@@ -260,14 +455,25 @@ fn handle_host_event(&mut self, event: &DapEvent) -> Result<()> {
             DapEvent::Exited(_exited) => {}
             DapEvent::Terminated(_) => {
                 self.terminated = true;
-                // self.plugin_rpc.core_rpc.dap_terminated(self.dap_rpc.dap_id);
+                // A child session isn't restarted on its own, so it's removed
+                // from the session tree once it's done
+                if self.config.parent.is_some() {
+                    self.plugin_rpc.core_rpc.dap_terminated(self.dap_rpc.dap_id);
+                    let _ = self.plugin_rpc.dap_disconnected(self.dap_rpc.dap_id);
+                }
                 if let Some(term_id) = self.term_id {
                     self.plugin_rpc.proxy_rpc.terminal_close(term_id);
                 }
                 let _ = self.check_restart();
             }
             DapEvent::Thread { .. } => {}
-            DapEvent::Output(_) => {}
+            DapEvent::Output(output) => {
+                if output.category.as_deref() != Some("telemetry") {
+                    self.plugin_rpc
+                        .core_rpc
+                        .dap_output(self.dap_rpc.dap_id, output.clone());
+                }
+            }
             DapEvent::Breakpoint { .. } => {}
             DapEvent::Module { .. } => {}
             DapEvent::LoadedSource { .. } => {}
@@ -294,34 +500,114 @@ pub(crate) fn initialize(&mut self) -> Result<()> {
             supports_run_in_terminal_request: Some(false),
             #[cfg(not(target_os = "windows"))]
             supports_run_in_terminal_request: Some(true),
-            supports_memory_references: Some(false),
+            supports_memory_references: Some(true),
             supports_progress_reporting: Some(false),
             supports_invalidated_event: Some(false),
+            supports_start_debugging_request: Some(true),
         };
 
         let resp = self
             .dap_rpc
             .request::<Initialize>(params)
             .map_err(|e| anyhow!(e.message))?;
+        self.plugin_rpc
+            .core_rpc
+            .dap_capabilities(self.config.dap_id, resp.clone());
         self.capabilities = Some(resp);
 
         Ok(())
     }
 
-    fn stop(&self) {
-        let dap_rpc = self.dap_rpc.clone();
-        if self
+    /// Send the enabled exception filters. A filter that the user hasn't
+    /// toggled uses the default advertised by the debug adapter.
+    fn send_exception_breakpoints(&self) -> Result<()> {
+        let filters = match self
             .capabilities
             .as_ref()
-            .and_then(|c| c.supports_terminate_request)
+            .and_then(|c| c.exception_breakpoint_filters.as_ref())
+        {
+            Some(filters) => filters,
+            None => return Ok(()),
+        };
+        let filters = filters
+            .iter()
+            .filter(|filter| {
+                self.exception_filters
+                    .get(&filter.filter)
+                    .copied()
+                    .unwrap_or_else(|| filter.default.unwrap_or(false))
+            })
+            .map(|filter| filter.filter.clone())
+            .collect();
+        self.dap_rpc.set_exception_breakpoints(filters)?;
+        Ok(())
+    }
+
+    fn send_function_breakpoints(&self) -> Result<()> {
+        if !self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.supports_function_breakpoints)
             .unwrap_or(false)
+        {
+            return Ok(());
+        }
+        self.dap_rpc
+            .set_function_breakpoints(self.function_breakpoints.clone())?;
+        Ok(())
+    }
+
+    /// Open the terminal of a session that attaches to a process, since the
+    /// debug adapter doesn't ask for one to run the debuggee in. A child
+    /// session is shown under its parent instead.
+    fn open_attach_terminal(&mut self) -> Result<()> {
+        if self.config.parent.is_some() {
+            return Ok(());
+        }
+        self.plugin_rpc
+            .core_rpc
+            .run_in_terminal(self.config.clone());
+        let (term_id, _) = self.dap_rpc.termain_process_rx.recv()?;
+        self.term_id = Some(term_id);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        for child in &self.children {
+            child.stop();
+        }
+
+        let dap_rpc = self.dap_rpc.clone();
+        // The adapter that was spawned is killed once it has ended the session,
+        // unless it's restarted, which connects to it again
+        let process = if self.restarted {
+            None
+        } else {
+            self.process.take()
+        };
+        let kill = move || {
+            if let Some(mut process) = process {
+                let _ = process.kill();
+                let _ = process.wait();
+            }
+        };
+        // Terminating would kill the process that was attached to, while
+        // disconnecting leaves it running
+        if self.config.request == DebugRequest::Launch
+            && self
+                .capabilities
+                .as_ref()
+                .and_then(|c| c.supports_terminate_request)
+                .unwrap_or(false)
         {
             thread::spawn(move || {
                 let _ = dap_rpc.terminate();
+                kill();
             });
         } else {
             thread::spawn(move || {
                 let _ = dap_rpc.disconnect();
+                kill();
             });
         }
     }
@@ -354,16 +640,35 @@ fn check_restart(&mut self) -> Result<()> {
 
         let dap_rpc = self.dap_rpc.clone();
         let config = self.config.clone();
+        let cwd = self.dap_server.cwd.clone();
+        let core_rpc = self.plugin_rpc.core_rpc.clone();
         thread::spawn(move || {
-            let _ = dap_rpc.launch(&config);
+            if let Err(err) = run_pre_launch(&config, cwd.as_deref(), &core_rpc) {
+                core_rpc.show_message(
+                    "Pre-launch".to_string(),
+                    ShowMessageParams {
+                        typ: MessageType::ERROR,
+                        message: err.to_string(),
+                    },
+                );
+                return;
+            }
+            let _ = dap_rpc.launch_or_attach(&config);
         });
 
         Ok(())
     }
 
-    fn restart(&mut self, breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>) {
+    fn restart(
+        &mut self,
+        breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
+    ) {
         self.restarted = true;
         self.breakpoints = breakpoints;
+        self.exception_filters = exception_filters;
+        self.function_breakpoints = function_breakpoints;
         if !self.terminated {
             self.stop();
         } else {
@@ -372,11 +677,127 @@ fn restart(&mut self, breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>) {
     }
 }
 
+/// Run the pre-launch command of the config in a shell, sending what it prints
+/// to the debug console of the session as it runs, and fail if it didn't
+/// succeed.
+pub fn run_pre_launch(
+    config: &RunDebugConfig,
+    workspace: Option<&Path>,
+    core_rpc: &CoreRpcHandler,
+) -> Result<()> {
+    let pre_launch = match config.pre_launch.as_ref() {
+        Some(pre_launch) if !pre_launch.trim().is_empty() => pre_launch,
+        _ => return Ok(()),
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let mut command = {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(pre_launch);
+        command
+    };
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(pre_launch);
+        // CREATE_NO_WINDOW
+        std::os::windows::process::CommandExt::creation_flags(
+            &mut command,
+            0x08000000,
+        );
+        command
+    };
+
+    if let Some(cwd) = config
+        .cwd
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(|| workspace.map(|workspace| workspace.to_path_buf()))
+    {
+        command.current_dir(cwd);
+    }
+    if let Some(env) = config.env.as_ref() {
+        command.envs(env);
+    }
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    core_rpc.dap_output(
+        config.dap_id,
+        pre_launch_output(format!("> {pre_launch}\n"), "console"),
+    );
+    let stdout = child.stdout.take().map(|stdout| {
+        forward_pre_launch_output(stdout, "stdout", config.dap_id, core_rpc)
+    });
+    let stderr = child.stderr.take().map(|stderr| {
+        forward_pre_launch_output(stderr, "stderr", config.dap_id, core_rpc)
+    });
+    for reader in [stdout, stderr].into_iter().flatten() {
+        let _ = reader.join();
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("`{pre_launch}` failed with {status}"));
+    }
+    Ok(())
+}
+
+/// Send every line read from the output of the pre-launch command to the debug
+/// console, until it's closed.
+fn forward_pre_launch_output(
+    output: impl Read + Send + 'static,
+    category: &'static str,
+    dap_id: DapId,
+    core_rpc: &CoreRpcHandler,
+) -> thread::JoinHandle<()> {
+    let core_rpc = core_rpc.clone();
+    thread::spawn(move || {
+        let mut reader = BufReader::new(output);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => return,
+                Ok(_) => {
+                    let text = String::from_utf8_lossy(&line).into_owned();
+                    core_rpc.dap_output(dap_id, pre_launch_output(text, category));
+                }
+            }
+        }
+    })
+}
+
+fn pre_launch_output(output: String, category: &str) -> dap_types::Output {
+    dap_types::Output {
+        output,
+        category: Some(category.to_string()),
+        group: None,
+        line: None,
+        column: None,
+        variables_reference: None,
+        source: None,
+        data: None,
+    }
+}
+
 pub enum DapRpc {
     HostRequest(DapRequest),
     HostEvent(DapEvent),
+    OpenAttachTerminal,
     Stop,
-    Restart(HashMap<PathBuf, Vec<SourceBreakpoint>>),
+    Restart {
+        breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
+    },
+    SetExceptionBreakpoints(HashMap<String, bool>),
+    SetFunctionBreakpoints(Vec<FunctionBreakpoint>),
+    ChildStarted(DapRpcHandler),
     Shutdown,
     Disconnected,
 }
@@ -430,13 +851,36 @@ pub fn mainloop(&self, dap_client: &mut DapClient) {
                 DapRpc::HostEvent(event) => {
                     let _ = dap_client.handle_host_event(&event);
                 }
+                DapRpc::OpenAttachTerminal => {
+                    let _ = dap_client.open_attach_terminal();
+                }
                 DapRpc::Stop => {
                     dap_client.stop();
                 }
-                DapRpc::Restart(breakpoints) => {
-                    dap_client.restart(breakpoints);
+                DapRpc::Restart {
+                    breakpoints,
+                    exception_filters,
+                    function_breakpoints,
+                } => {
+                    dap_client.restart(
+                        breakpoints,
+                        exception_filters,
+                        function_breakpoints,
+                    );
+                }
+                DapRpc::SetExceptionBreakpoints(exception_filters) => {
+                    dap_client.exception_filters = exception_filters;
+                    let _ = dap_client.send_exception_breakpoints();
+                }
+                DapRpc::SetFunctionBreakpoints(function_breakpoints) => {
+                    dap_client.function_breakpoints = function_breakpoints;
+                    let _ = dap_client.send_function_breakpoints();
+                }
+                DapRpc::ChildStarted(child) => {
+                    dap_client.children.push(child);
                 }
                 DapRpc::Shutdown => {
+                    dap_client.kill_process();
                     if let Some(term_id) = dap_client.term_id {
                         dap_client.plugin_rpc.proxy_rpc.terminal_close(term_id);
                     }
@@ -444,6 +888,9 @@ pub fn mainloop(&self, dap_client: &mut DapClient) {
                 }
                 DapRpc::Disconnected => {
                     dap_client.disconnected = true;
+                    if !dap_client.restarted {
+                        dap_client.kill_process();
+                    }
                     if let Some(term_id) = dap_client.term_id {
                         dap_client.plugin_rpc.proxy_rpc.terminal_close(term_id);
                     }
@@ -557,24 +1004,102 @@ pub fn handle_server_message(&self, message_str: &str) {
     }
 
     pub fn launch(&self, config: &RunDebugConfig) -> Result<()> {
-        let params = serde_json::json!({
-            "program": config.program,
-            "args": config.args,
-            "cwd": config.cwd,
-            "runInTerminal": true,
-        });
+        let mut params = serde_json::Map::new();
+        // A child session has the program in the configuration from the
+        // debug adapter, if at all
+        if !config.program.is_empty() {
+            params.insert("program".to_string(), config.program.clone().into());
+            params.insert("args".to_string(), config.args.clone().into());
+        }
+        params.insert("cwd".to_string(), config.cwd.clone().into());
+        params.insert("runInTerminal".to_string(), true.into());
+        if let Some(Value::Object(launch)) = config.launch.as_ref() {
+            for (key, value) in launch {
+                params.insert(key.clone(), value.clone());
+            }
+        }
         let _resp = self
-            .request::<Launch>(params)
+            .request::<Launch>(Value::Object(params))
             .map_err(|e| anyhow!(e.message))?;
         Ok(())
     }
 
+    pub fn attach(&self, config: &RunDebugConfig) -> Result<()> {
+        // The terminal is opened by the main loop before it handles the events
+        // that the attach request causes.
+        let _ = self.rpc_tx.send(DapRpc::OpenAttachTerminal);
+
+        let mut params = match config.attach.clone() {
+            Some(Value::Object(params)) => params,
+            _ => serde_json::Map::new(),
+        };
+        if !config.program.is_empty() {
+            params
+                .entry("program")
+                .or_insert_with(|| config.program.clone().into());
+        }
+        if let Some(pid) = config.pid {
+            // Adapters name it either way, e.g. `pid` for lldb and `processId`
+            // for the ones following vscode's node and cpptools adapters
+            params.entry("pid").or_insert_with(|| pid.into());
+            params.entry("processId").or_insert_with(|| pid.into());
+        }
+        if let Some(host) = config.host.as_ref() {
+            params.entry("host").or_insert_with(|| host.clone().into());
+        }
+        if let Some(port) = config.port {
+            params.entry("port").or_insert_with(|| port.into());
+        }
+        let _resp = self
+            .request::<Attach>(Value::Object(params))
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    pub fn launch_or_attach(&self, config: &RunDebugConfig) -> Result<()> {
+        match config.request {
+            DebugRequest::Launch => self.launch(config),
+            DebugRequest::Attach => self.attach(config),
+        }
+    }
+
     pub fn stop(&self) {
         let _ = self.rpc_tx.send(DapRpc::Stop);
     }
 
-    pub fn restart(&self, breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>) {
-        let _ = self.rpc_tx.send(DapRpc::Restart(breakpoints));
+    fn child_started(&self, child: DapRpcHandler) {
+        let _ = self.rpc_tx.send(DapRpc::ChildStarted(child));
+    }
+
+    pub fn restart(
+        &self,
+        breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
+    ) {
+        let _ = self.rpc_tx.send(DapRpc::Restart {
+            breakpoints,
+            exception_filters,
+            function_breakpoints,
+        });
+    }
+
+    pub fn update_exception_breakpoints(
+        &self,
+        exception_filters: HashMap<String, bool>,
+    ) {
+        let _ = self
+            .rpc_tx
+            .send(DapRpc::SetExceptionBreakpoints(exception_filters));
+    }
+
+    pub fn update_function_breakpoints(
+        &self,
+        function_breakpoints: Vec<FunctionBreakpoint>,
+    ) {
+        let _ = self
+            .rpc_tx
+            .send(DapRpc::SetFunctionBreakpoints(function_breakpoints));
     }
 
     fn disconnected(&self) {
@@ -641,6 +1166,31 @@ pub fn set_breakpoints(
         Ok(resp)
     }
 
+    fn set_exception_breakpoints(
+        &self,
+        filters: Vec<String>,
+    ) -> Result<SetExceptionBreakpointsResponse> {
+        let params = SetExceptionBreakpointsArguments {
+            filters,
+            filter_options: None,
+        };
+        let resp = self
+            .request::<SetExceptionBreakpoints>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(resp)
+    }
+
+    fn set_function_breakpoints(
+        &self,
+        breakpoints: Vec<FunctionBreakpoint>,
+    ) -> Result<SetFunctionBreakpointsResponse> {
+        let params = SetFunctionBreakpointsArguments { breakpoints };
+        let resp = self
+            .request::<SetFunctionBreakpoints>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(resp)
+    }
+
     pub fn continue_thread(&self, thread_id: ThreadId) -> Result<ContinueResponse> {
         let params = ContinueArguments { thread_id };
         let resp = self
@@ -656,6 +1206,90 @@ pub fn pause_thread(&self, thread_id: ThreadId) -> Result<()> {
         Ok(())
     }
 
+    pub fn next(&self, thread_id: ThreadId) -> Result<()> {
+        let params = NextArguments {
+            thread_id,
+            ..Default::default()
+        };
+        self.request::<Next>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    pub fn step_instruction(&self, thread_id: ThreadId) -> Result<()> {
+        let params = NextArguments {
+            thread_id,
+            granularity: Some(SteppingGranularity::Instruction),
+            ..Default::default()
+        };
+        self.request::<Next>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    pub fn step_in(&self, thread_id: ThreadId) -> Result<()> {
+        let params = StepInArguments {
+            thread_id,
+            ..Default::default()
+        };
+        self.request::<StepIn>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    pub fn step_out(&self, thread_id: ThreadId) -> Result<()> {
+        let params = StepOutArguments {
+            thread_id,
+            ..Default::default()
+        };
+        self.request::<StepOut>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    pub fn step_back(&self, thread_id: ThreadId) -> Result<()> {
+        let params = StepBackArguments {
+            thread_id,
+            ..Default::default()
+        };
+        self.request::<StepBack>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    /// Move the execution of the thread to the first location the debug adapter
+    /// allows to jump to on the line, without running the code in between.
+    /// The line starts at 1.
+    pub fn goto(
+        &self,
+        thread_id: ThreadId,
+        path: PathBuf,
+        line: usize,
+    ) -> Result<()> {
+        let params = GotoTargetsArguments {
+            source: Source {
+                path: Some(path),
+                ..Default::default()
+            },
+            line,
+            column: None,
+        };
+        let resp = self
+            .request::<GotoTargets>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        let target = resp
+            .targets
+            .first()
+            .ok_or_else(|| anyhow!("no goto targets on line {line}"))?;
+        let params = GotoArguments {
+            thread_id,
+            target_id: target.id,
+        };
+        self.request::<Goto>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
     pub fn threads(&self) -> Result<ThreadsResponse> {
         let resp = self
             .request::<Threads>(())
@@ -673,4 +1307,104 @@ pub fn stack_trace(&self, thread_id: ThreadId) -> Result<StackTraceResponse> {
             .map_err(|e| anyhow!(e.message))?;
         Ok(resp)
     }
+
+    pub fn scopes_async(
+        &self,
+        frame_id: usize,
+        f: impl RpcCallback<ScopesResponse, RpcError> + 'static,
+    ) {
+        let params = ScopesArguments { frame_id };
+        self.request_async::<Scopes>(params, f);
+    }
+
+    pub fn variables_async(
+        &self,
+        variables_reference: usize,
+        f: impl RpcCallback<VariablesResponse, RpcError> + 'static,
+    ) {
+        let params = VariablesArguments {
+            variables_reference,
+            ..Default::default()
+        };
+        self.request_async::<Variables>(params, f);
+    }
+
+    pub fn evaluate_async(
+        &self,
+        expression: String,
+        frame_id: Option<usize>,
+        context: Option<String>,
+        f: impl RpcCallback<EvaluateResponse, RpcError> + 'static,
+    ) {
+        let params = EvaluateArguments {
+            expression,
+            frame_id,
+            context,
+        };
+        self.request_async::<Evaluate>(params, f);
+    }
+
+    pub fn set_variable_async(
+        &self,
+        variables_reference: usize,
+        name: String,
+        value: String,
+        f: impl RpcCallback<SetVariableResponse, RpcError> + 'static,
+    ) {
+        let params = SetVariableArguments {
+            variables_reference,
+            name,
+            value,
+        };
+        self.request_async::<SetVariable>(params, f);
+    }
+
+    /// The column starts at 1
+    pub fn completions_async(
+        &self,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+        f: impl RpcCallback<CompletionsResponse, RpcError> + 'static,
+    ) {
+        let params = CompletionsArguments {
+            frame_id,
+            text,
+            column,
+            line: None,
+        };
+        self.request_async::<Completions>(params, f);
+    }
+
+    pub fn disassemble_async(
+        &self,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: usize,
+        f: impl RpcCallback<DisassembleResponse, RpcError> + 'static,
+    ) {
+        let params = DisassembleArguments {
+            memory_reference,
+            offset: None,
+            instruction_offset: Some(instruction_offset),
+            instruction_count,
+            resolve_symbols: Some(true),
+        };
+        self.request_async::<Disassemble>(params, f);
+    }
+
+    pub fn read_memory_async(
+        &self,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: impl RpcCallback<ReadMemoryResponse, RpcError> + 'static,
+    ) {
+        let params = ReadMemoryArguments {
+            memory_reference,
+            offset: Some(offset),
+            count,
+        };
+        self.request_async::<ReadMemory>(params, f);
+    }
 }
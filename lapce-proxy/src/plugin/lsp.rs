@@ -1,6 +1,7 @@
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 use std::{
+    collections::HashMap,
     io::{BufRead, BufReader, BufWriter, Write},
     path::{Path, PathBuf},
     process::{self, Child, Command, Stdio},
@@ -23,10 +24,11 @@
 use serde_json::Value;
 
 use super::{
-    client_capabilities,
+    client_capabilities, initialize_params,
     psp::{
-        handle_plugin_server_message, PluginHandlerNotification, PluginHostHandler,
-        PluginServerHandler, PluginServerRpcHandler, RpcCallback,
+        handle_plugin_server_message, inline_completion_provider,
+        PluginHandlerNotification, PluginHostHandler, PluginServerHandler,
+        PluginServerRpcHandler, RpcCallback,
     },
 };
 use crate::{buffer::Buffer, plugin::PluginCatalogRpcHandler};
@@ -68,6 +70,10 @@ fn method_registered(&mut self, method: &'static str) -> bool {
         self.host.method_registered(method)
     }
 
+    fn params_supported(&mut self, method: &'static str, params: &Params) -> bool {
+        self.host.params_supported(method, params)
+    }
+
     fn document_supported(
         &mut self,
         lanaguage_id: Option<&str>,
@@ -91,6 +97,9 @@ fn handle_handler_notification(
             Shutdown => {
                 self.shutdown();
             }
+            PullDocumentDiagnostics { uri, version } => {
+                self.host.pull_changed_document_diagnostics(uri, version);
+            }
         }
     }
 
@@ -108,6 +117,17 @@ fn handle_host_notification(&mut self, method: String, params: Params) {
         let _ = self.host.handle_notification(method, params);
     }
 
+    fn handle_did_open_text_document(&mut self, document: TextDocumentItem) {
+        self.host.handle_did_open_text_document(document);
+    }
+
+    fn handle_did_change_configuration(
+        &mut self,
+        configurations: Option<HashMap<String, Value>>,
+    ) {
+        self.host.handle_did_change_configuration(configurations);
+    }
+
     fn handle_did_save_text_document(
         &self,
         language_id: String,
@@ -165,6 +185,7 @@ fn new(
         workspace: Option<PathBuf>,
         volt_id: VoltID,
         volt_display_name: String,
+        configurations: Option<HashMap<String, Value>>,
         pwd: Option<PathBuf>,
         server_uri: Url,
         args: Vec<String>,
@@ -256,6 +277,7 @@ fn new(
             volt_id,
             volt_display_name,
             document_selector,
+            configurations,
             server_rpc.clone(),
             plugin_rpc.clone(),
         );
@@ -277,6 +299,7 @@ pub fn start(
         workspace: Option<PathBuf>,
         volt_id: VoltID,
         volt_display_name: String,
+        configurations: Option<HashMap<String, Value>>,
         pwd: Option<PathBuf>,
         server_uri: Url,
         args: Vec<String>,
@@ -288,6 +311,7 @@ pub fn start(
             workspace,
             volt_id,
             volt_display_name,
+            configurations,
             pwd,
             server_uri,
             args,
@@ -327,11 +351,14 @@ fn initialize(&mut self) {
         };
         if let Ok(value) = self.server_rpc.server_request(
             Initialize::METHOD,
-            params,
+            initialize_params(params),
             None,
             None,
             false,
         ) {
+            self.host.inline_completion_provider = inline_completion_provider(
+                &value["capabilities"]["inlineCompletionProvider"],
+            );
             let result: InitializeResult = serde_json::from_value(value).unwrap();
             self.host.server_capabilities = result.capabilities;
             self.server_rpc.server_notification(
@@ -341,6 +368,7 @@ fn initialize(&mut self) {
                 None,
                 false,
             );
+            self.host.pull_workspace_diagnostics();
             if self
                 .plugin_rpc
                 .plugin_server_loaded(self.server_rpc.clone())
@@ -22,7 +22,15 @@
 use lapce_core::directory::Directory;
 use lapce_rpc::{
     core::CoreRpcHandler,
-    dap_types::{DapId, RunDebugConfig, SourceBreakpoint, ThreadId},
+    dap_types::{
+        CompletionsResponse, DapId, DisassembleResponse, EvaluateResponse,
+        FunctionBreakpoint, ReadMemoryResponse, RunDebugConfig, ScopesResponse,
+        SetVariableResponse, SourceBreakpoint, ThreadId, VariablesResponse,
+    },
+    inline_completion::{
+        InlineCompletionContext, InlineCompletionParams, InlineCompletionRequest,
+        InlineCompletionResponse, InlineCompletionTriggerKind,
+    },
     plugin::{PluginId, VoltInfo, VoltMetadata},
     proxy::ProxyRpcHandler,
     style::LineStyle,
@@ -31,39 +39,66 @@
 };
 use lapce_xi_rope::{Rope, RopeDelta};
 use lsp_types::{
+    notification::{
+        DidChangeWatchedFiles, DidCreateFiles, DidDeleteFiles, DidRenameFiles,
+        Notification,
+    },
     request::{
-        CodeActionRequest, CodeActionResolveRequest, Completion,
-        DocumentSymbolRequest, Formatting, GotoDefinition, GotoTypeDefinition,
+        CodeActionRequest, CodeActionResolveRequest, ColorPresentationRequest,
+        Completion, DocumentColor, DocumentLinkRequest, DocumentSymbolRequest,
+        ExecuteCommand, Formatting, GotoDeclaration, GotoDeclarationParams,
+        GotoDeclarationResponse, GotoDefinition, GotoImplementation,
+        GotoImplementationParams, GotoImplementationResponse, GotoTypeDefinition,
         GotoTypeDefinitionParams, GotoTypeDefinitionResponse, HoverRequest,
-        InlayHintRequest, PrepareRenameRequest, References, Rename, Request,
-        ResolveCompletionItem, SelectionRangeRequest, SemanticTokensFullRequest,
-        SignatureHelpRequest, WorkspaceSymbol,
+        InlayHintRequest, LinkedEditingRange, OnTypeFormatting,
+        PrepareRenameRequest, RangeFormatting, References, Rename, Request,
+        ResolveCompletionItem, SelectionRangeRequest,
+        SemanticTokensFullDeltaRequest, SemanticTokensFullRequest,
+        SemanticTokensRangeRequest, SignatureHelpRequest, WillRenameFiles,
+        WorkspaceSymbol,
     },
     ClientCapabilities, CodeAction, CodeActionCapabilityResolveSupport,
     CodeActionClientCapabilities, CodeActionContext, CodeActionKind,
     CodeActionKindLiteralSupport, CodeActionLiteralSupport, CodeActionParams,
-    CodeActionResponse, CompletionClientCapabilities, CompletionItem,
-    CompletionItemCapability, CompletionItemCapabilityResolveSupport,
-    CompletionParams, CompletionResponse, Diagnostic, DocumentFormattingParams,
-    DocumentSymbolParams, DocumentSymbolResponse, FormattingOptions, GotoCapability,
+    CodeActionResponse, Color, ColorInformation, ColorPresentation,
+    ColorPresentationParams, Command, CompletionClientCapabilities,
+    CompletionContext, CompletionItem, CompletionItemCapability,
+    CompletionItemCapabilityResolveSupport, CompletionParams, CompletionResponse,
+    CompletionTextEdit, CompletionTriggerKind, CreateFilesParams, DeleteFilesParams,
+    Diagnostic, DiagnosticClientCapabilities, DiagnosticWorkspaceClientCapabilities,
+    DidChangeWatchedFilesClientCapabilities, DidChangeWatchedFilesParams,
+    DocumentChangeOperation, DocumentChanges, DocumentColorClientCapabilities,
+    DocumentColorParams, DocumentFormattingParams, DocumentLink,
+    DocumentLinkClientCapabilities, DocumentLinkParams,
+    DocumentOnTypeFormattingParams, DocumentRangeFormattingParams,
+    DocumentSymbolParams, DocumentSymbolResponse,
+    DynamicRegistrationClientCapabilities, ExecuteCommandParams, FileCreate,
+    FileDelete, FileEvent, FileRename, FormattingOptions, GotoCapability,
     GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverClientCapabilities,
-    HoverParams, InlayHint, InlayHintClientCapabilities, InlayHintParams, Location,
-    MarkupKind, MessageActionItemCapabilities, ParameterInformationSettings,
+    HoverParams, InitializeParams, InlayHint, InlayHintClientCapabilities,
+    InlayHintParams, InsertReplaceEdit, InsertTextFormat, InsertTextMode,
+    LinkedEditingRangeClientCapabilities, LinkedEditingRangeParams,
+    LinkedEditingRanges, Location, MarkupKind, MessageActionItemCapabilities, OneOf,
+    OptionalVersionedTextDocumentIdentifier, ParameterInformationSettings,
     PartialResultParams, Position, PrepareRenameResponse, Range, ReferenceContext,
-    ReferenceParams, RenameParams, SelectionRange, SelectionRangeParams,
-    SemanticTokens, SemanticTokensClientCapabilities, SemanticTokensParams,
+    ReferenceParams, RenameFilesParams, RenameParams, SelectionRange,
+    SelectionRangeParams, SemanticTokens, SemanticTokensClientCapabilities,
+    SemanticTokensClientCapabilitiesRequests, SemanticTokensDeltaParams,
+    SemanticTokensFullDeltaResult, SemanticTokensFullOptions, SemanticTokensParams,
+    SemanticTokensRangeParams, SemanticTokensRangeResult,
     ShowMessageRequestClientCapabilities, SignatureHelp,
-    SignatureHelpClientCapabilities, SignatureHelpParams,
+    SignatureHelpClientCapabilities, SignatureHelpContext, SignatureHelpParams,
     SignatureInformationSettings, SymbolInformation, TextDocumentClientCapabilities,
-    TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams,
-    TextDocumentSyncClientCapabilities, TextEdit, Url,
+    TextDocumentEdit, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, TextDocumentSyncClientCapabilities, TextEdit, Url,
     VersionedTextDocumentIdentifier, WindowClientCapabilities,
     WorkDoneProgressParams, WorkspaceClientCapabilities, WorkspaceEdit,
-    WorkspaceSymbolClientCapabilities, WorkspaceSymbolParams,
+    WorkspaceFileOperationsClientCapabilities, WorkspaceSymbolClientCapabilities,
+    WorkspaceSymbolParams,
 };
 use parking_lot::Mutex;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use tar::Archive;
 
 use self::{
@@ -133,6 +168,8 @@ pub enum PluginCatalogNotification {
     DapStart {
         config: RunDebugConfig,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
     },
     DapProcessId {
         dap_id: DapId,
@@ -147,6 +184,32 @@ pub enum PluginCatalogNotification {
         dap_id: DapId,
         thread_id: ThreadId,
     },
+    DapStepOver {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepInto {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepOut {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepBack {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepInstruction {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapGoto {
+        dap_id: DapId,
+        thread_id: ThreadId,
+        path: PathBuf,
+        line: usize,
+    },
     DapStop {
         dap_id: DapId,
     },
@@ -156,12 +219,67 @@ pub enum PluginCatalogNotification {
     DapRestart {
         dap_id: DapId,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
     },
     DapSetBreakpoints {
         dap_id: DapId,
         path: PathBuf,
         breakpoints: Vec<SourceBreakpoint>,
     },
+    DapSetExceptionBreakpoints {
+        dap_id: DapId,
+        exception_filters: HashMap<String, bool>,
+    },
+    DapSetFunctionBreakpoints {
+        dap_id: DapId,
+        breakpoints: Vec<FunctionBreakpoint>,
+    },
+    DapScopes {
+        dap_id: DapId,
+        frame_id: usize,
+        f: Box<dyn RpcCallback<ScopesResponse, RpcError>>,
+    },
+    DapVariables {
+        dap_id: DapId,
+        variables_reference: usize,
+        f: Box<dyn RpcCallback<VariablesResponse, RpcError>>,
+    },
+    DapEvaluate {
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        context: Option<String>,
+        f: Box<dyn RpcCallback<EvaluateResponse, RpcError>>,
+    },
+    DapSetVariable {
+        dap_id: DapId,
+        variables_reference: usize,
+        name: String,
+        value: String,
+        f: Box<dyn RpcCallback<SetVariableResponse, RpcError>>,
+    },
+    DapCompletions {
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+        f: Box<dyn RpcCallback<CompletionsResponse, RpcError>>,
+    },
+    DapDisassemble {
+        dap_id: DapId,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: usize,
+        f: Box<dyn RpcCallback<DisassembleResponse, RpcError>>,
+    },
+    DapReadMemory {
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: Box<dyn RpcCallback<ReadMemoryResponse, RpcError>>,
+    },
     Shutdown,
 }
 
@@ -372,6 +490,23 @@ fn send_request<P: Serialize>(
         let _ = self.plugin_tx.send(rpc);
     }
 
+    fn send_notification<P: Serialize>(
+        &self,
+        method: &'static str,
+        params: P,
+        language_id: Option<String>,
+        path: Option<PathBuf>,
+    ) {
+        let params = serde_json::to_value(params).unwrap();
+        let rpc = PluginCatalogRpc::ServerNotification {
+            method,
+            params,
+            language_id,
+            path,
+        };
+        let _ = self.plugin_tx.send(rpc);
+    }
+
     pub fn format_semantic_tokens(
         &self,
         plugin_id: PluginId,
@@ -387,6 +522,95 @@ pub fn format_semantic_tokens(
         });
     }
 
+    /// Ask the servers for the edits to make before the path is renamed,
+    /// e.g. updating the imports of a moved module. The edits of all the
+    /// servers are merged, and the callback is called once every server has
+    /// responded.
+    pub fn will_rename_files(
+        &self,
+        from: &Path,
+        to: &Path,
+        cb: impl FnOnce(Option<WorkspaceEdit>) + Send + 'static,
+    ) {
+        let method = WillRenameFiles::METHOD;
+        let params = RenameFilesParams {
+            files: vec![FileRename {
+                old_uri: Url::from_file_path(from).unwrap().to_string(),
+                new_uri: Url::from_file_path(to).unwrap().to_string(),
+            }],
+        };
+        let request_sent = Arc::new(AtomicUsize::new(0));
+        let received = Arc::new(AtomicUsize::new(0));
+        #[allow(clippy::type_complexity)]
+        let pending: Arc<
+            Mutex<(
+                Vec<WorkspaceEdit>,
+                Option<Box<dyn FnOnce(Option<WorkspaceEdit>) + Send>>,
+            )>,
+        > = Arc::new(Mutex::new((Vec::new(), Some(Box::new(cb)))));
+        let local_request_sent = request_sent.clone();
+        self.send_request(
+            None,
+            Some(request_sent),
+            method,
+            params,
+            None,
+            None,
+            move |_, result| {
+                let mut pending = pending.lock();
+                // the servers that didn't register for the rename respond
+                // with an error, and those without edits respond with null
+                if let Some(edit) = result.ok().and_then(|value| {
+                    serde_json::from_value::<Option<WorkspaceEdit>>(value)
+                        .ok()
+                        .flatten()
+                }) {
+                    pending.0.push(edit);
+                }
+                let received = received.fetch_add(1, Ordering::AcqRel) + 1;
+                if received == local_request_sent.load(Ordering::Acquire) {
+                    if let Some(cb) = pending.1.take() {
+                        let edits = std::mem::take(&mut pending.0);
+                        cb(merge_workspace_edits(edits));
+                    }
+                }
+            },
+        );
+    }
+
+    pub fn did_rename_files(&self, from: &Path, to: &Path) {
+        let params = RenameFilesParams {
+            files: vec![FileRename {
+                old_uri: Url::from_file_path(from).unwrap().to_string(),
+                new_uri: Url::from_file_path(to).unwrap().to_string(),
+            }],
+        };
+        self.send_notification(DidRenameFiles::METHOD, params, None, None);
+    }
+
+    pub fn did_create_files(&self, path: &Path) {
+        let params = CreateFilesParams {
+            files: vec![FileCreate {
+                uri: Url::from_file_path(path).unwrap().to_string(),
+            }],
+        };
+        self.send_notification(DidCreateFiles::METHOD, params, None, None);
+    }
+
+    pub fn did_delete_files(&self, path: &Path) {
+        let params = DeleteFilesParams {
+            files: vec![FileDelete {
+                uri: Url::from_file_path(path).unwrap().to_string(),
+            }],
+        };
+        self.send_notification(DidDeleteFiles::METHOD, params, None, None);
+    }
+
+    pub fn did_change_watched_files(&self, changes: Vec<FileEvent>) {
+        let params = DidChangeWatchedFilesParams { changes };
+        self.send_notification(DidChangeWatchedFiles::METHOD, params, None, None);
+    }
+
     pub fn did_save_text_document(&self, path: &Path, text: Rope) {
         let text_document =
             TextDocumentIdentifier::new(Url::from_file_path(path).unwrap());
@@ -485,6 +709,68 @@ pub fn get_type_definition(
         );
     }
 
+    pub fn get_implementation(
+        &self,
+        path: &Path,
+        position: Position,
+        cb: impl FnOnce(PluginId, Result<GotoImplementationResponse, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = GotoImplementation::METHOD;
+        let params = GotoImplementationParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
+    pub fn get_declaration(
+        &self,
+        path: &Path,
+        position: Position,
+        cb: impl FnOnce(PluginId, Result<GotoDeclarationResponse, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = GotoDeclaration::METHOD;
+        let params = GotoDeclarationParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
     pub fn get_references(
         &self,
         path: &Path,
@@ -582,6 +868,88 @@ pub fn get_inlay_hints(
         );
     }
 
+    pub fn get_document_links(
+        &self,
+        path: &Path,
+        cb: impl FnOnce(PluginId, Result<Vec<DocumentLink>, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = DocumentLinkRequest::METHOD;
+        let params = DocumentLinkParams {
+            text_document: TextDocumentIdentifier { uri },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
+    pub fn get_document_colors(
+        &self,
+        path: &Path,
+        cb: impl FnOnce(PluginId, Result<Vec<ColorInformation>, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = DocumentColor::METHOD;
+        let params = DocumentColorParams {
+            text_document: TextDocumentIdentifier { uri },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
+    pub fn get_color_presentations(
+        &self,
+        path: &Path,
+        color: Color,
+        range: Range,
+        cb: impl FnOnce(PluginId, Result<Vec<ColorPresentation>, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = ColorPresentationRequest::METHOD;
+        let params = ColorPresentationParams {
+            text_document: TextDocumentIdentifier { uri },
+            color,
+            range,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
     pub fn get_document_symbols(
         &self,
         path: &Path,
@@ -655,20 +1023,26 @@ pub fn get_document_formatting(
         );
     }
 
-    pub fn prepare_rename(
+    pub fn get_range_formatting(
         &self,
         path: &Path,
-        position: Position,
-        cb: impl FnOnce(PluginId, Result<PrepareRenameResponse, RpcError>)
+        range: Range,
+        cb: impl FnOnce(PluginId, Result<Vec<TextEdit>, RpcError>)
             + Clone
             + Send
             + 'static,
     ) {
         let uri = Url::from_file_path(path).unwrap();
-        let method = PrepareRenameRequest::METHOD;
-        let params = TextDocumentPositionParams {
+        let method = RangeFormatting::METHOD;
+        let params = DocumentRangeFormattingParams {
             text_document: TextDocumentIdentifier { uri },
-            position,
+            range,
+            options: FormattingOptions {
+                tab_size: 4,
+                insert_spaces: true,
+                ..Default::default()
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
         };
         let language_id =
             Some(language_id_from_path(path).unwrap_or("").to_string());
@@ -681,25 +1055,31 @@ pub fn prepare_rename(
         );
     }
 
-    pub fn rename(
+    /// Only the plugins which have `ch` as one of their trigger characters
+    /// will receive the request.
+    pub fn get_on_type_formatting(
         &self,
         path: &Path,
         position: Position,
-        new_name: String,
-        cb: impl FnOnce(PluginId, Result<WorkspaceEdit, RpcError>)
+        ch: String,
+        cb: impl FnOnce(PluginId, Result<Vec<TextEdit>, RpcError>)
             + Clone
             + Send
             + 'static,
     ) {
         let uri = Url::from_file_path(path).unwrap();
-        let method = Rename::METHOD;
-        let params = RenameParams {
+        let method = OnTypeFormatting::METHOD;
+        let params = DocumentOnTypeFormattingParams {
             text_document_position: TextDocumentPositionParams {
                 text_document: TextDocumentIdentifier { uri },
                 position,
             },
-            new_name,
-            work_done_progress_params: WorkDoneProgressParams::default(),
+            ch,
+            options: FormattingOptions {
+                tab_size: 4,
+                insert_spaces: true,
+                ..Default::default()
+            },
         };
         let language_id =
             Some(language_id_from_path(path).unwrap_or("").to_string());
@@ -712,20 +1092,20 @@ pub fn rename(
         );
     }
 
-    pub fn get_semantic_tokens(
+    pub fn prepare_rename(
         &self,
         path: &Path,
-        cb: impl FnOnce(PluginId, Result<SemanticTokens, RpcError>)
+        position: Position,
+        cb: impl FnOnce(PluginId, Result<PrepareRenameResponse, RpcError>)
             + Clone
             + Send
             + 'static,
     ) {
         let uri = Url::from_file_path(path).unwrap();
-        let method = SemanticTokensFullRequest::METHOD;
-        let params = SemanticTokensParams {
+        let method = PrepareRenameRequest::METHOD;
+        let params = TextDocumentPositionParams {
             text_document: TextDocumentIdentifier { uri },
-            work_done_progress_params: WorkDoneProgressParams::default(),
-            partial_result_params: PartialResultParams::default(),
+            position,
         };
         let language_id =
             Some(language_id_from_path(path).unwrap_or("").to_string());
@@ -738,21 +1118,181 @@ pub fn get_semantic_tokens(
         );
     }
 
-    pub fn get_selection_range(
+    pub fn linked_editing_range(
         &self,
         path: &Path,
-        positions: Vec<Position>,
-        cb: impl FnOnce(PluginId, Result<Vec<SelectionRange>, RpcError>)
+        position: Position,
+        cb: impl FnOnce(PluginId, Result<LinkedEditingRanges, RpcError>)
             + Clone
             + Send
             + 'static,
     ) {
         let uri = Url::from_file_path(path).unwrap();
-        let method = SelectionRangeRequest::METHOD;
-        let params = SelectionRangeParams {
-            text_document: TextDocumentIdentifier { uri },
-            positions,
-            work_done_progress_params: WorkDoneProgressParams::default(),
+        let method = LinkedEditingRange::METHOD;
+        let params = LinkedEditingRangeParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
+    pub fn rename(
+        &self,
+        path: &Path,
+        position: Position,
+        new_name: String,
+        cb: impl FnOnce(PluginId, Result<WorkspaceEdit, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = Rename::METHOD;
+        let params = RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            new_name,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
+    pub fn get_semantic_tokens(
+        &self,
+        path: &Path,
+        cb: impl FnOnce(PluginId, Result<SemanticTokens, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = SemanticTokensFullRequest::METHOD;
+        let params = SemanticTokensParams {
+            text_document: TextDocumentIdentifier { uri },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
+    /// Request the edits to the semantic tokens of the last full or delta
+    /// response, which only the plugin that made that response can answer
+    pub fn get_semantic_tokens_delta(
+        &self,
+        plugin_id: PluginId,
+        path: &Path,
+        previous_result_id: String,
+        cb: impl FnOnce(Result<SemanticTokensFullDeltaResult, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = SemanticTokensFullDeltaRequest::METHOD;
+        let params = SemanticTokensDeltaParams {
+            text_document: TextDocumentIdentifier { uri },
+            previous_result_id,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request(
+            Some(plugin_id),
+            None,
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            move |_, result| {
+                let result = match result {
+                    Ok(value) => serde_json::from_value::<
+                        SemanticTokensFullDeltaResult,
+                    >(value)
+                    .map_err(|_| RpcError {
+                        code: 0,
+                        message: "semantic tokens delta deserialize error"
+                            .to_string(),
+                    }),
+                    Err(e) => Err(e),
+                };
+                cb(result)
+            },
+        );
+    }
+
+    pub fn get_semantic_tokens_range(
+        &self,
+        path: &Path,
+        range: Range,
+        cb: impl FnOnce(PluginId, Result<SemanticTokensRangeResult, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = SemanticTokensRangeRequest::METHOD;
+        let params = SemanticTokensRangeParams {
+            text_document: TextDocumentIdentifier { uri },
+            range,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
+    pub fn get_selection_range(
+        &self,
+        path: &Path,
+        positions: Vec<Position>,
+        cb: impl FnOnce(PluginId, Result<Vec<SelectionRange>, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = SelectionRangeRequest::METHOD;
+        let params = SelectionRangeParams {
+            text_document: TextDocumentIdentifier { uri },
+            positions,
+            work_done_progress_params: WorkDoneProgressParams::default(),
             partial_result_params: Default::default(),
         };
         let language_id =
@@ -799,6 +1339,7 @@ pub fn completion(
         path: &Path,
         input: String,
         position: Position,
+        trigger_kind: CompletionTriggerKind,
     ) {
         let uri = Url::from_file_path(path).unwrap();
         let method = Completion::METHOD;
@@ -809,7 +1350,10 @@ pub fn completion(
             },
             work_done_progress_params: WorkDoneProgressParams::default(),
             partial_result_params: PartialResultParams::default(),
-            context: None,
+            context: Some(CompletionContext {
+                trigger_kind,
+                trigger_character: None,
+            }),
         };
 
         let core_rpc = self.core_rpc.clone();
@@ -823,9 +1367,34 @@ pub fn completion(
             Some(path.to_path_buf()),
             move |plugin_id, result| {
                 if let Ok(value) = result {
-                    if let Ok(resp) =
+                    let item_defaults = value.get("itemDefaults").cloned();
+                    // The text to insert when the default edit range is used
+                    let text_edit_texts: Vec<Option<String>> = value
+                        .get("items")
+                        .and_then(|items| items.as_array())
+                        .map(|items| {
+                            items
+                                .iter()
+                                .map(|item| {
+                                    item.get("textEditText")
+                                        .and_then(|text| text.as_str())
+                                        .map(|text| text.to_string())
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    if let Ok(mut resp) =
                         serde_json::from_value::<CompletionResponse>(value)
                     {
+                        if let (CompletionResponse::List(list), Some(defaults)) =
+                            (&mut resp, item_defaults)
+                        {
+                            apply_completion_item_defaults(
+                                &mut list.items,
+                                &text_edit_texts,
+                                &defaults,
+                            );
+                        }
                         core_rpc
                             .completion_response(request_id, input, resp, plugin_id);
                     }
@@ -870,43 +1439,94 @@ pub fn completion_resolve(
         );
     }
 
+    /// Run a command of the plugin. Its result is ignored, as the changes it
+    /// makes come back as separate requests, e.g. `workspace/applyEdit`.
+    pub fn execute_command(&self, plugin_id: PluginId, command: Command) {
+        let method = ExecuteCommand::METHOD;
+        let params = ExecuteCommandParams {
+            command: command.command,
+            arguments: command.arguments.unwrap_or_default(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        self.send_request(
+            Some(plugin_id),
+            None,
+            method,
+            params,
+            None,
+            None,
+            move |_, result| {
+                if let Err(err) = result {
+                    eprintln!("{}", err.message);
+                }
+            },
+        );
+    }
+
     pub fn signature_help(
         &self,
-        request_id: usize,
         path: &Path,
         position: Position,
+        context: Option<SignatureHelpContext>,
+        cb: impl FnOnce(PluginId, Result<SignatureHelp, RpcError>)
+            + Clone
+            + Send
+            + 'static,
     ) {
         let uri = Url::from_file_path(path).unwrap();
         let method = SignatureHelpRequest::METHOD;
         let params = SignatureHelpParams {
-            // TODO: We could provide more information about the signature for the LSP to work with
-            context: None,
+            context,
             text_document_position_params: TextDocumentPositionParams {
                 text_document: TextDocumentIdentifier { uri },
                 position,
             },
             work_done_progress_params: WorkDoneProgressParams::default(),
         };
-
-        let core_rpc = self.core_rpc.clone();
         let language_id =
             Some(language_id_from_path(path).unwrap_or("").to_string());
-        self.send_request(
-            None,
-            None,
+
+        self.send_request_to_all_plugins(
             method,
             params,
             language_id,
             Some(path.to_path_buf()),
-            move |plugin_id, result| {
-                if let Ok(value) = result {
-                    if let Ok(resp) = serde_json::from_value::<SignatureHelp>(value)
-                    {
-                        core_rpc
-                            .signature_help_response(request_id, resp, plugin_id);
-                    }
-                }
+            cb,
+        );
+    }
+
+    pub fn get_inline_completions(
+        &self,
+        path: &Path,
+        position: Position,
+        trigger_kind: InlineCompletionTriggerKind,
+        cb: impl FnOnce(PluginId, Result<InlineCompletionResponse, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = InlineCompletionRequest::METHOD;
+        let params = InlineCompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            context: InlineCompletionContext {
+                trigger_kind,
+                selected_completion_info: None,
             },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
         );
     }
 
@@ -1012,10 +1632,14 @@ pub fn dap_start(
         &self,
         config: RunDebugConfig,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
     ) -> Result<()> {
         self.catalog_notification(PluginCatalogNotification::DapStart {
             config,
             breakpoints,
+            exception_filters,
+            function_breakpoints,
         })
     }
 
@@ -1046,6 +1670,60 @@ pub fn dap_pause(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
         })
     }
 
+    pub fn dap_step_over(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapStepOver {
+            dap_id,
+            thread_id,
+        })
+    }
+
+    pub fn dap_step_into(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapStepInto {
+            dap_id,
+            thread_id,
+        })
+    }
+
+    pub fn dap_step_out(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapStepOut {
+            dap_id,
+            thread_id,
+        })
+    }
+
+    pub fn dap_step_back(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapStepBack {
+            dap_id,
+            thread_id,
+        })
+    }
+
+    pub fn dap_step_instruction(
+        &self,
+        dap_id: DapId,
+        thread_id: ThreadId,
+    ) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapStepInstruction {
+            dap_id,
+            thread_id,
+        })
+    }
+
+    pub fn dap_goto(
+        &self,
+        dap_id: DapId,
+        thread_id: ThreadId,
+        path: PathBuf,
+        line: usize,
+    ) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapGoto {
+            dap_id,
+            thread_id,
+            path,
+            line,
+        })
+    }
+
     pub fn dap_stop(&self, dap_id: DapId) -> Result<()> {
         self.catalog_notification(PluginCatalogNotification::DapStop { dap_id })
     }
@@ -1060,10 +1738,14 @@ pub fn dap_restart(
         &self,
         dap_id: DapId,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
     ) -> Result<()> {
         self.catalog_notification(PluginCatalogNotification::DapRestart {
             dap_id,
             breakpoints,
+            exception_filters,
+            function_breakpoints,
         })
     }
 
@@ -1079,6 +1761,147 @@ pub fn dap_set_breakpoints(
             breakpoints,
         })
     }
+
+    pub fn dap_set_exception_breakpoints(
+        &self,
+        dap_id: DapId,
+        exception_filters: HashMap<String, bool>,
+    ) -> Result<()> {
+        self.catalog_notification(
+            PluginCatalogNotification::DapSetExceptionBreakpoints {
+                dap_id,
+                exception_filters,
+            },
+        )
+    }
+
+    pub fn dap_set_function_breakpoints(
+        &self,
+        dap_id: DapId,
+        breakpoints: Vec<FunctionBreakpoint>,
+    ) -> Result<()> {
+        self.catalog_notification(
+            PluginCatalogNotification::DapSetFunctionBreakpoints {
+                dap_id,
+                breakpoints,
+            },
+        )
+    }
+
+    pub fn dap_scopes(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+        f: impl FnOnce(Result<ScopesResponse, RpcError>) + Send + 'static,
+    ) {
+        let _ = self.catalog_notification(PluginCatalogNotification::DapScopes {
+            dap_id,
+            frame_id,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_variables(
+        &self,
+        dap_id: DapId,
+        variables_reference: usize,
+        f: impl FnOnce(Result<VariablesResponse, RpcError>) + Send + 'static,
+    ) {
+        let _ = self.catalog_notification(PluginCatalogNotification::DapVariables {
+            dap_id,
+            variables_reference,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        context: Option<String>,
+        f: impl FnOnce(Result<EvaluateResponse, RpcError>) + Send + 'static,
+    ) {
+        let _ = self.catalog_notification(PluginCatalogNotification::DapEvaluate {
+            dap_id,
+            expression,
+            frame_id,
+            context,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_set_variable(
+        &self,
+        dap_id: DapId,
+        variables_reference: usize,
+        name: String,
+        value: String,
+        f: impl FnOnce(Result<SetVariableResponse, RpcError>) + Send + 'static,
+    ) {
+        let _ =
+            self.catalog_notification(PluginCatalogNotification::DapSetVariable {
+                dap_id,
+                variables_reference,
+                name,
+                value,
+                f: Box::new(f),
+            });
+    }
+
+    pub fn dap_completions(
+        &self,
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+        f: impl FnOnce(Result<CompletionsResponse, RpcError>) + Send + 'static,
+    ) {
+        let _ =
+            self.catalog_notification(PluginCatalogNotification::DapCompletions {
+                dap_id,
+                frame_id,
+                text,
+                column,
+                f: Box::new(f),
+            });
+    }
+
+    pub fn dap_disassemble(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: usize,
+        f: impl FnOnce(Result<DisassembleResponse, RpcError>) + Send + 'static,
+    ) {
+        let _ =
+            self.catalog_notification(PluginCatalogNotification::DapDisassemble {
+                dap_id,
+                memory_reference,
+                instruction_offset,
+                instruction_count,
+                f: Box::new(f),
+            });
+    }
+
+    pub fn dap_read_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: impl FnOnce(Result<ReadMemoryResponse, RpcError>) + Send + 'static,
+    ) {
+        let _ =
+            self.catalog_notification(PluginCatalogNotification::DapReadMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                count,
+                f: Box::new(f),
+            });
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1202,6 +2025,24 @@ pub fn remove_volt(
     Ok(())
 }
 
+/// Serialize the params of the `initialize` request, adding the client
+/// capabilities that are newer than the protocol version of `lsp_types`.
+fn initialize_params(params: InitializeParams) -> Value {
+    let mut params = serde_json::to_value(params).unwrap_or_default();
+    let text_document = &mut params["capabilities"]["textDocument"];
+    text_document["completion"]["completionList"] = json!({
+        "itemDefaults": [
+            "commitCharacters",
+            "editRange",
+            "insertTextFormat",
+            "insertTextMode",
+            "data",
+        ],
+    });
+    text_document["inlineCompletion"] = json!({ "dynamicRegistration": false });
+    params
+}
+
 fn client_capabilities() -> ClientCapabilities {
     ClientCapabilities {
         text_document: Some(TextDocumentClientCapabilities {
@@ -1213,11 +2054,15 @@ fn client_capabilities() -> ClientCapabilities {
             completion: Some(CompletionClientCapabilities {
                 completion_item: Some(CompletionItemCapability {
                     snippet_support: Some(true),
+                    commit_characters_support: Some(true),
+                    preselect_support: Some(true),
+                    insert_replace_support: Some(true),
                     resolve_support: Some(CompletionItemCapabilityResolveSupport {
                         properties: vec!["additionalTextEdits".to_string()],
                     }),
                     ..Default::default()
                 }),
+                context_support: Some(true),
                 ..Default::default()
             }),
             signature_help: Some(SignatureHelpClientCapabilities {
@@ -1231,6 +2076,7 @@ fn client_capabilities() -> ClientCapabilities {
                     }),
                     active_parameter_support: Some(true),
                 }),
+                context_support: Some(true),
                 ..Default::default()
             }),
             hover: Some(HoverClientCapabilities {
@@ -1243,6 +2089,16 @@ fn client_capabilities() -> ClientCapabilities {
             inlay_hint: Some(InlayHintClientCapabilities {
                 ..Default::default()
             }),
+            linked_editing_range: Some(LinkedEditingRangeClientCapabilities {
+                ..Default::default()
+            }),
+            document_link: Some(DocumentLinkClientCapabilities {
+                tooltip_support: Some(true),
+                ..Default::default()
+            }),
+            color_provider: Some(DocumentColorClientCapabilities {
+                ..Default::default()
+            }),
             code_action: Some(CodeActionClientCapabilities {
                 data_support: Some(true),
                 resolve_support: Some(CodeActionCapabilityResolveSupport {
@@ -1269,6 +2125,12 @@ fn client_capabilities() -> ClientCapabilities {
                 ..Default::default()
             }),
             semantic_tokens: Some(SemanticTokensClientCapabilities {
+                requests: SemanticTokensClientCapabilitiesRequests {
+                    range: Some(true),
+                    full: Some(SemanticTokensFullOptions::Delta {
+                        delta: Some(true),
+                    }),
+                },
                 ..Default::default()
             }),
             type_definition: Some(GotoCapability {
@@ -1280,6 +2142,18 @@ fn client_capabilities() -> ClientCapabilities {
             definition: Some(GotoCapability {
                 ..Default::default()
             }),
+            implementation: Some(GotoCapability {
+                link_support: Some(false),
+                ..Default::default()
+            }),
+            declaration: Some(GotoCapability {
+                link_support: Some(false),
+                ..Default::default()
+            }),
+            diagnostic: Some(DiagnosticClientCapabilities {
+                related_document_support: Some(true),
+                ..Default::default()
+            }),
             ..Default::default()
         }),
         window: Some(WindowClientCapabilities {
@@ -1295,9 +2169,308 @@ fn client_capabilities() -> ClientCapabilities {
             symbol: Some(WorkspaceSymbolClientCapabilities {
                 ..Default::default()
             }),
-            configuration: Some(false),
+            configuration: Some(true),
+            file_operations: Some(WorkspaceFileOperationsClientCapabilities {
+                did_create: Some(true),
+                did_rename: Some(true),
+                will_rename: Some(true),
+                did_delete: Some(true),
+                ..Default::default()
+            }),
+            did_change_watched_files: Some(
+                DidChangeWatchedFilesClientCapabilities {
+                    dynamic_registration: Some(true),
+                    ..Default::default()
+                },
+            ),
+            did_change_configuration: Some(DynamicRegistrationClientCapabilities {
+                dynamic_registration: Some(false),
+            }),
+            diagnostic: Some(DiagnosticWorkspaceClientCapabilities {
+                refresh_support: Some(true),
+            }),
             ..Default::default()
         }),
         ..Default::default()
     }
 }
+
+/// Merge the edits of several servers into one, keeping all the edits of a
+/// document together since the client applies them per document.
+fn merge_workspace_edits(edits: Vec<WorkspaceEdit>) -> Option<WorkspaceEdit> {
+    fn push_edit(
+        operations: &mut Vec<DocumentChangeOperation>,
+        edit: TextDocumentEdit,
+    ) {
+        let existing = operations.iter_mut().find_map(|operation| match operation {
+            DocumentChangeOperation::Edit(existing)
+                if existing.text_document.uri == edit.text_document.uri =>
+            {
+                Some(existing)
+            }
+            _ => None,
+        });
+        match existing {
+            Some(existing) => existing.edits.extend(edit.edits),
+            None => operations.push(DocumentChangeOperation::Edit(edit)),
+        }
+    }
+
+    if edits.len() <= 1 {
+        return edits.into_iter().next();
+    }
+
+    let mut operations: Vec<DocumentChangeOperation> = Vec::new();
+    let mut change_annotations = HashMap::new();
+    for edit in edits {
+        // the document changes take precedence over the changes when a
+        // server gives both
+        match (edit.document_changes, edit.changes) {
+            (Some(DocumentChanges::Edits(edits)), _) => {
+                for edit in edits {
+                    push_edit(&mut operations, edit);
+                }
+            }
+            (Some(DocumentChanges::Operations(ops)), _) => {
+                for op in ops {
+                    match op {
+                        DocumentChangeOperation::Edit(edit) => {
+                            push_edit(&mut operations, edit)
+                        }
+                        op => operations.push(op),
+                    }
+                }
+            }
+            (None, Some(changes)) => {
+                for (uri, edits) in changes {
+                    push_edit(
+                        &mut operations,
+                        TextDocumentEdit {
+                            text_document: OptionalVersionedTextDocumentIdentifier {
+                                uri,
+                                version: None,
+                            },
+                            edits: edits.into_iter().map(OneOf::Left).collect(),
+                        },
+                    );
+                }
+            }
+            (None, None) => {}
+        }
+        if let Some(annotations) = edit.change_annotations {
+            change_annotations.extend(annotations);
+        }
+    }
+
+    Some(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: if change_annotations.is_empty() {
+            None
+        } else {
+            Some(change_annotations)
+        },
+    })
+}
+
+/// Fill in the properties that the items of a completion list leave out with
+/// the `itemDefaults` of the list, which `lsp_types` doesn't know about.
+fn apply_completion_item_defaults(
+    items: &mut [CompletionItem],
+    text_edit_texts: &[Option<String>],
+    defaults: &Value,
+) {
+    fn get<T: DeserializeOwned>(value: &Value, key: &str) -> Option<T> {
+        serde_json::from_value(value.get(key)?.clone()).ok()
+    }
+
+    let commit_characters: Option<Vec<String>> = get(defaults, "commitCharacters");
+    let insert_text_format: Option<InsertTextFormat> =
+        get(defaults, "insertTextFormat");
+    let insert_text_mode: Option<InsertTextMode> = get(defaults, "insertTextMode");
+    let data: Option<Value> = defaults.get("data").cloned();
+    // The edit range is either a range, or the insert and replace ranges
+    let edit_range: Option<Range> = get(defaults, "editRange");
+    let insert_replace_range: Option<(Range, Range)> =
+        defaults.get("editRange").and_then(|edit_range| {
+            Some((get(edit_range, "insert")?, get(edit_range, "replace")?))
+        });
+
+    for (i, item) in items.iter_mut().enumerate() {
+        if item.commit_characters.is_none() {
+            item.commit_characters = commit_characters.clone();
+        }
+        if item.insert_text_format.is_none() {
+            item.insert_text_format = insert_text_format;
+        }
+        if item.insert_text_mode.is_none() {
+            item.insert_text_mode = insert_text_mode;
+        }
+        if item.data.is_none() {
+            item.data = data.clone();
+        }
+        if item.text_edit.is_none() {
+            let new_text = text_edit_texts
+                .get(i)
+                .cloned()
+                .flatten()
+                .unwrap_or_else(|| item.label.clone());
+            if let Some(range) = edit_range {
+                item.text_edit =
+                    Some(CompletionTextEdit::Edit(TextEdit { range, new_text }));
+            } else if let Some((insert, replace)) = insert_replace_range {
+                item.text_edit =
+                    Some(CompletionTextEdit::InsertAndReplace(InsertReplaceEdit {
+                        new_text,
+                        insert,
+                        replace,
+                    }));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{
+        CompletionItem, CompletionTextEdit, InsertReplaceEdit, InsertTextFormat,
+        InsertTextMode, Position, Range, TextEdit,
+    };
+    use serde_json::json;
+
+    use super::apply_completion_item_defaults;
+
+    fn item(label: &str) -> CompletionItem {
+        CompletionItem {
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[test]
+    fn defaults_fill_in_missing_fields() {
+        let mut items = vec![item("foo")];
+        apply_completion_item_defaults(
+            &mut items,
+            &[None],
+            &json!({
+                "commitCharacters": [".", "("],
+                "insertTextFormat": 2,
+                "insertTextMode": 2,
+                "data": { "id": 1 },
+            }),
+        );
+        let item = &items[0];
+        assert_eq!(
+            item.commit_characters,
+            Some(vec![".".to_string(), "(".to_string()])
+        );
+        assert_eq!(item.insert_text_format, Some(InsertTextFormat::SNIPPET));
+        assert_eq!(
+            item.insert_text_mode,
+            Some(InsertTextMode::ADJUST_INDENTATION)
+        );
+        assert_eq!(item.data, Some(json!({ "id": 1 })));
+        // There's no edit range to make an edit with
+        assert_eq!(item.text_edit, None);
+    }
+
+    #[test]
+    fn defaults_dont_override_the_fields_of_the_item() {
+        let edit = CompletionTextEdit::Edit(TextEdit {
+            range: range(1, 0, 2),
+            new_text: "bar".to_string(),
+        });
+        let mut items = vec![CompletionItem {
+            commit_characters: Some(vec![",".to_string()]),
+            insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+            insert_text_mode: Some(InsertTextMode::AS_IS),
+            data: Some(json!("item")),
+            text_edit: Some(edit.clone()),
+            ..item("foo")
+        }];
+        apply_completion_item_defaults(
+            &mut items,
+            &[Some("baz".to_string())],
+            &json!({
+                "commitCharacters": ["."],
+                "insertTextFormat": 2,
+                "insertTextMode": 2,
+                "data": "list",
+                "editRange": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": 0, "character": 3 },
+                },
+            }),
+        );
+        let item = &items[0];
+        assert_eq!(item.commit_characters, Some(vec![",".to_string()]));
+        assert_eq!(item.insert_text_format, Some(InsertTextFormat::PLAIN_TEXT));
+        assert_eq!(item.insert_text_mode, Some(InsertTextMode::AS_IS));
+        assert_eq!(item.data, Some(json!("item")));
+        assert_eq!(item.text_edit, Some(edit));
+    }
+
+    #[test]
+    fn default_edit_range() {
+        let mut items = vec![item("foo"), item("bar")];
+        apply_completion_item_defaults(
+            &mut items,
+            &[None, Some("bar()".to_string())],
+            &json!({
+                "editRange": {
+                    "start": { "line": 0, "character": 4 },
+                    "end": { "line": 0, "character": 6 },
+                },
+            }),
+        );
+        // The label is inserted without a `textEditText`
+        assert_eq!(
+            items[0].text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit {
+                range: range(0, 4, 6),
+                new_text: "foo".to_string(),
+            }))
+        );
+        assert_eq!(
+            items[1].text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit {
+                range: range(0, 4, 6),
+                new_text: "bar()".to_string(),
+            }))
+        );
+    }
+
+    #[test]
+    fn default_insert_and_replace_ranges() {
+        let mut items = vec![item("foo")];
+        apply_completion_item_defaults(
+            &mut items,
+            &[],
+            &json!({
+                "editRange": {
+                    "insert": {
+                        "start": { "line": 2, "character": 1 },
+                        "end": { "line": 2, "character": 3 },
+                    },
+                    "replace": {
+                        "start": { "line": 2, "character": 1 },
+                        "end": { "line": 2, "character": 5 },
+                    },
+                },
+            }),
+        );
+        assert_eq!(
+            items[0].text_edit,
+            Some(CompletionTextEdit::InsertAndReplace(InsertReplaceEdit {
+                new_text: "foo".to_string(),
+                insert: range(2, 1, 3),
+                replace: range(2, 1, 5),
+            }))
+        );
+    }
+}
@@ -6,6 +6,7 @@
         Arc,
     },
     thread,
+    time::Duration,
 };
 
 use anyhow::{anyhow, Result};
@@ -17,6 +18,8 @@
     encoding::offset_utf16_to_utf8,
 };
 use lapce_rpc::{
+    core::CoreRpcHandler,
+    inline_completion::InlineCompletionRequest,
     plugin::{PluginId, VoltID},
     style::{LineStyle, Style},
     RpcError,
@@ -24,38 +27,56 @@
 use lapce_xi_rope::{Rope, RopeDelta};
 use lsp_types::{
     notification::{
-        DidChangeTextDocument, DidOpenTextDocument, DidSaveTextDocument,
-        Initialized, LogMessage, Notification, Progress, PublishDiagnostics,
-        ShowMessage,
+        DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles,
+        DidCreateFiles, DidDeleteFiles, DidOpenTextDocument, DidRenameFiles,
+        DidSaveTextDocument, Initialized, LogMessage, Notification, Progress,
+        PublishDiagnostics, ShowMessage,
     },
     request::{
-        CodeActionRequest, CodeActionResolveRequest, Completion,
-        DocumentSymbolRequest, Formatting, GotoDefinition, GotoTypeDefinition,
-        HoverRequest, Initialize, InlayHintRequest, PrepareRenameRequest,
-        References, RegisterCapability, Rename, ResolveCompletionItem,
-        SelectionRangeRequest, SemanticTokensFullRequest, SignatureHelpRequest,
-        WorkDoneProgressCreate, WorkspaceSymbol,
+        CodeActionRequest, CodeActionResolveRequest, ColorPresentationRequest,
+        Completion, DocumentColor, DocumentDiagnosticRequest, DocumentLinkRequest,
+        DocumentSymbolRequest, ExecuteCommand, Formatting, GotoDeclaration,
+        GotoDefinition, GotoImplementation, GotoTypeDefinition, HoverRequest,
+        Initialize, InlayHintRequest, LinkedEditingRange, OnTypeFormatting,
+        PrepareRenameRequest, RangeFormatting, References, RegisterCapability,
+        Rename, ResolveCompletionItem, SelectionRangeRequest,
+        SemanticTokensFullDeltaRequest, SemanticTokensFullRequest,
+        SemanticTokensRangeRequest, SignatureHelpRequest, WillRenameFiles,
+        WorkDoneProgressCreate, WorkspaceConfiguration, WorkspaceDiagnosticRefresh,
+        WorkspaceDiagnosticRequest, WorkspaceSymbol,
     },
-    CodeActionProviderCapability, DidChangeTextDocumentParams,
-    DidSaveTextDocumentParams, DocumentSelector, HoverProviderCapability,
-    InitializeResult, LogMessageParams, OneOf, ProgressParams,
+    CodeActionProviderCapability, ColorProviderCapability, ConfigurationParams,
+    CreateFilesParams, DeleteFilesParams, Diagnostic, DiagnosticOptions,
+    DiagnosticServerCapabilities, DidChangeConfigurationParams,
+    DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+    DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentDiagnosticParams,
+    DocumentDiagnosticReport, DocumentDiagnosticReportKind,
+    DocumentDiagnosticReportResult, DocumentOnTypeFormattingParams,
+    DocumentSelector, FileChangeType, FileEvent, FileOperationPatternKind,
+    FileOperationRegistrationOptions, HoverProviderCapability, InitializeResult,
+    LogMessageParams, OneOf, PartialResultParams, PreviousResultId, ProgressParams,
     PublishDiagnosticsParams, Range, Registration, RegistrationParams,
-    SemanticTokens, SemanticTokensLegend, SemanticTokensServerCapabilities,
-    ServerCapabilities, ShowMessageParams, TextDocumentContentChangeEvent,
-    TextDocumentIdentifier, TextDocumentSaveRegistrationOptions,
+    RenameFilesParams, SemanticTokens, SemanticTokensFullOptions,
+    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensServerCapabilities,
+    ServerCapabilities, ShowMessageParams, SignatureHelpParams,
+    SignatureHelpTriggerKind, TextDocumentContentChangeEvent,
+    TextDocumentIdentifier, TextDocumentItem, TextDocumentSaveRegistrationOptions,
     TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncSaveOptions,
-    VersionedTextDocumentIdentifier,
+    Url, VersionedTextDocumentIdentifier, WorkDoneProgressParams,
+    WorkspaceDiagnosticParams, WorkspaceDiagnosticReportResult,
+    WorkspaceDocumentDiagnosticReport, WorkspaceFileOperationsServerCapabilities,
 };
 use parking_lot::Mutex;
 use psp_types::{
     ExecuteProcess, ExecuteProcessParams, ExecuteProcessResult, Request,
     StartLspServer, StartLspServerParams,
 };
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 
 use super::{
     lsp::{DocumentFilter, LspClient},
+    wasi::unflatten_map,
     PluginCatalogRpcHandler,
 };
 
@@ -97,10 +118,19 @@ fn call(self: Box<F>, result: Result<Resp, Error>) {
     }
 }
 
+/// How long after the last change of a document its diagnostics are pulled,
+/// in milliseconds
+const DIAGNOSTIC_PULL_DELAY: u64 = 300;
+
 pub enum PluginHandlerNotification {
     Initialize,
     InitializeResult(InitializeResult),
     Shutdown,
+    /// Pull the diagnostics of the document, if it's still at the version
+    PullDocumentDiagnostics {
+        uri: Url,
+        version: i32,
+    },
 }
 
 pub enum PluginServerRpc {
@@ -130,6 +160,12 @@ pub enum PluginServerRpc {
         method: String,
         params: Params,
     },
+    DidOpenTextDocument {
+        document: TextDocumentItem,
+    },
+    DidChangeConfiguration {
+        configurations: Option<HashMap<String, Value>>,
+    },
     DidSaveTextDocument {
         language_id: String,
         path: PathBuf,
@@ -174,6 +210,7 @@ fn document_supported(
         path: Option<&Path>,
     ) -> bool;
     fn method_registered(&mut self, method: &'static str) -> bool;
+    fn params_supported(&mut self, method: &'static str, params: &Params) -> bool;
     fn handle_host_notification(&mut self, method: String, params: Params);
     fn handle_host_request(
         &mut self,
@@ -186,6 +223,11 @@ fn handle_handler_notification(
         &mut self,
         notification: PluginHandlerNotification,
     );
+    fn handle_did_open_text_document(&mut self, document: TextDocumentItem);
+    fn handle_did_change_configuration(
+        &mut self,
+        configurations: Option<HashMap<String, Value>>,
+    );
     fn handle_did_save_text_document(
         &self,
         language_id: String,
@@ -394,6 +436,7 @@ pub fn mainloop<H>(&self, handler: &mut H)
                     if handler
                         .document_supported(language_id.as_deref(), path.as_deref())
                         && handler.method_registered(method)
+                        && handler.params_supported(method, &params)
                     {
                         self.send_server_request(id, method, params, rh);
                     } else {
@@ -412,6 +455,7 @@ pub fn mainloop<H>(&self, handler: &mut H)
                     if handler
                         .document_supported(language_id.as_deref(), path.as_deref())
                         && handler.method_registered(method)
+                        && handler.params_supported(method, &params)
                     {
                         self.send_server_notification(method, params);
                     }
@@ -427,6 +471,12 @@ pub fn mainloop<H>(&self, handler: &mut H)
                 PluginServerRpc::HostNotification { method, params } => {
                     handler.handle_host_notification(method, params);
                 }
+                PluginServerRpc::DidOpenTextDocument { document } => {
+                    handler.handle_did_open_text_document(document);
+                }
+                PluginServerRpc::DidChangeConfiguration { configurations } => {
+                    handler.handle_did_change_configuration(configurations);
+                }
                 PluginServerRpc::DidSaveTextDocument {
                     language_id,
                     path,
@@ -536,9 +586,72 @@ struct SaveRegistration {
     filters: Vec<DocumentFilter>,
 }
 
+struct FileWatcherRegistration {
+    pattern: globset::GlobMatcher,
+    /// The bits of the changes to watch, 1 for create, 2 for change and 4 for
+    /// delete
+    kind: u64,
+}
+
+impl FileWatcherRegistration {
+    fn matches(&self, event: &FileEvent) -> bool {
+        let kind = match event.typ {
+            FileChangeType::CREATED => 1,
+            FileChangeType::CHANGED => 2,
+            FileChangeType::DELETED => 4,
+            _ => return false,
+        };
+        self.kind & kind != 0
+            && event
+                .uri
+                .to_file_path()
+                .map(|path| self.pattern.is_match(path))
+                .unwrap_or(false)
+    }
+}
+
 #[derive(Default)]
 struct ServerRegistrations {
     save: Option<SaveRegistration>,
+    watched_files: Vec<FileWatcherRegistration>,
+}
+
+/// The state of the diagnostics pulled from the server, shared with the
+/// callbacks of the pull requests
+#[derive(Default)]
+struct DiagnosticPulls {
+    /// The result id of the last report for each document, which is sent
+    /// back to the server so it can answer with an unchanged report
+    result_ids: HashMap<Url, String>,
+    /// The latest version of each document opened on the server
+    versions: HashMap<Url, i32>,
+}
+
+impl DiagnosticPulls {
+    fn update(
+        &mut self,
+        uri: Url,
+        result_id: Option<String>,
+        diagnostics: Option<Vec<Diagnostic>>,
+        core_rpc: &CoreRpcHandler,
+    ) {
+        match result_id {
+            Some(result_id) => {
+                self.result_ids.insert(uri.clone(), result_id);
+            }
+            None => {
+                self.result_ids.remove(&uri);
+            }
+        }
+        // an unchanged report keeps the diagnostics the editor already has
+        if let Some(diagnostics) = diagnostics {
+            core_rpc.publish_diagnostics(PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            });
+        }
+    }
 }
 
 pub struct PluginHostHandler {
@@ -547,10 +660,15 @@ pub struct PluginHostHandler {
     pwd: Option<PathBuf>,
     pub(crate) workspace: Option<PathBuf>,
     document_selector: Vec<DocumentFilter>,
+    configurations: Option<HashMap<String, Value>>,
     catalog_rpc: PluginCatalogRpcHandler,
     pub server_rpc: PluginServerRpcHandler,
     pub server_capabilities: ServerCapabilities,
+    /// Whether the server has the `inlineCompletionProvider` capability, which
+    /// `ServerCapabilities` can't hold
+    pub inline_completion_provider: bool,
     server_registrations: ServerRegistrations,
+    diagnostic_pulls: Arc<Mutex<DiagnosticPulls>>,
 }
 
 impl PluginHostHandler {
@@ -560,6 +678,7 @@ pub fn new(
         volt_id: VoltID,
         volt_display_name: String,
         document_selector: DocumentSelector,
+        configurations: Option<HashMap<String, Value>>,
         server_rpc: PluginServerRpcHandler,
         catalog_rpc: PluginCatalogRpcHandler,
     ) -> Self {
@@ -573,10 +692,13 @@ pub fn new(
             volt_id,
             volt_display_name,
             document_selector,
+            configurations,
             catalog_rpc,
             server_rpc,
             server_capabilities: ServerCapabilities::default(),
+            inline_completion_provider: false,
             server_registrations: ServerRegistrations::default(),
+            diagnostic_pulls: Arc::new(Mutex::new(DiagnosticPulls::default())),
         }
     }
 
@@ -651,6 +773,19 @@ pub fn method_registered(&mut self, method: &'static str) -> bool {
             SignatureHelpRequest::METHOD => {
                 self.server_capabilities.signature_help_provider.is_some()
             }
+            InlineCompletionRequest::METHOD => {
+                self.inline_completion_provider
+                    || self
+                        .server_capabilities
+                        .experimental
+                        .as_ref()
+                        .map(|experimental| {
+                            inline_completion_provider(
+                                &experimental["inlineCompletionProvider"],
+                            )
+                        })
+                        .unwrap_or(false)
+            }
             HoverRequest::METHOD => self
                 .server_capabilities
                 .hover_provider
@@ -672,6 +807,12 @@ pub fn method_registered(&mut self, method: &'static str) -> bool {
             GotoTypeDefinition::METHOD => {
                 self.server_capabilities.type_definition_provider.is_some()
             }
+            GotoImplementation::METHOD => {
+                self.server_capabilities.implementation_provider.is_some()
+            }
+            GotoDeclaration::METHOD => {
+                self.server_capabilities.declaration_provider.is_some()
+            }
             References::METHOD => self
                 .server_capabilities
                 .references_provider
@@ -699,21 +840,66 @@ pub fn method_registered(&mut self, method: &'static str) -> bool {
                     OneOf::Right(_) => true,
                 })
                 .unwrap_or(false),
+            RangeFormatting::METHOD => self
+                .server_capabilities
+                .document_range_formatting_provider
+                .as_ref()
+                .map(|f| match f {
+                    OneOf::Left(is_capable) => *is_capable,
+                    OneOf::Right(_) => true,
+                })
+                .unwrap_or(false),
+            OnTypeFormatting::METHOD => self
+                .server_capabilities
+                .document_on_type_formatting_provider
+                .is_some(),
             SemanticTokensFullRequest::METHOD => {
                 self.server_capabilities.semantic_tokens_provider.is_some()
             }
+            SemanticTokensFullDeltaRequest::METHOD => self
+                .server_capabilities
+                .semantic_tokens_provider
+                .as_ref()
+                .and_then(|provider| semantic_tokens_options(provider).full.as_ref())
+                .map(|full| match full {
+                    SemanticTokensFullOptions::Bool(_) => false,
+                    SemanticTokensFullOptions::Delta { delta } => {
+                        delta.unwrap_or(false)
+                    }
+                })
+                .unwrap_or(false),
+            SemanticTokensRangeRequest::METHOD => self
+                .server_capabilities
+                .semantic_tokens_provider
+                .as_ref()
+                .and_then(|provider| semantic_tokens_options(provider).range)
+                .unwrap_or(false),
             InlayHintRequest::METHOD => {
                 self.server_capabilities.inlay_hint_provider.is_some()
             }
+            DocumentLinkRequest::METHOD => {
+                self.server_capabilities.document_link_provider.is_some()
+            }
+            DocumentColor::METHOD | ColorPresentationRequest::METHOD => !matches!(
+                self.server_capabilities.color_provider,
+                None | Some(ColorProviderCapability::Simple(false))
+            ),
             DocumentSymbolRequest::METHOD => {
                 self.server_capabilities.document_symbol_provider.is_some()
             }
+            // Commands are only sent to the plugin that gave them, so it can
+            // run them even when it doesn't list them in its capabilities
+            ExecuteCommand::METHOD => true,
             WorkspaceSymbol::METHOD => {
                 self.server_capabilities.workspace_symbol_provider.is_some()
             }
             PrepareRenameRequest::METHOD => {
                 self.server_capabilities.rename_provider.is_some()
             }
+            LinkedEditingRange::METHOD => self
+                .server_capabilities
+                .linked_editing_range_provider
+                .is_some(),
             Rename::METHOD => self.server_capabilities.rename_provider.is_some(),
             SelectionRangeRequest::METHOD => {
                 self.server_capabilities.selection_range_provider.is_some()
@@ -721,10 +907,158 @@ pub fn method_registered(&mut self, method: &'static str) -> bool {
             CodeActionResolveRequest::METHOD => {
                 self.server_capabilities.code_action_provider.is_some()
             }
+            WillRenameFiles::METHOD => self
+                .file_operations()
+                .map(|operations| operations.will_rename.is_some())
+                .unwrap_or(false),
+            DidRenameFiles::METHOD => self
+                .file_operations()
+                .map(|operations| operations.did_rename.is_some())
+                .unwrap_or(false),
+            DidCreateFiles::METHOD => self
+                .file_operations()
+                .map(|operations| operations.did_create.is_some())
+                .unwrap_or(false),
+            DidDeleteFiles::METHOD => self
+                .file_operations()
+                .map(|operations| operations.did_delete.is_some())
+                .unwrap_or(false),
+            DidChangeWatchedFiles::METHOD => {
+                !self.server_registrations.watched_files.is_empty()
+            }
+            DocumentDiagnosticRequest::METHOD => {
+                self.server_capabilities.diagnostic_provider.is_some()
+            }
+            WorkspaceDiagnosticRequest::METHOD => self
+                .diagnostic_options()
+                .map(|options| options.workspace_diagnostics)
+                .unwrap_or(false),
             _ => false,
         }
     }
 
+    /// Checks the params of requests which the server only wants for some
+    /// inputs, e.g. on type formatting is only for the trigger characters.
+    pub fn params_supported(&self, method: &'static str, params: &Params) -> bool {
+        match method {
+            OnTypeFormatting::METHOD => {
+                let options = match self
+                    .server_capabilities
+                    .document_on_type_formatting_provider
+                    .as_ref()
+                {
+                    Some(options) => options,
+                    None => return false,
+                };
+                let ch = match serde_json::to_value(params).and_then(|params| {
+                    serde_json::from_value::<DocumentOnTypeFormattingParams>(params)
+                }) {
+                    Ok(params) => params.ch,
+                    Err(_) => return false,
+                };
+                options.first_trigger_character == ch
+                    || options
+                        .more_trigger_character
+                        .as_ref()
+                        .map(|chars| chars.contains(&ch))
+                        .unwrap_or(false)
+            }
+            SignatureHelpRequest::METHOD => {
+                let options = match self
+                    .server_capabilities
+                    .signature_help_provider
+                    .as_ref()
+                {
+                    Some(options) => options,
+                    None => return false,
+                };
+                let context = match parse_params::<SignatureHelpParams>(params)
+                    .and_then(|params| params.context)
+                {
+                    Some(context) => context,
+                    None => return true,
+                };
+                if context.trigger_kind
+                    != SignatureHelpTriggerKind::TRIGGER_CHARACTER
+                {
+                    return true;
+                }
+                // Only send the typed character to servers that trigger on it,
+                // or retrigger on it while the signature help is shown
+                let ch = context.trigger_character.unwrap_or_default();
+                let contains = |chars: &Option<Vec<String>>| {
+                    chars
+                        .as_ref()
+                        .map(|chars| chars.contains(&ch))
+                        .unwrap_or(false)
+                };
+                contains(&options.trigger_characters)
+                    || (context.is_retrigger
+                        && contains(&options.retrigger_characters))
+            }
+            WillRenameFiles::METHOD | DidRenameFiles::METHOD => {
+                let operations = self.file_operations();
+                let options = if method == WillRenameFiles::METHOD {
+                    operations.and_then(|o| o.will_rename.as_ref())
+                } else {
+                    operations.and_then(|o| o.did_rename.as_ref())
+                };
+                parse_params::<RenameFilesParams>(params)
+                    .map(|params| {
+                        params.files.iter().any(|file| {
+                            file_operation_matches(options, &file.old_uri)
+                        })
+                    })
+                    .unwrap_or(false)
+            }
+            DidCreateFiles::METHOD => {
+                let options =
+                    self.file_operations().and_then(|o| o.did_create.as_ref());
+                parse_params::<CreateFilesParams>(params)
+                    .map(|params| {
+                        params
+                            .files
+                            .iter()
+                            .any(|file| file_operation_matches(options, &file.uri))
+                    })
+                    .unwrap_or(false)
+            }
+            DidDeleteFiles::METHOD => {
+                let options =
+                    self.file_operations().and_then(|o| o.did_delete.as_ref());
+                parse_params::<DeleteFilesParams>(params)
+                    .map(|params| {
+                        params
+                            .files
+                            .iter()
+                            .any(|file| file_operation_matches(options, &file.uri))
+                    })
+                    .unwrap_or(false)
+            }
+            DidChangeWatchedFiles::METHOD => {
+                parse_params::<DidChangeWatchedFilesParams>(params)
+                    .map(|params| {
+                        params.changes.iter().any(|change| {
+                            self.server_registrations
+                                .watched_files
+                                .iter()
+                                .any(|watcher| watcher.matches(change))
+                        })
+                    })
+                    .unwrap_or(false)
+            }
+            _ => true,
+        }
+    }
+
+    fn file_operations(&self) -> Option<&WorkspaceFileOperationsServerCapabilities> {
+        self.server_capabilities
+            .workspace
+            .as_ref()?
+            .file_operations
+            .as_ref()
+    }
+
     fn check_save_capability(&self, language_id: &str, path: &Path) -> (bool, bool) {
         if self.document_supported(Some(language_id), Some(path)) {
             let (should_send, include_text) = self
@@ -790,6 +1124,55 @@ fn register_capability(&mut self, registration: Registration) -> Result<()> {
                         .unwrap_or_default(),
                 });
             }
+            DidChangeWatchedFiles::METHOD => {
+                let options = registration
+                    .register_options
+                    .ok_or_else(|| anyhow!("don't have options"))?;
+                let watchers = options
+                    .get("watchers")
+                    .and_then(|watchers| watchers.as_array())
+                    .ok_or_else(|| anyhow!("don't have watchers"))?;
+                for watcher in watchers {
+                    // the glob pattern is read by hand because it can be either
+                    // a string or a pattern relative to a base uri
+                    let pattern = match watcher.get("globPattern") {
+                        Some(Value::String(pattern)) => pattern.clone(),
+                        Some(Value::Object(relative)) => {
+                            let base = relative
+                                .get("baseUri")
+                                .and_then(|base| {
+                                    base.as_str()
+                                        .or_else(|| base.get("uri")?.as_str())
+                                })
+                                .and_then(|base| Url::parse(base).ok())
+                                .and_then(|base| base.to_file_path().ok());
+                            let pattern = relative
+                                .get("pattern")
+                                .and_then(|pattern| pattern.as_str());
+                            match (base, pattern) {
+                                (Some(base), Some(pattern)) => {
+                                    format!("{}/{pattern}", base.to_string_lossy())
+                                }
+                                _ => continue,
+                            }
+                        }
+                        _ => continue,
+                    };
+                    let Ok(glob) = globset::Glob::new(&pattern) else {
+                        continue;
+                    };
+                    self.server_registrations.watched_files.push(
+                        FileWatcherRegistration {
+                            pattern: glob.compile_matcher(),
+                            // all the changes when it's not given
+                            kind: watcher
+                                .get("kind")
+                                .and_then(|kind| kind.as_u64())
+                                .unwrap_or(7),
+                        },
+                    );
+                }
+            }
             _ => {
                 eprintln!(
                     "don't handle register capability for {}",
@@ -821,6 +1204,30 @@ pub fn process_request(
     ) -> Result<Value> {
         match method.as_str() {
             WorkDoneProgressCreate::METHOD => Ok(Value::Null),
+            WorkspaceConfiguration::METHOD => {
+                let params: ConfigurationParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                let configurations = self
+                    .configurations
+                    .as_ref()
+                    .map(unflatten_map)
+                    .unwrap_or(Value::Null);
+                let result: Vec<Value> = params
+                    .items
+                    .iter()
+                    .map(|item| {
+                        configuration_section(
+                            &configurations,
+                            item.section.as_deref(),
+                        )
+                    })
+                    .collect();
+                Ok(serde_json::to_value(result)?)
+            }
+            WorkspaceDiagnosticRefresh::METHOD => {
+                self.refresh_diagnostics();
+                Ok(Value::Null)
+            }
             RegisterCapability::METHOD => {
                 let params: RegistrationParams =
                     serde_json::from_value(serde_json::to_value(params)?)?;
@@ -857,6 +1264,7 @@ pub fn handle_notification(
                 let catalog_rpc = self.catalog_rpc.clone();
                 let volt_id = self.volt_id.clone();
                 let volt_display_name = self.volt_display_name.clone();
+                let configurations = self.configurations.clone();
                 thread::spawn(move || {
                     let _ = LspClient::start(
                         catalog_rpc,
@@ -864,6 +1272,7 @@ pub fn handle_notification(
                         workspace,
                         volt_id,
                         volt_display_name,
+                        configurations,
                         pwd,
                         params.server_uri,
                         params.server_args,
@@ -899,6 +1308,52 @@ pub fn handle_notification(
         Ok(())
     }
 
+    pub fn handle_did_open_text_document(&mut self, document: TextDocumentItem) {
+        let path = document.uri.to_file_path().ok();
+        if !self.document_supported(Some(&document.language_id), path.as_deref()) {
+            return;
+        }
+
+        let uri = document.uri.clone();
+        if self.method_registered(DidOpenTextDocument::METHOD) {
+            self.diagnostic_pulls
+                .lock()
+                .versions
+                .insert(uri.clone(), document.version);
+            self.server_rpc.server_notification(
+                DidOpenTextDocument::METHOD,
+                DidOpenTextDocumentParams {
+                    text_document: document,
+                },
+                None,
+                None,
+                false,
+            );
+        }
+        self.pull_document_diagnostics(uri);
+    }
+
+    /// Keep the new configurations of the volt for the configuration requests,
+    /// and let the server know they changed
+    pub fn handle_did_change_configuration(
+        &mut self,
+        configurations: Option<HashMap<String, Value>>,
+    ) {
+        self.configurations = configurations;
+        let settings = self
+            .configurations
+            .as_ref()
+            .map(unflatten_map)
+            .unwrap_or(Value::Null);
+        self.server_rpc.server_notification(
+            DidChangeConfiguration::METHOD,
+            DidChangeConfigurationParams { settings },
+            None,
+            None,
+            false,
+        );
+    }
+
     pub fn handle_did_save_text_document(
         &self,
         language_id: String,
@@ -926,6 +1381,17 @@ pub fn handle_did_save_text_document(
             Some(path),
             false,
         );
+
+        // diagnostics of other documents might depend on the saved one
+        if self
+            .diagnostic_options()
+            .map(|options| options.inter_file_dependencies)
+            .unwrap_or(false)
+        {
+            self.refresh_diagnostics();
+        } else {
+            self.pull_workspace_diagnostics();
+        }
     }
 
     pub fn handle_did_change_text_document(
@@ -984,6 +1450,18 @@ pub fn handle_did_change_text_document(
         };
 
         let path = document.uri.to_file_path().ok();
+        let uri = document.uri.clone();
+        let version = document.version;
+        let opened = {
+            let mut pulls = self.diagnostic_pulls.lock();
+            match pulls.versions.get_mut(&uri) {
+                Some(version) => {
+                    *version = document.version;
+                    true
+                }
+                None => false,
+            }
+        };
 
         let params = DidChangeTextDocumentParams {
             text_document: document,
@@ -997,6 +1475,227 @@ pub fn handle_did_change_text_document(
             path,
             false,
         );
+
+        if opened && self.diagnostic_options().is_some() {
+            // Pulled once the changes stop, rather than on every keystroke
+            let server_rpc = self.server_rpc.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(DIAGNOSTIC_PULL_DELAY));
+                server_rpc.handle_rpc(PluginServerRpc::Handler(
+                    PluginHandlerNotification::PullDocumentDiagnostics {
+                        uri,
+                        version,
+                    },
+                ));
+            });
+        }
+    }
+
+    /// Pull the diagnostics of the changed document, unless it has changed
+    /// again since
+    pub fn pull_changed_document_diagnostics(&self, uri: Url, version: i32) {
+        let current = self.diagnostic_pulls.lock().versions.get(&uri).copied();
+        if current == Some(version) {
+            self.pull_document_diagnostics(uri);
+        }
+    }
+
+    fn diagnostic_options(&self) -> Option<&DiagnosticOptions> {
+        match self.server_capabilities.diagnostic_provider.as_ref()? {
+            DiagnosticServerCapabilities::Options(options) => Some(options),
+            DiagnosticServerCapabilities::RegistrationOptions(options) => {
+                Some(&options.diagnostic_options)
+            }
+        }
+    }
+
+    /// Pull the diagnostics of the document from the server, the report is
+    /// dropped if the document has changed since the request was made
+    pub fn pull_document_diagnostics(&self, uri: Url) {
+        let options = match self.diagnostic_options() {
+            Some(options) => options,
+            None => return,
+        };
+
+        let (previous_result_id, version) = {
+            let pulls = self.diagnostic_pulls.lock();
+            (
+                pulls.result_ids.get(&uri).cloned(),
+                pulls.versions.get(&uri).copied(),
+            )
+        };
+        let params = DocumentDiagnosticParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            identifier: options.identifier.clone(),
+            previous_result_id,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        let pulls = self.diagnostic_pulls.clone();
+        let core_rpc = self.catalog_rpc.core_rpc.clone();
+        self.server_rpc.server_request_async(
+            DocumentDiagnosticRequest::METHOD,
+            params,
+            None,
+            None,
+            false,
+            move |result: Result<Value, RpcError>| {
+                let report = match result.ok().and_then(|value| {
+                    serde_json::from_value::<DocumentDiagnosticReportResult>(value)
+                        .ok()
+                }) {
+                    Some(DocumentDiagnosticReportResult::Report(report)) => report,
+                    _ => return,
+                };
+
+                let mut pulls = pulls.lock();
+                if pulls.versions.get(&uri).copied() != version {
+                    return;
+                }
+                let related_documents = match report {
+                    DocumentDiagnosticReport::Full(report) => {
+                        let full = report.full_document_diagnostic_report;
+                        pulls.update(
+                            uri,
+                            full.result_id,
+                            Some(full.items),
+                            &core_rpc,
+                        );
+                        report.related_documents
+                    }
+                    DocumentDiagnosticReport::Unchanged(report) => {
+                        pulls.update(
+                            uri,
+                            Some(
+                                report
+                                    .unchanged_document_diagnostic_report
+                                    .result_id,
+                            ),
+                            None,
+                            &core_rpc,
+                        );
+                        report.related_documents
+                    }
+                };
+                for (uri, report) in related_documents.into_iter().flatten() {
+                    match report {
+                        DocumentDiagnosticReportKind::Full(report) => {
+                            pulls.update(
+                                uri,
+                                report.result_id,
+                                Some(report.items),
+                                &core_rpc,
+                            );
+                        }
+                        DocumentDiagnosticReportKind::Unchanged(report) => {
+                            pulls.update(
+                                uri,
+                                Some(report.result_id),
+                                None,
+                                &core_rpc,
+                            );
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    /// Pull the diagnostics of the whole workspace, if the server supports it
+    pub fn pull_workspace_diagnostics(&self) {
+        let options = match self.diagnostic_options() {
+            Some(options) if options.workspace_diagnostics => options,
+            _ => return,
+        };
+
+        let previous_result_ids = self
+            .diagnostic_pulls
+            .lock()
+            .result_ids
+            .iter()
+            .map(|(uri, value)| PreviousResultId {
+                uri: uri.clone(),
+                value: value.clone(),
+            })
+            .collect();
+        let params = WorkspaceDiagnosticParams {
+            identifier: options.identifier.clone(),
+            previous_result_ids,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        let pulls = self.diagnostic_pulls.clone();
+        let core_rpc = self.catalog_rpc.core_rpc.clone();
+        self.server_rpc.server_request_async(
+            WorkspaceDiagnosticRequest::METHOD,
+            params,
+            None,
+            None,
+            false,
+            move |result: Result<Value, RpcError>| {
+                let report = match result.ok().and_then(|value| {
+                    serde_json::from_value::<WorkspaceDiagnosticReportResult>(value)
+                        .ok()
+                }) {
+                    Some(WorkspaceDiagnosticReportResult::Report(report)) => report,
+                    _ => return,
+                };
+
+                let mut pulls = pulls.lock();
+                for item in report.items {
+                    match item {
+                        WorkspaceDocumentDiagnosticReport::Full(report) => {
+                            // the document pulls are more accurate for opened
+                            // documents with newer versions
+                            if let (Some(version), Some(current)) =
+                                (report.version, pulls.versions.get(&report.uri))
+                            {
+                                if version != *current as i64 {
+                                    continue;
+                                }
+                            }
+                            let full = report.full_document_diagnostic_report;
+                            pulls.update(
+                                report.uri,
+                                full.result_id,
+                                Some(full.items),
+                                &core_rpc,
+                            );
+                        }
+                        WorkspaceDocumentDiagnosticReport::Unchanged(report) => {
+                            pulls.update(
+                                report.uri,
+                                Some(
+                                    report
+                                        .unchanged_document_diagnostic_report
+                                        .result_id,
+                                ),
+                                None,
+                                &core_rpc,
+                            );
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    /// Pull the diagnostics of all the opened documents and the workspace
+    /// again, e.g. when the server asks for a refresh
+    pub fn refresh_diagnostics(&self) {
+        let uris: Vec<Url> = self
+            .diagnostic_pulls
+            .lock()
+            .versions
+            .keys()
+            .cloned()
+            .collect();
+        for uri in uris {
+            self.pull_document_diagnostics(uri);
+        }
+        self.pull_workspace_diagnostics();
     }
 
     pub fn format_semantic_tokens(
@@ -1018,6 +1717,77 @@ pub fn format_semantic_tokens(
     }
 }
 
+fn parse_params<T: DeserializeOwned>(params: &Params) -> Option<T> {
+    serde_json::from_value(serde_json::to_value(params).ok()?).ok()
+}
+
+/// Checks if the file matches any of the filters the server gave for the
+/// file operation
+fn file_operation_matches(
+    options: Option<&FileOperationRegistrationOptions>,
+    uri: &str,
+) -> bool {
+    let options = match options {
+        Some(options) => options,
+        None => return false,
+    };
+    let uri = match Url::parse(uri) {
+        Ok(uri) => uri,
+        Err(_) => return false,
+    };
+    let path = match uri.to_file_path() {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+    options.filters.iter().any(|filter| {
+        if filter
+            .scheme
+            .as_deref()
+            .map(|scheme| scheme != uri.scheme())
+            .unwrap_or(false)
+        {
+            return false;
+        }
+        let ignore_case = filter
+            .pattern
+            .options
+            .as_ref()
+            .and_then(|options| options.ignore_case)
+            .unwrap_or(false);
+        let matcher = match globset::GlobBuilder::new(&filter.pattern.glob)
+            .case_insensitive(ignore_case)
+            .build()
+        {
+            Ok(glob) => glob.compile_matcher(),
+            Err(_) => return false,
+        };
+        if !matcher.is_match(&path) {
+            return false;
+        }
+        match filter.pattern.matches.as_ref() {
+            // the path can be gone already, e.g. when it's deleted
+            Some(FileOperationPatternKind::File) if path.exists() => !path.is_dir(),
+            Some(FileOperationPatternKind::Folder) if path.exists() => path.is_dir(),
+            _ => true,
+        }
+    })
+}
+
+/// Get the value of the section, e.g. `yaml.schemas`, from the configurations,
+/// or all of them when there's no section
+fn configuration_section(configurations: &Value, section: Option<&str>) -> Value {
+    let mut value = configurations;
+    if let Some(section) = section {
+        for part in section.split('.') {
+            value = match value.get(part) {
+                Some(value) => value,
+                None => return Value::Null,
+            };
+        }
+    }
+    value.clone()
+}
+
 fn get_document_content_change(
     text: &Rope,
     delta: &RopeDelta,
@@ -1112,12 +1882,107 @@ fn format_semantic_styles(
 fn semantic_tokens_legend(
     semantic_tokens_provider: &SemanticTokensServerCapabilities,
 ) -> &SemanticTokensLegend {
+    &semantic_tokens_options(semantic_tokens_provider).legend
+}
+
+/// Check the `inlineCompletionProvider` capability, which is either a boolean
+/// or the options of the provider.
+pub fn inline_completion_provider(capability: &Value) -> bool {
+    !matches!(capability, Value::Null | Value::Bool(false))
+}
+
+fn semantic_tokens_options(
+    semantic_tokens_provider: &SemanticTokensServerCapabilities,
+) -> &SemanticTokensOptions {
     match semantic_tokens_provider {
-        SemanticTokensServerCapabilities::SemanticTokensOptions(options) => {
-            &options.legend
-        }
+        SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options,
         SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(
             options,
-        ) => &options.semantic_tokens_options.legend,
+        ) => &options.semantic_tokens_options,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{json, Value};
+
+    use super::configuration_section;
+    use crate::plugin::wasi::unflatten_map;
+
+    fn configurations() -> Value {
+        let map: HashMap<String, Value> = serde_json::from_value(json!({
+            "rust-analyzer.cargo.features": ["all"],
+            "rust-analyzer.checkOnSave": true,
+            "rust-analyzer.cargo.buildScripts.enable": false,
+            "other": 1,
+        }))
+        .unwrap();
+        unflatten_map(&map)
+    }
+
+    #[test]
+    fn unflatten_configurations() {
+        assert_eq!(
+            configurations(),
+            json!({
+                "rust-analyzer": {
+                    "cargo": {
+                        "features": ["all"],
+                        "buildScripts": {
+                            "enable": false,
+                        },
+                    },
+                    "checkOnSave": true,
+                },
+                "other": 1,
+            })
+        );
+    }
+
+    #[test]
+    fn configuration_sections() {
+        let configurations = configurations();
+        assert_eq!(configuration_section(&configurations, None), configurations);
+        assert_eq!(
+            configuration_section(&configurations, Some("rust-analyzer.cargo")),
+            json!({
+                "features": ["all"],
+                "buildScripts": {
+                    "enable": false,
+                },
+            })
+        );
+        assert_eq!(
+            configuration_section(
+                &configurations,
+                Some("rust-analyzer.cargo.buildScripts.enable")
+            ),
+            json!(false)
+        );
+        assert_eq!(
+            configuration_section(&configurations, Some("other")),
+            json!(1)
+        );
+    }
+
+    #[test]
+    fn missing_configuration_sections() {
+        let configurations = configurations();
+        assert_eq!(
+            configuration_section(&configurations, Some("rust-analyzer.files")),
+            Value::Null
+        );
+        // Past a value that isn't an object
+        assert_eq!(
+            configuration_section(&configurations, Some("other.value")),
+            Value::Null
+        );
+        assert_eq!(
+            configuration_section(&Value::Null, Some("rust-analyzer")),
+            Value::Null
+        );
+        assert_eq!(configuration_section(&Value::Null, None), Value::Null);
     }
 }
@@ -24,7 +24,7 @@
 use lsp_types::{
     notification::Initialized, request::Initialize, DocumentFilter,
     InitializeParams, InitializedParams, TextDocumentContentChangeEvent,
-    TextDocumentIdentifier, Url, VersionedTextDocumentIdentifier,
+    TextDocumentIdentifier, TextDocumentItem, Url, VersionedTextDocumentIdentifier,
 };
 use parking_lot::Mutex;
 use psp_types::{Notification, Request};
@@ -32,7 +32,7 @@
 use wasmtime_wasi::WasiCtxBuilder;
 
 use super::{
-    client_capabilities,
+    client_capabilities, initialize_params,
     psp::{
         handle_plugin_server_message, PluginHandlerNotification, PluginHostHandler,
         PluginServerHandler, PluginServerRpc, RpcCallback,
@@ -93,6 +93,10 @@ fn method_registered(&mut self, method: &'static str) -> bool {
         self.host.method_registered(method)
     }
 
+    fn params_supported(&mut self, method: &'static str, params: &Params) -> bool {
+        self.host.params_supported(method, params)
+    }
+
     fn document_supported(
         &mut self,
         language_id: Option<&str>,
@@ -116,6 +120,9 @@ fn handle_handler_notification(
             Shutdown => {
                 self.shutdown();
             }
+            PullDocumentDiagnostics { uri, version } => {
+                self.host.pull_changed_document_diagnostics(uri, version);
+            }
         }
     }
 
@@ -133,6 +140,18 @@ fn handle_host_request(
         self.host.handle_request(id, method, params, chan);
     }
 
+    fn handle_did_open_text_document(&mut self, document: TextDocumentItem) {
+        self.host.handle_did_open_text_document(document);
+    }
+
+    fn handle_did_change_configuration(
+        &mut self,
+        configurations: Option<HashMap<String, serde_json::Value>>,
+    ) {
+        self.configurations = configurations.clone();
+        self.host.handle_did_change_configuration(configurations);
+    }
+
     fn handle_did_save_text_document(
         &self,
         language_id: String,
@@ -191,7 +210,7 @@ fn initialize(&mut self) {
         self.host.server_rpc.server_request_async(
             Initialize::METHOD,
             #[allow(deprecated)]
-            InitializeParams {
+            initialize_params(InitializeParams {
                 process_id: Some(process::id()),
                 root_path: None,
                 root_uri,
@@ -201,7 +220,7 @@ fn initialize(&mut self) {
                 locale: None,
                 initialization_options: configurations,
                 workspace_folders: None,
-            },
+            }),
             None,
             None,
             false,
@@ -502,6 +521,7 @@ pub fn start_volt(
                         }),
                 )
                 .collect(),
+            configurations.clone(),
             rpc.clone(),
             plugin_rpc.clone(),
         ),
@@ -524,7 +544,9 @@ fn wasi_read_string(stdout: &Arc<RwLock<WasiPipe>>) -> Result<String> {
     Ok(buf)
 }
 
-fn unflatten_map(map: &HashMap<String, serde_json::Value>) -> serde_json::Value {
+pub(crate) fn unflatten_map(
+    map: &HashMap<String, serde_json::Value>,
+) -> serde_json::Value {
     let mut new = serde_json::json!({});
     for (key, value) in map.iter() {
         let mut current = new.as_object_mut().unwrap();
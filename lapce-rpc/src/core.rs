@@ -10,7 +10,7 @@
 use crossbeam_channel::{Receiver, Sender};
 use lsp_types::{
     CompletionResponse, LogMessageParams, ProgressParams, PublishDiagnosticsParams,
-    ShowMessageParams, SignatureHelp,
+    ShowMessageParams, WorkspaceEdit,
 };
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
@@ -48,11 +48,6 @@ pub enum CoreNotification {
         resp: CompletionResponse,
         plugin_id: PluginId,
     },
-    SignatureHelpResponse {
-        request_id: usize,
-        resp: SignatureHelp,
-        plugin_id: PluginId,
-    },
     OpenPaths {
         paths: Vec<PathObject>,
     },
@@ -118,20 +113,45 @@ pub enum CoreNotification {
     DapContinued {
         dap_id: DapId,
     },
+    DapOutput {
+        dap_id: DapId,
+        output: dap_types::Output,
+    },
+    DapCapabilities {
+        dap_id: DapId,
+        capabilities: dap_types::DebuggerCapabilities,
+    },
     DapBreakpointsResp {
         dap_id: DapId,
         path: PathBuf,
         breakpoints: Vec<dap_types::Breakpoint>,
     },
+    /// A session was started by the debug adapter of its parent session
+    DapChildSession {
+        dap_id: DapId,
+        parent: DapId,
+        name: String,
+    },
+    DapTerminated {
+        dap_id: DapId,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum CoreRequest {}
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "method", content = "params")]
+pub enum CoreRequest {
+    /// Apply the edit, responding once it's applied so that the files can be
+    /// changed on disk afterwards
+    ApplyWorkspaceEdit { edit: WorkspaceEdit },
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "method", content = "params")]
-pub enum CoreResponse {}
+pub enum CoreResponse {
+    ApplyWorkspaceEditResponse { applied: bool },
+}
 
 pub type CoreMessage = RpcMessage<CoreRequest, CoreNotification, CoreResponse>;
 
@@ -245,19 +265,6 @@ pub fn completion_response(
         });
     }
 
-    pub fn signature_help_response(
-        &self,
-        request_id: usize,
-        resp: SignatureHelp,
-        plugin_id: PluginId,
-    ) {
-        self.notification(CoreNotification::SignatureHelpResponse {
-            request_id,
-            resp,
-            plugin_id,
-        });
-    }
-
     pub fn volt_installed(&self, volt: VoltMetadata, icon: Option<Vec<u8>>) {
         self.notification(CoreNotification::VoltInstalled { volt, icon });
     }
@@ -292,6 +299,14 @@ pub fn publish_diagnostics(&self, diagnostics: PublishDiagnosticsParams) {
         self.notification(CoreNotification::PublishDiagnostics { diagnostics });
     }
 
+    /// Apply the edit and block until the editor has applied it
+    pub fn apply_workspace_edit_sync(
+        &self,
+        edit: WorkspaceEdit,
+    ) -> Result<CoreResponse, RpcError> {
+        self.request(CoreRequest::ApplyWorkspaceEdit { edit })
+    }
+
     pub fn work_done_progress(&self, progress: ProgressParams) {
         self.notification(CoreNotification::WorkDoneProgress { progress });
     }
@@ -336,6 +351,21 @@ pub fn dap_continued(&self, dap_id: DapId) {
         self.notification(CoreNotification::DapContinued { dap_id });
     }
 
+    pub fn dap_output(&self, dap_id: DapId, output: dap_types::Output) {
+        self.notification(CoreNotification::DapOutput { dap_id, output });
+    }
+
+    pub fn dap_capabilities(
+        &self,
+        dap_id: DapId,
+        capabilities: dap_types::DebuggerCapabilities,
+    ) {
+        self.notification(CoreNotification::DapCapabilities {
+            dap_id,
+            capabilities,
+        });
+    }
+
     pub fn dap_breakpoints_resp(
         &self,
         dap_id: DapId,
@@ -349,6 +379,18 @@ pub fn dap_breakpoints_resp(
         });
     }
 
+    pub fn dap_child_session(&self, dap_id: DapId, parent: DapId, name: String) {
+        self.notification(CoreNotification::DapChildSession {
+            dap_id,
+            parent,
+            name,
+        });
+    }
+
+    pub fn dap_terminated(&self, dap_id: DapId) {
+        self.notification(CoreNotification::DapTerminated { dap_id });
+    }
+
     pub fn home_dir(&self, path: PathBuf) {
         self.notification(CoreNotification::HomeDir { path });
     }
@@ -21,23 +21,114 @@ pub fn next() -> Self {
     }
 }
 
+#[derive(Clone)]
 pub struct DapServer {
     pub program: String,
     pub args: Vec<String>,
     pub cwd: Option<PathBuf>,
+    pub transport: DapTransport,
+}
+
+/// How the messages are exchanged with the debug adapter
+#[derive(Clone)]
+pub enum DapTransport {
+    /// The stdin and stdout of the spawned adapter
+    Stdio,
+    /// A TCP connection to the adapter listening on the address. The adapter
+    /// is spawned first if `spawn` is set, otherwise it's already running.
+    Tcp {
+        host: String,
+        port: u16,
+        spawn: bool,
+    },
+}
+
+/// The debug adapter of a run config, and how to talk to it
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugAdapterConfig {
+    /// The adapter to spawn, which isn't needed when connecting to one
+    /// that's already running
+    pub program: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The host the adapter listens on, localhost by default
+    pub host: Option<String>,
+    /// The port the adapter listens on. Stdin and stdout are used when it
+    /// isn't set.
+    pub port: Option<u16>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DebugRequest {
+    #[default]
+    Launch,
+    Attach,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct RunDebugConfig {
     pub name: String,
+    #[serde(default)]
     pub program: String,
+    #[serde(default)]
     pub args: Vec<String>,
     pub cwd: Option<String>,
     pub env: Option<HashMap<String, String>>,
+    /// The command that's run before running or debugging, which must succeed
+    /// for the program to start. What it prints goes to the debug console.
+    pub pre_launch: Option<String>,
+    /// The arguments of the launch request that are specific to the debug
+    /// adapter, which are merged into the ones from the config
+    pub launch: Option<Value>,
+    /// Whether the debugger launches the program, or attaches to a process
+    /// that's already running
+    #[serde(default)]
+    pub request: DebugRequest,
+    /// The process to attach to. The local processes are listed to pick from
+    /// when neither this nor a host or port is set.
+    pub pid: Option<u32>,
+    /// The host of a remote debugger to attach to
+    pub host: Option<String>,
+    /// The port of a remote debugger to attach to
+    pub port: Option<u16>,
+    /// The arguments of the attach request that are specific to the debug
+    /// adapter, which are passed through as they are
+    pub attach: Option<Value>,
+    /// The debug adapter to use instead of the default one
+    pub adapter: Option<DebugAdapterConfig>,
     #[serde(skip)]
     pub debug_command: Option<String>,
     #[serde(skip)]
     pub dap_id: DapId,
+    /// The session that started this one with a `startDebugging` request
+    #[serde(skip)]
+    pub parent: Option<DapId>,
+}
+
+impl RunDebugConfig {
+    /// Whether a process needs to be picked before attaching
+    pub fn needs_process(&self) -> bool {
+        self.request == DebugRequest::Attach
+            && self.pid.is_none()
+            && self.host.is_none()
+            && self.port.is_none()
+            && !self
+                .attach
+                .as_ref()
+                .map(|attach| {
+                    attach.get("pid").is_some() || attach.get("processId").is_some()
+                })
+                .unwrap_or(false)
+    }
+}
+
+/// A process that's running on the machine of the proxy, which a debugger can
+/// attach to
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub command: String,
 }
 
 pub trait Request {
@@ -140,6 +231,8 @@ pub struct InitializeParams {
     pub supports_progress_reporting: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub supports_invalidated_event: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_start_debugging_request: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -455,6 +548,14 @@ pub struct Memory {
     pub count: usize,
 }
 
+pub enum Attach {}
+
+impl Request for Attach {
+    type Arguments = Value;
+    type Result = Value;
+    const COMMAND: &'static str = "attach";
+}
+
 pub enum Launch {}
 
 impl Request for Launch {
@@ -495,6 +596,25 @@ impl Request for RunInTerminal {
     const COMMAND: &'static str = "runInTerminal";
 }
 
+/// The session that the debug adapter asks to start, as a child of the one
+/// that it sent the request on
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartDebuggingRequestArguments {
+    /// The arguments of the launch or attach request of the child session
+    pub configuration: Value,
+    pub request: DebugRequest,
+}
+
+#[derive(Debug)]
+pub enum StartDebugging {}
+
+impl Request for StartDebugging {
+    type Arguments = StartDebuggingRequestArguments;
+    type Result = ();
+    const COMMAND: &'static str = "startDebugging";
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetBreakpointsArguments {
@@ -522,6 +642,69 @@ impl Request for SetBreakpoints {
     const COMMAND: &'static str = "setBreakpoints";
 }
 
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionFilterOptions {
+    pub filter_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetExceptionBreakpointsArguments {
+    pub filters: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_options: Option<Vec<ExceptionFilterOptions>>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetExceptionBreakpointsResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakpoints: Option<Vec<Breakpoint>>,
+}
+
+#[derive(Debug)]
+pub enum SetExceptionBreakpoints {}
+
+impl Request for SetExceptionBreakpoints {
+    type Arguments = SetExceptionBreakpointsArguments;
+    type Result = SetExceptionBreakpointsResponse;
+    const COMMAND: &'static str = "setExceptionBreakpoints";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionBreakpoint {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetFunctionBreakpointsArguments {
+    pub breakpoints: Vec<FunctionBreakpoint>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetFunctionBreakpointsResponse {
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Debug)]
+pub enum SetFunctionBreakpoints {}
+
+impl Request for SetFunctionBreakpoints {
+    type Arguments = SetFunctionBreakpointsArguments;
+    type Result = SetFunctionBreakpointsResponse;
+    const COMMAND: &'static str = "setFunctionBreakpoints";
+}
+
 #[derive(Debug)]
 pub enum ConfigurationDone {}
 
@@ -671,3 +854,446 @@ impl Request for Pause {
     type Result = ();
     const COMMAND: &'static str = "pause";
 }
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SteppingGranularity {
+    Statement,
+    Line,
+    Instruction,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NextArguments {
+    pub thread_id: ThreadId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<SteppingGranularity>,
+}
+
+#[derive(Debug)]
+pub enum Next {}
+
+impl Request for Next {
+    type Arguments = NextArguments;
+    type Result = ();
+    const COMMAND: &'static str = "next";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepInArguments {
+    pub thread_id: ThreadId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<SteppingGranularity>,
+}
+
+#[derive(Debug)]
+pub enum StepIn {}
+
+impl Request for StepIn {
+    type Arguments = StepInArguments;
+    type Result = ();
+    const COMMAND: &'static str = "stepIn";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepOutArguments {
+    pub thread_id: ThreadId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<SteppingGranularity>,
+}
+
+#[derive(Debug)]
+pub enum StepOut {}
+
+impl Request for StepOut {
+    type Arguments = StepOutArguments;
+    type Result = ();
+    const COMMAND: &'static str = "stepOut";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepBackArguments {
+    pub thread_id: ThreadId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<SteppingGranularity>,
+}
+
+#[derive(Debug)]
+pub enum StepBack {}
+
+impl Request for StepBack {
+    type Arguments = StepBackArguments;
+    type Result = ();
+    const COMMAND: &'static str = "stepBack";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotoTarget {
+    pub id: usize,
+    pub label: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_pointer_reference: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotoTargetsArguments {
+    pub source: Source,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotoTargetsResponse {
+    pub targets: Vec<GotoTarget>,
+}
+
+#[derive(Debug)]
+pub enum GotoTargets {}
+
+impl Request for GotoTargets {
+    type Arguments = GotoTargetsArguments;
+    type Result = GotoTargetsResponse;
+    const COMMAND: &'static str = "gotoTargets";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotoArguments {
+    pub thread_id: ThreadId,
+    pub target_id: usize,
+}
+
+#[derive(Debug)]
+pub enum Goto {}
+
+impl Request for Goto {
+    type Arguments = GotoArguments;
+    type Result = ();
+    const COMMAND: &'static str = "goto";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
+    pub variables_reference: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+    #[serde(default)]
+    pub expensive: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesArguments {
+    pub frame_id: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesResponse {
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Debug)]
+pub enum Scopes {}
+
+impl Request for Scopes {
+    type Arguments = ScopesArguments;
+    type Result = ScopesResponse;
+    const COMMAND: &'static str = "scopes";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablePresentationHint {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lazy: Option<bool>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<VariablePresentationHint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluate_name: Option<String>,
+    #[serde(default)]
+    pub variables_reference: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesArguments {
+    pub variables_reference: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesResponse {
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Debug)]
+pub enum Variables {}
+
+impl Request for Variables {
+    type Arguments = VariablesArguments;
+    type Result = VariablesResponse;
+    const COMMAND: &'static str = "variables";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateArguments {
+    pub expression: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponse {
+    pub result: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<VariablePresentationHint>,
+    #[serde(default)]
+    pub variables_reference: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Evaluate {}
+
+impl Request for Evaluate {
+    type Arguments = EvaluateArguments;
+    type Result = EvaluateResponse;
+    const COMMAND: &'static str = "evaluate";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetVariableArguments {
+    pub variables_reference: usize,
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetVariableResponse {
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum SetVariable {}
+
+impl Request for SetVariable {
+    type Arguments = SetVariableArguments;
+    type Result = SetVariableResponse;
+    const COMMAND: &'static str = "setVariable";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionItem {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_length: Option<usize>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionsArguments {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<usize>,
+    pub text: String,
+    pub column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionsResponse {
+    pub targets: Vec<CompletionItem>,
+}
+
+#[derive(Debug)]
+pub enum Completions {}
+
+impl Request for Completions {
+    type Arguments = CompletionsArguments;
+    type Result = CompletionsResponse;
+    const COMMAND: &'static str = "completions";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembleArguments {
+    pub memory_reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_offset: Option<i64>,
+    pub instruction_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_symbols: Option<bool>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembledInstruction {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_bytes: Option<String>,
+    pub instruction: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembleResponse {
+    pub instructions: Vec<DisassembledInstruction>,
+}
+
+#[derive(Debug)]
+pub enum Disassemble {}
+
+impl Request for Disassemble {
+    type Arguments = DisassembleArguments;
+    type Result = DisassembleResponse;
+    const COMMAND: &'static str = "disassemble";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMemoryArguments {
+    pub memory_reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    pub count: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMemoryResponse {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unreadable_bytes: Option<usize>,
+    /// The bytes read, encoded with base64
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ReadMemory {}
+
+impl Request for ReadMemory {
+    type Arguments = ReadMemoryArguments;
+    type Result = ReadMemoryResponse;
+    const COMMAND: &'static str = "readMemory";
+}
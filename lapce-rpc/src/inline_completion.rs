@@ -0,0 +1,105 @@
+//! The `textDocument/inlineCompletion` request of LSP 3.18, which is newer
+//! than the protocol version of `lsp_types`.
+//!
+//! Volts provide inline completions through the same request. As the
+//! capabilities they return are limited to the ones `lsp_types` knows about,
+//! they advertise it with `inlineCompletionProvider` in their `experimental`
+//! capabilities instead.
+
+use lsp_types::{
+    request::Request, Command, Range, TextDocumentPositionParams,
+    WorkDoneProgressParams,
+};
+use serde::{Deserialize, Serialize};
+
+pub enum InlineCompletionRequest {}
+
+impl Request for InlineCompletionRequest {
+    type Params = InlineCompletionParams;
+    type Result = Option<InlineCompletionResponse>;
+    const METHOD: &'static str = "textDocument/inlineCompletion";
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionParams {
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+    pub context: InlineCompletionContext,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionContext {
+    pub trigger_kind: InlineCompletionTriggerKind,
+    /// The completion item that is selected in the completion list, which the
+    /// inline completion should extend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_completion_info: Option<SelectedCompletionInfo>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct InlineCompletionTriggerKind(i32);
+
+impl InlineCompletionTriggerKind {
+    /// Inline completion was requested explicitly by a command.
+    pub const INVOKED: InlineCompletionTriggerKind = InlineCompletionTriggerKind(1);
+    /// Inline completion was requested automatically while editing.
+    pub const AUTOMATIC: InlineCompletionTriggerKind =
+        InlineCompletionTriggerKind(2);
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+pub struct SelectedCompletionInfo {
+    pub range: Range,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum InlineCompletionResponse {
+    Array(Vec<InlineCompletionItem>),
+    List(InlineCompletionList),
+}
+
+impl InlineCompletionResponse {
+    pub fn into_items(self) -> Vec<InlineCompletionItem> {
+        match self {
+            InlineCompletionResponse::Array(items) => items,
+            InlineCompletionResponse::List(list) => list.items,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct InlineCompletionList {
+    pub items: Vec<InlineCompletionItem>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionItem {
+    pub insert_text: InlineCompletionText,
+    /// The text to check the typed text against, defaults to `insert_text`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_text: Option<String>,
+    /// The range to replace, defaults to the cursor position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum InlineCompletionText {
+    String(String),
+    /// A `StringValue`, whose `kind` is `snippet`
+    Snippet {
+        kind: String,
+        value: String,
+    },
+}
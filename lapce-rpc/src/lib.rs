@@ -5,6 +5,7 @@
 pub mod counter;
 pub mod dap_types;
 pub mod file;
+pub mod inline_completion;
 mod parse;
 pub mod plugin;
 pub mod proxy;
@@ -11,10 +11,16 @@
 use indexmap::IndexMap;
 use lapce_xi_rope::RopeDelta;
 use lsp_types::{
-    request::GotoTypeDefinitionResponse, CodeAction, CodeActionResponse,
-    CompletionItem, Diagnostic, DocumentSymbolResponse, GotoDefinitionResponse,
-    Hover, InlayHint, Location, Position, PrepareRenameResponse, SelectionRange,
-    SymbolInformation, TextDocumentItem, TextEdit, WorkspaceEdit,
+    request::{
+        GotoDeclarationResponse, GotoImplementationResponse,
+        GotoTypeDefinitionResponse,
+    },
+    CodeAction, CodeActionResponse, Color, ColorInformation, ColorPresentation,
+    Command, CompletionItem, CompletionTriggerKind, Diagnostic, DocumentLink,
+    DocumentSymbolResponse, GotoDefinitionResponse, Hover, InlayHint,
+    LinkedEditingRanges, Location, Position, PrepareRenameResponse, Range,
+    SelectionRange, SignatureHelp, SignatureHelpContext, SymbolInformation,
+    TextDocumentItem, TextEdit, WorkspaceEdit,
 };
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
@@ -22,10 +28,15 @@
 use super::plugin::VoltID;
 use crate::{
     buffer::BufferId,
-    dap_types::{DapId, RunDebugConfig, SourceBreakpoint, ThreadId},
+    dap_types::{
+        self, DapId, EvaluateResponse, FunctionBreakpoint, ProcessInfo,
+        RunDebugConfig, Scope, SetVariableResponse, SourceBreakpoint, ThreadId,
+        Variable,
+    },
     file::{FileNodeItem, PathObject},
+    inline_completion::{InlineCompletionItem, InlineCompletionTriggerKind},
     plugin::{PluginId, VoltInfo, VoltMetadata},
-    source_control::FileDiff,
+    source_control::{BlameHunk, CommitInfo, FileDiff},
     style::SemanticStyles,
     terminal::TermId,
     RequestId, RpcError, RpcMessage,
@@ -61,8 +72,10 @@ pub enum ProxyRequest {
         buffer_id: BufferId,
         path: PathBuf,
     },
+    /// The content of the file at the revision, or at `HEAD` without one
     BufferHead {
         path: PathBuf,
+        revision: Option<String>,
     },
     GlobalSearch {
         pattern: String,
@@ -84,8 +97,16 @@ pub enum ProxyRequest {
         position: Position,
     },
     GetSignature {
-        buffer_id: BufferId,
+        request_id: usize,
+        path: PathBuf,
         position: Position,
+        context: Option<SignatureHelpContext>,
+    },
+    GetInlineCompletions {
+        request_id: usize,
+        path: PathBuf,
+        position: Position,
+        trigger_kind: InlineCompletionTriggerKind,
     },
     GetSelectionRange {
         path: PathBuf,
@@ -94,6 +115,31 @@ pub enum ProxyRequest {
     GitGetRemoteFileUrl {
         file: PathBuf,
     },
+    GitBlame {
+        path: PathBuf,
+    },
+    /// The commits reachable from `HEAD`, newest first, that changed the path,
+    /// if there's one, and whose author and message contain the filters.
+    /// After the first `skip` of them, up to `limit` are responded.
+    GitLog {
+        path: Option<PathBuf>,
+        author: String,
+        text: String,
+        skip: usize,
+        limit: usize,
+    },
+    /// The files the commit changed from its first parent
+    GitCommitChanges {
+        commit_id: String,
+    },
+    /// Revert the unstaged changes of the lines, from `start_line` up to
+    /// `end_line`, of the file to the index. The lines are of the content of
+    /// the buffer, if it's open, which is responded with the changes reverted.
+    GitRevertLines {
+        path: PathBuf,
+        start_line: usize,
+        end_line: usize,
+    },
     GetReferences {
         path: PathBuf,
         position: Position,
@@ -108,16 +154,45 @@ pub enum ProxyRequest {
         path: PathBuf,
         position: Position,
     },
+    GetImplementation {
+        request_id: usize,
+        path: PathBuf,
+        position: Position,
+    },
+    GetDeclaration {
+        request_id: usize,
+        path: PathBuf,
+        position: Position,
+    },
     GetInlayHints {
         path: PathBuf,
     },
+    GetDocumentLinks {
+        path: PathBuf,
+    },
+    GetDocumentColors {
+        path: PathBuf,
+    },
+    GetColorPresentations {
+        path: PathBuf,
+        color: Color,
+        range: Range,
+    },
     GetSemanticTokens {
         path: PathBuf,
     },
+    GetSemanticTokensRange {
+        path: PathBuf,
+        range: Range,
+    },
     PrepareRename {
         path: PathBuf,
         position: Position,
     },
+    LinkedEditingRange {
+        path: PathBuf,
+        position: Position,
+    },
     Rename {
         path: PathBuf,
         position: Position,
@@ -138,6 +213,16 @@ pub enum ProxyRequest {
     GetDocumentFormatting {
         path: PathBuf,
     },
+    GetRangeFormatting {
+        path: PathBuf,
+        range: Range,
+    },
+    GetOnTypeFormatting {
+        path: PathBuf,
+        position: Position,
+        /// The character that was typed
+        ch: String,
+    },
     GetOpenFilesContent {},
     GetFiles {
         path: String,
@@ -172,6 +257,50 @@ pub enum ProxyRequest {
         from: PathBuf,
         to: PathBuf,
     },
+    DapScopes {
+        dap_id: DapId,
+        frame_id: usize,
+    },
+    DapVariables {
+        dap_id: DapId,
+        variables_reference: usize,
+    },
+    DapEvaluate {
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        context: Option<String>,
+    },
+    DapSetVariable {
+        dap_id: DapId,
+        variables_reference: usize,
+        name: String,
+        value: String,
+    },
+    DapCompletions {
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+    },
+    DapDisassemble {
+        dap_id: DapId,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: usize,
+    },
+    DapReadMemory {
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+    },
+    /// Run the pre-launch command of the config, whose output is sent to the
+    /// debug console of its session
+    RunPreLaunch {
+        config: RunDebugConfig,
+    },
+    ListProcesses {},
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -197,11 +326,12 @@ pub enum ProxyNotification {
         path: PathBuf,
         input: String,
         position: Position,
+        trigger_kind: CompletionTriggerKind,
     },
-    SignatureHelp {
-        request_id: usize,
-        path: PathBuf,
-        position: Position,
+    /// Run a command of the plugin, e.g. the one of an accepted completion
+    ExecuteCommand {
+        plugin_id: PluginId,
+        command: Command,
     },
     Update {
         path: PathBuf,
@@ -244,6 +374,26 @@ pub enum ProxyNotification {
     },
     GitDiscardWorkspaceChanges {},
     GitInit {},
+    GitStageFiles {
+        files: Vec<PathBuf>,
+    },
+    GitUnstageFiles {
+        files: Vec<PathBuf>,
+    },
+    /// Stage the changes of the lines, from `start_line` up to `end_line`, of
+    /// the content of the buffer, if it's open, instead of what's on disk
+    GitStageLines {
+        path: PathBuf,
+        start_line: usize,
+        end_line: usize,
+    },
+    /// Unstage the changes of the lines, from `start_line` up to `end_line`,
+    /// of the content of the buffer, if it's open, instead of what's on disk
+    GitUnstageLines {
+        path: PathBuf,
+        start_line: usize,
+        end_line: usize,
+    },
     TerminalWrite {
         term_id: TermId,
         content: String,
@@ -259,6 +409,8 @@ pub enum ProxyNotification {
     DapStart {
         config: RunDebugConfig,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
     },
     DapProcessId {
         dap_id: DapId,
@@ -273,6 +425,32 @@ pub enum ProxyNotification {
         dap_id: DapId,
         thread_id: ThreadId,
     },
+    DapStepOver {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepInto {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepOut {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepBack {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepInstruction {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapGoto {
+        dap_id: DapId,
+        thread_id: ThreadId,
+        path: PathBuf,
+        line: usize,
+    },
     DapStop {
         dap_id: DapId,
     },
@@ -282,12 +460,24 @@ pub enum ProxyNotification {
     DapRestart {
         dap_id: DapId,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
     },
     DapSetBreakpoints {
         dap_id: DapId,
         path: PathBuf,
         breakpoints: Vec<SourceBreakpoint>,
     },
+    /// The exception filters that were turned on or off, by their id. The
+    /// other filters that the debug adapter has use their default.
+    DapSetExceptionBreakpoints {
+        dap_id: DapId,
+        exception_filters: HashMap<String, bool>,
+    },
+    DapSetFunctionBreakpoints {
+        dap_id: DapId,
+        breakpoints: Vec<FunctionBreakpoint>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -297,6 +487,18 @@ pub enum ProxyResponse {
     GitGetRemoteFileUrl {
         file_url: String,
     },
+    GitBlameResponse {
+        hunks: Vec<BlameHunk>,
+    },
+    GitRevertLinesResponse {
+        content: String,
+    },
+    GitLogResponse {
+        commits: Vec<CommitInfo>,
+    },
+    GitCommitChangesResponse {
+        diffs: Vec<FileDiff>,
+    },
     NewBufferResponse {
         content: String,
         read_only: bool,
@@ -318,6 +520,15 @@ pub enum ProxyResponse {
         request_id: usize,
         hover: Hover,
     },
+    GetSignatureResponse {
+        request_id: usize,
+        signature_help: SignatureHelp,
+    },
+    GetInlineCompletionsResponse {
+        request_id: usize,
+        plugin_id: PluginId,
+        items: Vec<InlineCompletionItem>,
+    },
     GetDefinitionResponse {
         request_id: usize,
         definition: GotoDefinitionResponse,
@@ -326,6 +537,14 @@ pub enum ProxyResponse {
         request_id: usize,
         definition: GotoTypeDefinitionResponse,
     },
+    GetImplementationResponse {
+        request_id: usize,
+        implementation: GotoImplementationResponse,
+    },
+    GetDeclarationResponse {
+        request_id: usize,
+        declaration: GotoDeclarationResponse,
+    },
     GetReferencesResponse {
         references: Vec<Location>,
     },
@@ -339,6 +558,12 @@ pub enum ProxyResponse {
     GetDocumentFormatting {
         edits: Vec<TextEdit>,
     },
+    GetRangeFormatting {
+        edits: Vec<TextEdit>,
+    },
+    GetOnTypeFormatting {
+        edits: Vec<TextEdit>,
+    },
     GetDocumentSymbols {
         resp: DocumentSymbolResponse,
     },
@@ -351,12 +576,27 @@ pub enum ProxyResponse {
     GetInlayHints {
         hints: Vec<InlayHint>,
     },
+    GetDocumentLinks {
+        links: Vec<DocumentLink>,
+    },
+    GetDocumentColors {
+        colors: Vec<ColorInformation>,
+    },
+    GetColorPresentations {
+        presentations: Vec<ColorPresentation>,
+    },
     GetSemanticTokens {
         styles: SemanticStyles,
     },
+    GetSemanticTokensRange {
+        styles: SemanticStyles,
+    },
     PrepareRename {
         resp: PrepareRenameResponse,
     },
+    LinkedEditingRange {
+        ranges: LinkedEditingRanges,
+    },
     Rename {
         edit: WorkspaceEdit,
     },
@@ -368,6 +608,30 @@ pub enum ProxyResponse {
     },
     Success {},
     SaveResponse {},
+    DapScopesResponse {
+        scopes: Vec<Scope>,
+    },
+    DapVariablesResponse {
+        variables: Vec<Variable>,
+    },
+    DapEvaluateResponse {
+        resp: EvaluateResponse,
+    },
+    DapSetVariableResponse {
+        resp: SetVariableResponse,
+    },
+    DapCompletionsResponse {
+        targets: Vec<dap_types::CompletionItem>,
+    },
+    DapDisassembleResponse {
+        instructions: Vec<dap_types::DisassembledInstruction>,
+    },
+    DapReadMemoryResponse {
+        resp: dap_types::ReadMemoryResponse,
+    },
+    ListProcessesResponse {
+        processes: Vec<ProcessInfo>,
+    },
 }
 
 pub type ProxyMessage = RpcMessage<ProxyRequest, ProxyNotification, ProxyResponse>;
@@ -499,6 +763,40 @@ pub fn git_checkout(&self, reference: String) {
         self.notification(ProxyNotification::GitCheckout { reference });
     }
 
+    pub fn git_stage_files(&self, files: Vec<PathBuf>) {
+        self.notification(ProxyNotification::GitStageFiles { files });
+    }
+
+    pub fn git_unstage_files(&self, files: Vec<PathBuf>) {
+        self.notification(ProxyNotification::GitUnstageFiles { files });
+    }
+
+    pub fn git_stage_lines(
+        &self,
+        path: PathBuf,
+        start_line: usize,
+        end_line: usize,
+    ) {
+        self.notification(ProxyNotification::GitStageLines {
+            path,
+            start_line,
+            end_line,
+        });
+    }
+
+    pub fn git_unstage_lines(
+        &self,
+        path: PathBuf,
+        start_line: usize,
+        end_line: usize,
+    ) {
+        self.notification(ProxyNotification::GitUnstageLines {
+            path,
+            start_line,
+            end_line,
+        });
+    }
+
     pub fn install_volt(&self, volt: VoltInfo) {
         self.notification(ProxyNotification::InstallVolt { volt });
     }
@@ -547,26 +845,19 @@ pub fn completion(
         path: PathBuf,
         input: String,
         position: Position,
+        trigger_kind: CompletionTriggerKind,
     ) {
         self.notification(ProxyNotification::Completion {
             request_id,
             path,
             input,
             position,
+            trigger_kind,
         });
     }
 
-    pub fn signature_help(
-        &self,
-        request_id: usize,
-        path: PathBuf,
-        position: Position,
-    ) {
-        self.notification(ProxyNotification::SignatureHelp {
-            request_id,
-            path,
-            position,
-        });
+    pub fn execute_command(&self, plugin_id: PluginId, command: Command) {
+        self.notification(ProxyNotification::ExecuteCommand { plugin_id, command });
     }
 
     pub fn new_terminal(
@@ -610,7 +901,29 @@ pub fn new_buffer(
     }
 
     pub fn get_buffer_head(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
-        self.request_async(ProxyRequest::BufferHead { path }, f);
+        self.request_async(
+            ProxyRequest::BufferHead {
+                path,
+                revision: None,
+            },
+            f,
+        );
+    }
+
+    /// Get the content of the file at a git revision, like a commit id.
+    pub fn get_buffer_revision(
+        &self,
+        path: PathBuf,
+        revision: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::BufferHead {
+                path,
+                revision: Some(revision),
+            },
+            f,
+        );
     }
 
     pub fn create_file(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
@@ -755,6 +1068,44 @@ pub fn get_hover(
         );
     }
 
+    pub fn get_signature(
+        &self,
+        request_id: usize,
+        path: PathBuf,
+        position: Position,
+        context: Option<SignatureHelpContext>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetSignature {
+                request_id,
+                path,
+                position,
+                context,
+            },
+            f,
+        );
+    }
+
+    pub fn get_inline_completions(
+        &self,
+        request_id: usize,
+        path: PathBuf,
+        position: Position,
+        trigger_kind: InlineCompletionTriggerKind,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetInlineCompletions {
+                request_id,
+                path,
+                position,
+                trigger_kind,
+            },
+            f,
+        );
+    }
+
     pub fn get_definition(
         &self,
         request_id: usize,
@@ -789,6 +1140,40 @@ pub fn get_type_definition(
         );
     }
 
+    pub fn get_implementation(
+        &self,
+        request_id: usize,
+        path: PathBuf,
+        position: Position,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetImplementation {
+                request_id,
+                path,
+                position,
+            },
+            f,
+        );
+    }
+
+    pub fn get_declaration(
+        &self,
+        request_id: usize,
+        path: PathBuf,
+        position: Position,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetDeclaration {
+                request_id,
+                path,
+                position,
+            },
+            f,
+        );
+    }
+
     pub fn get_references(
         &self,
         path: PathBuf,
@@ -823,6 +1208,28 @@ pub fn get_document_formatting(
         self.request_async(ProxyRequest::GetDocumentFormatting { path }, f);
     }
 
+    pub fn get_range_formatting(
+        &self,
+        path: PathBuf,
+        range: Range,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GetRangeFormatting { path, range }, f);
+    }
+
+    pub fn get_on_type_formatting(
+        &self,
+        path: PathBuf,
+        position: Position,
+        ch: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetOnTypeFormatting { path, position, ch },
+            f,
+        );
+    }
+
     pub fn get_semantic_tokens(
         &self,
         path: PathBuf,
@@ -831,6 +1238,15 @@ pub fn get_semantic_tokens(
         self.request_async(ProxyRequest::GetSemanticTokens { path }, f);
     }
 
+    pub fn get_semantic_tokens_range(
+        &self,
+        path: PathBuf,
+        range: Range,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GetSemanticTokensRange { path, range }, f);
+    }
+
     pub fn get_document_symbols(
         &self,
         path: PathBuf,
@@ -856,6 +1272,15 @@ pub fn prepare_rename(
         self.request_async(ProxyRequest::PrepareRename { path, position }, f);
     }
 
+    pub fn linked_editing_range(
+        &self,
+        path: PathBuf,
+        position: Position,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::LinkedEditingRange { path, position }, f);
+    }
+
     pub fn git_get_remote_file_url(
         &self,
         file: PathBuf,
@@ -864,6 +1289,57 @@ pub fn git_get_remote_file_url(
         self.request_async(ProxyRequest::GitGetRemoteFileUrl { file }, f);
     }
 
+    /// Blame the lines of the file as they are in the buffer.
+    pub fn git_blame(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::GitBlame { path }, f);
+    }
+
+    pub fn git_log(
+        &self,
+        path: Option<PathBuf>,
+        author: String,
+        text: String,
+        skip: usize,
+        limit: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GitLog {
+                path,
+                author,
+                text,
+                skip,
+                limit,
+            },
+            f,
+        );
+    }
+
+    pub fn git_commit_changes(
+        &self,
+        commit_id: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GitCommitChanges { commit_id }, f);
+    }
+
+    pub fn git_revert_lines(
+        &self,
+        path: PathBuf,
+        start_line: usize,
+        end_line: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GitRevertLines {
+                path,
+                start_line,
+                end_line,
+            },
+            f,
+        );
+    }
+
     pub fn rename(
         &self,
         path: PathBuf,
@@ -885,6 +1361,35 @@ pub fn get_inlay_hints(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
         self.request_async(ProxyRequest::GetInlayHints { path }, f);
     }
 
+    pub fn get_document_links(
+        &self,
+        path: PathBuf,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GetDocumentLinks { path }, f);
+    }
+
+    pub fn get_document_colors(
+        &self,
+        path: PathBuf,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GetDocumentColors { path }, f);
+    }
+
+    pub fn get_color_presentations(
+        &self,
+        path: PathBuf,
+        color: Color,
+        range: Range,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetColorPresentations { path, color, range },
+            f,
+        );
+    }
+
     pub fn update(&self, path: PathBuf, delta: RopeDelta, rev: u64) {
         self.notification(ProxyNotification::Update { path, delta, rev });
     }
@@ -917,10 +1422,14 @@ pub fn dap_start(
         &self,
         config: RunDebugConfig,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
     ) {
         self.notification(ProxyNotification::DapStart {
             config,
             breakpoints,
+            exception_filters,
+            function_breakpoints,
         })
     }
 
@@ -941,10 +1450,14 @@ pub fn dap_restart(
         &self,
         dap_id: DapId,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        exception_filters: HashMap<String, bool>,
+        function_breakpoints: Vec<FunctionBreakpoint>,
     ) {
         self.notification(ProxyNotification::DapRestart {
             dap_id,
             breakpoints,
+            exception_filters,
+            function_breakpoints,
         })
     }
 
@@ -956,6 +1469,46 @@ pub fn dap_pause(&self, dap_id: DapId, thread_id: ThreadId) {
         self.notification(ProxyNotification::DapPause { dap_id, thread_id })
     }
 
+    pub fn dap_step_over(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.notification(ProxyNotification::DapStepOver { dap_id, thread_id })
+    }
+
+    pub fn dap_step_into(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.notification(ProxyNotification::DapStepInto { dap_id, thread_id })
+    }
+
+    pub fn dap_step_out(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.notification(ProxyNotification::DapStepOut { dap_id, thread_id })
+    }
+
+    pub fn dap_step_back(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.notification(ProxyNotification::DapStepBack { dap_id, thread_id })
+    }
+
+    /// Step over a single machine instruction instead of a line.
+    pub fn dap_step_instruction(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.notification(ProxyNotification::DapStepInstruction {
+            dap_id,
+            thread_id,
+        })
+    }
+
+    /// Jump to the line, starting at 1, without running the code in between.
+    pub fn dap_goto(
+        &self,
+        dap_id: DapId,
+        thread_id: ThreadId,
+        path: PathBuf,
+        line: usize,
+    ) {
+        self.notification(ProxyNotification::DapGoto {
+            dap_id,
+            thread_id,
+            path,
+            line,
+        })
+    }
+
     pub fn dap_stop(&self, dap_id: DapId) {
         self.notification(ProxyNotification::DapStop { dap_id })
     }
@@ -976,6 +1529,161 @@ pub fn dap_set_breakpoints(
             breakpoints,
         })
     }
+
+    pub fn dap_set_exception_breakpoints(
+        &self,
+        dap_id: DapId,
+        exception_filters: HashMap<String, bool>,
+    ) {
+        self.notification(ProxyNotification::DapSetExceptionBreakpoints {
+            dap_id,
+            exception_filters,
+        })
+    }
+
+    pub fn dap_set_function_breakpoints(
+        &self,
+        dap_id: DapId,
+        breakpoints: Vec<FunctionBreakpoint>,
+    ) {
+        self.notification(ProxyNotification::DapSetFunctionBreakpoints {
+            dap_id,
+            breakpoints,
+        })
+    }
+
+    pub fn dap_scopes(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::DapScopes { dap_id, frame_id }, f);
+    }
+
+    pub fn dap_variables(
+        &self,
+        dap_id: DapId,
+        variables_reference: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapVariables {
+                dap_id,
+                variables_reference,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        context: Option<String>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapEvaluate {
+                dap_id,
+                expression,
+                frame_id,
+                context,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_set_variable(
+        &self,
+        dap_id: DapId,
+        variables_reference: usize,
+        name: String,
+        value: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapSetVariable {
+                dap_id,
+                variables_reference,
+                name,
+                value,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_completions(
+        &self,
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapCompletions {
+                dap_id,
+                frame_id,
+                text,
+                column,
+            },
+            f,
+        );
+    }
+
+    /// Disassemble `instruction_count` instructions, starting `instruction_offset`
+    /// instructions away from the memory reference.
+    pub fn dap_disassemble(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapDisassemble {
+                dap_id,
+                memory_reference,
+                instruction_offset,
+                instruction_count,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_read_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapReadMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                count,
+            },
+            f,
+        );
+    }
+
+    pub fn run_pre_launch(
+        &self,
+        config: RunDebugConfig,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::RunPreLaunch { config }, f);
+    }
+
+    pub fn list_processes(&self, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::ListProcesses {}, f);
+    }
 }
 
 impl Default for ProxyRpcHandler {
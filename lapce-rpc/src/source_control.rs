@@ -7,7 +7,10 @@ pub struct DiffInfo {
     pub head: String,
     pub branches: Vec<String>,
     pub tags: Vec<String>,
+    /// The changes of the working tree that aren't staged
     pub diffs: Vec<FileDiff>,
+    /// The changes staged in the index
+    pub staged: Vec<FileDiff>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -18,6 +21,42 @@ pub enum FileDiff {
     Renamed(PathBuf, PathBuf),
 }
 
+/// The lines of a file that were last changed by the same commit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlameHunk {
+    /// The id of the commit, which is all zeros for the lines that aren't
+    /// committed yet
+    pub commit_id: String,
+    pub author: String,
+    pub email: String,
+    /// The time of the commit, in seconds since the Unix epoch
+    pub time: i64,
+    pub summary: String,
+    pub message: String,
+    /// The first line of the hunk, starting at 0
+    pub start_line: usize,
+    pub lines: usize,
+}
+
+/// A commit of the log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommitInfo {
+    pub id: String,
+    pub parents: Vec<String>,
+    pub author: String,
+    pub email: String,
+    /// The time of the commit, in seconds since the Unix epoch
+    pub time: i64,
+    pub summary: String,
+    pub message: String,
+}
+
+impl BlameHunk {
+    pub fn is_committed(&self) -> bool {
+        self.commit_id.chars().any(|c| c != '0')
+    }
+}
+
 impl FileDiff {
     pub fn path(&self) -> &PathBuf {
         match &self {